@@ -1,5 +1,10 @@
 fn main() {
-	match serial2::SerialPort::available_ports() {
+	if std::env::args().any(|arg| arg == "--verbose") {
+		list_ports_verbose();
+		return;
+	}
+
+	match serial2::SerialPort::available_ports_info() {
 		Err(e) => {
 			eprintln!("Failed to enumerate serial ports: {}", e);
 			std::process::exit(1);
@@ -7,7 +12,28 @@ fn main() {
 		Ok(ports) => {
 			eprintln!("Found {} ports", ports.len());
 			for port in ports {
-				println!("{}", port.display())
+				let mut line = port.path().display().to_string();
+				if port.kind() != serial2::PortKind::Unknown {
+					line += &format!(" ({:?})", port.kind());
+				}
+				if let Some(id) = port.id() {
+					line += &format!(" [{}]", id);
+				}
+				println!("{}", line);
+			}
+		},
+	}
+}
+
+fn list_ports_verbose() {
+	match serial2::SerialPort::available_ports_verbose() {
+		Err(e) => {
+			eprintln!("Failed to enumerate serial ports: {}", e);
+			std::process::exit(1);
+		},
+		Ok(entries) => {
+			for entry in entries {
+				println!("{}: {}", entry.path().display(), entry.status());
 			}
 		},
 	}
@@ -0,0 +1,63 @@
+//! Reset sequences to enter the bootloader of common development boards.
+//!
+//! Many development boards use the control lines of their USB serial converter to reset the microcontroller
+//! or to put it in bootloader mode.
+//! This module implements the canonical sequences for the most common boards.
+//!
+//! All functions are built on the regular control line API.
+//! The sequences that use an open serial port accept any [`SerialTransport`].
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{SerialPort, SerialTransport};
+
+/// Perform the 1200 baud touch that puts Arduino-style boards in bootloader mode.
+///
+/// Boards with native USB (like the Arduino Leonardo, Micro, Zero and many SAMD and RP2040 boards)
+/// enter their bootloader when the serial port is opened at 1200 baud and closed again.
+///
+/// This opens the serial port at 1200 baud, clears the DTR line and closes the port again.
+/// The board will usually disconnect and come back as a new serial port after a short while,
+/// possibly with a different name.
+pub fn touch_1200(name: impl AsRef<Path>) -> std::io::Result<()> {
+	let port = SerialPort::open(name, 1200)?;
+	port.set_dtr(false)?;
+	drop(port);
+	Ok(())
+}
+
+/// Put an ESP8266 or ESP32 in bootloader mode using the auto-program circuit.
+///
+/// Most ESP development boards have a circuit where the DTR line controls the `IO0` (or `GPIO0`) pin
+/// and the RTS line controls the `EN` (reset) pin.
+/// This uses the same sequence as the "classic reset" of `esptool`:
+/// hold the chip in reset, pull `IO0` low, and then release the reset.
+///
+/// This function blocks the current thread for about 150 milliseconds.
+pub fn esp_enter_bootloader<T: SerialTransport + ?Sized>(port: &T) -> std::io::Result<()> {
+	// IO0 high, EN low: the chip is held in reset.
+	port.set_dtr(false)?;
+	port.set_rts(true)?;
+	std::thread::sleep(Duration::from_millis(100));
+
+	// IO0 low, EN high: the chip boots into the bootloader.
+	port.set_dtr(true)?;
+	port.set_rts(false)?;
+	std::thread::sleep(Duration::from_millis(50));
+
+	// Release IO0 again.
+	port.set_dtr(false)
+}
+
+/// Reset an ESP8266 or ESP32 into normal operation using the auto-program circuit.
+///
+/// This pulses the `EN` (reset) pin through the RTS line while leaving `IO0` high.
+///
+/// This function blocks the current thread for about 100 milliseconds.
+pub fn esp_hard_reset<T: SerialTransport + ?Sized>(port: &T) -> std::io::Result<()> {
+	port.set_dtr(false)?;
+	port.set_rts(true)?;
+	std::thread::sleep(Duration::from_millis(100));
+	port.set_rts(false)
+}
@@ -0,0 +1,294 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::util::lock;
+use crate::SerialTransport;
+
+/// The default buffer capacity of a [`SerialBufWriter`].
+const DEFAULT_CAPACITY: usize = 4096;
+
+/// A transport wrapper that batches small writes into larger transfers.
+///
+/// Every write to a serial port is a system call, and on Windows it is also an overlapped I/O operation.
+/// Protocols that write many small pieces of data can be much faster if the pieces are combined.
+/// This wrapper collects written data in a buffer, and only writes it to the wrapped transport when the buffer is full,
+/// when [`Self::flush_buffer()`] or [`SerialTransport::flush()`] is called, or when the wrapper is dropped.
+///
+/// Calling [`SerialTransport::flush()`] writes the buffered data and then flushes the wrapped transport,
+/// so for a [`SerialPort`][crate::SerialPort] it still waits until all data has been transmitted by the UART.
+/// Dropping the wrapper only writes the buffered data, and errors while doing so are ignored.
+/// Call [`SerialTransport::flush()`] or [`Self::flush_buffer()`] before dropping it if you need to handle errors.
+///
+/// Reads and all other functions are passed through to the wrapped transport unchanged.
+/// Note that data is not written automatically before a read,
+/// so you must flush the buffer yourself after sending a request that you expect a response to.
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{SerialBufWriter, SerialPort, SerialTransport};
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+/// let port = SerialBufWriter::new(port);
+/// for value in 0..100u8 {
+///     port.write_all(&[b'V', value, b'\n'])?;
+/// }
+/// port.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SerialBufWriter<T: SerialTransport> {
+	/// The wrapped transport, which is only taken out by [`Self::into_parts()`].
+	inner: Option<T>,
+
+	/// The buffered data.
+	///
+	/// The lock is held while writing the buffer to the wrapped transport, so concurrent writes are not reordered.
+	buffer: Mutex<Vec<u8>>,
+
+	/// The capacity of the buffer.
+	capacity: usize,
+}
+
+impl<T: SerialTransport> SerialBufWriter<T> {
+	/// Wrap a transport with a buffer of the default capacity (currently 4 KiB).
+	pub fn new(inner: T) -> Self {
+		Self::with_capacity(DEFAULT_CAPACITY, inner)
+	}
+
+	/// Wrap a transport with a buffer of the given capacity.
+	///
+	/// Writes of at least `capacity` bytes bypass the buffer.
+	pub fn with_capacity(capacity: usize, inner: T) -> Self {
+		Self {
+			inner: Some(inner),
+			buffer: Mutex::new(Vec::with_capacity(capacity)),
+			capacity,
+		}
+	}
+
+	/// Get a reference to the wrapped transport.
+	///
+	/// Writing to the wrapped transport directly bypasses the buffer.
+	pub fn get_ref(&self) -> &T {
+		self.inner()
+	}
+
+	/// Get the capacity of the buffer.
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Get the number of bytes in the buffer that have not been written to the wrapped transport yet.
+	pub fn buffered_len(&self) -> usize {
+		lock(&self.buffer).len()
+	}
+
+	/// Write all buffered data to the wrapped transport.
+	///
+	/// Unlike [`SerialTransport::flush()`], this does not wait for the data to be transmitted.
+	///
+	/// If an error occurs, the data that was not written yet stays in the buffer.
+	pub fn flush_buffer(&self) -> std::io::Result<()> {
+		self.write_buffer(&mut lock(&self.buffer))
+	}
+
+	/// Write all buffered data to the wrapped transport, and get the wrapped transport back.
+	///
+	/// If writing the buffered data fails, the error is returned together with the writer,
+	/// so the data that was not written yet is not lost.
+	/// Note that dropping the returned writer tries to write the data again.
+	/// Use [`Self::into_parts()`] to get the wrapped transport and the buffered data without writing it.
+	pub fn into_inner(self) -> Result<T, IntoInnerError<Self>> {
+		match self.flush_buffer() {
+			Ok(()) => Ok(self.into_parts().0),
+			Err(error) => Err(IntoInnerError { writer: self, error }),
+		}
+	}
+
+	/// Get the wrapped transport and the buffered data back, without writing the buffered data.
+	pub fn into_parts(mut self) -> (T, Vec<u8>) {
+		let buffer = std::mem::take(&mut *lock(&self.buffer));
+		let inner = self.inner.take().expect("the wrapped transport is only taken by into_parts()");
+		(inner, buffer)
+	}
+
+	fn inner(&self) -> &T {
+		self.inner.as_ref().expect("the wrapped transport is only taken by into_parts()")
+	}
+
+	/// Write the buffered data to the wrapped transport.
+	fn write_buffer(&self, buffer: &mut Vec<u8>) -> std::io::Result<()> {
+		let mut written = 0;
+		let result = loop {
+			if written == buffer.len() {
+				break Ok(());
+			}
+			match self.inner().write(&buffer[written..]) {
+				Ok(0) => break Err(std::io::ErrorKind::WriteZero.into()),
+				Ok(n) => written += n,
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => break Err(e),
+			}
+		};
+		buffer.drain(..written);
+		result
+	}
+}
+
+impl<T: SerialTransport> SerialTransport for SerialBufWriter<T> {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		self.inner().read(buf)
+	}
+
+	/// Write data to the buffer.
+	///
+	/// If the data does not fit in the buffer, the buffer is written to the wrapped transport first.
+	/// Data that is larger than the buffer capacity is written directly to the wrapped transport.
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		let mut buffer = lock(&self.buffer);
+		if buffer.len() + buf.len() > self.capacity {
+			self.write_buffer(&mut buffer)?;
+		}
+		if buf.len() >= self.capacity {
+			self.inner().write(buf)
+		} else {
+			buffer.extend_from_slice(buf);
+			Ok(buf.len())
+		}
+	}
+
+	/// Write the buffered data to the wrapped transport and flush it.
+	fn flush(&self) -> std::io::Result<()> {
+		self.flush_buffer()?;
+		self.inner().flush()
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner().set_read_timeout(timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		self.inner().get_read_timeout()
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner().set_write_timeout(timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		self.inner().get_write_timeout()
+	}
+
+	/// Discard the buffered data and the kernel input and output buffers.
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		lock(&self.buffer).clear();
+		self.inner().discard_buffers()
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.inner().set_rts(state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		self.inner().read_cts()
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.inner().set_dtr(state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		self.inner().read_dsr()
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		self.inner().read_ri()
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		self.inner().read_cd()
+	}
+
+	fn now(&self) -> Instant {
+		self.inner().now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		self.inner().sleep(duration)
+	}
+}
+
+impl<T: SerialTransport> Drop for SerialBufWriter<T> {
+	fn drop(&mut self) {
+		if self.inner.is_some() {
+			let _ = self.flush_buffer();
+		}
+	}
+}
+
+impl<T: SerialTransport + std::fmt::Debug> std::fmt::Debug for SerialBufWriter<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SerialBufWriter")
+			.field("inner", self.inner())
+			.field("buffered_len", &self.buffered_len())
+			.field("capacity", &self.capacity)
+			.finish()
+	}
+}
+
+/// The error returned by [`SerialBufWriter::into_inner()`] if the buffered data could not be written.
+///
+/// The error contains the writer, so the buffered data is not lost.
+pub struct IntoInnerError<W> {
+	writer: W,
+	error: std::io::Error,
+}
+
+impl<W> IntoInnerError<W> {
+	/// Get the error that occurred while writing the buffered data.
+	pub fn error(&self) -> &std::io::Error {
+		&self.error
+	}
+
+	/// Get the writer back, discarding the error.
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+
+	/// Get the error back, discarding the writer.
+	///
+	/// Dropping the writer tries to write the buffered data again.
+	pub fn into_error(self) -> std::io::Error {
+		self.error
+	}
+
+	/// Get the error and the writer back.
+	pub fn into_parts(self) -> (std::io::Error, W) {
+		(self.error, self.writer)
+	}
+}
+
+impl<W> std::fmt::Debug for IntoInnerError<W> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IntoInnerError").field("error", &self.error).finish_non_exhaustive()
+	}
+}
+
+impl<W> std::fmt::Display for IntoInnerError<W> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.error.fmt(f)
+	}
+}
+
+impl<W> std::error::Error for IntoInnerError<W> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		self.error.source()
+	}
+}
+
+impl<W> From<IntoInnerError<W>> for std::io::Error {
+	fn from(other: IntoInnerError<W>) -> Self {
+		other.error
+	}
+}
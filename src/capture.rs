@@ -0,0 +1,684 @@
+//! Continuous capture of received data with timestamps.
+//!
+//! A [`Capture`] reads from a transport on a dedicated thread,
+//! and stores the received data in a bounded buffer as timestamped [`Chunk`]s.
+//! Because the capture thread is always reading, the OS buffers do not overflow
+//! if the application is busy for a while, which makes this suitable for building protocol analyzers.
+//!
+//! The buffer is a ring buffer with a limited capacity in bytes.
+//! When it is full, the oldest chunks are discarded to make room for new data,
+//! and the number of discarded bytes is reported by [`Capture::dropped_bytes()`].
+//!
+//! The timestamp of a chunk is the monotonic time at which the read call returned.
+//! Kernel timestamps for received data are not available for serial ports on the supported platforms.
+//!
+//! To see the data in both directions without changing the code that uses a transport,
+//! wrap the transport in a [`TeePort`].
+//! It passes all calls through to the wrapped transport, and mirrors all data read and written to a [`TeeSink`].
+//! A [`Recorder`] can be used as sink to write the traffic to a file.
+//!
+//! A recorded session can be loaded again as a [`Recording`],
+//! and it can be replayed through a [`MockSerialPort`][crate::mock::MockSerialPort] with the original timing.
+//! This allows you to reproduce the behaviour of a device reported from the field in a local test.
+//!
+//! # Example: Capture received data
+//! ```no_run
+//! # fn example() -> std::io::Result<()> {
+//! use serial2::SerialPort;
+//! use serial2::capture::Capture;
+//! use std::time::Duration;
+//!
+//! let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+//! port.set_read_timeout(Duration::from_millis(10))?;
+//! let capture = Capture::start(port, 1024 * 1024)?;
+//!
+//! loop {
+//!     let chunk = capture.recv_timeout(Duration::from_secs(1))?;
+//!     println!("{:?}: {:02X?}", chunk.timestamp, chunk.data);
+//! }
+//! # }
+//! ```
+//!
+//! # Example: Log all traffic to a file
+//! ```no_run
+//! # fn example() -> std::io::Result<()> {
+//! use serial2::{SerialPort, SerialTransport};
+//! use serial2::capture::{Recorder, TeePort};
+//!
+//! let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+//! let recorder = Recorder::new(std::fs::File::create("traffic.txt")?)?;
+//! let port = TeePort::new(port, recorder);
+//! port.write_all(b"PING\n")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::util::lock;
+use crate::SerialTransport;
+
+/// The size of the buffer used for each read call of the capture thread.
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// A chunk of data received by a [`Capture`].
+#[derive(Debug, Clone)]
+pub struct Chunk {
+	/// The monotonic time at which the read call returned the data.
+	pub timestamp: Instant,
+
+	/// The received data.
+	pub data: Vec<u8>,
+}
+
+/// Capture of received data on a dedicated thread.
+///
+/// See the [module documentation][self] for more information.
+pub struct Capture<T> {
+	/// State shared with the capture thread.
+	shared: Arc<Shared>,
+
+	/// The capture thread, which returns the transport when it stops.
+	thread: Option<JoinHandle<T>>,
+}
+
+struct Shared {
+	state: Mutex<State>,
+	condvar: Condvar,
+	stop: AtomicBool,
+}
+
+struct State {
+	/// Captured chunks that have not been taken yet.
+	chunks: VecDeque<Chunk>,
+
+	/// The total number of bytes in `chunks`.
+	buffered: usize,
+
+	/// The maximum number of bytes to keep in `chunks`.
+	capacity: usize,
+
+	/// The total number of bytes discarded because the buffer was full.
+	dropped: u64,
+
+	/// The capture thread has stopped.
+	stopped: bool,
+
+	/// The error that stopped the capture thread, if it has not been reported yet.
+	error: Option<std::io::Error>,
+}
+
+impl<T: SerialTransport + Send + 'static> Capture<T> {
+	/// Start capturing data from a transport.
+	///
+	/// The `capacity` is the maximum number of bytes to keep in the buffer.
+	///
+	/// The capture thread checks if it should stop every time a read returns or times out,
+	/// so you should configure a short read timeout on the transport before starting the capture.
+	pub fn start(transport: T, capacity: usize) -> std::io::Result<Self> {
+		let shared = Arc::new(Shared {
+			state: Mutex::new(State {
+				chunks: VecDeque::new(),
+				buffered: 0,
+				capacity,
+				dropped: 0,
+				stopped: false,
+				error: None,
+			}),
+			condvar: Condvar::new(),
+			stop: AtomicBool::new(false),
+		});
+
+		let thread = std::thread::Builder::new().name("serial2-capture".into()).spawn({
+			let shared = shared.clone();
+			move || capture_thread(transport, &shared)
+		})?;
+
+		Ok(Self {
+			shared,
+			thread: Some(thread),
+		})
+	}
+
+	/// Stop the capture and get back the transport.
+	///
+	/// This waits for the pending read of the capture thread to return.
+	/// Data that has been captured but not taken yet is discarded.
+	pub fn stop(mut self) -> T {
+		self.shared.stop.store(true, Ordering::Relaxed);
+		match self.thread.take().map(|thread| thread.join()) {
+			Some(Ok(transport)) => transport,
+			Some(Err(panic)) => std::panic::resume_unwind(panic),
+			None => unreachable!("the capture thread is only taken when stopping the capture"),
+		}
+	}
+}
+
+impl<T> Capture<T> {
+	/// Wait for the next chunk of captured data.
+	///
+	/// If no data is captured before the timeout expires, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	/// Use [`Duration::MAX`] to wait forever.
+	///
+	/// If the capture thread stopped because of a read error, that error is returned once all captured data has been taken.
+	/// After that, or if the capture thread stopped because the transport reached the end of file,
+	/// an error of kind [`std::io::ErrorKind::BrokenPipe`] is returned.
+	pub fn recv_timeout(&self, timeout: Duration) -> std::io::Result<Chunk> {
+		// If the deadline can not be represented, wait forever.
+		let deadline = Instant::now().checked_add(timeout);
+		let mut state = lock(&self.shared.state);
+		loop {
+			if let Some(chunk) = state.pop() {
+				return Ok(chunk);
+			}
+			if state.stopped {
+				return Err(state.error.take().unwrap_or_else(stopped_error));
+			}
+			state = match deadline {
+				None => self.shared.condvar.wait(state).unwrap_or_else(|e| e.into_inner()),
+				Some(deadline) => {
+					let now = Instant::now();
+					if now >= deadline {
+						return Err(std::io::ErrorKind::TimedOut.into());
+					}
+					self.shared.condvar.wait_timeout(state, deadline - now)
+						.unwrap_or_else(|e| e.into_inner())
+						.0
+				},
+			};
+		}
+	}
+
+	/// Take the next chunk of captured data, if there is any.
+	pub fn try_recv(&self) -> Option<Chunk> {
+		lock(&self.shared.state).pop()
+	}
+
+	/// Take all captured data.
+	pub fn drain(&self) -> Vec<Chunk> {
+		let mut state = lock(&self.shared.state);
+		state.buffered = 0;
+		state.chunks.drain(..).collect()
+	}
+
+	/// Get the number of captured bytes that have not been taken yet.
+	pub fn buffered_bytes(&self) -> usize {
+		lock(&self.shared.state).buffered
+	}
+
+	/// Get the total number of bytes that were discarded because the buffer was full.
+	pub fn dropped_bytes(&self) -> u64 {
+		lock(&self.shared.state).dropped
+	}
+
+	/// Check if the capture thread is still running.
+	///
+	/// The capture thread stops when the transport reports an error or reaches the end of file.
+	pub fn is_running(&self) -> bool {
+		!lock(&self.shared.state).stopped
+	}
+}
+
+impl<T> Drop for Capture<T> {
+	fn drop(&mut self) {
+		self.shared.stop.store(true, Ordering::Relaxed);
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl<T> std::fmt::Debug for Capture<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let state = lock(&self.shared.state);
+		f.debug_struct("Capture")
+			.field("buffered", &state.buffered)
+			.field("capacity", &state.capacity)
+			.field("dropped", &state.dropped)
+			.field("stopped", &state.stopped)
+			.finish_non_exhaustive()
+	}
+}
+
+impl State {
+	/// Take the oldest chunk from the buffer.
+	fn pop(&mut self) -> Option<Chunk> {
+		let chunk = self.chunks.pop_front()?;
+		self.buffered -= chunk.data.len();
+		Some(chunk)
+	}
+
+	/// Add a chunk to the buffer, discarding the oldest data if the buffer is full.
+	fn push(&mut self, mut chunk: Chunk) {
+		// Keep only the newest data of chunks that are larger than the whole buffer.
+		if chunk.data.len() > self.capacity {
+			let excess = chunk.data.len() - self.capacity;
+			chunk.data.drain(..excess);
+			self.dropped += excess as u64;
+		}
+		while self.buffered + chunk.data.len() > self.capacity {
+			match self.pop() {
+				Some(old) => self.dropped += old.data.len() as u64,
+				None => break,
+			}
+		}
+		if !chunk.data.is_empty() {
+			self.buffered += chunk.data.len();
+			self.chunks.push_back(chunk);
+		}
+	}
+}
+
+/// Read from the transport until the capture is stopped or the transport fails.
+fn capture_thread<T: SerialTransport>(transport: T, shared: &Shared) -> T {
+	let mut buffer = [0; READ_BUFFER_SIZE];
+	let error = loop {
+		if shared.stop.load(Ordering::Relaxed) {
+			break None;
+		}
+		match transport.read(&mut buffer) {
+			Ok(0) => break None,
+			Ok(n) => {
+				let chunk = Chunk {
+					timestamp: transport.now(),
+					data: buffer[..n].to_vec(),
+				};
+				lock(&shared.state).push(chunk);
+				shared.condvar.notify_all();
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+			Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+			Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+			Err(e) => break Some(e),
+		}
+	};
+
+	let mut state = lock(&shared.state);
+	state.stopped = true;
+	state.error = error;
+	drop(state);
+	shared.condvar.notify_all();
+	transport
+}
+
+/// The direction of data mirrored by a [`TeePort`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+	/// The data was read from the transport.
+	Read,
+
+	/// The data was written to the transport.
+	Write,
+}
+
+/// Data read or written through a [`TeePort`].
+#[derive(Debug, Clone)]
+pub struct TeeEvent {
+	/// The direction of the data.
+	pub direction: Direction,
+
+	/// The monotonic time at which the read or write call returned.
+	pub timestamp: Instant,
+
+	/// The data that was read or written.
+	pub data: Vec<u8>,
+}
+
+/// A sink for the data mirrored by a [`TeePort`].
+///
+/// This trait is implemented for closures that take a [`Direction`], an [`Instant`] and a `&[u8]`,
+/// for the sending half of a [`std::sync::mpsc`] channel of [`TeeEvent`]s, and for a [`Recorder`].
+///
+/// The sink is called from the thread doing the read or write, so it should not block for a long time.
+pub trait TeeSink: Send + Sync {
+	/// Record data that was read from or written to the transport.
+	fn record(&self, direction: Direction, timestamp: Instant, data: &[u8]);
+}
+
+impl<F> TeeSink for F
+where
+	F: Fn(Direction, Instant, &[u8]) + Send + Sync,
+{
+	fn record(&self, direction: Direction, timestamp: Instant, data: &[u8]) {
+		self(direction, timestamp, data)
+	}
+}
+
+impl TeeSink for std::sync::mpsc::Sender<TeeEvent> {
+	/// Send the data over the channel.
+	///
+	/// If the receiver has been dropped, the data is discarded.
+	fn record(&self, direction: Direction, timestamp: Instant, data: &[u8]) {
+		let _ = self.send(TeeEvent {
+			direction,
+			timestamp,
+			data: data.to_vec(),
+		});
+	}
+}
+
+/// A transport wrapper that mirrors all data read and written to a [`TeeSink`].
+///
+/// Only data that was actually transferred is mirrored:
+/// for a partial read or write, only the transferred bytes are passed to the sink,
+/// and failed calls are not passed to the sink at all.
+///
+/// See the [module documentation][self] for more information.
+pub struct TeePort<T, S> {
+	inner: T,
+	sink: S,
+}
+
+impl<T: SerialTransport, S: TeeSink> TeePort<T, S> {
+	/// Wrap a transport, mirroring all data read and written to `sink`.
+	pub fn new(inner: T, sink: S) -> Self {
+		Self { inner, sink }
+	}
+
+	/// Get a reference to the wrapped transport.
+	pub fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Get a reference to the sink.
+	pub fn get_sink(&self) -> &S {
+		&self.sink
+	}
+
+	/// Consume the wrapper and get back the transport and the sink.
+	pub fn into_inner(self) -> (T, S) {
+		(self.inner, self.sink)
+	}
+}
+
+impl<T: SerialTransport, S: TeeSink> SerialTransport for TeePort<T, S> {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let read = self.inner.read(buf)?;
+		if read > 0 {
+			self.sink.record(Direction::Read, self.inner.now(), &buf[..read]);
+		}
+		Ok(read)
+	}
+
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		let written = self.inner.write(buf)?;
+		if written > 0 {
+			self.sink.record(Direction::Write, self.inner.now(), &buf[..written]);
+		}
+		Ok(written)
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_read_timeout(timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_read_timeout()
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_write_timeout(timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_write_timeout()
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		self.inner.discard_buffers()
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_rts(state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		self.inner.read_cts()
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_dtr(state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		self.inner.read_dsr()
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		self.inner.read_ri()
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		self.inner.read_cd()
+	}
+
+	fn now(&self) -> Instant {
+		self.inner.now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		self.inner.sleep(duration)
+	}
+}
+
+impl<T: std::fmt::Debug, S> std::fmt::Debug for TeePort<T, S> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TeePort")
+			.field("inner", &self.inner)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`TeeSink`] that writes all traffic to a file or other writer.
+///
+/// Each read or write is written as one line of text with the time in microseconds since the recorder was created,
+/// the direction (`R` or `W`) and the data in hexadecimal:
+/// ```text
+/// # serial2 capture
+/// 1520 W 50494E470A
+/// 3410 R 504F4E470A
+/// ```
+///
+/// Errors from the writer can not be reported to the reads or writes of the [`TeePort`].
+/// Instead, the first error is kept, and it is returned by [`Self::finish()`].
+pub struct Recorder<W> {
+	start: Instant,
+	state: Mutex<RecorderState<W>>,
+}
+
+struct RecorderState<W> {
+	writer: W,
+	error: Option<std::io::Error>,
+}
+
+impl<W: Write + Send> Recorder<W> {
+	/// Create a recorder that writes to the given writer.
+	///
+	/// This immediately writes the header line of the capture format.
+	pub fn new(mut writer: W) -> std::io::Result<Self> {
+		writeln!(writer, "{}", RECORDING_HEADER)?;
+		Ok(Self {
+			start: Instant::now(),
+			state: Mutex::new(RecorderState { writer, error: None }),
+		})
+	}
+
+	/// Flush the writer and get it back.
+	///
+	/// If writing any of the recorded data failed, the first error is returned instead.
+	pub fn finish(self) -> std::io::Result<W> {
+		let state = self.state.into_inner().unwrap_or_else(|e| e.into_inner());
+		if let Some(error) = state.error {
+			return Err(error);
+		}
+		let mut writer = state.writer;
+		writer.flush()?;
+		Ok(writer)
+	}
+}
+
+impl<W: Write + Send> TeeSink for Recorder<W> {
+	fn record(&self, direction: Direction, timestamp: Instant, data: &[u8]) {
+		let direction = match direction {
+			Direction::Read => 'R',
+			Direction::Write => 'W',
+		};
+		let mut line = format!("{} {} ", timestamp.saturating_duration_since(self.start).as_micros(), direction);
+		for byte in data {
+			line.push_str(&format!("{:02X}", byte));
+		}
+		line.push('\n');
+
+		let mut state = lock(&self.state);
+		if state.error.is_none() {
+			if let Err(e) = state.writer.write_all(line.as_bytes()) {
+				state.error = Some(e);
+			}
+		}
+	}
+}
+
+impl<W> std::fmt::Debug for Recorder<W> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Recorder")
+			.field("start", &self.start)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A recorded session, as written by a [`Recorder`].
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+	events: Vec<RecordedEvent>,
+}
+
+/// A single read or write of a [`Recording`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecordedEvent {
+	/// The time since the start of the recording.
+	pub offset: Duration,
+
+	/// The direction of the data.
+	pub direction: Direction,
+
+	/// The data that was read or written.
+	pub data: Vec<u8>,
+}
+
+impl Recording {
+	/// Load a recording from a file.
+	pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+		let file = std::fs::File::open(path)?;
+		Self::parse(std::io::BufReader::new(file))
+	}
+
+	/// Parse a recording in the format written by a [`Recorder`].
+	///
+	/// Empty lines and lines starting with `#` are ignored.
+	/// An error of kind [`std::io::ErrorKind::InvalidData`] is returned if any other line is not a valid event.
+	pub fn parse(reader: impl BufRead) -> std::io::Result<Self> {
+		let mut events = Vec::new();
+		for (index, line) in reader.lines().enumerate() {
+			let line = line?;
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let event = parse_event(line).ok_or_else(|| {
+				std::io::Error::new(
+					std::io::ErrorKind::InvalidData,
+					format!("invalid event on line {} of recording: {:?}", index + 1, line),
+				)
+			})?;
+			events.push(event);
+		}
+		Ok(Self { events })
+	}
+
+	/// Get the recorded events.
+	pub fn events(&self) -> &[RecordedEvent] {
+		&self.events
+	}
+
+	/// Get all data that was written during the recording, concatenated.
+	///
+	/// You can compare this to [`MockSerialPort::take_written()`][crate::mock::MockSerialPort::take_written]
+	/// after replaying the recording, to check that the code under test sent the same data.
+	pub fn written_data(&self) -> Vec<u8> {
+		self.data(Direction::Write)
+	}
+
+	/// Get all data that was read during the recording, concatenated.
+	pub fn read_data(&self) -> Vec<u8> {
+		self.data(Direction::Read)
+	}
+
+	fn data(&self, direction: Direction) -> Vec<u8> {
+		self.events
+			.iter()
+			.filter(|event| event.direction == direction)
+			.flat_map(|event| event.data.iter().copied())
+			.collect()
+	}
+
+	/// Queue the recorded reads on a mock serial port, with the original timing.
+	///
+	/// Before each read event, a delay is queued with the time between that event and the previous event of the recording.
+	/// Because a delay starts when a read reaches it,
+	/// this reproduces the response time of the device relative to the data written by the code under test.
+	///
+	/// The recorded writes are not queued, but you can check them with [`Self::written_data()`].
+	#[cfg(any(feature = "doc", feature = "mock"))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "mock")))]
+	pub fn replay(&self, port: &crate::mock::MockSerialPort) {
+		let mut previous = Duration::ZERO;
+		for event in &self.events {
+			if event.direction == Direction::Read {
+				let delay = event.offset.saturating_sub(previous);
+				if !delay.is_zero() {
+					port.push_read_delay(delay);
+				}
+				port.push_read_data(&event.data);
+			}
+			previous = event.offset;
+		}
+	}
+}
+
+/// Parse a single event line of a recording.
+fn parse_event(line: &str) -> Option<RecordedEvent> {
+	let mut fields = line.split_whitespace();
+	let offset = Duration::from_micros(fields.next()?.parse().ok()?);
+	let direction = match fields.next()? {
+		"R" => Direction::Read,
+		"W" => Direction::Write,
+		_ => return None,
+	};
+	let hex = fields.next().unwrap_or("");
+	if fields.next().is_some() {
+		return None;
+	}
+	let data = (0..hex.len())
+		.step_by(2)
+		// An odd number of digits fails because the last slice is out of bounds.
+		.map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+		.collect::<Option<Vec<u8>>>()?;
+	Some(RecordedEvent { offset, direction, data })
+}
+
+/// The first line of the capture format written by a [`Recorder`].
+const RECORDING_HEADER: &str = "# serial2 capture";
+
+fn stopped_error() -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::BrokenPipe, "the capture has stopped")
+}
@@ -0,0 +1,71 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A source of time for timeouts and delays of the mock serial port.
+///
+/// Code that waits with a condition variable uses [`Self::wait_timeout()`] to know how long to wait for real,
+/// so that a manual clock can make the wait last until the clock is advanced instead.
+///
+/// Only [`MockSerialPort`][crate::mock::MockSerialPort] uses this clock directly.
+/// The transport wrappers follow it through [`SerialTransport::now()`][crate::SerialTransport::now]
+/// and [`SerialTransport::sleep()`][crate::SerialTransport::sleep] of the mock port.
+pub(crate) trait Clock: Send + Sync {
+	/// Get the current time.
+	fn now(&self) -> Instant;
+
+	/// Get the real time to wait until the clock reaches `until`.
+	///
+	/// Returns `None` if the clock does not advance by itself.
+	/// The caller must then wait until it is notified that the clock was advanced.
+	fn wait_timeout(&self, until: Instant) -> Option<Duration>;
+}
+
+/// The system clock, using [`Instant::now()`].
+#[derive(Debug, Default)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+
+	fn wait_timeout(&self, until: Instant) -> Option<Duration> {
+		Some(until.saturating_duration_since(Instant::now()))
+	}
+}
+
+/// A clock that only advances when [`Self::advance()`] is called.
+#[derive(Debug)]
+pub(crate) struct ManualClock {
+	/// The real time when the clock was created, used as the starting point of manual time.
+	start: Instant,
+
+	/// The total time the clock was advanced by.
+	elapsed: Mutex<Duration>,
+}
+
+impl ManualClock {
+	/// Create a new manual clock, starting at the current system time.
+	pub fn new() -> Self {
+		Self {
+			start: Instant::now(),
+			elapsed: Mutex::new(Duration::ZERO),
+		}
+	}
+
+	/// Advance the clock by the given duration.
+	pub fn advance(&self, duration: Duration) {
+		let mut elapsed = self.elapsed.lock().unwrap_or_else(|e| e.into_inner());
+		*elapsed = elapsed.saturating_add(duration);
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> Instant {
+		self.start + *self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn wait_timeout(&self, _until: Instant) -> Option<Duration> {
+		None
+	}
+}
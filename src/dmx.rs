@@ -0,0 +1,280 @@
+//! DMX512 transmission for lighting controllers.
+//!
+//! DMX512 is a unidirectional protocol on top of RS-485, used to control stage lighting and effects.
+//! The controller continuously transmits frames (called packets) with the values for up to 512 channels (called slots),
+//! which together make up a *universe*.
+//!
+//! Each packet starts with a break of at least 88 µs, followed by a mark after break of at least 8 µs,
+//! a start code (`0` for regular dimmer data) and the slot values.
+//! All bytes are transmitted at 250 kbaud with 8 data bits, no parity and 2 stop bits.
+//!
+//! This module works with any serial port that supports a baud rate of 250 kbaud and break conditions,
+//! like the common RS-485 dongles based on FTDI chips.
+//! Use [`configure()`] and [`send_packet()`] to transmit packets yourself,
+//! or a [`DmxSender`] to transmit a universe at a fixed refresh rate on a dedicated thread.
+//!
+//! The break and mark after break are timed with [`std::thread::sleep()`].
+//! Because the OS may sleep longer than requested, both are usually longer than configured.
+//! This is allowed by the standard, but a heavily loaded system can lower the achieved refresh rate.
+//!
+//! # Example
+//! ```no_run
+//! # fn example() -> std::io::Result<()> {
+//! use serial2::SerialPort;
+//! use serial2::dmx::{DmxSender, Timing};
+//!
+//! let port = SerialPort::open("/dev/ttyUSB0", serial2::KeepSettings)?;
+//! let sender = DmxSender::start(port, Timing::default())?;
+//!
+//! // Set channel 1 (for example, the dimmer of a fixture) to full brightness.
+//! sender.set_channel(1, 255);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::util::lock;
+use crate::{CharSize, FlowControl, Parity, SerialPort, StopBits};
+
+/// The baud rate used by DMX512.
+pub const BAUD_RATE: u32 = 250_000;
+
+/// The maximum number of slots in a packet.
+pub const MAX_SLOTS: usize = 512;
+
+/// The start code for regular dimmer data.
+pub const NULL_START_CODE: u8 = 0x00;
+
+/// The timing of transmitted packets.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Timing {
+	/// The duration of the break at the start of each packet.
+	///
+	/// The standard requires at least 88 µs. The default is 176 µs.
+	pub break_duration: Duration,
+
+	/// The duration of the mark after break, between the break and the start code.
+	///
+	/// The standard requires at least 8 µs. The default is 12 µs.
+	pub mark_after_break: Duration,
+
+	/// The time between the start of consecutive packets, used by [`DmxSender`].
+	///
+	/// A packet with all 512 slots takes about 23 ms to transmit, so shorter intervals can not be achieved for a full universe.
+	/// The standard also requires a packet at least once per second.
+	/// The default is 25 ms, for a refresh rate of 40 Hz.
+	pub refresh_interval: Duration,
+}
+
+impl Default for Timing {
+	fn default() -> Self {
+		Self {
+			break_duration: Duration::from_micros(176),
+			mark_after_break: Duration::from_micros(12),
+			refresh_interval: Duration::from_millis(25),
+		}
+	}
+}
+
+/// Configure a serial port for DMX512: 250 kbaud, 8 data bits, no parity, 2 stop bits and no flow control.
+pub fn configure(port: &SerialPort) -> std::io::Result<()> {
+	port.modify_configuration(|settings| {
+		settings.set_raw();
+		settings.set_baud_rate(BAUD_RATE)?;
+		settings.set_char_size(CharSize::Bits8);
+		settings.set_parity(Parity::None);
+		settings.set_stop_bits(StopBits::Two);
+		settings.set_flow_control(FlowControl::None);
+		Ok(())
+	})
+}
+
+/// Transmit a single DMX512 packet.
+///
+/// This waits for previously written data to be transmitted,
+/// sends the break and mark after break, and writes the start code followed by the slot values.
+/// It returns as soon as the data is queued in the OS buffer.
+///
+/// The serial port must already be configured with [`configure()`].
+///
+/// If there are more than [`MAX_SLOTS`] slot values, an error of kind [`std::io::ErrorKind::InvalidInput`] is returned.
+pub fn send_packet(port: &SerialPort, timing: &Timing, start_code: u8, slots: &[u8]) -> std::io::Result<()> {
+	if slots.len() > MAX_SLOTS {
+		return Err(std::io::Error::new(
+			std::io::ErrorKind::InvalidInput,
+			format!("a DMX512 packet can hold at most {MAX_SLOTS} slots, got {}", slots.len()),
+		));
+	}
+
+	let mut packet = [0; MAX_SLOTS + 1];
+	packet[0] = start_code;
+	packet[1..][..slots.len()].copy_from_slice(slots);
+
+	// The break must not cut off the end of the previous packet.
+	port.flush()?;
+	port.send_break(timing.break_duration)?;
+	std::thread::sleep(timing.mark_after_break);
+	port.write_all(&packet[..slots.len() + 1])
+}
+
+/// Transmit a DMX512 universe at a fixed refresh rate on a dedicated thread.
+///
+/// The sender starts with a universe of 512 slots set to zero and the [`NULL_START_CODE`].
+/// Changes to the universe are picked up by the next transmitted packet.
+///
+/// If transmitting a packet fails, the sender thread stops,
+/// and the error is reported by [`Self::stop()`].
+pub struct DmxSender {
+	/// State shared with the sender thread.
+	shared: Arc<Shared>,
+
+	/// The sender thread, which returns the serial port when it stops.
+	thread: Option<JoinHandle<std::io::Result<SerialPort>>>,
+}
+
+struct Shared {
+	universe: Mutex<Universe>,
+	stop: AtomicBool,
+}
+
+#[derive(Clone)]
+struct Universe {
+	/// The start code of the packets.
+	start_code: u8,
+
+	/// The slot values.
+	slots: [u8; MAX_SLOTS],
+
+	/// The number of slots to transmit.
+	len: usize,
+}
+
+impl DmxSender {
+	/// Configure the serial port for DMX512 and start transmitting packets.
+	pub fn start(port: SerialPort, timing: Timing) -> std::io::Result<Self> {
+		configure(&port)?;
+		let shared = Arc::new(Shared {
+			universe: Mutex::new(Universe {
+				start_code: NULL_START_CODE,
+				slots: [0; MAX_SLOTS],
+				len: MAX_SLOTS,
+			}),
+			stop: AtomicBool::new(false),
+		});
+
+		let thread = std::thread::Builder::new().name("serial2-dmx".into()).spawn({
+			let shared = shared.clone();
+			move || sender_thread(port, &timing, &shared)
+		})?;
+
+		Ok(Self {
+			shared,
+			thread: Some(thread),
+		})
+	}
+
+	/// Set the value of a single channel.
+	///
+	/// Channels are numbered from 1 to 512, like on lighting consoles.
+	///
+	/// # Panics
+	/// This function panics if the channel is 0 or higher than 512.
+	pub fn set_channel(&self, channel: usize, value: u8) {
+		assert!((1..=MAX_SLOTS).contains(&channel), "DMX512 channel must be in the range 1 to 512, got {channel}");
+		let mut universe = lock(&self.shared.universe);
+		universe.slots[channel - 1] = value;
+		universe.len = universe.len.max(channel);
+	}
+
+	/// Get the value of a single channel.
+	///
+	/// Channels are numbered from 1 to 512, like on lighting consoles.
+	///
+	/// # Panics
+	/// This function panics if the channel is 0 or higher than 512.
+	pub fn get_channel(&self, channel: usize) -> u8 {
+		assert!((1..=MAX_SLOTS).contains(&channel), "DMX512 channel must be in the range 1 to 512, got {channel}");
+		lock(&self.shared.universe).slots[channel - 1]
+	}
+
+	/// Replace all slot values.
+	///
+	/// Only the given number of slots is transmitted in each packet,
+	/// which allows for a higher refresh rate if you do not need the full universe.
+	///
+	/// If there are more than [`MAX_SLOTS`] slot values, an error of kind [`std::io::ErrorKind::InvalidInput`] is returned.
+	pub fn set_universe(&self, slots: &[u8]) -> std::io::Result<()> {
+		if slots.len() > MAX_SLOTS {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("a DMX512 universe can hold at most {MAX_SLOTS} slots, got {}", slots.len()),
+			));
+		}
+		let mut universe = lock(&self.shared.universe);
+		universe.slots[..slots.len()].copy_from_slice(slots);
+		universe.slots[slots.len()..].fill(0);
+		universe.len = slots.len();
+		Ok(())
+	}
+
+	/// Set the start code of the transmitted packets.
+	///
+	/// Use [`NULL_START_CODE`] for regular dimmer data.
+	pub fn set_start_code(&self, start_code: u8) {
+		lock(&self.shared.universe).start_code = start_code;
+	}
+
+	/// Check if the sender thread is still running.
+	///
+	/// The sender thread stops when transmitting a packet fails.
+	pub fn is_running(&self) -> bool {
+		self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+	}
+
+	/// Stop transmitting and get back the serial port.
+	///
+	/// This waits for the packet that is currently being transmitted to be queued.
+	/// If the sender thread stopped because of an error, the error is returned instead.
+	pub fn stop(mut self) -> std::io::Result<SerialPort> {
+		self.shared.stop.store(true, Ordering::Relaxed);
+		match self.thread.take().map(|thread| thread.join()) {
+			Some(Ok(result)) => result,
+			Some(Err(panic)) => std::panic::resume_unwind(panic),
+			None => unreachable!("the sender thread is only taken when stopping the sender"),
+		}
+	}
+}
+
+impl Drop for DmxSender {
+	fn drop(&mut self) {
+		self.shared.stop.store(true, Ordering::Relaxed);
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl std::fmt::Debug for DmxSender {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let universe = lock(&self.shared.universe);
+		f.debug_struct("DmxSender")
+			.field("start_code", &universe.start_code)
+			.field("slots", &&universe.slots[..universe.len])
+			.field("running", &self.is_running())
+			.finish_non_exhaustive()
+	}
+}
+
+fn sender_thread(port: SerialPort, timing: &Timing, shared: &Shared) -> std::io::Result<SerialPort> {
+	while !shared.stop.load(Ordering::Relaxed) {
+		let next_packet = Instant::now() + timing.refresh_interval;
+		let universe = lock(&shared.universe).clone();
+		send_packet(&port, timing, universe.start_code, &universe.slots[..universe.len])?;
+		std::thread::sleep(next_packet.saturating_duration_since(Instant::now()));
+	}
+	Ok(port)
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use crate::Settings;
+
+/// Error with additional context about the failed operation.
+///
+/// This error type is returned by the `_detailed` variants of some functions of [`SerialPort`][crate::SerialPort],
+/// such as [`SerialPort::open_detailed()`][crate::SerialPort::open_detailed].
+/// It wraps the underlying [`std::io::Error`], together with the name of the serial port
+/// and (if it could be determined) the setting that could not be applied.
+///
+/// The error can be converted into a [`std::io::Error`] with the same [`kind()`][std::io::Error::kind],
+/// so it can be propagated with `?` from functions that return a [`std::io::Result`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+	/// Failed to open the serial port.
+	Open {
+		/// The name of the serial port.
+		port: PathBuf,
+
+		/// The underlying I/O error.
+		source: std::io::Error,
+	},
+
+	/// Failed to apply the configuration of the serial port.
+	Configure {
+		/// The name of the serial port, if it was opened by name.
+		port: Option<PathBuf>,
+
+		/// The first setting that does not match the requested configuration, if it could be determined.
+		setting: Option<Setting>,
+
+		/// The underlying I/O error.
+		source: std::io::Error,
+	},
+}
+
+/// A serial port setting, used to report which setting could not be applied.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Setting {
+	/// The baud rate.
+	BaudRate,
+
+	/// The character size.
+	CharSize,
+
+	/// The number of stop bits.
+	StopBits,
+
+	/// The parity mode.
+	Parity,
+
+	/// The flow control mode.
+	FlowControl,
+}
+
+impl Error {
+	/// Get the name of the serial port, if it is known.
+	pub fn port(&self) -> Option<&Path> {
+		match self {
+			Self::Open { port, .. } => Some(port),
+			Self::Configure { port, .. } => port.as_deref(),
+		}
+	}
+
+	/// Get the setting that could not be applied, if it could be determined.
+	pub fn setting(&self) -> Option<Setting> {
+		match self {
+			Self::Open { .. } => None,
+			Self::Configure { setting, .. } => *setting,
+		}
+	}
+
+	/// Get the kind of the underlying I/O error.
+	pub fn kind(&self) -> std::io::ErrorKind {
+		self.io_error().kind()
+	}
+
+	/// Get a reference to the underlying I/O error.
+	pub fn io_error(&self) -> &std::io::Error {
+		match self {
+			Self::Open { source, .. } => source,
+			Self::Configure { source, .. } => source,
+		}
+	}
+
+	/// Get the underlying I/O error, discarding the context.
+	pub fn into_io_error(self) -> std::io::Error {
+		match self {
+			Self::Open { source, .. } => source,
+			Self::Configure { source, .. } => source,
+		}
+	}
+}
+
+impl Setting {
+	/// Get a human readable name of the setting.
+	pub fn as_str(self) -> &'static str {
+		match self {
+			Self::BaudRate => "baud rate",
+			Self::CharSize => "character size",
+			Self::StopBits => "stop bits",
+			Self::Parity => "parity",
+			Self::FlowControl => "flow control",
+		}
+	}
+
+	/// Find the first setting that differs between the applied and the requested settings.
+	pub(crate) fn find_mismatch(applied: &Settings, requested: &Settings) -> Option<Self> {
+		if !baud_rate_matches(applied, requested) {
+			Some(Self::BaudRate)
+		} else if applied.get_char_size().ok() != requested.get_char_size().ok() {
+			Some(Self::CharSize)
+		} else if applied.get_stop_bits().ok() != requested.get_stop_bits().ok() {
+			Some(Self::StopBits)
+		} else if applied.get_parity().ok() != requested.get_parity().ok() {
+			Some(Self::Parity)
+		} else if applied.get_flow_control().ok() != requested.get_flow_control().ok() {
+			Some(Self::FlowControl)
+		} else {
+			None
+		}
+	}
+}
+
+/// Check if the applied baud rate matches the requested baud rate.
+///
+/// Like the verification done when applying settings on Unix, this allows for a 2.5% deviation.
+fn baud_rate_matches(applied: &Settings, requested: &Settings) -> bool {
+	match (applied.get_baud_rate(), requested.get_baud_rate()) {
+		(Ok(applied), Ok(requested)) => applied.abs_diff(requested) <= requested / 40,
+		(Err(_), Err(_)) => true,
+		_ => false,
+	}
+}
+
+impl std::fmt::Display for Setting {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// The error returned when the applied settings do not match the requested settings.
+///
+/// This is wrapped in a [`std::io::Error`], so the mismatching setting can still be reported
+/// after the previous configuration has been restored.
+#[derive(Debug)]
+pub(crate) struct MismatchError {
+	pub(crate) setting: Option<Setting>,
+}
+
+impl MismatchError {
+	/// Create an I/O error for settings that were not applied as requested.
+	pub(crate) fn new_io_error(applied: &Settings, requested: &Settings) -> std::io::Error {
+		let setting = Setting::find_mismatch(applied, requested);
+		std::io::Error::other(Self { setting })
+	}
+
+	/// Get the mismatching setting from an I/O error, if it was created by [`Self::new_io_error()`].
+	pub(crate) fn setting_from_io_error(error: &std::io::Error) -> Option<Option<Setting>> {
+		let error = error.get_ref()?.downcast_ref::<Self>()?;
+		Some(error.setting)
+	}
+}
+
+impl std::fmt::Display for MismatchError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("failed to apply some or all settings")
+	}
+}
+
+impl std::error::Error for MismatchError {}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Open { port, source } => {
+				write!(f, "failed to open serial port {}: {}", port.display(), source)
+			},
+			Self::Configure { port, setting, source } => {
+				f.write_str("failed to configure serial port")?;
+				if let Some(port) = port {
+					write!(f, " {}", port.display())?;
+				}
+				if let Some(setting) = setting {
+					write!(f, ": failed to apply {}", setting)?;
+				}
+				write!(f, ": {}", source)
+			},
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(self.io_error())
+	}
+}
+
+impl From<Error> for std::io::Error {
+	fn from(error: Error) -> Self {
+		std::io::Error::new(error.kind(), error)
+	}
+}
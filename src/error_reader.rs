@@ -0,0 +1,233 @@
+use crate::SerialPort;
+
+/// A reader that reports parity errors, framing errors and break conditions together with the received data.
+///
+/// Normally, the OS silently drops or replaces bytes that were received with an error.
+/// This reader configures the serial port to report them instead,
+/// which is useful for protocol analyzers and other applications that must not lose corrupted data.
+///
+/// Each call to [`Self::read()`] returns a chunk of data together with the errors that apply to it:
+/// * On Unix, this uses the `PARMRK` input flag, which marks each byte that was received with an error.
+///   A byte with an error is always returned in a chunk of its own, so the errors apply to exactly that byte.
+///   A break condition is reported as a single `0` byte.
+/// * On Windows, this uses the errors reported by `ClearCommError()` after each read.
+///   The driver does not report which byte was affected, so the errors apply to the chunk as a whole.
+///   Errors that are detected while the data is still buffered by the driver may be reported with a later chunk.
+///
+/// Parity checking still needs to be enabled with [`Settings::set_parity()`][crate::Settings::set_parity]
+/// to detect parity errors.
+#[derive(Debug)]
+pub struct ErrorReader<'a> {
+	/// The serial port to read from.
+	port: &'a SerialPort,
+
+	/// Received data that has not been decoded yet.
+	#[cfg(unix)]
+	raw: Vec<u8>,
+}
+
+/// The errors detected by the serial port while receiving data.
+///
+/// See [`ErrorReader`] for more information.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct LineErrors {
+	parity_or_framing: bool,
+	break_condition: bool,
+	overrun: bool,
+}
+
+/// A chunk of data read by an [`ErrorReader`], together with the errors that apply to it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ReadChunk {
+	/// The number of bytes read into the buffer.
+	pub len: usize,
+
+	/// The errors that apply to the data.
+	pub errors: LineErrors,
+}
+
+impl<'a> ErrorReader<'a> {
+	/// Create a reader that reports errors in the received data.
+	///
+	/// This changes the configuration of the serial port,
+	/// so that bytes with errors and break conditions are reported instead of dropped:
+	/// * On Unix, the `PARMRK` and `INPCK` input flags are set,
+	///   and the `IGNPAR`, `IGNBRK`, `BRKINT` and `ISTRIP` input flags are cleared.
+	/// * On Windows, `fErrorChar`, `fNull` and `fAbortOnError` are disabled in the `DCB`.
+	///
+	/// The configuration is not restored when the reader is dropped.
+	/// Use [`SerialPort::save_settings()`] before creating the reader if you need to restore it later.
+	///
+	/// While the serial port is configured like this, reading directly from the serial port on Unix returns the raw marked data.
+	pub fn new(port: &'a SerialPort) -> std::io::Result<Self> {
+		port.modify_configuration(|settings| {
+			#[cfg(unix)] {
+				let termios = &mut settings.inner.termios;
+				termios.c_iflag |= libc::PARMRK | libc::INPCK;
+				termios.c_iflag &= !(libc::IGNPAR | libc::IGNBRK | libc::BRKINT | libc::ISTRIP);
+			}
+			#[cfg(windows)] {
+				let dcb = &mut settings.inner.dcb;
+				dcb.set_fErrorChar(0);
+				dcb.set_fNull(0);
+				dcb.set_fAbortOnError(0);
+			}
+			Ok(())
+		})?;
+
+		// Do not report errors that happened before the reader was created.
+		#[cfg(windows)]
+		port.inner.take_comm_errors()?;
+
+		Ok(Self {
+			port,
+			#[cfg(unix)]
+			raw: Vec::new(),
+		})
+	}
+
+	/// Get the serial port that the reader reads from.
+	pub fn port(&self) -> &'a SerialPort {
+		self.port
+	}
+
+	/// Read data from the serial port, and report the errors that apply to it.
+	///
+	/// A chunk with a length of `0` means that the serial port reported end-of-file,
+	/// or that the buffer is empty.
+	///
+	/// Errors are reported using the read timeout and [`TimeoutBehavior`][crate::TimeoutBehavior] of the serial port.
+	pub fn read(&mut self, buf: &mut [u8]) -> std::io::Result<ReadChunk> {
+		#[cfg(unix)] {
+			self.read_unix(buf)
+		}
+		#[cfg(windows)] {
+			let len = self.port.read(buf)?;
+			let errors = LineErrors::from_comm_errors(self.port.inner.take_comm_errors()?);
+			Ok(ReadChunk { len, errors })
+		}
+	}
+
+	#[cfg(unix)]
+	fn read_unix(&mut self, buf: &mut [u8]) -> std::io::Result<ReadChunk> {
+		if buf.is_empty() {
+			return Ok(ReadChunk { len: 0, errors: LineErrors::default() });
+		}
+
+		loop {
+			if let Some(chunk) = self.decode(buf) {
+				return Ok(chunk);
+			}
+
+			// Each decoded byte takes at least one raw byte, so read no more than fits in the buffer.
+			let start = self.raw.len();
+			self.raw.resize(start + buf.len(), 0);
+			let read = match self.port.read(&mut self.raw[start..]) {
+				Ok(read) => read,
+				Err(e) => {
+					self.raw.truncate(start);
+					return Err(e);
+				},
+			};
+			self.raw.truncate(start + read);
+
+			// Discard an incomplete escape sequence at end-of-file.
+			if read == 0 {
+				self.raw.clear();
+				return Ok(ReadChunk { len: 0, errors: LineErrors::default() });
+			}
+		}
+	}
+
+	/// Decode the data marked by `PARMRK` into the buffer.
+	///
+	/// Returns `None` if there is no complete data to decode.
+	#[cfg(unix)]
+	fn decode(&mut self, buf: &mut [u8]) -> Option<ReadChunk> {
+		let mut len = 0;
+		let mut consumed = 0;
+		let mut errors = LineErrors::default();
+
+		while len < buf.len() && consumed < self.raw.len() {
+			let remaining = &self.raw[consumed..];
+			match remaining {
+				// An escaped `0xFF` byte.
+				[0xFF, 0xFF, ..] => {
+					buf[len] = 0xFF;
+					len += 1;
+					consumed += 2;
+				},
+				// A byte with an error.
+				[0xFF, 0x00, byte, ..] => {
+					// Return the byte in a chunk of its own.
+					if len > 0 {
+						break;
+					}
+					buf[0] = *byte;
+					len = 1;
+					consumed += 3;
+					if *byte == 0 {
+						errors.break_condition = true;
+					} else {
+						errors.parity_or_framing = true;
+					}
+					break;
+				},
+				// An incomplete escape sequence: wait for the rest.
+				[0xFF] | [0xFF, 0x00] => break,
+				[byte, ..] => {
+					buf[len] = *byte;
+					len += 1;
+					consumed += 1;
+				},
+				[] => unreachable!("the loop stops when all raw data is consumed"),
+			}
+		}
+
+		self.raw.drain(..consumed);
+		if len == 0 {
+			None
+		} else {
+			Some(ReadChunk { len, errors })
+		}
+	}
+}
+
+impl LineErrors {
+	/// Check if no errors were detected.
+	pub fn is_empty(&self) -> bool {
+		!self.parity_or_framing && !self.break_condition && !self.overrun
+	}
+
+	/// Check if data was received with a parity error or a framing error.
+	///
+	/// On Unix, parity errors and framing errors can not be distinguished.
+	pub fn parity_or_framing(&self) -> bool {
+		self.parity_or_framing
+	}
+
+	/// Check if a break condition was detected.
+	///
+	/// On Unix, a parity or framing error on a `0` byte can not be distinguished from a break condition.
+	pub fn break_condition(&self) -> bool {
+		self.break_condition
+	}
+
+	/// Check if received data was lost because the hardware or OS buffer overflowed.
+	///
+	/// This is only reported on Windows.
+	pub fn overrun(&self) -> bool {
+		self.overrun
+	}
+
+	/// Convert the `CE_*` flags reported by `ClearCommError()`.
+	#[cfg(windows)]
+	fn from_comm_errors(errors: u32) -> Self {
+		use winapi::um::winbase::{CE_BREAK, CE_FRAME, CE_OVERRUN, CE_RXOVER, CE_RXPARITY};
+		Self {
+			parity_or_framing: errors & (CE_RXPARITY | CE_FRAME) != 0,
+			break_condition: errors & CE_BREAK != 0,
+			overrun: errors & (CE_OVERRUN | CE_RXOVER) != 0,
+		}
+	}
+}
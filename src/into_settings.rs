@@ -1,4 +1,4 @@
-use crate::Settings;
+use crate::{CharSize, Parity, Settings, StopBits};
 
 /// Trait for objects that can configure a serial port.
 ///
@@ -7,6 +7,10 @@ use crate::Settings;
 /// configure a character size of 8 bits with 1 stop bit,
 /// and it disables paritity checks and flow control.
 ///
+/// To also choose the character size, parity and stop bits, pass a tuple like `(9600, CharSize::Bits7, Parity::Even, StopBits::One)`,
+/// or the same settings in the common short notation: `(9600, "7E1")`.
+/// You can also pass a reference to a complete [`Settings`] struct.
+///
 /// For more control, it is possible to pass a `Fn(Settings) -> std::io::Result<Settings>`.
 /// If you do, you will generally want to start with a call to [`Settings::set_raw()`].
 ///
@@ -34,6 +38,43 @@ impl IntoSettings for u32 {
 	}
 }
 
+impl IntoSettings for (u32, CharSize, Parity, StopBits) {
+	fn apply_to_settings(self, settings: &mut Settings) -> std::io::Result<()> {
+		let (baud_rate, char_size, parity, stop_bits) = self;
+		settings.set_raw();
+		settings.set_baud_rate(baud_rate)?;
+		settings.set_char_size(char_size);
+		settings.set_parity(parity);
+		settings.set_stop_bits(stop_bits);
+		Ok(())
+	}
+}
+
+/// Use a baud rate and a frame format in the common short notation, like `"8N1"` or `"7E1"`.
+///
+/// The frame format consists of the character size (5 to 8),
+/// the parity (`N` for none, `O` for odd, `E` for even, `M` for mark or `S` for space, case insensitive)
+/// and the number of stop bits (1 or 2).
+impl IntoSettings for (u32, &str) {
+	fn apply_to_settings(self, settings: &mut Settings) -> std::io::Result<()> {
+		let (baud_rate, format) = self;
+		let (char_size, parity, stop_bits) = parse_frame_format(format).ok_or_else(|| {
+			std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("invalid frame format: {format:?}, expected something like \"8N1\""),
+			)
+		})?;
+		(baud_rate, char_size, parity, stop_bits).apply_to_settings(settings)
+	}
+}
+
+impl IntoSettings for &Settings {
+	fn apply_to_settings(self, settings: &mut Settings) -> std::io::Result<()> {
+		settings.clone_from(self);
+		Ok(())
+	}
+}
+
 /// A serial port "configuration" that simply keeps all existing settings.
 ///
 /// You can pass this to [`SerialPort::open()`][crate::SerialPort::open()] to prevent it from changing any port settings.
@@ -47,3 +88,31 @@ impl IntoSettings for KeepSettings {
 		Ok(())
 	}
 }
+
+/// Parse a frame format like `"8N1"` into the character size, parity and stop bits.
+fn parse_frame_format(format: &str) -> Option<(CharSize, Parity, StopBits)> {
+	let &[char_size, parity, stop_bits] = format.as_bytes() else {
+		return None;
+	};
+	let char_size = match char_size {
+		b'5' => CharSize::Bits5,
+		b'6' => CharSize::Bits6,
+		b'7' => CharSize::Bits7,
+		b'8' => CharSize::Bits8,
+		_ => return None,
+	};
+	let parity = match parity.to_ascii_uppercase() {
+		b'N' => Parity::None,
+		b'O' => Parity::Odd,
+		b'E' => Parity::Even,
+		b'M' => Parity::Mark,
+		b'S' => Parity::Space,
+		_ => return None,
+	};
+	let stop_bits = match stop_bits {
+		b'1' => StopBits::One,
+		b'2' => StopBits::Two,
+		_ => return None,
+	};
+	Some((char_size, parity, stop_bits))
+}
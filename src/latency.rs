@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use crate::SerialTransport;
+
+/// Round-trip time statistics, as measured by [`measure_rtt()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RttStats {
+	pub(crate) iterations: usize,
+	pub(crate) min: Duration,
+	pub(crate) max: Duration,
+	pub(crate) total: Duration,
+}
+
+impl RttStats {
+	/// Get the number of measured request/response exchanges.
+	pub fn iterations(&self) -> usize {
+		self.iterations
+	}
+
+	/// Get the shortest measured round-trip time.
+	pub fn min(&self) -> Duration {
+		self.min
+	}
+
+	/// Get the longest measured round-trip time.
+	pub fn max(&self) -> Duration {
+		self.max
+	}
+
+	/// Get the average round-trip time.
+	pub fn avg(&self) -> Duration {
+		self.total / self.iterations as u32
+	}
+
+	/// Add a measured round-trip time.
+	fn record(&mut self, rtt: Duration) {
+		self.iterations += 1;
+		self.min = self.min.min(rtt);
+		self.max = self.max.max(rtt);
+		self.total += rtt;
+	}
+}
+
+/// Measure the round-trip time of a request/response exchange.
+///
+/// This discards the input and output buffers of the transport,
+/// and then repeatedly writes the request and reads a response of `response_len` bytes.
+/// The time from the start of the write until the last byte of the response is received
+/// is measured with a monotonic clock, and summarized over all iterations.
+///
+/// The content of the response is not checked.
+/// The device on the other side must reply to each request with exactly `response_len` bytes,
+/// or a loopback plug must be used with a `response_len` equal to the length of the request.
+///
+/// Each response must be received within the read timeout of the transport.
+/// The first error aborts the measurement and is returned.
+///
+/// This is useful to qualify USB serial adapters (which often buffer received data for a while)
+/// and remote links such as RFC 2217 servers.
+///
+/// If `iterations` is zero, an error of kind [`std::io::ErrorKind::InvalidInput`] is returned.
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::SerialPort;
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+/// let stats = serial2::measure_rtt(&port, b"PING\n", 5, 100)?;
+/// println!("min: {:?}, avg: {:?}, max: {:?}", stats.min(), stats.avg(), stats.max());
+/// # Ok(())
+/// # }
+/// ```
+pub fn measure_rtt<T: SerialTransport + ?Sized>(
+	transport: &T,
+	request: &[u8],
+	response_len: usize,
+	iterations: usize,
+) -> std::io::Result<RttStats> {
+	if iterations == 0 {
+		return Err(std::io::Error::new(
+			std::io::ErrorKind::InvalidInput,
+			"round-trip time measurement needs at least one iteration",
+		));
+	}
+
+	transport.discard_buffers()?;
+	let mut response = vec![0; response_len];
+	let mut stats = RttStats {
+		iterations: 0,
+		min: Duration::MAX,
+		max: Duration::ZERO,
+		total: Duration::ZERO,
+	};
+	for _ in 0..iterations {
+		let start = transport.now();
+		transport.write_all(request)?;
+		transport.read_exact(&mut response)?;
+		stats.record(transport.now().saturating_duration_since(start));
+	}
+	Ok(stats)
+}
@@ -6,7 +6,7 @@
 //! Currently supported features:
 //! * Simple interface: one [`SerialPort`] struct for all supported platforms.
 //! * List available ports.
-//! * Custom baud rates on all supported platforms except Solaris and Illumos.
+//! * Custom baud rates on all supported platforms except Solaris, Illumos and Haiku.
 //! * Concurrent reads and writes from multiple threads, even on Windows.
 //! * Purge the OS buffers (useful to discard read noise when the line should have been silent, for example).
 //! * Read and control individual modem status lines to use them as general purpose I/O.
@@ -18,11 +18,21 @@
 //!   * Flow control
 //!   * Read/write timeouts
 //! * Full access to platform specific serial port settings using target specific feature flags (`"unix"` or `"windows"`).
+//! * In-memory mock serial port for testing protocol code without hardware (`"mock"` feature).
+//! * Client for remote serial ports using RFC 2217 over TCP (`"rfc2217"` feature).
+//! * Transport for serial ports forwarded over a raw TCP connection (`"tcp"` feature).
+//! * Reset sequences for Arduino and ESP bootloaders (`"bootstrap"` feature).
+//! * Continuous capture of received data with timestamps, for building protocol analyzers (`"capture"` feature).
+//! * DMX512 transmission for lighting controllers (`"dmx"` feature).
+//! * MIDI over serial ports, with reassembly of received messages (`"midi"` feature).
+//! * USB CDC-ACM devices through a usbfs file descriptor, for unrooted Android apps (`"usbfs"` feature).
+//! * Zero-copy reads into and writes from buffers of the `bytes` crate (`"bytes"` feature).
 //!
 //! You can open and configure a serial port in one go with [`SerialPort::open()`].
 //! The second argument to `open()` must be a type that implements [`IntoSettings`].
 //! In the simplest case, it is enough to pass a `u32` for the baud rate.
 //! Doing that will also configure a character size of 8 bits with 1 stop bit and disables parity checks and flow control.
+//! To use a different frame format, pass a tuple with the baud rate and the format in the common short notation, like `(9600, "7E1")`.
 //! For full control over the applied settings, pass a closure that receives the current [`Settings`] and return the desired settings.
 //! If you do, you will almost always want to call [`Settings::set_raw()`] before changing any other settings.
 //!
@@ -59,18 +69,99 @@
 #![warn(private_bounds)]
 
 mod sys;
+mod util;
+
+mod buf_writer;
+pub use buf_writer::{IntoInnerError, SerialBufWriter};
+
+mod error;
+pub use error::{Error, Setting};
+
+mod error_reader;
+pub use error_reader::{ErrorReader, LineErrors, ReadChunk};
 
 mod into_settings;
 pub use into_settings::{IntoSettings, KeepSettings};
 
+mod latency;
+pub use latency::{measure_rtt, RttStats};
+
+mod line_script;
+pub use line_script::LineScript;
+
+mod lines;
+pub use lines::Lines;
+
+mod nine_bit;
+pub use nine_bit::NineBit;
+
+mod open_options;
+pub use open_options::OpenOptions;
+
+mod pacing;
+pub use pacing::PacedPort;
+
+mod port_info;
+pub use port_info::{PortDiagnostic, PortInfo, PortInfoRef, PortKind, PortStatus};
+
+mod retry;
+pub use retry::{RetryPolicy, RetryPort};
+
 mod serial_port;
-pub use serial_port::SerialPort;
+pub use serial_port::{ApplyMode, ControlLineLoopback, PortState, ReadMode, SavedSettings, SerialPort, TimeoutBehavior};
+
+mod shared;
+pub use shared::SharedSerialPort;
+
+mod statistics;
+pub use statistics::PortStats;
+
+mod transport;
+pub use transport::SerialTransport;
 
 mod settings;
-pub use settings::{CharSize, FlowControl, Parity, Settings, StopBits, TryFromError, COMMON_BAUD_RATES};
+pub use settings::{
+	closest_standard_baud_rate, estimate_baud_rate_error, standard_baud_rates, CharSize, FlowControl, NewlineMode,
+	Parity, Settings, StopBits, SupportedBaudRates, TryFromError, COMMON_BAUD_RATES,
+};
 
 pub mod os;
 
 #[cfg(any(feature = "doc", feature = "rs4xx"))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "rs4xx")))]
 pub mod rs4xx;
+
+#[cfg(any(feature = "doc", feature = "mock"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "mock")))]
+pub mod mock;
+
+#[cfg(any(feature = "doc", feature = "mock"))]
+mod clock;
+
+#[cfg(any(feature = "doc", feature = "rfc2217"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "rfc2217")))]
+pub mod rfc2217;
+
+#[cfg(any(feature = "doc", feature = "tcp"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "tcp")))]
+pub mod tcp;
+
+#[cfg(any(feature = "doc", feature = "bootstrap"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "bootstrap")))]
+pub mod bootstrap;
+
+#[cfg(any(feature = "doc", feature = "capture"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "capture")))]
+pub mod capture;
+
+#[cfg(any(feature = "doc", feature = "dmx"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "dmx")))]
+pub mod dmx;
+
+#[cfg(any(feature = "doc", feature = "midi"))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "midi")))]
+pub mod midi;
+
+#[cfg(all(any(feature = "doc", feature = "usbfs"), any(target_os = "linux", target_os = "android")))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "usbfs", any(target_os = "linux", target_os = "android")))))]
+pub mod usbfs;
@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use crate::SerialPort;
+
+/// Sleeps shorter than this are done entirely by spinning, longer sleeps only spin for the last part.
+const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+/// A sequence of control line changes, break conditions and delays, executed in one call.
+///
+/// Reset sequences for microcontrollers often depend on the timing between control line changes.
+/// If the changes are made with individual calls to [`SerialPort::set_rts()`] and [`std::thread::sleep()`],
+/// every sleep can take longer than requested, and the errors add up.
+/// A `LineScript` is executed by [`Self::run()`], which avoids these problems:
+/// * Consecutive RTS and DTR changes without a delay between them are applied together.
+///   On Unix platforms, they are applied with a single system call, so the lines change at the same time.
+///   On Windows, they are changed one after the other, because there is no function to change both at once.
+/// * Delays are measured from the start of the script, so the time needed to change the lines
+///   and any oversleeping of earlier delays is subtracted from later delays.
+/// * The last part of each delay is spent busy-waiting instead of sleeping, for better accuracy.
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{LineScript, SerialPort};
+/// use std::time::Duration;
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+///
+/// // The "classic reset" of an ESP32 into its bootloader.
+/// let script = LineScript::new()
+///     .dtr(false)
+///     .rts(true)
+///     .sleep(Duration::from_millis(100))
+///     .dtr(true)
+///     .rts(false)
+///     .sleep(Duration::from_millis(50))
+///     .dtr(false);
+/// script.run(&port)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineScript {
+	steps: Vec<Step>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Step {
+	Lines {
+		rts: Option<bool>,
+		dtr: Option<bool>,
+	},
+	Break(bool),
+	Sleep(Duration),
+}
+
+impl LineScript {
+	/// Create an empty script.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the state of the Ready To Send line.
+	pub fn rts(self, state: bool) -> Self {
+		self.lines(Some(state), None)
+	}
+
+	/// Set the state of the Data Terminal Ready line.
+	pub fn dtr(self, state: bool) -> Self {
+		self.lines(None, Some(state))
+	}
+
+	/// Start or stop transmitting a break condition.
+	pub fn set_break(mut self, state: bool) -> Self {
+		self.steps.push(Step::Break(state));
+		self
+	}
+
+	/// Wait for the given duration before executing the next step.
+	pub fn sleep(mut self, duration: Duration) -> Self {
+		match self.steps.last_mut() {
+			Some(Step::Sleep(last)) => *last += duration,
+			_ => self.steps.push(Step::Sleep(duration)),
+		}
+		self
+	}
+
+	/// Get the total duration of all delays in the script.
+	pub fn duration(&self) -> Duration {
+		self.steps
+			.iter()
+			.map(|step| match step {
+				Step::Sleep(duration) => *duration,
+				_ => Duration::ZERO,
+			})
+			.sum()
+	}
+
+	/// Execute the script on a serial port.
+	///
+	/// This blocks the current thread until all steps have been executed.
+	/// If a step fails, the error is returned immediately and the remaining steps are not executed.
+	pub fn run(&self, port: &SerialPort) -> std::io::Result<()> {
+		let mut deadline = Instant::now();
+		for step in &self.steps {
+			match *step {
+				Step::Lines { rts, dtr } => port.inner.set_rts_dtr(rts, dtr)?,
+				Step::Break(state) => port.set_break(state)?,
+				Step::Sleep(duration) => {
+					deadline += duration;
+					sleep_until(deadline);
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Add a change of the RTS and DTR lines, merging it with a directly preceding change.
+	fn lines(mut self, rts: Option<bool>, dtr: Option<bool>) -> Self {
+		match self.steps.last_mut() {
+			Some(Step::Lines { rts: last_rts, dtr: last_dtr }) => {
+				*last_rts = rts.or(*last_rts);
+				*last_dtr = dtr.or(*last_dtr);
+			},
+			_ => self.steps.push(Step::Lines { rts, dtr }),
+		}
+		self
+	}
+}
+
+/// Sleep until the deadline, busy-waiting for the last part.
+fn sleep_until(deadline: Instant) {
+	let remaining = deadline.saturating_duration_since(Instant::now());
+	if remaining > SPIN_THRESHOLD {
+		std::thread::sleep(remaining - SPIN_THRESHOLD);
+	}
+	while Instant::now() < deadline {
+		std::hint::spin_loop();
+	}
+}
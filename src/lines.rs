@@ -0,0 +1,96 @@
+use crate::SerialPort;
+
+/// An iterator over the lines received on a serial port.
+///
+/// Use [`SerialPort::lines()`] to create one.
+///
+/// Each item is a line without the trailing `\n` or `\r\n`.
+/// The iterator ends when the serial port reports end-of-file.
+/// If there is an incomplete line at end-of-file, it is returned as the last line.
+///
+/// If no complete line is received before the read timeout expires,
+/// the iterator yields an error of kind [`std::io::ErrorKind::TimedOut`],
+/// regardless of the [`TimeoutBehavior`][crate::TimeoutBehavior] of the port.
+/// You can keep using the iterator after a timeout.
+/// If the timeout happened in the middle of a line, the partially received line is kept,
+/// and the next item continues with it.
+/// Use [`Self::pending()`] to distinguish this case from a timeout without any data.
+///
+/// A line that is not valid UTF-8 is discarded and reported as an error of kind [`std::io::ErrorKind::InvalidData`].
+///
+/// The iterator reads more data than it returns, so received data that has not been returned as a line yet
+/// is lost when the iterator is dropped.
+#[derive(Debug)]
+pub struct Lines<'a> {
+	port: &'a SerialPort,
+
+	/// Received data that has not been returned as a line yet.
+	pending: Vec<u8>,
+
+	/// The serial port reported end-of-file.
+	eof: bool,
+}
+
+impl<'a> Lines<'a> {
+	/// Create an iterator over the lines received on a serial port.
+	pub(crate) fn new(port: &'a SerialPort) -> Self {
+		Self {
+			port,
+			pending: Vec::new(),
+			eof: false,
+		}
+	}
+
+	/// Get the data of an incomplete line that was received before the last timeout.
+	///
+	/// This is empty if the last timeout happened between lines.
+	pub fn pending(&self) -> &[u8] {
+		&self.pending
+	}
+
+	/// Take the next complete line from the pending data, if there is one.
+	fn take_line(&mut self) -> Option<Vec<u8>> {
+		let end = self.pending.iter().position(|&byte| byte == b'\n')?;
+		let mut line: Vec<u8> = self.pending.drain(..=end).collect();
+		line.pop();
+		if line.last() == Some(&b'\r') {
+			line.pop();
+		}
+		Some(line)
+	}
+}
+
+impl Iterator for Lines<'_> {
+	type Item = std::io::Result<String>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let line = loop {
+			if let Some(line) = self.take_line() {
+				break line;
+			}
+			if self.eof {
+				if self.pending.is_empty() {
+					return None;
+				}
+				break std::mem::take(&mut self.pending);
+			}
+
+			// Read without the timeout behavior of the port, so timeouts are never mistaken for end-of-file.
+			let mut buffer = [0; 256];
+			match self.port.read_raw(&mut buffer) {
+				Ok(0) => self.eof = true,
+				Ok(n) => self.pending.extend_from_slice(&buffer[..n]),
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) if e.kind() == std::io::ErrorKind::TimedOut && !self.pending.is_empty() => {
+					return Some(Err(std::io::Error::new(
+						std::io::ErrorKind::TimedOut,
+						"timed out in the middle of a line",
+					)));
+				},
+				Err(e) => return Some(Err(e)),
+			}
+		};
+
+		Some(String::from_utf8(line).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+	}
+}
@@ -0,0 +1,280 @@
+//! MIDI over serial ports.
+//!
+//! Serial MIDI interfaces (the classic 5-pin DIN ports, and many microcontroller based instruments)
+//! transmit MIDI messages at 31250 baud with 8 data bits, no parity and 1 stop bit.
+//! Use [`configure()`] to set up a serial port for MIDI.
+//!
+//! The received byte stream does not contain message boundaries.
+//! A [`Framer`] reassembles the bytes into complete [`Message`]s.
+//! It handles *running status* (where the status byte is omitted for consecutive messages of the same type)
+//! and realtime messages that are interleaved with the bytes of other messages.
+//!
+//! Note that 31250 baud is not a standard baud rate, so it is not supported by all platforms and devices.
+//!
+//! # Example
+//! ```no_run
+//! # fn example() -> std::io::Result<()> {
+//! use serial2::SerialPort;
+//! use serial2::midi::{self, Framer};
+//!
+//! let port = SerialPort::open("/dev/ttyAMA0", serial2::KeepSettings)?;
+//! midi::configure(&port)?;
+//!
+//! let mut framer = Framer::new();
+//! let mut buffer = [0; 256];
+//! loop {
+//!     let read = port.read(&mut buffer)?;
+//!     for message in framer.push_slice(&buffer[..read]) {
+//!         println!("{:02X?}", message.as_bytes());
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::{CharSize, FlowControl, Parity, SerialPort, StopBits};
+
+/// The baud rate used by MIDI.
+pub const BAUD_RATE: u32 = 31250;
+
+/// The default maximum length of a system exclusive message accepted by a [`Framer`].
+pub const DEFAULT_MAX_SYSEX_LEN: usize = 64 * 1024;
+
+/// The status byte that starts a system exclusive message.
+const SYSEX_START: u8 = 0xF0;
+
+/// The status byte that ends a system exclusive message.
+const SYSEX_END: u8 = 0xF7;
+
+/// Configure a serial port for MIDI: 31250 baud, 8 data bits, no parity, 1 stop bit and no flow control.
+pub fn configure(port: &SerialPort) -> std::io::Result<()> {
+	port.modify_configuration(|settings| {
+		settings.set_raw();
+		settings.set_baud_rate(BAUD_RATE)?;
+		settings.set_char_size(CharSize::Bits8);
+		settings.set_parity(Parity::None);
+		settings.set_stop_bits(StopBits::One);
+		settings.set_flow_control(FlowControl::None);
+		Ok(())
+	})
+}
+
+/// A complete MIDI message.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Message {
+	/// A channel message (like note on or control change) or a system common message.
+	///
+	/// Messages received with running status are reported with their status byte filled in.
+	Short(ShortMessage),
+
+	/// A system realtime message (like timing clock or start), in the range `0xF8` to `0xFF`.
+	Realtime(u8),
+
+	/// A system exclusive message, including the leading `0xF0` and trailing `0xF7` bytes.
+	SysEx(Vec<u8>),
+}
+
+impl Message {
+	/// Get the raw bytes of the message, as they would be transmitted without running status.
+	pub fn as_bytes(&self) -> &[u8] {
+		match self {
+			Self::Short(message) => message.as_bytes(),
+			Self::Realtime(status) => std::slice::from_ref(status),
+			Self::SysEx(data) => data,
+		}
+	}
+}
+
+/// A channel message or system common message with a status byte and up to two data bytes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ShortMessage {
+	bytes: [u8; 3],
+	len: u8,
+}
+
+impl ShortMessage {
+	/// Get the status byte of the message.
+	pub fn status(&self) -> u8 {
+		self.bytes[0]
+	}
+
+	/// Get the channel of a channel message, in the range 0 to 15.
+	///
+	/// Returns `None` for system common messages.
+	pub fn channel(&self) -> Option<u8> {
+		if self.status() < 0xF0 {
+			Some(self.status() & 0x0F)
+		} else {
+			None
+		}
+	}
+
+	/// Get the data bytes of the message.
+	pub fn data(&self) -> &[u8] {
+		&self.bytes[1..usize::from(self.len)]
+	}
+
+	/// Get the raw bytes of the message, including the status byte.
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.bytes[..usize::from(self.len)]
+	}
+}
+
+/// Reassembles MIDI messages from a stream of received bytes.
+///
+/// Push received bytes with [`Self::push()`] or [`Self::push_slice()`] to get the complete messages.
+///
+/// The framer follows the rules of the MIDI specification:
+/// * Realtime messages are reported immediately, even if they are received in the middle of another message.
+/// * Data bytes without a preceding status byte use the last channel status byte (running status).
+/// * System common and system exclusive messages cancel the running status.
+/// * A status byte that interrupts a system exclusive message ends it.
+///   The incomplete system exclusive message is discarded.
+/// * Data bytes that do not belong to any message are discarded.
+#[derive(Debug, Clone)]
+pub struct Framer {
+	/// The message being received, if a status byte has been received.
+	current: Option<ShortMessage>,
+
+	/// The status byte to use for data bytes without a status byte.
+	running_status: Option<u8>,
+
+	/// The system exclusive message being received, if any.
+	sysex: Option<Vec<u8>>,
+
+	/// The maximum length of a system exclusive message.
+	max_sysex_len: usize,
+}
+
+impl Framer {
+	/// Create a new framer.
+	///
+	/// System exclusive messages longer than [`DEFAULT_MAX_SYSEX_LEN`] are discarded.
+	pub fn new() -> Self {
+		Self::with_max_sysex_len(DEFAULT_MAX_SYSEX_LEN)
+	}
+
+	/// Create a new framer with a maximum length for system exclusive messages.
+	///
+	/// Longer system exclusive messages are discarded, to limit memory usage.
+	pub fn with_max_sysex_len(max_sysex_len: usize) -> Self {
+		Self {
+			current: None,
+			running_status: None,
+			sysex: None,
+			max_sysex_len,
+		}
+	}
+
+	/// Process a received byte.
+	///
+	/// Returns a message if the byte completes one.
+	pub fn push(&mut self, byte: u8) -> Option<Message> {
+		match byte {
+			0xF8..=0xFF => Some(Message::Realtime(byte)),
+			SYSEX_START => {
+				self.current = None;
+				self.running_status = None;
+				self.sysex = Some(vec![SYSEX_START]);
+				None
+			},
+			SYSEX_END => {
+				self.current = None;
+				let mut sysex = self.sysex.take()?;
+				sysex.push(SYSEX_END);
+				Some(Message::SysEx(sysex))
+			},
+			0x80..=0xF6 => {
+				self.sysex = None;
+				if byte < 0xF0 {
+					self.running_status = Some(byte);
+				} else {
+					self.running_status = None;
+				}
+				self.start_message(byte)
+			},
+			_ => self.push_data(byte),
+		}
+	}
+
+	/// Process a slice of received bytes.
+	///
+	/// Returns an iterator over the completed messages.
+	/// Bytes are only processed while the iterator is consumed.
+	pub fn push_slice<'a>(&'a mut self, data: &'a [u8]) -> impl Iterator<Item = Message> + 'a {
+		data.iter().filter_map(move |&byte| self.push(byte))
+	}
+
+	/// Discard any partially received message and the running status.
+	pub fn reset(&mut self) {
+		self.current = None;
+		self.running_status = None;
+		self.sysex = None;
+	}
+
+	/// Start a new message with the given status byte.
+	fn start_message(&mut self, status: u8) -> Option<Message> {
+		let message = ShortMessage {
+			bytes: [status, 0, 0],
+			len: 1,
+		};
+		match data_len(status) {
+			// Tune request has no data bytes.
+			Some(0) => {
+				self.current = None;
+				Some(Message::Short(message))
+			},
+			Some(_) => {
+				self.current = Some(message);
+				None
+			},
+			// Undefined system common messages are ignored.
+			None => {
+				self.current = None;
+				None
+			},
+		}
+	}
+
+	/// Process a data byte.
+	fn push_data(&mut self, byte: u8) -> Option<Message> {
+		if let Some(sysex) = &mut self.sysex {
+			if sysex.len() < self.max_sysex_len {
+				sysex.push(byte);
+			} else {
+				self.sysex = None;
+			}
+			return None;
+		}
+
+		if self.current.is_none() {
+			let status = self.running_status?;
+			self.start_message(status);
+		}
+
+		let current = self.current.as_mut()?;
+		current.bytes[usize::from(current.len)] = byte;
+		current.len += 1;
+		if usize::from(current.len) - 1 < data_len(current.status())? {
+			return None;
+		}
+		self.current.take().map(Message::Short)
+	}
+}
+
+impl Default for Framer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Get the number of data bytes for a status byte, or `None` for undefined messages.
+fn data_len(status: u8) -> Option<usize> {
+	match status {
+		0xC0..=0xDF => Some(1),
+		0x80..=0xEF => Some(2),
+		0xF1 | 0xF3 => Some(1),
+		0xF2 => Some(2),
+		0xF6 => Some(0),
+		_ => None,
+	}
+}
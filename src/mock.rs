@@ -0,0 +1,721 @@
+//! In-memory mock serial port for testing.
+//!
+//! The [`MockSerialPort`] can be used to test protocol logic without real hardware or pseudo-terminals.
+//! The test decides what data is returned by reads, which errors are returned, and how long operations take.
+//! Data written by the code under test is recorded, so the test can inspect it.
+//!
+//! A [`MockSerialPort`] can be cloned cheaply.
+//! All clones share the same state, so you can give one clone to the code under test and use another to control it.
+//!
+//! By default, timeouts and delays use the system clock.
+//! A port created with [`MockSerialPort::with_manual_clock()`] uses a clock that only advances when you call [`MockSerialPort::advance_time()`].
+//! This makes tests of timeout handling deterministic and fast.
+//! Code under test can use [`SerialTransport::now()`] and [`SerialTransport::sleep()`] to follow the same clock.
+//!
+//! The transport wrappers and helpers of this crate use the clock of the transport they wrap.
+//! This covers the delays of `PacedPort`, `RetryPort` and `SoftwareRs485Port`,
+//! the timestamps of `TeePort` and `Capture`, and the measurements of `measure_rtt()`.
+//! Waiting for a timeout in `Capture::recv_timeout()` is not done by the transport, so it always uses the system clock.
+//! Functions that only work on a real `SerialPort`, like `SerialPort::lines()`, use the system clock too.
+//!
+//! To test error handling, you can inject a [`Fault`] at a scripted point in the reads or writes,
+//! with [`MockSerialPort::push_read_fault()`] and [`MockSerialPort::push_write_fault()`].
+//!
+//! # Example
+//! ```
+//! # use serial2::mock::MockSerialPort;
+//! # fn main() -> std::io::Result<()> {
+//! let port = MockSerialPort::new();
+//! port.add_response(b"PING\n", b"PONG\n");
+//!
+//! port.write_all(b"PING\n")?;
+//! let mut buffer = [0; 5];
+//! port.read_exact(&mut buffer)?;
+//! assert_eq!(&buffer, b"PONG\n");
+//! assert_eq!(port.take_written(), b"PING\n");
+//! #   Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{IoSlice, IoSliceMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, ManualClock, SystemClock};
+#[cfg(doc)]
+use crate::SerialTransport;
+
+/// An in-memory mock serial port.
+///
+/// See the [module documentation][self] for more information.
+#[derive(Clone)]
+pub struct MockSerialPort {
+	shared: Arc<Shared>,
+}
+
+struct Shared {
+	state: Mutex<State>,
+	condvar: Condvar,
+
+	/// The clock used for timeouts and delays.
+	clock: Arc<dyn Clock>,
+
+	/// The same clock as `clock`, if it is a manual clock.
+	manual_clock: Option<Arc<ManualClock>>,
+}
+
+struct State {
+	/// Scripted events for reads.
+	read_queue: VecDeque<ReadEvent>,
+
+	/// Scripted events for the next writes.
+	write_queue: VecDeque<WriteEvent>,
+
+	/// The maximum number of bytes to return from the next read of queued data.
+	read_limit: Option<usize>,
+
+	/// The port was disconnected by a [`Fault::Disconnect`].
+	disconnected: bool,
+
+	/// Data written to the port that has not been taken by the test yet.
+	written: Vec<u8>,
+
+	/// Data written to the port that has not been matched against a response yet.
+	unmatched: Vec<u8>,
+
+	/// Scripted responses to written data.
+	responses: VecDeque<(Vec<u8>, Vec<u8>)>,
+
+	read_timeout: Duration,
+	write_timeout: Duration,
+	write_delay: Duration,
+
+	rts: bool,
+	dtr: bool,
+	cts: bool,
+	dsr: bool,
+	ri: bool,
+	cd: bool,
+}
+
+enum ReadEvent {
+	Data(Vec<u8>),
+	Error(std::io::Error),
+	Delay {
+		delay: Duration,
+		until: Option<Instant>,
+	},
+	Timeout,
+	Partial(usize),
+	Disconnect,
+}
+
+enum WriteEvent {
+	Error(std::io::Error),
+	Timeout,
+	Partial(usize),
+	Disconnect,
+	Garbage(Vec<u8>),
+}
+
+/// A failure to inject into the reads or writes of a [`MockSerialPort`].
+///
+/// Each fault mimics a failure mode of real serial ports.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Fault {
+	/// The operation waits for the full read or write timeout and fails with [`std::io::ErrorKind::TimedOut`].
+	///
+	/// If the timeout is infinite, the operation fails immediately instead of blocking forever.
+	Timeout,
+
+	/// The operation fails with [`std::io::ErrorKind::Interrupted`], like a system call interrupted by a signal (`EINTR`).
+	Interrupted,
+
+	/// The next read returns at most this many bytes, or the next write accepts at most this many bytes.
+	///
+	/// A limit of zero is treated as one, because a real serial port never returns zero bytes from a successful transfer.
+	Partial(usize),
+
+	/// The device is disconnected.
+	///
+	/// The operation and all following reads and writes fail with [`std::io::ErrorKind::NotConnected`],
+	/// which is recognized by [`SerialPort::is_disconnected_error()`][crate::SerialPort::is_disconnected_error].
+	Disconnect,
+
+	/// Line noise: the bytes are received in the middle of the read data, or transmitted before the written data.
+	Garbage(Vec<u8>),
+}
+
+impl MockSerialPort {
+	/// Create a new mock serial port.
+	///
+	/// The new port has no data available for reading,
+	/// and it uses the same default timeouts as a real [`SerialPort`][crate::SerialPort].
+	pub fn new() -> Self {
+		Self::with_clock(Arc::new(SystemClock), None)
+	}
+
+	/// Create a new mock serial port that uses a manual clock.
+	///
+	/// Time does not pass for the port by itself: reads and writes that wait for a timeout or delay
+	/// block until the clock is advanced far enough with [`Self::advance_time()`].
+	/// The clock starts at the current system time.
+	/// Wrappers from this crate follow this clock too, see the [module documentation][self].
+	///
+	/// All clones of the port share the same clock.
+	pub fn with_manual_clock() -> Self {
+		let clock = Arc::new(ManualClock::new());
+		Self::with_clock(clock.clone(), Some(clock))
+	}
+
+	fn with_clock(clock: Arc<dyn Clock>, manual_clock: Option<Arc<ManualClock>>) -> Self {
+		let default_timeout = Duration::from_millis(crate::sys::DEFAULT_TIMEOUT_MS.into());
+		Self {
+			shared: Arc::new(Shared {
+				state: Mutex::new(State {
+					read_queue: VecDeque::new(),
+					write_queue: VecDeque::new(),
+					read_limit: None,
+					disconnected: false,
+					written: Vec::new(),
+					unmatched: Vec::new(),
+					responses: VecDeque::new(),
+					read_timeout: default_timeout,
+					write_timeout: default_timeout,
+					write_delay: Duration::ZERO,
+					rts: false,
+					dtr: false,
+					cts: false,
+					dsr: false,
+					ri: false,
+					cd: false,
+				}),
+				condvar: Condvar::new(),
+				clock,
+				manual_clock,
+			}),
+		}
+	}
+
+	/// Get the current time of the clock used by the port.
+	///
+	/// For a port created with [`Self::new()`], this is the same as [`Instant::now()`].
+	pub fn now(&self) -> Instant {
+		self.shared.clock.now()
+	}
+
+	/// Sleep for a duration on the clock used by the port.
+	///
+	/// With a manual clock, this blocks until the clock is advanced far enough with [`Self::advance_time()`].
+	/// For a port created with [`Self::new()`], this is the same as [`std::thread::sleep()`].
+	pub fn sleep(&self, duration: Duration) {
+		let mut state = self.lock();
+		let until = self.shared.clock.now().checked_add(duration);
+		// A duration that does not fit in an `Instant` sleeps forever.
+		loop {
+			match until {
+				Some(until) if self.shared.clock.now() >= until => return,
+				_ => state = self.wait(state, until),
+			}
+		}
+	}
+
+	/// Advance the manual clock of the port, and wake up any reads and writes waiting for it.
+	///
+	/// # Panics
+	/// This function panics if the port was not created with [`Self::with_manual_clock()`].
+	pub fn advance_time(&self, duration: Duration) {
+		let clock = self.shared.manual_clock.as_ref();
+		let clock = clock.expect("MockSerialPort::advance_time() requires a port created with MockSerialPort::with_manual_clock()");
+		// Hold the lock, so a waiting thread can not miss the notification.
+		let state = self.lock();
+		clock.advance(duration);
+		drop(state);
+		self.shared.condvar.notify_all();
+	}
+
+	/// Queue data to be returned by reads.
+	pub fn push_read_data(&self, data: &[u8]) {
+		if data.is_empty() {
+			return;
+		}
+		self.modify(|state| state.read_queue.push_back(ReadEvent::Data(data.to_vec())));
+	}
+
+	/// Queue an error to be returned by a read.
+	///
+	/// The error is returned after all previously queued data has been read.
+	pub fn push_read_error(&self, error: std::io::Error) {
+		self.modify(|state| state.read_queue.push_back(ReadEvent::Error(error)));
+	}
+
+	/// Queue a delay for reads.
+	///
+	/// Data queued after the delay will not be available until the delay has passed.
+	/// The delay starts when a read reaches it in the queue.
+	/// A read waiting for the delay still times out according to the read timeout.
+	pub fn push_read_delay(&self, delay: Duration) {
+		self.modify(|state| state.read_queue.push_back(ReadEvent::Delay { delay, until: None }));
+	}
+
+	/// Queue an error to be returned by the next write.
+	pub fn push_write_error(&self, error: std::io::Error) {
+		self.modify(|state| state.write_queue.push_back(WriteEvent::Error(error)));
+	}
+
+	/// Queue a fault for reads.
+	///
+	/// The fault takes effect after all previously queued data has been read.
+	pub fn push_read_fault(&self, fault: Fault) {
+		let event = match fault {
+			Fault::Timeout => ReadEvent::Timeout,
+			Fault::Interrupted => ReadEvent::Error(std::io::ErrorKind::Interrupted.into()),
+			Fault::Partial(limit) => ReadEvent::Partial(limit.max(1)),
+			Fault::Disconnect => ReadEvent::Disconnect,
+			Fault::Garbage(data) => return self.push_read_data(&data),
+		};
+		self.modify(|state| state.read_queue.push_back(event));
+	}
+
+	/// Queue a fault for the next write.
+	///
+	/// Faults and errors queued with [`Self::push_write_error()`] apply to consecutive writes, in the order they were queued.
+	pub fn push_write_fault(&self, fault: Fault) {
+		let event = match fault {
+			Fault::Timeout => WriteEvent::Timeout,
+			Fault::Interrupted => WriteEvent::Error(std::io::ErrorKind::Interrupted.into()),
+			Fault::Partial(limit) => WriteEvent::Partial(limit.max(1)),
+			Fault::Disconnect => WriteEvent::Disconnect,
+			Fault::Garbage(data) => WriteEvent::Garbage(data),
+		};
+		self.modify(|state| state.write_queue.push_back(event));
+	}
+
+	/// Check if the port was disconnected by a [`Fault::Disconnect`].
+	pub fn is_disconnected(&self) -> bool {
+		self.lock().disconnected
+	}
+
+	/// Set a delay to apply to every write.
+	///
+	/// If the delay is longer than the write timeout, writes fail with [`std::io::ErrorKind::TimedOut`] after the timeout.
+	///
+	/// Other reads and writes are not blocked while a write waits for the delay.
+	pub fn set_write_delay(&self, delay: Duration) {
+		self.modify(|state| state.write_delay = delay);
+	}
+
+	/// Add a scripted response.
+	///
+	/// When the written data matches `request`, the `response` is queued for reading.
+	/// Responses are matched in the order they were added.
+	/// Written data that does not match the next request is skipped.
+	pub fn add_response(&self, request: &[u8], response: &[u8]) {
+		self.modify(|state| {
+			state.responses.push_back((request.to_vec(), response.to_vec()));
+			state.match_responses();
+		});
+	}
+
+	/// Take all data written to the port since the last call.
+	pub fn take_written(&self) -> Vec<u8> {
+		std::mem::take(&mut self.lock().written)
+	}
+
+	/// Get the number of bytes queued for reading.
+	pub fn read_queue_len(&self) -> usize {
+		self.lock()
+			.read_queue
+			.iter()
+			.map(|event| match event {
+				ReadEvent::Data(data) => data.len(),
+				_ => 0,
+			})
+			.sum()
+	}
+
+	/// Read bytes from the mock serial port.
+	///
+	/// This blocks until queued data is available, or until the read timeout expires.
+	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let mut state = self.lock();
+		let clock = &self.shared.clock;
+		let deadline = clock.now().checked_add(state.read_timeout);
+		loop {
+			if state.disconnected {
+				return Err(disconnected_error());
+			}
+			let mut wake_up = deadline;
+			let read_limit = state.read_limit;
+			match state.read_queue.front_mut() {
+				None => (),
+				Some(ReadEvent::Data(data)) => {
+					let len = data.len().min(buf.len()).min(read_limit.unwrap_or(usize::MAX));
+					buf[..len].copy_from_slice(&data[..len]);
+					data.drain(..len);
+					if data.is_empty() {
+						state.read_queue.pop_front();
+					}
+					state.read_limit = None;
+					return Ok(len);
+				},
+				Some(ReadEvent::Error(_)) => {
+					if let Some(ReadEvent::Error(e)) = state.read_queue.pop_front() {
+						return Err(e);
+					}
+				},
+				Some(ReadEvent::Delay { delay, until }) => {
+					// The delay starts when a read first reaches it.
+					let until = *until.get_or_insert_with(|| clock.now() + *delay);
+					if clock.now() >= until {
+						state.read_queue.pop_front();
+						continue;
+					}
+					wake_up = Some(deadline.map_or(until, |deadline| deadline.min(until)));
+				},
+				Some(ReadEvent::Timeout) => match deadline {
+					// Wait for the read timeout to expire.
+					Some(deadline) if clock.now() < deadline => (),
+					_ => {
+						state.read_queue.pop_front();
+						return Err(std::io::ErrorKind::TimedOut.into());
+					},
+				},
+				Some(ReadEvent::Partial(limit)) => {
+					state.read_limit = Some(*limit);
+					state.read_queue.pop_front();
+					continue;
+				},
+				Some(ReadEvent::Disconnect) => {
+					state.read_queue.pop_front();
+					state.disconnected = true;
+					drop(state);
+					self.shared.condvar.notify_all();
+					return Err(disconnected_error());
+				},
+			}
+			if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+				return Err(std::io::ErrorKind::TimedOut.into());
+			}
+			state = self.wait(state, wake_up);
+		}
+	}
+
+	/// Read bytes from the mock serial port into a slice of buffers.
+	pub fn read_vectored(&self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		match buf.iter_mut().find(|buf| !buf.is_empty()) {
+			Some(buf) => self.read(buf),
+			None => self.read(&mut []),
+		}
+	}
+
+	/// Read the exact number of bytes required to fill the buffer from the mock serial port.
+	///
+	/// This behaves like [`SerialPort::read_exact()`][crate::SerialPort::read_exact].
+	pub fn read_exact(&self, mut buf: &mut [u8]) -> std::io::Result<()> {
+		while !buf.is_empty() {
+			match self.read(buf) {
+				Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+				Ok(n) => buf = &mut buf[n..],
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Write bytes to the mock serial port.
+	///
+	/// The data is recorded, and it is matched against the scripted responses.
+	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		let mut state = self.lock();
+		if state.disconnected {
+			return Err(disconnected_error());
+		}
+		let mut buf = buf;
+		match state.write_queue.pop_front() {
+			None => (),
+			Some(WriteEvent::Error(e)) => return Err(e),
+			Some(WriteEvent::Timeout) => {
+				let until = self.shared.clock.now().checked_add(state.write_timeout);
+				while until.is_some_and(|until| self.shared.clock.now() < until) {
+					state = self.wait(state, until);
+				}
+				return Err(std::io::ErrorKind::TimedOut.into());
+			},
+			Some(WriteEvent::Partial(limit)) => buf = &buf[..buf.len().min(limit)],
+			Some(WriteEvent::Disconnect) => {
+				state.disconnected = true;
+				drop(state);
+				self.shared.condvar.notify_all();
+				return Err(disconnected_error());
+			},
+			Some(WriteEvent::Garbage(data)) => {
+				state.written.extend_from_slice(&data);
+				state.unmatched.extend_from_slice(&data);
+			},
+		}
+		if !state.write_delay.is_zero() {
+			let delay = state.write_delay;
+			let timeout = state.write_timeout;
+			let until = self.shared.clock.now().checked_add(delay.min(timeout));
+			while until.is_some_and(|until| self.shared.clock.now() < until) {
+				state = self.wait(state, until);
+			}
+			if delay > timeout {
+				return Err(std::io::ErrorKind::TimedOut.into());
+			}
+		}
+		state.written.extend_from_slice(buf);
+		state.unmatched.extend_from_slice(buf);
+		state.match_responses();
+		drop(state);
+		self.shared.condvar.notify_all();
+		Ok(buf.len())
+	}
+
+	/// Write all bytes to the mock serial port.
+	pub fn write_all(&self, mut buf: &[u8]) -> std::io::Result<()> {
+		while !buf.is_empty() {
+			match self.write(buf) {
+				Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+				Ok(n) => buf = &buf[n..],
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Write bytes to the mock serial port from a slice of buffers.
+	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		let data: Vec<u8> = buf.iter().flat_map(|buf| buf.iter().copied()).collect();
+		self.write(&data)
+	}
+
+	/// Flush all data queued to be written.
+	///
+	/// Written data is recorded immediately, so this does nothing.
+	pub fn flush(&self) -> std::io::Result<()> {
+		Ok(())
+	}
+
+	/// Set the read timeout.
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.modify(|state| state.read_timeout = timeout);
+		Ok(())
+	}
+
+	/// Get the read timeout.
+	pub fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		Ok(self.lock().read_timeout)
+	}
+
+	/// Set the write timeout.
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.modify(|state| state.write_timeout = timeout);
+		Ok(())
+	}
+
+	/// Get the write timeout.
+	pub fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		Ok(self.lock().write_timeout)
+	}
+
+	/// Discard all data queued for reading and all recorded written data.
+	pub fn discard_buffers(&self) -> std::io::Result<()> {
+		self.discard_input_buffer()?;
+		self.discard_output_buffer()
+	}
+
+	/// Discard all data queued for reading.
+	///
+	/// Queued errors and delays are discarded too.
+	pub fn discard_input_buffer(&self) -> std::io::Result<()> {
+		self.modify(|state| state.read_queue.clear());
+		Ok(())
+	}
+
+	/// Discard all recorded written data.
+	pub fn discard_output_buffer(&self) -> std::io::Result<()> {
+		self.modify(|state| {
+			state.written.clear();
+			state.unmatched.clear();
+		});
+		Ok(())
+	}
+
+	/// Set the state of the Ready To Send line.
+	pub fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.modify(|x| x.rts = state);
+		Ok(())
+	}
+
+	/// Get the state of the Ready To Send line as set by [`Self::set_rts()`].
+	pub fn rts(&self) -> bool {
+		self.lock().rts
+	}
+
+	/// Set the state of the Data Terminal Ready line.
+	pub fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.modify(|x| x.dtr = state);
+		Ok(())
+	}
+
+	/// Get the state of the Data Terminal Ready line as set by [`Self::set_dtr()`].
+	pub fn dtr(&self) -> bool {
+		self.lock().dtr
+	}
+
+	/// Set the state of the Clear To Send line as seen by [`Self::read_cts()`].
+	pub fn set_cts(&self, state: bool) {
+		self.modify(|x| x.cts = state);
+	}
+
+	/// Read the state of the Clear To Send line.
+	pub fn read_cts(&self) -> std::io::Result<bool> {
+		Ok(self.lock().cts)
+	}
+
+	/// Set the state of the Data Set Ready line as seen by [`Self::read_dsr()`].
+	pub fn set_dsr(&self, state: bool) {
+		self.modify(|x| x.dsr = state);
+	}
+
+	/// Read the state of the Data Set Ready line.
+	pub fn read_dsr(&self) -> std::io::Result<bool> {
+		Ok(self.lock().dsr)
+	}
+
+	/// Set the state of the Ring Indicator line as seen by [`Self::read_ri()`].
+	pub fn set_ri(&self, state: bool) {
+		self.modify(|x| x.ri = state);
+	}
+
+	/// Read the state of the Ring Indicator line.
+	pub fn read_ri(&self) -> std::io::Result<bool> {
+		Ok(self.lock().ri)
+	}
+
+	/// Set the state of the Carrier Detect line as seen by [`Self::read_cd()`].
+	pub fn set_cd(&self, state: bool) {
+		self.modify(|x| x.cd = state);
+	}
+
+	/// Read the state of the Carrier Detect line.
+	pub fn read_cd(&self) -> std::io::Result<bool> {
+		Ok(self.lock().cd)
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.shared.state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Modify the state and wake up any waiting reads.
+	fn modify<F: FnOnce(&mut State)>(&self, modify: F) {
+		modify(&mut self.lock());
+		self.shared.condvar.notify_all();
+	}
+
+	/// Wait for the state to change, or until the wake up time.
+	///
+	/// With a manual clock, the wake up time is reached when the clock is advanced, which also notifies the condition variable.
+	fn wait<'a>(&'a self, state: MutexGuard<'a, State>, wake_up: Option<Instant>) -> MutexGuard<'a, State> {
+		let condvar = &self.shared.condvar;
+		match wake_up.and_then(|wake_up| self.shared.clock.wait_timeout(wake_up)) {
+			None => condvar.wait(state).unwrap_or_else(|e| e.into_inner()),
+			Some(timeout) => condvar.wait_timeout(state, timeout).unwrap_or_else(|e| e.into_inner()).0,
+		}
+	}
+}
+
+/// Get the error returned by a disconnected mock serial port.
+fn disconnected_error() -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::NotConnected, "mock serial port is disconnected")
+}
+
+impl State {
+	/// Match written data against the scripted responses.
+	fn match_responses(&mut self) {
+		while let Some((request, _)) = self.responses.front() {
+			let position = if request.is_empty() {
+				Some(0)
+			} else {
+				self.unmatched.windows(request.len()).position(|window| window == request.as_slice())
+			};
+			let position = match position {
+				Some(x) => x,
+				None => break,
+			};
+			let end = position + request.len();
+			self.unmatched.drain(..end);
+			if let Some((_, response)) = self.responses.pop_front() {
+				if !response.is_empty() {
+					self.read_queue.push_back(ReadEvent::Data(response));
+				}
+			}
+		}
+	}
+}
+
+impl Default for MockSerialPort {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl std::fmt::Debug for MockSerialPort {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("MockSerialPort").finish_non_exhaustive()
+	}
+}
+
+impl std::io::Read for MockSerialPort {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		MockSerialPort::read(self, buf)
+	}
+
+	fn read_vectored(&mut self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		MockSerialPort::read_vectored(self, buf)
+	}
+}
+
+impl std::io::Read for &'_ MockSerialPort {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		MockSerialPort::read(self, buf)
+	}
+
+	fn read_vectored(&mut self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		MockSerialPort::read_vectored(self, buf)
+	}
+}
+
+impl std::io::Write for MockSerialPort {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		MockSerialPort::write(self, buf)
+	}
+
+	fn write_vectored(&mut self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		MockSerialPort::write_vectored(self, buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		MockSerialPort::flush(self)
+	}
+}
+
+impl std::io::Write for &'_ MockSerialPort {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		MockSerialPort::write(self, buf)
+	}
+
+	fn write_vectored(&mut self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		MockSerialPort::write_vectored(self, buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		MockSerialPort::flush(self)
+	}
+}
@@ -0,0 +1,169 @@
+use crate::{CharSize, ErrorReader, LineErrors, Parity, SerialPort};
+
+/// Send and receive 9-bit words, using the parity bit as the ninth bit.
+///
+/// Multidrop protocols (like MDB for vending machines and many industrial buses) use 9 data bits,
+/// where the ninth bit marks address bytes.
+/// Most UARTs can not transmit 9 data bits, but the same signal is produced by 8 data bits and a parity bit,
+/// if the parity bit is set to the value of the ninth bit.
+///
+/// This helper uses mark parity for words with the ninth bit set, and space parity for words without it.
+/// The serial port is reconfigured whenever the next word needs a different parity,
+/// which happens only between address bytes and data bytes.
+/// Before reconfiguring, all written data is flushed, so the new parity does not apply to data that is still queued.
+/// Mark and space parity are only supported on Linux, Android and Windows.
+///
+/// Received words are decoded with space parity:
+/// a byte without parity error has the ninth bit cleared, and a byte with a parity error has the ninth bit set.
+/// This relies on an [`ErrorReader`] to detect the parity errors, which has some limitations:
+/// * On Unix, a parity error is indistinguishable from a framing error or a break condition.
+///   Those are also reported as a set ninth bit.
+/// * On Windows, words are read one at a time, because the driver does not report which byte has a parity error.
+///   The driver is not guaranteed to report the error before the next read.
+///
+/// Words are `u16` values with the data in the lower 8 bits and the ninth bit in [`Self::ADDRESS_BIT`].
+///
+/// The parity is also used to check received data, so data received while writing may be decoded incorrectly.
+/// This is not a problem for half-duplex protocols, where the response is only sent after the request.
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{NineBit, SerialPort};
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 9600)?;
+/// let mut bus = NineBit::new(&port)?;
+///
+/// // Send the address byte 0x08 with the ninth bit set, followed by a checksum.
+/// bus.write_words(&[NineBit::ADDRESS_BIT | 0x08, 0x08])?;
+///
+/// let mut response = [0; 36];
+/// let read = bus.read_words(&mut response)?;
+/// println!("{:03X?}", &response[..read]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NineBit<'a> {
+	/// The reader used to detect parity errors.
+	reader: ErrorReader<'a>,
+
+	/// The parity that is currently configured on the serial port.
+	parity: Parity,
+}
+
+impl<'a> NineBit<'a> {
+	/// The ninth bit of a word, used to mark address bytes.
+	pub const ADDRESS_BIT: u16 = 0x100;
+
+	/// Configure a serial port for 9-bit words: 8 data bits with space parity.
+	///
+	/// The baud rate and stop bits are not changed.
+	/// See [`ErrorReader::new()`] for other changes made to the configuration.
+	///
+	/// If the platform does not support mark and space parity,
+	/// an error of kind [`std::io::ErrorKind::Unsupported`] is returned.
+	pub fn new(port: &'a SerialPort) -> std::io::Result<Self> {
+		let reader = ErrorReader::new(port)?;
+		port.modify_configuration(|settings| {
+			settings.set_char_size(CharSize::Bits8);
+			settings.set_parity(Parity::Space);
+			Ok(())
+		})?;
+		Ok(Self {
+			reader,
+			parity: Parity::Space,
+		})
+	}
+
+	/// Get the serial port that the words are sent and received on.
+	pub fn port(&self) -> &'a SerialPort {
+		self.reader.port()
+	}
+
+	/// Write all words to the serial port.
+	///
+	/// This returns as soon as the last data is queued in the OS buffer.
+	///
+	/// If a word does not fit in 9 bits, an error of kind [`std::io::ErrorKind::InvalidInput`] is returned,
+	/// and nothing is written.
+	pub fn write_words(&mut self, words: &[u16]) -> std::io::Result<()> {
+		if let Some(word) = words.iter().find(|&&word| word > Self::ADDRESS_BIT | 0xFF) {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("a 9-bit word must be at most 0x1FF, got 0x{word:X}"),
+			));
+		}
+
+		let mut remaining = words;
+		while let Some(&first) = remaining.first() {
+			let parity = Self::parity_for(first);
+			let run = remaining.iter().position(|&word| Self::parity_for(word) != parity).unwrap_or(remaining.len());
+			self.set_parity(parity)?;
+
+			let bytes: Vec<u8> = remaining[..run].iter().map(|&word| word as u8).collect();
+			self.port().write_all(&bytes)?;
+			remaining = &remaining[run..];
+		}
+		Ok(())
+	}
+
+	/// Read words from the serial port.
+	///
+	/// Returns the number of words read, or `0` if the serial port reported end-of-file.
+	/// Errors are reported using the read timeout and [`TimeoutBehavior`][crate::TimeoutBehavior] of the serial port.
+	pub fn read_words(&mut self, words: &mut [u16]) -> std::io::Result<usize> {
+		if words.is_empty() {
+			return Ok(0);
+		}
+		self.set_parity(Parity::Space)?;
+
+		// On Windows, errors are only reported per read, so read one byte at a time.
+		let max_len = if cfg!(windows) { 1 } else { words.len().min(256) };
+		let mut buffer = [0; 256];
+		let chunk = self.reader.read(&mut buffer[..max_len])?;
+		Ok(Self::decode_words(&buffer[..chunk.len], &chunk.errors, words))
+	}
+
+	/// Get the parity that makes the parity bit equal to the ninth bit of a word.
+	///
+	/// This is [`Parity::Mark`] if [`Self::ADDRESS_BIT`] is set, and [`Parity::Space`] otherwise.
+	pub fn parity_for(word: u16) -> Parity {
+		if word & Self::ADDRESS_BIT != 0 {
+			Parity::Mark
+		} else {
+			Parity::Space
+		}
+	}
+
+	/// Decode bytes received with space parity into 9-bit words.
+	///
+	/// The `data` and `errors` should come from a single [`ReadChunk`][crate::ReadChunk] of an [`ErrorReader`].
+	/// If any error applies to the data, the ninth bit is set on all decoded words.
+	///
+	/// Returns the number of decoded words, which is the length of `data` or `words`, whichever is shorter.
+	pub fn decode_words(data: &[u8], errors: &LineErrors, words: &mut [u16]) -> usize {
+		// On Unix, a byte with an error is returned in a chunk of its own.
+		let ninth_bit = if errors.is_empty() { 0 } else { Self::ADDRESS_BIT };
+		for (word, &byte) in words.iter_mut().zip(data) {
+			*word = u16::from(byte) | ninth_bit;
+		}
+		data.len().min(words.len())
+	}
+
+	/// Configure the parity of the serial port, if it is not already configured.
+	fn set_parity(&mut self, parity: Parity) -> std::io::Result<()> {
+		if self.parity == parity {
+			return Ok(());
+		}
+		let port = self.port();
+		port.flush()?;
+		port.modify_configuration(|settings| {
+			settings.set_parity(parity);
+			Ok(())
+		})?;
+		self.parity = parity;
+		Ok(())
+	}
+}
+
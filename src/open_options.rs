@@ -0,0 +1,180 @@
+use std::path::Path;
+
+use crate::{IntoSettings, SerialPort};
+
+/// Options for opening a serial port in a non-default way.
+///
+/// Most applications should simply use [`SerialPort::open()`].
+/// These options are meant for devices whose drivers reject the default open mode,
+/// or for ports that may only be opened for reading or writing.
+///
+/// By default, the port is opened for reading and writing with the same flags as [`SerialPort::open()`].
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::OpenOptions;
+///
+/// // Open a GPS receiver that is only ever read from.
+/// let port = OpenOptions::new()
+///     .write(false)
+///     .open("/dev/ttyS1", 4800)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpenOptions {
+	pub(crate) read: bool,
+	pub(crate) write: bool,
+
+	#[cfg(unix)]
+	pub(crate) custom_flags: i32,
+
+	#[cfg(unix)]
+	pub(crate) no_ctty: bool,
+
+	#[cfg(unix)]
+	pub(crate) open_nonblocking: bool,
+
+	#[cfg(windows)]
+	pub(crate) share_mode: Option<u32>,
+
+	#[cfg(windows)]
+	pub(crate) attributes: u32,
+}
+
+impl OpenOptions {
+	/// Create options that open a serial port for reading and writing with the default flags.
+	pub fn new() -> Self {
+		Self {
+			read: true,
+			write: true,
+			#[cfg(unix)]
+			custom_flags: 0,
+			#[cfg(unix)]
+			no_ctty: true,
+			#[cfg(unix)]
+			open_nonblocking: true,
+			#[cfg(windows)]
+			share_mode: None,
+			#[cfg(windows)]
+			attributes: 0,
+		}
+	}
+
+	/// Set whether the serial port is opened for reading.
+	///
+	/// If a port is opened without read access, all reads will fail.
+	pub fn read(&mut self, read: bool) -> &mut Self {
+		self.read = read;
+		self
+	}
+
+	/// Set whether the serial port is opened for writing.
+	///
+	/// If a port is opened without write access, all writes will fail.
+	pub fn write(&mut self, write: bool) -> &mut Self {
+		self.write = write;
+		self
+	}
+
+	/// Add custom flags to pass to `open()`, like `O_SYNC`.
+	///
+	/// The flags are added to the flags set by the other options.
+	/// The access mode flags (`O_RDONLY`, `O_WRONLY` and `O_RDWR`) are ignored: use [`Self::read()`] and [`Self::write()`] instead.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn custom_flags(&mut self, flags: i32) -> &mut Self {
+		#[cfg(unix)] {
+			self.custom_flags = flags;
+			self
+		}
+		#[cfg(windows)] {
+			let _ = flags;
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Set whether to pass `O_NOCTTY` to `open()`.
+	///
+	/// This is enabled by default, so that the serial port does not become the controlling terminal of the process.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn no_ctty(&mut self, no_ctty: bool) -> &mut Self {
+		#[cfg(unix)] {
+			self.no_ctty = no_ctty;
+			self
+		}
+		#[cfg(windows)] {
+			let _ = no_ctty;
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Set whether to pass `O_NONBLOCK` to `open()`.
+	///
+	/// This is enabled by default.
+	/// If disabled, opening a TTY may block until the carrier detect line is asserted,
+	/// unless the `CLOCAL` flag is already set for the device.
+	///
+	/// The serial port always uses non-blocking I/O internally to implement timeouts,
+	/// so `O_NONBLOCK` is set on the file descriptor after opening the device.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn open_nonblocking(&mut self, open_nonblocking: bool) -> &mut Self {
+		#[cfg(unix)] {
+			self.open_nonblocking = open_nonblocking;
+			self
+		}
+		#[cfg(windows)] {
+			let _ = open_nonblocking;
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Set the share mode to pass to `CreateFileW()`.
+	///
+	/// By default, the share mode of [`std::fs::OpenOptions`] is used.
+	/// Note that most serial port drivers do not allow multiple handles to the same port, regardless of the share mode.
+	#[cfg(any(feature = "doc", all(windows, feature = "windows")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn share_mode(&mut self, share_mode: u32) -> &mut Self {
+		#[cfg(windows)] {
+			self.share_mode = Some(share_mode);
+			self
+		}
+		#[cfg(not(windows))] {
+			let _ = share_mode;
+			unreachable!("this code is only enabled on Windows or during documentation generation")
+		}
+	}
+
+	/// Add file attributes and flags to pass to `CreateFileW()`.
+	///
+	/// The `FILE_FLAG_OVERLAPPED` flag is always added, because the serial port needs it to implement timeouts.
+	#[cfg(any(feature = "doc", all(windows, feature = "windows")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn attributes(&mut self, attributes: u32) -> &mut Self {
+		#[cfg(windows)] {
+			self.attributes = attributes;
+			self
+		}
+		#[cfg(not(windows))] {
+			let _ = attributes;
+			unreachable!("this code is only enabled on Windows or during documentation generation")
+		}
+	}
+
+	/// Open and configure a serial port with these options.
+	///
+	/// This works the same as [`SerialPort::open()`], except for the options used to open the device.
+	pub fn open(&self, name: impl AsRef<Path>, settings: impl IntoSettings) -> std::io::Result<SerialPort> {
+		SerialPort::open_with_options(name.as_ref(), self, settings)
+	}
+}
+
+impl Default for OpenOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
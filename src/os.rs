@@ -1,5 +1,105 @@
 //! OS specific definitions.
 
+/// Define a newtype for a set of bit flags, with the usual set operations.
+#[cfg(any(feature = "doc", all(feature = "unix", unix), all(feature = "windows", windows)))]
+macro_rules! flags_type {
+	($(#[$meta:meta])* $name:ident($inner:ty)) => {
+		$(#[$meta])*
+		#[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+		pub struct $name($inner);
+
+		impl $name {
+			/// Create a set of flags from the raw bits.
+			///
+			/// Unknown bits are kept as they are.
+			pub const fn from_bits(bits: $inner) -> Self {
+				Self(bits)
+			}
+
+			/// Get the raw bits of the flags.
+			pub const fn bits(self) -> $inner {
+				self.0
+			}
+
+			/// Create an empty set of flags.
+			pub const fn empty() -> Self {
+				Self(0)
+			}
+
+			/// Check if no flags are set.
+			pub const fn is_empty(self) -> bool {
+				self.0 == 0
+			}
+
+			/// Check if all the given flags are set.
+			pub const fn contains(self, other: Self) -> bool {
+				self.0 & other.0 == other.0
+			}
+
+			/// Set the given flags.
+			pub fn insert(&mut self, other: Self) {
+				self.0 |= other.0;
+			}
+
+			/// Clear the given flags.
+			pub fn remove(&mut self, other: Self) {
+				self.0 &= !other.0;
+			}
+
+			/// Set or clear the given flags.
+			pub fn set(&mut self, other: Self, enable: bool) {
+				if enable {
+					self.insert(other);
+				} else {
+					self.remove(other);
+				}
+			}
+		}
+
+		impl std::ops::BitOr for $name {
+			type Output = Self;
+
+			fn bitor(self, other: Self) -> Self {
+				Self(self.0 | other.0)
+			}
+		}
+
+		impl std::ops::BitOrAssign for $name {
+			fn bitor_assign(&mut self, other: Self) {
+				self.0 |= other.0;
+			}
+		}
+
+		impl std::ops::BitAnd for $name {
+			type Output = Self;
+
+			fn bitand(self, other: Self) -> Self {
+				Self(self.0 & other.0)
+			}
+		}
+
+		impl std::ops::BitAndAssign for $name {
+			fn bitand_assign(&mut self, other: Self) {
+				self.0 &= other.0;
+			}
+		}
+
+		impl std::ops::Not for $name {
+			type Output = Self;
+
+			fn not(self) -> Self {
+				Self(!self.0)
+			}
+		}
+
+		impl std::fmt::Debug for $name {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				write!(f, "{}({:#x})", stringify!($name), self.0)
+			}
+		}
+	};
+}
+
 /// Unix specific definitions.
 #[cfg(any(feature = "doc", all(feature = "unix", unix)))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
@@ -29,6 +129,160 @@ pub mod unix {
 	pub struct RawTermios {
 		_priv: (),
 	}
+
+	/// Get the `B*` speed constant for a baud rate, for use with [`RawTermios`].
+	///
+	/// Returns `None` if the platform has no constant for the baud rate.
+	/// On Apple platforms and the BSDs, the speed constants are the baud rate itself,
+	/// so this returns the baud rate unchanged.
+	///
+	/// On Linux and Android, arbitrary baud rates can be used with the `BOTHER` flag instead,
+	/// which is what [`crate::Settings::set_baud_rate()`] does.
+	///
+	/// See also [`crate::standard_baud_rates()`] for the full list of baud rates with a constant.
+	#[cfg(unix)]
+	pub fn baud_rate_to_speed(baud_rate: u32) -> Option<libc::speed_t> {
+		crate::sys::baud_rate_to_speed(baud_rate)
+	}
+
+	/// Get the baud rate for a `B*` speed constant, as used in [`RawTermios`].
+	///
+	/// Returns `None` if the value is not a known speed constant.
+	/// On Apple platforms and the BSDs, the speed constants are the baud rate itself,
+	/// so this returns the value unchanged.
+	#[cfg(unix)]
+	pub fn speed_to_baud_rate(speed: libc::speed_t) -> Option<u32> {
+		crate::sys::speed_to_baud_rate(speed)
+	}
+
+	flags_type! {
+		/// The input mode flags of the `termios` struct (`c_iflag`).
+		///
+		/// Use [`crate::Settings::get_input_flags()`] and [`crate::Settings::set_input_flags()`]
+		/// to access them without touching the raw `termios` struct.
+		///
+		/// The flags are stored as `u64`, because the size of `tcflag_t` differs between platforms.
+		/// Only the flags defined by POSIX have a constant, but other flags are kept as they are.
+		InputFlags(u64)
+	}
+
+	#[cfg(unix)]
+	impl InputFlags {
+		/// Ignore a break condition (`IGNBRK`).
+		pub const IGNBRK: Self = Self(libc::IGNBRK as u64);
+		/// Flush the buffers and send `SIGINT` on a break condition (`BRKINT`).
+		pub const BRKINT: Self = Self(libc::BRKINT as u64);
+		/// Ignore bytes with framing or parity errors (`IGNPAR`).
+		pub const IGNPAR: Self = Self(libc::IGNPAR as u64);
+		/// Mark bytes with framing or parity errors with a `0xFF 0x00` prefix (`PARMRK`).
+		pub const PARMRK: Self = Self(libc::PARMRK as u64);
+		/// Enable parity checking of received bytes (`INPCK`).
+		pub const INPCK: Self = Self(libc::INPCK as u64);
+		/// Strip the highest bit of received bytes (`ISTRIP`).
+		pub const ISTRIP: Self = Self(libc::ISTRIP as u64);
+		/// Translate received newlines to carriage returns (`INLCR`).
+		pub const INLCR: Self = Self(libc::INLCR as u64);
+		/// Discard received carriage returns (`IGNCR`).
+		pub const IGNCR: Self = Self(libc::IGNCR as u64);
+		/// Translate received carriage returns to newlines (`ICRNL`).
+		pub const ICRNL: Self = Self(libc::ICRNL as u64);
+		/// Enable XON/XOFF flow control for output (`IXON`).
+		pub const IXON: Self = Self(libc::IXON as u64);
+		/// Let any received byte restart stopped output (`IXANY`).
+		pub const IXANY: Self = Self(libc::IXANY as u64);
+		/// Enable XON/XOFF flow control for input (`IXOFF`).
+		pub const IXOFF: Self = Self(libc::IXOFF as u64);
+	}
+
+	flags_type! {
+		/// The output mode flags of the `termios` struct (`c_oflag`).
+		///
+		/// Use [`crate::Settings::get_output_flags()`] and [`crate::Settings::set_output_flags()`]
+		/// to access them without touching the raw `termios` struct.
+		///
+		/// The flags are stored as `u64`, because the size of `tcflag_t` differs between platforms.
+		/// Only the flags defined by POSIX have a constant, but other flags are kept as they are.
+		OutputFlags(u64)
+	}
+
+	#[cfg(unix)]
+	impl OutputFlags {
+		/// Enable output processing (`OPOST`).
+		///
+		/// The other output flags have no effect without this flag.
+		pub const OPOST: Self = Self(libc::OPOST as u64);
+		/// Translate newlines to a carriage return followed by a newline (`ONLCR`).
+		pub const ONLCR: Self = Self(libc::ONLCR as u64);
+		/// Translate carriage returns to newlines (`OCRNL`).
+		pub const OCRNL: Self = Self(libc::OCRNL as u64);
+		/// Do not transmit carriage returns in the first column (`ONOCR`).
+		pub const ONOCR: Self = Self(libc::ONOCR as u64);
+		/// Let newlines also perform the function of a carriage return (`ONLRET`).
+		pub const ONLRET: Self = Self(libc::ONLRET as u64);
+	}
+
+	/// Linux specific serial port information, as used by the `TIOCGSERIAL` and `TIOCSSERIAL` ioctls.
+	///
+	/// This is the same as a `struct serial_struct` as defined by the Linux kernel in
+	/// [`include/uapi/linux/serial.h`](https://github.com/torvalds/linux/blob/master/include/uapi/linux/serial.h).
+	///
+	/// Use [`crate::SerialPort::get_serial_struct()`] to get the information,
+	/// and [`crate::SerialPort::set_serial_struct()`] to apply it.
+	///
+	/// Most fields are only meaningful for built-in UARTs and are ignored or rejected by USB serial drivers.
+	/// Changing them usually requires elevated permissions.
+	#[cfg(any(target_os = "linux", target_os = "android"))]
+	#[derive(Debug, Copy, Clone)]
+	#[repr(C)]
+	pub struct SerialStruct {
+		/// The UART type (`PORT_*` constant), called `type` in the kernel.
+		pub kind: libc::c_int,
+		/// The line number of the serial port.
+		pub line: libc::c_int,
+		/// The I/O port of the UART.
+		pub port: libc::c_uint,
+		/// The interrupt number of the UART.
+		pub irq: libc::c_int,
+		/// The `ASYNC_*` flags of the serial port.
+		pub flags: libc::c_int,
+		/// The size of the transmit FIFO.
+		pub xmit_fifo_size: libc::c_int,
+		/// The custom divisor, used when the `ASYNC_SPD_CUST` flag is set and the baud rate is set to 38400.
+		pub custom_divisor: libc::c_int,
+		/// The base baud rate of the UART: the highest baud rate, used with divisor 1.
+		pub baud_base: libc::c_int,
+		/// The time the DTR line is held low after closing the port, in hundredths of a second.
+		pub close_delay: libc::c_ushort,
+		/// The I/O type (`UPIO_*` constant).
+		pub io_type: libc::c_char,
+		#[allow(missing_docs)]
+		pub reserved_char: [libc::c_char; 1],
+		#[allow(missing_docs)]
+		pub hub6: libc::c_int,
+		/// The time to wait for output to drain when closing the port, in hundredths of a second.
+		pub closing_wait: libc::c_ushort,
+		/// No longer used.
+		pub closing_wait2: libc::c_ushort,
+		/// The base address of memory mapped I/O.
+		pub iomem_base: *mut libc::c_uchar,
+		/// The register shift for memory mapped I/O.
+		pub iomem_reg_shift: libc::c_ushort,
+		/// The high bits of the I/O port.
+		pub port_high: libc::c_uint,
+		/// The cookie passed to `ioremap`.
+		pub iomap_base: libc::c_ulong,
+	}
+
+	/// Linux specific serial port information, as used by the `TIOCGSERIAL` and `TIOCSSERIAL` ioctls.
+	///
+	/// This is the same as a `struct serial_struct` as defined by the Linux kernel.
+	///
+	/// Generate the documentation on Linux to get an overview of the struct fields.
+	#[cfg(not(any(target_os = "linux", target_os = "android")))]
+	#[repr(C)]
+	pub struct SerialStruct {
+		_priv: (),
+	}
 }
 
 /// Windows specific definitions.
@@ -52,6 +306,59 @@ pub mod windows {
 	#[non_exhaustive]
 	pub struct DCB;
 
+	flags_type! {
+		/// The single bit flags of the `DCB` struct.
+		///
+		/// Use [`crate::Settings::get_dcb_flags()`] and [`crate::Settings::set_dcb_flags()`]
+		/// to access them without touching the raw `DCB` struct.
+		///
+		/// The bits are the same as in the bit fields of the `DCB`.
+		/// The DTR and RTS control modes take two bits each, so they are not included.
+		DcbFlags(u32)
+	}
+
+	impl DcbFlags {
+		/// Binary mode (`fBinary`), which must always be enabled.
+		pub const BINARY: Self = Self(1 << 0);
+		/// Enable parity checking (`fParity`).
+		pub const PARITY: Self = Self(1 << 1);
+		/// Only transmit while the CTS line is asserted (`fOutxCtsFlow`).
+		pub const OUTX_CTS_FLOW: Self = Self(1 << 2);
+		/// Only transmit while the DSR line is asserted (`fOutxDsrFlow`).
+		pub const OUTX_DSR_FLOW: Self = Self(1 << 3);
+		/// Ignore received bytes while the DSR line is not asserted (`fDsrSensitivity`).
+		pub const DSR_SENSITIVITY: Self = Self(1 << 6);
+		/// Continue transmitting after XOFF was sent because the input buffer is almost full (`fTXContinueOnXoff`).
+		pub const TX_CONTINUE_ON_XOFF: Self = Self(1 << 7);
+		/// Enable XON/XOFF flow control for output (`fOutX`).
+		pub const OUTX: Self = Self(1 << 8);
+		/// Enable XON/XOFF flow control for input (`fInX`).
+		pub const INX: Self = Self(1 << 9);
+		/// Replace bytes with parity errors by the error character (`fErrorChar`).
+		pub const ERROR_CHAR: Self = Self(1 << 10);
+		/// Discard received null bytes (`fNull`).
+		pub const NULL: Self = Self(1 << 11);
+		/// Abort reads and writes when an error occurs (`fAbortOnError`).
+		pub const ABORT_ON_ERROR: Self = Self(1 << 14);
+
+		/// All single bit flags, with the accessors of the `DCB` struct.
+		#[cfg(windows)]
+		#[allow(clippy::type_complexity)]
+		pub(crate) const ACCESSORS: [(Self, fn(&DCB) -> u32, fn(&mut DCB, u32)); 11] = [
+			(Self::BINARY, DCB::fBinary, DCB::set_fBinary),
+			(Self::PARITY, DCB::fParity, DCB::set_fParity),
+			(Self::OUTX_CTS_FLOW, DCB::fOutxCtsFlow, DCB::set_fOutxCtsFlow),
+			(Self::OUTX_DSR_FLOW, DCB::fOutxDsrFlow, DCB::set_fOutxDsrFlow),
+			(Self::DSR_SENSITIVITY, DCB::fDsrSensitivity, DCB::set_fDsrSensitivity),
+			(Self::TX_CONTINUE_ON_XOFF, DCB::fTXContinueOnXoff, DCB::set_fTXContinueOnXoff),
+			(Self::OUTX, DCB::fOutX, DCB::set_fOutX),
+			(Self::INX, DCB::fInX, DCB::set_fInX),
+			(Self::ERROR_CHAR, DCB::fErrorChar, DCB::set_fErrorChar),
+			(Self::NULL, DCB::fNull, DCB::set_fNull),
+			(Self::ABORT_ON_ERROR, DCB::fAbortOnError, DCB::set_fAbortOnError),
+		];
+	}
+
 	/// Windows specific timeouts for a serial port.
 	///
 	/// Use [`crate::SerialPort::get_windows_timeouts()`] to get the timeouts,
@@ -73,4 +380,32 @@ pub mod windows {
 		pub write_total_timeout_multiplier: u32,
 		pub write_total_timeout_constant: u32,
 	}
+
+	/// Windows specific capabilities of a serial port driver.
+	///
+	/// Use [`crate::SerialPort::get_capabilities()`] to get the capabilities.
+	///
+	/// The fields are the same as those of the `COMMPROP` struct, but without the packet header and provider specific data.
+	/// For the meaning of the fields and the possible bit flags, see:
+	/// [https://learn.microsoft.com/en-us/windows/win32/api/winbase/ns-winbase-commprop](https://learn.microsoft.com/en-us/windows/win32/api/winbase/ns-winbase-commprop)
+	///
+	/// For example, [`Self::max_baud`] is a `BAUD_*` constant, not the baud rate itself,
+	/// and [`Self::settable_baud`] is a bit mask of the supported `BAUD_*` constants.
+	/// A value of `BAUD_USER` (`0x1000_0000`) means that arbitrary baud rates are supported.
+	#[allow(missing_docs)] // People should read the microsoft docs we link instead.
+	#[derive(Debug, Clone)]
+	pub struct CommProperties {
+		pub service_mask: u32,
+		pub max_tx_queue: u32,
+		pub max_rx_queue: u32,
+		pub max_baud: u32,
+		pub provider_sub_type: u32,
+		pub provider_capabilities: u32,
+		pub settable_params: u32,
+		pub settable_baud: u32,
+		pub settable_data: u16,
+		pub settable_stop_parity: u16,
+		pub current_tx_queue: u32,
+		pub current_rx_queue: u32,
+	}
 }
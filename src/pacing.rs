@@ -0,0 +1,169 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::util::lock;
+use crate::SerialTransport;
+
+/// A transport wrapper that inserts a delay between transmitted bytes or chunks.
+///
+/// Some old devices (like industrial scales and PLCs) drop characters when they are sent back-to-back at full speed.
+/// This wrapper splits all written data in chunks of at most `chunk_size` bytes.
+/// After each chunk, it waits until the chunk has been transmitted,
+/// and it does not start transmitting the next chunk until `delay` has passed.
+///
+/// The delay is implemented in software, so the actual gap between chunks can be longer than requested.
+/// It uses the clock of the wrapped transport (see [`SerialTransport::now()`]).
+/// Reads and all other functions are passed through to the wrapped transport unchanged.
+///
+/// The wrapper works with any [`SerialTransport`], but it is normally used with a [`SerialPort`][crate::SerialPort].
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{PacedPort, SerialPort, SerialTransport};
+/// use std::time::Duration;
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 9600)?;
+/// let port = PacedPort::new(port, 1, Duration::from_millis(2));
+/// port.write_all(b"W\r\n")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PacedPort<T> {
+	inner: T,
+	chunk_size: usize,
+	delay: Duration,
+
+	/// The time at which the last chunk was transmitted completely.
+	///
+	/// The lock is held during writes, so that concurrent writes are paced too.
+	last_chunk: Mutex<Option<Instant>>,
+}
+
+impl<T: SerialTransport> PacedPort<T> {
+	/// Wrap a transport, writing at most `chunk_size` bytes at a time with `delay` between the chunks.
+	///
+	/// A `chunk_size` of zero is treated as one, which inserts the delay between all bytes.
+	pub fn new(inner: T, chunk_size: usize, delay: Duration) -> Self {
+		Self {
+			inner,
+			chunk_size: chunk_size.max(1),
+			delay,
+			last_chunk: Mutex::new(None),
+		}
+	}
+
+	/// Get a reference to the wrapped transport.
+	pub fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Get the maximum number of bytes written at a time.
+	pub fn chunk_size(&self) -> usize {
+		self.chunk_size
+	}
+
+	/// Get the delay between chunks.
+	pub fn delay(&self) -> Duration {
+		self.delay
+	}
+
+	/// Get the wrapped transport back.
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+impl<T: SerialTransport> SerialTransport for PacedPort<T> {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		self.inner.read(buf)
+	}
+
+	/// Write a single chunk of data.
+	///
+	/// This waits until the delay since the previous chunk has passed,
+	/// and then waits until the chunk has been transmitted.
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		if buf.is_empty() {
+			return Ok(0);
+		}
+		let mut last_chunk = lock(&self.last_chunk);
+		if let Some(last_chunk) = *last_chunk {
+			let elapsed = self.inner.now().saturating_duration_since(last_chunk);
+			if elapsed < self.delay {
+				self.inner.sleep(self.delay - elapsed);
+			}
+		}
+		let chunk = &buf[..buf.len().min(self.chunk_size)];
+		let written = self.inner.write(chunk)?;
+		self.inner.flush()?;
+		*last_chunk = Some(self.inner.now());
+		Ok(written)
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_read_timeout(timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_read_timeout()
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_write_timeout(timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_write_timeout()
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		self.inner.discard_buffers()
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_rts(state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		self.inner.read_cts()
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_dtr(state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		self.inner.read_dsr()
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		self.inner.read_ri()
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		self.inner.read_cd()
+	}
+
+	fn now(&self) -> Instant {
+		self.inner.now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		self.inner.sleep(duration)
+	}
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for PacedPort<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("PacedPort")
+			.field("inner", &self.inner)
+			.field("chunk_size", &self.chunk_size)
+			.field("delay", &self.delay)
+			.finish_non_exhaustive()
+	}
+}
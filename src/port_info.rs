@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+/// Information about an available serial port.
+///
+/// Use [`SerialPort::available_ports_info()`][crate::SerialPort::available_ports_info] to get a list of available ports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortInfo {
+	pub(crate) path: PathBuf,
+	pub(crate) kind: PortKind,
+	pub(crate) id: Option<String>,
+}
+
+/// The type of a serial port, as far as it can be determined by enumeration.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PortKind {
+	/// The type of the serial port is unknown.
+	///
+	/// This is used for regular hardware ports and USB serial adapters.
+	Unknown,
+
+	/// A virtual serial port for a Bluetooth device, using the serial port profile (SPP) or RFCOMM.
+	Bluetooth,
+
+	/// A port used by a (soft)modem.
+	Modem,
+}
+
+impl PortInfo {
+	/// Create a new port info object.
+	pub(crate) fn new(path: impl Into<PathBuf>, kind: PortKind) -> Self {
+		Self {
+			path: path.into(),
+			kind,
+			id: None,
+		}
+	}
+
+	/// Get the path or name of the serial port.
+	///
+	/// This can be passed directly to [`SerialPort::open()`][crate::SerialPort::open].
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Get the type of the serial port.
+	pub fn kind(&self) -> PortKind {
+		self.kind
+	}
+
+	/// Get a stable identifier for the device behind the serial port, if one is known.
+	///
+	/// Unlike the path, the identifier does not change when a device is unplugged and plugged in again,
+	/// even if the operating system assigns a different name to the port.
+	/// Pass it to [`SerialPort::open_by_id()`][crate::SerialPort::open_by_id] to reconnect to the same device later.
+	///
+	/// The identifier is platform specific and should be treated as an opaque string:
+	/// * On Linux and Android, it is the name of the link in `/dev/serial/by-id`, like `usb-FTDI_FT232R_USB_UART_A50285BI-if00-port0`.
+	///   If udev did not create the link, an identifier is made from the USB vendor ID, product ID, serial number and interface number.
+	/// * On Windows, it is the device instance ID, like `USB\VID_0403&PID_6001\A50285BI` or `FTDIBUS\VID_0403+PID_6001+A50285BIA\0000`.
+	/// * On other platforms, no identifier is available.
+	///
+	/// Devices without a serial number (including most built-in serial ports) may not have a stable identifier.
+	pub fn id(&self) -> Option<&str> {
+		self.id.as_deref()
+	}
+
+	/// Get the path or name of the serial port, consuming the port info.
+	pub fn into_path(self) -> PathBuf {
+		self.path
+	}
+}
+
+/// Borrowed information about an available serial port.
+///
+/// This is passed to the callback of [`SerialPort::for_each_port()`][crate::SerialPort::for_each_port].
+/// Unlike [`PortInfo`], it does not own the path, so no memory is allocated for it.
+/// The stable identifier of [`PortInfo::id()`] is not available.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortInfoRef<'a> {
+	pub(crate) path: &'a Path,
+	pub(crate) kind: PortKind,
+}
+
+impl<'a> PortInfoRef<'a> {
+	/// Create a new borrowed port info object.
+	pub(crate) fn new(path: &'a Path, kind: PortKind) -> Self {
+		Self { path, kind }
+	}
+
+	/// Get the path or name of the serial port.
+	///
+	/// This can be passed directly to [`SerialPort::open()`][crate::SerialPort::open].
+	pub fn path(&self) -> &'a Path {
+		self.path
+	}
+
+	/// Get the type of the serial port.
+	pub fn kind(&self) -> PortKind {
+		self.kind
+	}
+
+	/// Copy the information into an owned [`PortInfo`].
+	pub fn to_port_info(&self) -> PortInfo {
+		PortInfo::new(self.path, self.kind)
+	}
+}
+
+/// The outcome of inspecting a single candidate during port enumeration.
+///
+/// Use [`SerialPort::available_ports_verbose()`][crate::SerialPort::available_ports_verbose] to get these diagnostics.
+#[derive(Debug)]
+pub struct PortDiagnostic {
+	pub(crate) path: PathBuf,
+	pub(crate) status: PortStatus,
+}
+
+/// The reason a candidate was reported or skipped during port enumeration.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PortStatus {
+	/// The candidate is reported as an available serial port.
+	Available(PortKind),
+
+	/// The candidate could not be inspected, for example because of missing permissions.
+	Inaccessible(std::io::Error),
+
+	/// The candidate is not a character device.
+	NotCharDevice,
+
+	/// The candidate was skipped by a heuristic, because it is probably not a serial port.
+	///
+	/// The string explains why.
+	Filtered(&'static str),
+}
+
+impl PortDiagnostic {
+	/// Create a new diagnostic entry.
+	pub(crate) fn new(path: impl Into<PathBuf>, status: PortStatus) -> Self {
+		Self {
+			path: path.into(),
+			status,
+		}
+	}
+
+	/// Get the path of the candidate.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Get the outcome for this candidate.
+	pub fn status(&self) -> &PortStatus {
+		&self.status
+	}
+
+	/// Check if the candidate is reported as an available serial port.
+	pub fn is_available(&self) -> bool {
+		matches!(self.status, PortStatus::Available(_))
+	}
+
+	/// Convert the diagnostic into a [`PortInfo`], if the candidate is an available serial port.
+	///
+	/// The stable identifier is not determined by [`SerialPort::available_ports_verbose()`][crate::SerialPort::available_ports_verbose],
+	/// so [`PortInfo::id()`] always returns `None` for the result.
+	pub fn into_port_info(self) -> Option<PortInfo> {
+		match self.status {
+			PortStatus::Available(kind) => Some(PortInfo::new(self.path, kind)),
+			_ => None,
+		}
+	}
+}
+
+impl std::fmt::Display for PortStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Available(PortKind::Unknown) => write!(f, "available"),
+			Self::Available(kind) => write!(f, "available ({kind:?})"),
+			Self::Inaccessible(e) => write!(f, "inaccessible: {e}"),
+			Self::NotCharDevice => write!(f, "not a character device"),
+			Self::Filtered(reason) => write!(f, "skipped: {reason}"),
+		}
+	}
+}
@@ -0,0 +1,238 @@
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+
+use crate::SerialTransport;
+
+/// A policy that decides which errors are retried by a [`RetryPort`], and how often.
+///
+/// By default, an operation is attempted up to 3 times.
+/// The first retry happens after 1 millisecond, and the backoff doubles for every following retry, up to 100 milliseconds.
+///
+/// By default, errors of kind [`ErrorKind::NotConnected`] and [`ErrorKind::Interrupted`] are retried.
+/// Many USB serial adapters sporadically report `EIO` (or `ERROR_GEN_FAILURE` on Windows) and recover immediately,
+/// but [`SerialPort`][crate::SerialPort] reports those errors as [`ErrorKind::NotConnected`],
+/// because the same errors are also reported when the device is unplugged.
+/// So if the device really is disconnected, the error is only reported after all attempts failed.
+/// Timeouts are not retried by default.
+///
+/// # Example
+/// ```
+/// use serial2::RetryPolicy;
+/// use std::io::ErrorKind;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .backoff(Duration::from_millis(10), Duration::from_secs(1))
+///     .retryable_kinds(&[ErrorKind::NotConnected, ErrorKind::TimedOut]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+	max_attempts: u32,
+	initial_backoff: Duration,
+	max_backoff: Duration,
+	retryable_kinds: Vec<ErrorKind>,
+}
+
+impl RetryPolicy {
+	/// Create a retry policy with the default settings.
+	pub fn new() -> Self {
+		Self {
+			max_attempts: 3,
+			initial_backoff: Duration::from_millis(1),
+			max_backoff: Duration::from_millis(100),
+			retryable_kinds: vec![ErrorKind::NotConnected, ErrorKind::Interrupted],
+		}
+	}
+
+	/// Set the maximum number of attempts for each operation, including the first attempt.
+	///
+	/// A value of zero is treated as one, which disables retries.
+	pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+		self.max_attempts = max_attempts.max(1);
+		self
+	}
+
+	/// Set the backoff before the first retry, and the maximum backoff.
+	///
+	/// The backoff doubles for every retry, until it reaches `max`.
+	pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+		self.initial_backoff = initial;
+		self.max_backoff = max;
+		self
+	}
+
+	/// Set the kinds of errors that are retried.
+	///
+	/// This replaces the default list.
+	pub fn retryable_kinds(mut self, kinds: &[ErrorKind]) -> Self {
+		self.retryable_kinds = kinds.to_vec();
+		self
+	}
+
+	/// Get the maximum number of attempts for each operation, including the first attempt.
+	pub fn get_max_attempts(&self) -> u32 {
+		self.max_attempts
+	}
+
+	/// Check if an error should be retried according to this policy.
+	pub fn is_retryable(&self, error: &std::io::Error) -> bool {
+		self.retryable_kinds.contains(&error.kind())
+	}
+
+	/// Get the backoff before the given retry, counting from zero for the first retry.
+	pub fn get_backoff(&self, retry: u32) -> Duration {
+		let factor = 1u32.checked_shl(retry).unwrap_or(u32::MAX);
+		self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+	}
+
+	/// Run an operation on a transport according to the retry policy.
+	///
+	/// The backoff uses the sleep function of the transport.
+	fn run<P, T, F>(&self, transport: &P, mut operation: F) -> std::io::Result<T>
+	where
+		P: SerialTransport,
+		F: FnMut(&P) -> std::io::Result<T>,
+	{
+		let mut retry = 0;
+		loop {
+			match operation(transport) {
+				Err(e) if retry + 1 < self.max_attempts && self.is_retryable(&e) => {
+					transport.sleep(self.get_backoff(retry));
+					retry += 1;
+				},
+				result => return result,
+			}
+		}
+	}
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A transport wrapper that retries reads, writes and flushes that fail with a transient error.
+///
+/// Failed operations are retried according to a [`RetryPolicy`].
+/// If all attempts fail, the error of the last attempt is returned.
+/// The backoff between attempts sleeps on the clock of the wrapped transport (see [`SerialTransport::sleep()`]).
+/// All other functions are passed through to the wrapped transport unchanged.
+///
+/// Retrying is only safe for errors that are reported before any data is transferred.
+/// This is the case for errors reported by the OS for a single read or write call,
+/// but a wrapped transport that combines multiple transfers may already have transferred some data when it fails.
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{RetryPolicy, RetryPort, SerialPort, SerialTransport};
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+/// let port = RetryPort::new(port, RetryPolicy::new().max_attempts(5));
+/// port.write_all(b"PING\n")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RetryPort<T> {
+	inner: T,
+	policy: RetryPolicy,
+}
+
+impl<T: SerialTransport> RetryPort<T> {
+	/// Wrap a transport, retrying failed operations according to the given policy.
+	pub fn new(inner: T, policy: RetryPolicy) -> Self {
+		Self { inner, policy }
+	}
+
+	/// Get a reference to the wrapped transport.
+	pub fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Get the retry policy.
+	pub fn policy(&self) -> &RetryPolicy {
+		&self.policy
+	}
+
+	/// Get the wrapped transport back.
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+impl<T: SerialTransport> SerialTransport for RetryPort<T> {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		self.policy.run(&self.inner, |inner| inner.read(buf))
+	}
+
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		self.policy.run(&self.inner, |inner| inner.write(buf))
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		self.policy.run(&self.inner, |inner| inner.flush())
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_read_timeout(timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_read_timeout()
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_write_timeout(timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_write_timeout()
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		self.inner.discard_buffers()
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_rts(state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		self.inner.read_cts()
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_dtr(state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		self.inner.read_dsr()
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		self.inner.read_ri()
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		self.inner.read_cd()
+	}
+
+	fn now(&self) -> Instant {
+		self.inner.now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		self.inner.sleep(duration)
+	}
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for RetryPort<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("RetryPort")
+			.field("inner", &self.inner)
+			.field("policy", &self.policy)
+			.finish()
+	}
+}
@@ -0,0 +1,564 @@
+//! RFC 2217 remote serial port client.
+//!
+//! [RFC 2217](https://datatracker.ietf.org/doc/html/rfc2217) extends the telnet protocol with the COM-PORT-OPTION,
+//! which allows a client to configure and control a serial port on a network serial server.
+//! Many serial device servers (for example from Moxa or Lantronix) and software like `ser2net` support it.
+//!
+//! The [`Rfc2217Port`] provides an API similar to [`SerialPort`][crate::SerialPort],
+//! and it implements [`SerialTransport`][crate::SerialTransport].
+//!
+//! # Example
+//! ```no_run
+//! # fn example() -> std::io::Result<()> {
+//! use serial2::rfc2217::Rfc2217Port;
+//!
+//! let port = Rfc2217Port::connect("192.168.1.10:4001")?;
+//! port.set_baud_rate(115200)?;
+//! port.write_all(b"Hello!")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::util::{lock, map_timeout_error, socket_timeout};
+use crate::{CharSize, FlowControl, Parity, Settings, StopBits};
+
+/// Telnet protocol constants.
+mod telnet {
+	pub const SE: u8 = 240;
+	pub const SB: u8 = 250;
+	pub const WILL: u8 = 251;
+	pub const WONT: u8 = 252;
+	pub const DO: u8 = 253;
+	pub const DONT: u8 = 254;
+	pub const IAC: u8 = 255;
+
+	pub const BINARY: u8 = 0;
+	pub const SGA: u8 = 3;
+	pub const COM_PORT_OPTION: u8 = 44;
+}
+
+/// COM-PORT-OPTION commands sent by the client.
+///
+/// The server responds with the same command plus [`SERVER_OFFSET`].
+mod command {
+	pub const SET_BAUDRATE: u8 = 1;
+	pub const SET_DATASIZE: u8 = 2;
+	pub const SET_PARITY: u8 = 3;
+	pub const SET_STOPSIZE: u8 = 4;
+	pub const SET_CONTROL: u8 = 5;
+	pub const NOTIFY_MODEMSTATE: u8 = 7;
+	pub const FLOWCONTROL_SUSPEND: u8 = 8;
+	pub const FLOWCONTROL_RESUME: u8 = 9;
+	pub const SET_MODEMSTATE_MASK: u8 = 11;
+	pub const PURGE_DATA: u8 = 12;
+
+	pub const SERVER_OFFSET: u8 = 100;
+}
+
+/// Values for the SET-CONTROL command.
+mod control {
+	pub const FLOW_NONE: u8 = 1;
+	pub const FLOW_XON_XOFF: u8 = 2;
+	pub const FLOW_HARDWARE: u8 = 3;
+	pub const BREAK_ON: u8 = 5;
+	pub const BREAK_OFF: u8 = 6;
+	pub const DTR_ON: u8 = 8;
+	pub const DTR_OFF: u8 = 9;
+	pub const RTS_ON: u8 = 11;
+	pub const RTS_OFF: u8 = 12;
+}
+
+/// Bits of the modem state reported by the server.
+mod modem_state {
+	pub const CTS: u8 = 0x10;
+	pub const DSR: u8 = 0x20;
+	pub const RI: u8 = 0x40;
+	pub const CD: u8 = 0x80;
+}
+
+/// A serial port on a remote RFC 2217 server.
+///
+/// See the [module documentation][self] for more information.
+///
+/// A background thread receives data and notifications from the server.
+/// The thread stops when the [`Rfc2217Port`] is dropped or when the connection is closed.
+pub struct Rfc2217Port {
+	/// The stream used for writing, protected by a mutex so that commands and data are not interleaved.
+	writer: Arc<Mutex<TcpStream>>,
+
+	/// State shared with the receiving thread.
+	shared: Arc<Shared>,
+
+	read_timeout: Mutex<Duration>,
+	write_timeout: Mutex<Duration>,
+}
+
+struct Shared {
+	state: Mutex<State>,
+	condvar: Condvar,
+}
+
+struct State {
+	/// Received data that has not been read yet.
+	data: VecDeque<u8>,
+
+	/// The last modem state reported by the server.
+	modem_state: u8,
+
+	/// The connection was closed by the server.
+	closed: bool,
+
+	/// The receiving thread encountered an error.
+	error: Option<std::io::Error>,
+}
+
+impl Rfc2217Port {
+	/// Connect to an RFC 2217 server.
+	///
+	/// This opens the TCP connection and negotiates the telnet options needed for RFC 2217.
+	/// The serial port settings on the server are not changed.
+	pub fn connect(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+		Self::from_stream(TcpStream::connect(address)?)
+	}
+
+	/// Use an existing TCP connection to an RFC 2217 server.
+	pub fn from_stream(stream: TcpStream) -> std::io::Result<Self> {
+		stream.set_nodelay(true)?;
+		let reader = stream.try_clone()?;
+		let default_timeout = Duration::from_millis(crate::sys::DEFAULT_TIMEOUT_MS.into());
+		stream.set_write_timeout(Some(default_timeout))?;
+
+		let writer = Arc::new(Mutex::new(stream));
+		let shared = Arc::new(Shared {
+			state: Mutex::new(State {
+				data: VecDeque::new(),
+				modem_state: 0,
+				closed: false,
+				error: None,
+			}),
+			condvar: Condvar::new(),
+		});
+
+		{
+			let mut writer = lock(&writer);
+			writer.write_all(&[telnet::IAC, telnet::WILL, telnet::BINARY])?;
+			writer.write_all(&[telnet::IAC, telnet::DO, telnet::BINARY])?;
+			writer.write_all(&[telnet::IAC, telnet::WILL, telnet::SGA])?;
+			writer.write_all(&[telnet::IAC, telnet::DO, telnet::SGA])?;
+			writer.write_all(&[telnet::IAC, telnet::WILL, telnet::COM_PORT_OPTION])?;
+		}
+
+		std::thread::Builder::new().name("serial2-rfc2217".into()).spawn({
+			let writer = writer.clone();
+			let shared = shared.clone();
+			move || receive_loop(reader, &writer, &shared)
+		})?;
+
+		let port = Self {
+			writer,
+			shared,
+			read_timeout: Mutex::new(default_timeout),
+			write_timeout: Mutex::new(default_timeout),
+		};
+
+		// Ask the server to notify us about changes of all modem lines.
+		port.send_command(command::SET_MODEMSTATE_MASK, &[0xFF])?;
+		Ok(port)
+	}
+
+	/// Read bytes from the remote serial port.
+	///
+	/// This blocks until data is available, or until the read timeout expires.
+	/// Returns `Ok(0)` if the connection was closed by the server.
+	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let timeout = *lock(&self.read_timeout);
+		let deadline = Instant::now().checked_add(timeout);
+		let mut state = lock(&self.shared.state);
+		loop {
+			if !state.data.is_empty() {
+				let len = state.data.len().min(buf.len());
+				for (dest, src) in buf.iter_mut().zip(state.data.drain(..len)) {
+					*dest = src;
+				}
+				return Ok(len);
+			}
+			if let Some(error) = state.error.take() {
+				return Err(error);
+			}
+			if state.closed {
+				return Ok(0);
+			}
+			state = match deadline {
+				None => self.shared.condvar.wait(state).unwrap_or_else(|e| e.into_inner()),
+				Some(deadline) => {
+					let remaining = deadline.saturating_duration_since(Instant::now());
+					if remaining.is_zero() {
+						return Err(std::io::ErrorKind::TimedOut.into());
+					}
+					self.shared
+						.condvar
+						.wait_timeout(state, remaining)
+						.unwrap_or_else(|e| e.into_inner())
+						.0
+				},
+			}
+		}
+	}
+
+	/// Read the exact number of bytes required to fill the buffer.
+	pub fn read_exact(&self, mut buf: &mut [u8]) -> std::io::Result<()> {
+		while !buf.is_empty() {
+			match self.read(buf) {
+				Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+				Ok(n) => buf = &mut buf[n..],
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Write bytes to the remote serial port.
+	///
+	/// Bytes with the value `0xFF` are escaped as required by the telnet protocol.
+	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		let mut escaped = Vec::with_capacity(buf.len());
+		for &byte in buf {
+			escaped.push(byte);
+			if byte == telnet::IAC {
+				escaped.push(telnet::IAC);
+			}
+		}
+		lock(&self.writer).write_all(&escaped).map_err(map_timeout_error)?;
+		Ok(buf.len())
+	}
+
+	/// Write all bytes to the remote serial port.
+	pub fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+		self.write(buf)?;
+		Ok(())
+	}
+
+	/// Flush all data queued to be written to the server.
+	///
+	/// Note that this does not wait for the server to transmit the data on the serial port.
+	pub fn flush(&self) -> std::io::Result<()> {
+		lock(&self.writer).flush()
+	}
+
+	/// Set the read timeout.
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		*lock(&self.read_timeout) = timeout;
+		Ok(())
+	}
+
+	/// Get the read timeout.
+	pub fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		Ok(*lock(&self.read_timeout))
+	}
+
+	/// Set the write timeout.
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		lock(&self.writer).set_write_timeout(Some(socket_timeout(timeout)))?;
+		*lock(&self.write_timeout) = timeout;
+		Ok(())
+	}
+
+	/// Get the write timeout.
+	pub fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		Ok(*lock(&self.write_timeout))
+	}
+
+	/// Set the baud rate of the remote serial port.
+	pub fn set_baud_rate(&self, baud_rate: u32) -> std::io::Result<()> {
+		self.send_command(command::SET_BAUDRATE, &baud_rate.to_be_bytes())
+	}
+
+	/// Set the character size of the remote serial port.
+	pub fn set_char_size(&self, char_size: CharSize) -> std::io::Result<()> {
+		self.send_command(command::SET_DATASIZE, &[char_size as u8])
+	}
+
+	/// Set the number of stop bits of the remote serial port.
+	pub fn set_stop_bits(&self, stop_bits: StopBits) -> std::io::Result<()> {
+		let value = match stop_bits {
+			StopBits::One => 1,
+			StopBits::Two => 2,
+		};
+		self.send_command(command::SET_STOPSIZE, &[value])
+	}
+
+	/// Set the parity mode of the remote serial port.
+	pub fn set_parity(&self, parity: Parity) -> std::io::Result<()> {
+		let value = match parity {
+			Parity::None => 1,
+			Parity::Odd => 2,
+			Parity::Even => 3,
+			Parity::Mark => 4,
+			Parity::Space => 5,
+		};
+		self.send_command(command::SET_PARITY, &[value])
+	}
+
+	/// Set the flow control mode of the remote serial port.
+	pub fn set_flow_control(&self, flow_control: FlowControl) -> std::io::Result<()> {
+		let value = match flow_control {
+			FlowControl::None => control::FLOW_NONE,
+			FlowControl::XonXoff => control::FLOW_XON_XOFF,
+			FlowControl::RtsCts => control::FLOW_HARDWARE,
+		};
+		self.send_command(command::SET_CONTROL, &[value])
+	}
+
+	/// Apply the baud rate, character size, stop bits, parity and flow control from a [`Settings`] object.
+	///
+	/// This can be used to apply the same configuration to a local and a remote serial port.
+	pub fn apply_settings(&self, settings: &Settings) -> std::io::Result<()> {
+		self.set_baud_rate(settings.get_baud_rate()?)?;
+		self.set_char_size(settings.get_char_size()?)?;
+		self.set_stop_bits(settings.get_stop_bits()?)?;
+		self.set_parity(settings.get_parity()?)?;
+		self.set_flow_control(settings.get_flow_control()?)?;
+		Ok(())
+	}
+
+	/// Ask the server to discard its input and output buffers.
+	///
+	/// Received data that has not been read yet is discarded too.
+	pub fn discard_buffers(&self) -> std::io::Result<()> {
+		self.send_command(command::PURGE_DATA, &[3])?;
+		lock(&self.shared.state).data.clear();
+		Ok(())
+	}
+
+	/// Ask the server to discard its input buffer.
+	///
+	/// Received data that has not been read yet is discarded too.
+	pub fn discard_input_buffer(&self) -> std::io::Result<()> {
+		self.send_command(command::PURGE_DATA, &[1])?;
+		lock(&self.shared.state).data.clear();
+		Ok(())
+	}
+
+	/// Ask the server to discard its output buffer.
+	pub fn discard_output_buffer(&self) -> std::io::Result<()> {
+		self.send_command(command::PURGE_DATA, &[2])
+	}
+
+	/// Ask the server to stop sending data to us.
+	pub fn suspend_input(&self) -> std::io::Result<()> {
+		self.send_command(command::FLOWCONTROL_SUSPEND, &[])
+	}
+
+	/// Ask the server to resume sending data to us.
+	pub fn resume_input(&self) -> std::io::Result<()> {
+		self.send_command(command::FLOWCONTROL_RESUME, &[])
+	}
+
+	/// Set the break state of the remote serial port.
+	pub fn set_break(&self, enable: bool) -> std::io::Result<()> {
+		let value = if enable { control::BREAK_ON } else { control::BREAK_OFF };
+		self.send_command(command::SET_CONTROL, &[value])
+	}
+
+	/// Set the state of the Ready To Send line.
+	pub fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		let value = if state { control::RTS_ON } else { control::RTS_OFF };
+		self.send_command(command::SET_CONTROL, &[value])
+	}
+
+	/// Set the state of the Data Terminal Ready line.
+	pub fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		let value = if state { control::DTR_ON } else { control::DTR_OFF };
+		self.send_command(command::SET_CONTROL, &[value])
+	}
+
+	/// Read the state of the Clear To Send line.
+	///
+	/// This reports the last modem state sent by the server.
+	pub fn read_cts(&self) -> std::io::Result<bool> {
+		self.read_modem_state(modem_state::CTS)
+	}
+
+	/// Read the state of the Data Set Ready line.
+	///
+	/// This reports the last modem state sent by the server.
+	pub fn read_dsr(&self) -> std::io::Result<bool> {
+		self.read_modem_state(modem_state::DSR)
+	}
+
+	/// Read the state of the Ring Indicator line.
+	///
+	/// This reports the last modem state sent by the server.
+	pub fn read_ri(&self) -> std::io::Result<bool> {
+		self.read_modem_state(modem_state::RI)
+	}
+
+	/// Read the state of the Carrier Detect line.
+	///
+	/// This reports the last modem state sent by the server.
+	pub fn read_cd(&self) -> std::io::Result<bool> {
+		self.read_modem_state(modem_state::CD)
+	}
+
+	fn read_modem_state(&self, bit: u8) -> std::io::Result<bool> {
+		Ok(lock(&self.shared.state).modem_state & bit != 0)
+	}
+
+	/// Send a COM-PORT-OPTION subnegotiation to the server.
+	fn send_command(&self, command: u8, value: &[u8]) -> std::io::Result<()> {
+		let mut message = vec![telnet::IAC, telnet::SB, telnet::COM_PORT_OPTION, command];
+		for &byte in value {
+			message.push(byte);
+			if byte == telnet::IAC {
+				message.push(telnet::IAC);
+			}
+		}
+		message.extend_from_slice(&[telnet::IAC, telnet::SE]);
+		lock(&self.writer).write_all(&message).map_err(map_timeout_error)
+	}
+}
+
+impl Drop for Rfc2217Port {
+	fn drop(&mut self) {
+		// Shutting down the socket stops the receiving thread.
+		let _ = lock(&self.writer).shutdown(Shutdown::Both);
+	}
+}
+
+impl std::fmt::Debug for Rfc2217Port {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let writer = lock(&self.writer);
+		f.debug_struct("Rfc2217Port")
+			.field("peer_addr", &writer.peer_addr().ok())
+			.finish_non_exhaustive()
+	}
+}
+
+/// The state of the telnet stream parser.
+enum ParseState {
+	/// Normal data.
+	Data,
+
+	/// Received IAC.
+	Iac,
+
+	/// Received IAC followed by WILL, WONT, DO or DONT.
+	Negotiate(u8),
+
+	/// Inside a subnegotiation.
+	Subnegotiation,
+
+	/// Received IAC inside a subnegotiation.
+	SubnegotiationIac,
+}
+
+/// Receive data from the server until the connection is closed.
+fn receive_loop(mut stream: TcpStream, writer: &Mutex<TcpStream>, shared: &Shared) {
+	let mut parse_state = ParseState::Data;
+	let mut subnegotiation = Vec::new();
+	let mut buffer = [0; 1024];
+	loop {
+		let read = match stream.read(&mut buffer) {
+			Ok(0) => {
+				lock(&shared.state).closed = true;
+				shared.condvar.notify_all();
+				return;
+			},
+			Ok(x) => x,
+			Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+			Err(e) => {
+				let mut state = lock(&shared.state);
+				state.error = Some(e);
+				state.closed = true;
+				drop(state);
+				shared.condvar.notify_all();
+				return;
+			},
+		};
+
+		let mut data = Vec::with_capacity(read);
+		let mut replies = Vec::new();
+		let mut modem_state = None;
+		for &byte in &buffer[..read] {
+			parse_state = match parse_state {
+				ParseState::Data if byte == telnet::IAC => ParseState::Iac,
+				ParseState::Data => {
+					data.push(byte);
+					ParseState::Data
+				},
+				ParseState::Iac => match byte {
+					telnet::IAC => {
+						data.push(telnet::IAC);
+						ParseState::Data
+					},
+					telnet::WILL | telnet::WONT | telnet::DO | telnet::DONT => ParseState::Negotiate(byte),
+					telnet::SB => {
+						subnegotiation.clear();
+						ParseState::Subnegotiation
+					},
+					// Ignore other commands, like NOP or GA.
+					_ => ParseState::Data,
+				},
+				ParseState::Negotiate(verb) => {
+					if let Some(reply) = negotiation_reply(verb, byte) {
+						replies.extend_from_slice(&[telnet::IAC, reply, byte]);
+					}
+					ParseState::Data
+				},
+				ParseState::Subnegotiation if byte == telnet::IAC => ParseState::SubnegotiationIac,
+				ParseState::Subnegotiation => {
+					subnegotiation.push(byte);
+					ParseState::Subnegotiation
+				},
+				ParseState::SubnegotiationIac => match byte {
+					telnet::SE => {
+						if let [telnet::COM_PORT_OPTION, cmd, value, ..] = subnegotiation[..] {
+							if cmd == command::NOTIFY_MODEMSTATE + command::SERVER_OFFSET {
+								modem_state = Some(value);
+							}
+						}
+						ParseState::Data
+					},
+					_ => {
+						subnegotiation.push(byte);
+						ParseState::Subnegotiation
+					},
+				},
+			}
+		}
+
+		if !replies.is_empty() {
+			// If this fails, the connection is broken and the next read will tell us.
+			let _ = lock(writer).write_all(&replies);
+		}
+
+		if !data.is_empty() || modem_state.is_some() {
+			let mut state = lock(&shared.state);
+			state.data.extend(data);
+			if let Some(modem_state) = modem_state {
+				state.modem_state = modem_state;
+			}
+			drop(state);
+			shared.condvar.notify_all();
+		}
+	}
+}
+
+/// Determine the reply to a telnet option negotiation.
+///
+/// We requested the options we need when connecting, so we only need to reply to refuse other options.
+fn negotiation_reply(verb: u8, option: u8) -> Option<u8> {
+	let supported = matches!(option, telnet::BINARY | telnet::SGA | telnet::COM_PORT_OPTION);
+	match verb {
+		telnet::DO if !supported => Some(telnet::WONT),
+		telnet::WILL if !supported => Some(telnet::DONT),
+		_ => None,
+	}
+}
@@ -1,6 +1,8 @@
 //! RS-422 and RS-485 configuration.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use crate::SerialTransport;
 
 /// The mode of a transceiver.
 ///
@@ -66,6 +68,15 @@ pub struct Rs485Config {
 
 	/// Invert the RTS signal: set it low during transmissions and high after.
 	invert_rts: bool,
+
+	/// Enable 9-bit addressing mode.
+	addressing: bool,
+
+	/// Only receive data sent to this address (requires addressing mode).
+	receive_address: Option<u8>,
+
+	/// Send data to this address (requires addressing mode).
+	destination_address: Option<u8>,
 }
 
 impl Rs485Config {
@@ -150,6 +161,52 @@ impl Rs485Config {
 	pub fn get_invert_rts(&self) -> bool {
 		self.invert_rts
 	}
+
+	/// Enable or disable 9-bit addressing mode for multidrop buses.
+	///
+	/// In addressing mode, the ninth bit of each character indicates if it is an address or data.
+	/// Use [`Self::set_receive_address()`] and [`Self::set_destination_address()`] to configure the addresses.
+	///
+	/// On Linux, this requires kernel version 6.0 or newer and a device driver that supports it.
+	///
+	/// Note that this option may be silently ignored by devices that do not support it.
+	pub fn set_addressing(&mut self, enable: bool) {
+		self.addressing = enable;
+	}
+
+	/// Check if 9-bit addressing mode is enabled.
+	pub fn get_addressing(&self) -> bool {
+		self.addressing
+	}
+
+	/// Set the address used to filter received data in addressing mode.
+	///
+	/// If set, only data sent to this address will be received.
+	/// Use `None` to receive all data.
+	///
+	/// This only has an effect if addressing mode is enabled with [`Self::set_addressing()`].
+	pub fn set_receive_address(&mut self, address: Option<u8>) {
+		self.receive_address = address;
+	}
+
+	/// Get the address used to filter received data in addressing mode.
+	pub fn get_receive_address(&self) -> Option<u8> {
+		self.receive_address
+	}
+
+	/// Set the destination address for transmitted data in addressing mode.
+	///
+	/// If set, the address is sent before the data written to the serial port.
+	///
+	/// This only has an effect if addressing mode is enabled with [`Self::set_addressing()`].
+	pub fn set_destination_address(&mut self, address: Option<u8>) {
+		self.destination_address = address;
+	}
+
+	/// Get the destination address for transmitted data in addressing mode.
+	pub fn get_destination_address(&self) -> Option<u8> {
+		self.destination_address
+	}
 }
 
 impl From<Rs485Config> for TransceiverMode {
@@ -157,3 +214,592 @@ impl From<Rs485Config> for TransceiverMode {
 		Self::Rs485(other)
 	}
 }
+
+/// The RS-485/422 features supported by the driver of a serial port.
+///
+/// Use [`supported_features()`] to query the features of a serial port.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rs4xxFeatures {
+	pub(crate) rs485: bool,
+	pub(crate) rs422: bool,
+	pub(crate) full_duplex: bool,
+	pub(crate) bus_termination: bool,
+	pub(crate) delay_before_send: bool,
+	pub(crate) delay_after_send: bool,
+	pub(crate) invert_rts: bool,
+	pub(crate) addressing: bool,
+}
+
+impl Rs4xxFeatures {
+	/// Check if RS-485 mode is supported.
+	///
+	/// If this is false, all other RS-485 features are reported as unsupported too.
+	pub fn rs485(&self) -> bool {
+		self.rs485
+	}
+
+	/// Check if RS-422 mode is supported.
+	pub fn rs422(&self) -> bool {
+		self.rs422
+	}
+
+	/// Check if full-duplex RS-485 mode (receiving while transmitting) is supported.
+	///
+	/// See [`Rs485Config::set_full_duplex()`].
+	pub fn full_duplex(&self) -> bool {
+		self.full_duplex
+	}
+
+	/// Check if the bus termination resistor can be controlled.
+	///
+	/// See [`Rs485Config::set_bus_termination()`].
+	pub fn bus_termination(&self) -> bool {
+		self.bus_termination
+	}
+
+	/// Check if a delay before sending is supported.
+	///
+	/// See [`Rs485Config::set_delay_before_send()`].
+	pub fn delay_before_send(&self) -> bool {
+		self.delay_before_send
+	}
+
+	/// Check if a delay after sending is supported.
+	///
+	/// See [`Rs485Config::set_delay_after_send()`].
+	pub fn delay_after_send(&self) -> bool {
+		self.delay_after_send
+	}
+
+	/// Check if the RTS signal can be inverted.
+	///
+	/// See [`Rs485Config::set_invert_rts()`].
+	pub fn invert_rts(&self) -> bool {
+		self.invert_rts
+	}
+
+	/// Check if 9-bit addressing mode is supported.
+	///
+	/// See [`Rs485Config::set_addressing()`].
+	pub fn addressing(&self) -> bool {
+		self.addressing
+	}
+}
+
+/// Query the RS-485/422 features supported by the driver of a serial port.
+///
+/// This is currently only supported on Linux.
+///
+/// Many drivers silently ignore unsupported options when the transceiver mode is set.
+/// This function detects which options are actually honored by applying configurations
+/// with all options enabled and reading back the configuration reported by the driver.
+/// The original configuration is restored afterwards.
+///
+/// Because this briefly changes the transceiver mode, you should not use this function while communicating on the bus.
+///
+/// If the driver does not support RS-485 at all, all features are reported as unsupported.
+/// Drivers can still lie about the features they support, so the result may not be completely reliable.
+#[cfg(any(feature = "doc", all(feature = "rs4xx", target_os = "linux")))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "rs4xx", target_os = "linux"))))]
+pub fn supported_features(port: &crate::SerialPort) -> std::io::Result<Rs4xxFeatures> {
+	#[cfg(all(feature = "rs4xx", target_os = "linux"))]
+	return crate::sys::get_rs4xx_supported_features(&port.inner);
+	#[allow(unreachable_code)] {
+		let _ = port;
+		panic!("unsupported platform");
+	}
+}
+
+/// Data received in 9-bit addressing mode, together with the address it was sent to.
+///
+/// See [`AddressedReader`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AddressedData {
+	/// The receive address that the data matched, if a receive address filter is active.
+	pub address: Option<u8>,
+
+	/// The number of bytes read into the buffer.
+	pub len: usize,
+}
+
+/// A reader that reports which receive address the received data matched in 9-bit addressing mode.
+///
+/// This is currently only supported on Linux.
+///
+/// The Linux kernel does not pass the address bytes of a multidrop bus to userspace.
+/// What it does expose is the receive address filter (see [`Rs485Config::set_receive_address()`]):
+/// when the filter is active, all received data was sent to that address.
+/// This reader reports that address with every read,
+/// and allows you to switch the filter to a different address with [`Self::set_receive_address()`],
+/// so a device that answers to multiple addresses can keep track of which address the data belongs to.
+///
+/// The kernel does not report at which point in the byte stream the address filter took effect.
+/// Data received just before switching the address may still be buffered by the OS,
+/// so you may want to call [`crate::SerialPort::discard_input_buffer()`] after switching.
+#[cfg(any(feature = "doc", all(feature = "rs4xx", target_os = "linux")))]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "rs4xx", target_os = "linux"))))]
+#[derive(Debug)]
+pub struct AddressedReader<'a> {
+	/// The serial port to read from.
+	port: &'a crate::SerialPort,
+
+	/// The RS-485 configuration of the serial port, with addressing mode enabled.
+	config: Rs485Config,
+}
+
+#[cfg(any(feature = "doc", all(feature = "rs4xx", target_os = "linux")))]
+impl<'a> AddressedReader<'a> {
+	/// Create a reader for a serial port in RS-485 mode with addressing mode enabled.
+	///
+	/// If the serial port is not in RS-485 mode with addressing mode enabled,
+	/// an error of kind [`std::io::ErrorKind::InvalidInput`] is returned.
+	pub fn new(port: &'a crate::SerialPort) -> std::io::Result<Self> {
+		match port.get_rs4xx_mode()? {
+			TransceiverMode::Rs485(config) if config.get_addressing() => Ok(Self { port, config }),
+			_ => Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"the serial port is not in RS-485 mode with addressing enabled",
+			)),
+		}
+	}
+
+	/// Get the receive address that the data read from the serial port currently matches.
+	///
+	/// Returns `None` if no receive address filter is active, in which case the data could be sent to any address.
+	pub fn receive_address(&self) -> Option<u8> {
+		self.config.get_receive_address()
+	}
+
+	/// Change the receive address filter of the serial port.
+	///
+	/// Pass `None` to disable the filter and receive data for all addresses.
+	pub fn set_receive_address(&mut self, address: Option<u8>) -> std::io::Result<()> {
+		let mut config = self.config.clone();
+		config.set_receive_address(address);
+		self.port.set_rs4xx_mode(TransceiverMode::Rs485(config.clone()))?;
+		self.config = config;
+		Ok(())
+	}
+
+	/// Read data from the serial port, and report the receive address that it matched.
+	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<AddressedData> {
+		let len = self.port.read(buf)?;
+		Ok(AddressedData {
+			address: self.receive_address(),
+			len,
+		})
+	}
+}
+
+/// The control line used to switch the direction of an RS-485 transceiver in software.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectionLine {
+	/// Use the RTS (request-to-send) line.
+	///
+	/// This is how most RS-485 adapters are wired.
+	#[default]
+	Rts,
+
+	/// Use the DTR (data-terminal-ready) line.
+	Dtr,
+
+	/// Do not control the direction of the transceiver.
+	///
+	/// Use this if the direction is already controlled by the kernel or the hardware,
+	/// but you still want to use the other features of [`SoftwareRs485Port`], like echo suppression.
+	None,
+}
+
+/// A custom way to switch the direction of an RS-485 transceiver.
+///
+/// Use this with [`SoftwareRs485Port::with_direction_control()`] if the direction pin of your transceiver
+/// is not wired to the RTS or DTR line, but for example to a GPIO line.
+///
+/// This trait is implemented for closures that take a `bool` and return a [`std::io::Result<()>`].
+pub trait DirectionControl: Send + Sync {
+	/// Enable or disable the transmitter.
+	///
+	/// This is called with `true` before each transmission and with `false` after each transmission.
+	fn set_transmit(&self, transmit: bool) -> std::io::Result<()>;
+}
+
+impl<F> DirectionControl for F
+where
+	F: Fn(bool) -> std::io::Result<()> + Send + Sync,
+{
+	fn set_transmit(&self, transmit: bool) -> std::io::Result<()> {
+		self(transmit)
+	}
+}
+
+/// Configuration for software RS-485 direction control.
+///
+/// This mirrors the [`Rs485Config`] options that can be emulated in software.
+/// See [`SoftwareRs485Port`] for more information.
+#[derive(Debug, Clone, Default)]
+pub struct SoftwareRs485Config {
+	/// The control line used to enable the transmitter.
+	direction_line: DirectionLine,
+
+	/// Time to delay after enabling the transmitter, before starting transmission.
+	delay_before_send: Duration,
+
+	/// Time to delay after finishing a transmission, before disabling the transmitter.
+	delay_after_send: Duration,
+
+	/// Invert the direction line: set it low during transmissions and high after.
+	invert_line: bool,
+
+	/// Discard the echo of transmitted data.
+	suppress_echo: bool,
+
+	/// Minimum time between receiving data and starting the next transmission.
+	turnaround_delay: Duration,
+}
+
+impl SoftwareRs485Config {
+	/// Create a new configuration that uses the RTS line without inversion, with all delays set to zero.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the control line used to enable the transmitter.
+	pub fn set_direction_line(&mut self, line: DirectionLine) {
+		self.direction_line = line;
+	}
+
+	/// Get the control line used to enable the transmitter.
+	pub fn get_direction_line(&self) -> DirectionLine {
+		self.direction_line
+	}
+
+	/// Set the time to delay after enabling the transmitter, before starting a transmission.
+	///
+	/// This may be needed to give some devices on the bus time to activate their receiver.
+	pub fn set_delay_before_send(&mut self, delay: Duration) {
+		self.delay_before_send = delay;
+	}
+
+	/// Get the time to delay after enabling the transmitter, before starting a transmission.
+	pub fn get_delay_before_send(&self) -> Duration {
+		self.delay_before_send
+	}
+
+	/// Set the time to delay after finishing a transmission, before disabling the transmitter.
+	///
+	/// This may be needed to give some devices on the bus time to fully receive the message before they disable their receiver.
+	pub fn set_delay_after_send(&mut self, delay: Duration) {
+		self.delay_after_send = delay;
+	}
+
+	/// Get the time to delay after finishing a transmission, before disabling the transmitter.
+	pub fn get_delay_after_send(&self) -> Duration {
+		self.delay_after_send
+	}
+
+	/// Set whether to invert the level of the direction line.
+	///
+	/// If enabled, the direction line will be set low during transmissions and high again after each transmission.
+	pub fn set_invert_line(&mut self, invert: bool) {
+		self.invert_line = invert;
+	}
+
+	/// Check if the level of the direction line is inverted.
+	pub fn get_invert_line(&self) -> bool {
+		self.invert_line
+	}
+
+	/// Enable or disable echo suppression.
+	///
+	/// On two-wire (half-duplex) RS-485 buses, the receiver may pick up the data you transmit yourself.
+	/// With echo suppression enabled, every write is followed by reading and discarding the same number of bytes,
+	/// so that you do not read back your own messages.
+	///
+	/// If the echo is not received within the read timeout, the write returns an error.
+	/// If the echo is different from the transmitted data, the write returns an [`std::io::ErrorKind::InvalidData`] error,
+	/// which usually indicates a collision on the bus.
+	///
+	/// Do not enable this if the transceiver disables the receiver while transmitting.
+	pub fn set_echo_suppression(&mut self, enable: bool) {
+		self.suppress_echo = enable;
+	}
+
+	/// Check if echo suppression is enabled.
+	pub fn get_echo_suppression(&self) -> bool {
+		self.suppress_echo
+	}
+
+	/// Set the minimum time between receiving data and starting the next transmission.
+	///
+	/// Some slow devices need time to switch their transceiver back to receiving after sending a response.
+	/// With a turnaround delay, a write will wait until at least this much time has passed since the last successful read.
+	///
+	/// This works regardless of kernel RS-485 support.
+	/// Combine it with [`DirectionLine::None`] if the direction is controlled by the kernel or the hardware.
+	pub fn set_turnaround_delay(&mut self, delay: Duration) {
+		self.turnaround_delay = delay;
+	}
+
+	/// Get the minimum time between receiving data and starting the next transmission.
+	pub fn get_turnaround_delay(&self) -> Duration {
+		self.turnaround_delay
+	}
+}
+
+/// Emulated RS-485 direction control for a serial port.
+///
+/// Some platforms and drivers do not support automatic RS-485 direction control (see [`TransceiverMode::Rs485`]).
+/// This includes Windows, macOS and many USB adapters.
+/// This wrapper emulates it in software:
+/// for every write it enables the transmitter with the RTS or DTR line, writes the data,
+/// waits for the data to be transmitted and then disables the transmitter again.
+///
+/// The timing of the direction line is less accurate than with kernel support,
+/// because the operating system may not resume this thread exactly when transmission is finished.
+/// If the other devices on the bus respond very quickly, you may need to lower the latency of your serial port.
+///
+/// The wrapper works with any [`SerialTransport`], but it is normally used with a [`SerialPort`][crate::SerialPort].
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{SerialPort, SerialTransport};
+/// use serial2::rs4xx::{SoftwareRs485Config, SoftwareRs485Port};
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+/// let port = SoftwareRs485Port::new(port, SoftwareRs485Config::new())?;
+/// port.write_all(b"Hello!")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SoftwareRs485Port<T> {
+	inner: T,
+	config: SoftwareRs485Config,
+
+	/// Custom direction control to use instead of the RTS or DTR line.
+	direction_control: Option<Box<dyn DirectionControl>>,
+
+	/// Lock to prevent concurrent writes from toggling the direction line while another write is in progress.
+	write_lock: std::sync::Mutex<()>,
+
+	/// The time data was last received, used for the turnaround delay.
+	last_receive: std::sync::Mutex<Option<Instant>>,
+}
+
+impl<T: SerialTransport> SoftwareRs485Port<T> {
+	/// Wrap a transport with software RS-485 direction control.
+	///
+	/// This immediately disables the transmitter.
+	pub fn new(inner: T, config: SoftwareRs485Config) -> std::io::Result<Self> {
+		Self::with_optional_direction_control(inner, config, None)
+	}
+
+	/// Wrap a transport with software RS-485 direction control using a custom [`DirectionControl`].
+	///
+	/// The direction control is used instead of the RTS or DTR line.
+	/// The [`SoftwareRs485Config::set_direction_line()`] and [`SoftwareRs485Config::set_invert_line()`] options are ignored,
+	/// but the delays are still applied.
+	///
+	/// This immediately disables the transmitter.
+	pub fn with_direction_control(
+		inner: T,
+		config: SoftwareRs485Config,
+		direction_control: impl DirectionControl + 'static,
+	) -> std::io::Result<Self> {
+		Self::with_optional_direction_control(inner, config, Some(Box::new(direction_control)))
+	}
+
+	fn with_optional_direction_control(
+		inner: T,
+		config: SoftwareRs485Config,
+		direction_control: Option<Box<dyn DirectionControl>>,
+	) -> std::io::Result<Self> {
+		let port = Self {
+			inner,
+			config,
+			direction_control,
+			write_lock: std::sync::Mutex::new(()),
+			last_receive: std::sync::Mutex::new(None),
+		};
+		port.set_transmit(false)?;
+		Ok(port)
+	}
+
+	/// Get a reference to the wrapped transport.
+	pub fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Get the configuration used for direction control.
+	pub fn get_config(&self) -> &SoftwareRs485Config {
+		&self.config
+	}
+
+	/// Get the wrapped transport back.
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+
+	/// Enable or disable the transmitter.
+	fn set_transmit(&self, transmit: bool) -> std::io::Result<()> {
+		if let Some(direction_control) = &self.direction_control {
+			return direction_control.set_transmit(transmit);
+		}
+		let level = transmit != self.config.invert_line;
+		match self.config.direction_line {
+			DirectionLine::Rts => self.inner.set_rts(level),
+			DirectionLine::Dtr => self.inner.set_dtr(level),
+			DirectionLine::None => Ok(()),
+		}
+	}
+
+	/// Write all bytes while the transmitter is enabled.
+	///
+	/// This blocks until all data has been transmitted and the transmitter has been disabled again.
+	/// The transmitter is also disabled again if writing fails.
+	fn transmit(&self, buf: &[u8]) -> std::io::Result<()> {
+		let _lock = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+		self.wait_turnaround();
+		self.set_transmit(true)?;
+		self.sleep(self.config.delay_before_send);
+		let result = self.inner.write_all(buf).and_then(|()| self.inner.flush());
+		self.sleep(self.config.delay_after_send);
+		let disabled = self.set_transmit(false);
+		result.and(disabled)?;
+		if self.config.suppress_echo {
+			self.discard_echo(buf)?;
+		}
+		Ok(())
+	}
+
+	/// Wait until the turnaround delay has passed since data was last received.
+	fn wait_turnaround(&self) {
+		let last_receive = *self.last_receive.lock().unwrap_or_else(|e| e.into_inner());
+		if let Some(last_receive) = last_receive {
+			let elapsed = self.inner.now().saturating_duration_since(last_receive);
+			self.sleep(self.config.turnaround_delay.saturating_sub(elapsed));
+		}
+	}
+
+	/// Remember that data was received now, for the turnaround delay.
+	fn mark_receive(&self) {
+		if !self.config.turnaround_delay.is_zero() {
+			*self.last_receive.lock().unwrap_or_else(|e| e.into_inner()) = Some(self.inner.now());
+		}
+	}
+
+	/// Sleep on the clock of the wrapped transport, unless the duration is zero.
+	fn sleep(&self, duration: Duration) {
+		if !duration.is_zero() {
+			self.inner.sleep(duration);
+		}
+	}
+
+	/// Read and discard the echo of transmitted data.
+	fn discard_echo(&self, transmitted: &[u8]) -> std::io::Result<()> {
+		let mut echo = vec![0; transmitted.len()];
+		self.inner.read_exact(&mut echo)?;
+		if echo != transmitted {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidData,
+				"received echo does not match transmitted data",
+			));
+		}
+		Ok(())
+	}
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for SoftwareRs485Port<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SoftwareRs485Port")
+			.field("inner", &self.inner)
+			.field("config", &self.config)
+			.field("custom_direction_control", &self.direction_control.is_some())
+			.finish_non_exhaustive()
+	}
+}
+
+impl<T: SerialTransport> SerialTransport for SoftwareRs485Port<T> {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let read = self.inner.read(buf)?;
+		if read > 0 {
+			self.mark_receive();
+		}
+		Ok(read)
+	}
+
+	/// Write bytes with the transmitter enabled.
+	///
+	/// Unlike a regular write, this always writes the whole buffer
+	/// and waits until the data has been transmitted.
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		self.transmit(buf)?;
+		Ok(buf.len())
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+
+	fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+		self.inner.read_exact(buf)?;
+		self.mark_receive();
+		Ok(())
+	}
+
+	fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+		self.transmit(buf)
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_read_timeout(timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_read_timeout()
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_write_timeout(timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_write_timeout()
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		self.inner.discard_buffers()
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_rts(state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		self.inner.read_cts()
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_dtr(state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		self.inner.read_dsr()
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		self.inner.read_ri()
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		self.inner.read_cd()
+	}
+
+	fn now(&self) -> Instant {
+		self.inner.now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		self.inner.sleep(duration)
+	}
+}
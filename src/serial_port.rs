@@ -1,15 +1,261 @@
 use std::io::{IoSlice, IoSliceMut};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crate::statistics::Counters;
 use crate::{sys, IntoSettings, Settings};
 
 #[cfg(any(feature = "doc", all(feature = "rs4xx", target_os = "linux")))]
 use crate::rs4xx;
 
+/// The interval for polling the output queue in [`SerialPort::flush_timeout()`].
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The maximum number of bytes read by one call to [`SerialPort::read_bytes()`].
+#[cfg(feature = "bytes")]
+const READ_BYTES_CHUNK_SIZE: usize = 4096;
+
 /// A serial port.
+///
+/// # Event loops
+/// On Unix platforms, a serial port can be registered directly with readiness based event loops,
+/// like the [`polling`](https://docs.rs/polling) crate (used by `smol` and `async-io`) or [`mio`](https://docs.rs/mio),
+/// through the [`AsFd`][std::os::fd::AsFd] implementation.
+/// Both level-triggered and edge-triggered registration are supported:
+/// * The file descriptor stays valid and does not change for the lifetime of the `SerialPort`.
+///   Only [`Self::reopen()`] creates a new file descriptor, which belongs to the new `SerialPort` it returns.
+/// * Ports created by [`Self::open()`] and `SerialPort::pair()` always use a file descriptor in non-blocking mode.
+///   A port created from an existing file descriptor keeps the mode of that file descriptor.
+///
+/// To make reads return immediately when the event loop reports a spurious wake-up,
+/// set the read timeout to zero and use [`TimeoutBehavior::WouldBlock`].
+/// Reads will then fail with [`std::io::ErrorKind::WouldBlock`] if no data is available, as event loops expect.
+/// With a write timeout of zero, a write that can not make progress fails with [`std::io::ErrorKind::TimedOut`].
+///
+/// On Windows, serial ports use overlapped I/O on a file handle,
+/// which can not be registered with readiness based event loops such as `polling`.
 pub struct SerialPort {
-	inner: sys::SerialPort,
+	pub(crate) inner: sys::SerialPort,
+
+	/// Statistics counters, if enabled with [`Self::enable_statistics()`].
+	stats: OnceLock<Arc<Counters>>,
+
+	/// The [`TimeoutBehavior`] for reads, stored as `u8`.
+	timeout_behavior: AtomicU8,
+}
+
+/// How [`SerialPort::read()`] reports a timeout.
+///
+/// Use [`SerialPort::set_timeout_behavior()`] to change it.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum TimeoutBehavior {
+	/// Return an error of kind [`std::io::ErrorKind::TimedOut`].
+	///
+	/// This is the default.
+	#[default]
+	Error,
+
+	/// Return `Ok(0)`.
+	///
+	/// Note that `Ok(0)` normally indicates the end of file,
+	/// so consumers can no longer distinguish a timeout from a closed stream.
+	ReturnZero,
+
+	/// Return an error of kind [`std::io::ErrorKind::WouldBlock`], like a non-blocking file does when no data is available.
+	WouldBlock,
+}
+
+impl TimeoutBehavior {
+	fn from_u8(value: u8) -> Self {
+		match value {
+			1 => Self::ReturnZero,
+			2 => Self::WouldBlock,
+			_ => Self::Error,
+		}
+	}
+
+	fn as_u8(self) -> u8 {
+		match self {
+			Self::Error => 0,
+			Self::ReturnZero => 1,
+			Self::WouldBlock => 2,
+		}
+	}
+}
+
+/// When [`SerialPort::read()`] returns.
+///
+/// Use [`SerialPort::set_read_mode()`] to change it.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ReadMode {
+	/// Return as soon as any data is available.
+	///
+	/// This is the default.
+	#[default]
+	FirstByte,
+
+	/// Wait until the buffer is full, or until no new data arrived for `inter_byte_timeout`.
+	///
+	/// The inter-byte timeout only starts after the first byte has been received.
+	/// The read timeout still applies to the read as a whole:
+	/// if it expires after some data has been received, the read returns the data received so far.
+	FillBuffer {
+		/// The maximum time to wait for the next byte.
+		inter_byte_timeout: Duration,
+	},
+}
+
+/// When new settings take effect, and what happens to buffered data.
+///
+/// Use [`SerialPort::set_apply_mode()`] to change it.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ApplyMode {
+	/// Wait until all queued output has been transmitted, then apply the new settings.
+	///
+	/// This is `TCSADRAIN` on Unix.
+	/// On Windows, the output is flushed with `FlushFileBuffers()` first.
+	///
+	/// This is the default.
+	#[default]
+	Drain,
+
+	/// Apply the new settings immediately.
+	///
+	/// Data that is still queued for transmission will be transmitted with the new settings.
+	///
+	/// This is `TCSANOW` on Unix.
+	Immediate,
+
+	/// Wait until all queued output has been transmitted, then discard all received but unread data and apply the new settings.
+	///
+	/// This is `TCSAFLUSH` on Unix.
+	DrainAndDiscardInput,
+
+	/// Discard all queued output and all received but unread data, then apply the new settings immediately.
+	///
+	/// This is useful when switching baud rates in a bootloader protocol,
+	/// where any data still in the buffers is garbage anyway.
+	Discard,
+}
+
+impl ApplyMode {
+	pub(crate) fn from_u8(value: u8) -> Self {
+		match value {
+			1 => Self::Immediate,
+			2 => Self::DrainAndDiscardInput,
+			3 => Self::Discard,
+			_ => Self::Drain,
+		}
+	}
+
+	pub(crate) fn as_u8(self) -> u8 {
+		match self {
+			Self::Drain => 0,
+			Self::Immediate => 1,
+			Self::DrainAndDiscardInput => 2,
+			Self::Discard => 3,
+		}
+	}
+}
+
+/// The result of [`SerialPort::loopback_test_control_lines()`].
+///
+/// Each field indicates if the input line follows the output line.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ControlLineLoopback {
+	/// The Clear To Send line follows the Ready To Send line.
+	pub rts_cts: bool,
+
+	/// The Data Set Ready line follows the Data Terminal Ready line.
+	pub dtr_dsr: bool,
+
+	/// The Carrier Detect line follows the Data Terminal Ready line.
+	pub dtr_cd: bool,
+}
+
+/// A snapshot of the complete configuration of a serial port.
+///
+/// Use [`SerialPort::save_settings()`] to take a snapshot,
+/// and [`SerialPort::restore_settings()`] to put the serial port back in the saved state.
+///
+/// The snapshot contains the [`Settings`], the read and write timeouts, the [`ReadMode`], the [`TimeoutBehavior`] and the [`ApplyMode`].
+/// On Windows, it also contains the exact timeouts configured on the handle,
+/// including those set with `SerialPort::set_windows_timeouts()`.
+#[derive(Clone)]
+pub struct SavedSettings {
+	settings: Settings,
+	timeouts: sys::SavedTimeouts,
+	timeout_behavior: TimeoutBehavior,
+	apply_mode: ApplyMode,
+}
+
+impl std::fmt::Debug for SavedSettings {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SavedSettings")
+			.field("settings", &self.settings)
+			.field("timeout_behavior", &self.timeout_behavior)
+			.field("apply_mode", &self.apply_mode)
+			.finish_non_exhaustive()
+	}
+}
+
+/// The state kept by this library for a [`SerialPort`], separate from the file descriptor or handle.
+///
+/// Use [`SerialPort::into_raw_parts()`] to split a serial port into a file descriptor or handle and its state,
+/// and [`SerialPort::from_raw_parts()`] to put them back together.
+/// This allows a fully configured serial port to be handed to another process,
+/// for example after `exec()` or by passing the file descriptor over a Unix socket.
+///
+/// All fields are public, so the state can be sent to the other process in any format you like.
+/// The default value matches the state of a serial port created from a bare file descriptor or handle.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PortState {
+	/// The name of the serial port, as reported by [`SerialPort::name()`].
+	pub name: Option<PathBuf>,
+
+	/// The read timeout, as reported by [`SerialPort::get_read_timeout()`].
+	pub read_timeout: Duration,
+
+	/// The write timeout, as reported by [`SerialPort::get_write_timeout()`].
+	pub write_timeout: Duration,
+
+	/// The read mode, as reported by [`SerialPort::get_read_mode()`].
+	pub read_mode: ReadMode,
+
+	/// The timeout behavior, as reported by [`SerialPort::get_timeout_behavior()`].
+	pub timeout_behavior: TimeoutBehavior,
+
+	/// The apply mode, as reported by [`SerialPort::get_apply_mode()`].
+	pub apply_mode: ApplyMode,
+
+	/// The verification of changes to the RTS and DTR lines, as reported by [`SerialPort::get_verify_control_lines()`].
+	pub verify_control_lines: bool,
+
+	/// The compatibility mode for USB CDC-ACM gadget serial ports, as reported by `SerialPort::cdc_acm_quirks()`.
+	///
+	/// This is ignored on platforms other than Unix.
+	pub cdc_acm_quirks: bool,
+}
+
+impl Default for PortState {
+	fn default() -> Self {
+		let default_timeout = Duration::from_millis(sys::DEFAULT_TIMEOUT_MS.into());
+		Self {
+			name: None,
+			read_timeout: default_timeout,
+			write_timeout: default_timeout,
+			read_mode: ReadMode::default(),
+			timeout_behavior: TimeoutBehavior::default(),
+			apply_mode: ApplyMode::default(),
+			verify_control_lines: false,
+			cdc_acm_quirks: false,
+		}
+	}
 }
 
 impl SerialPort {
@@ -24,6 +270,8 @@ impl SerialPort {
 	///
 	/// The library automatically uses the win32 device namespace on Windows,
 	/// so COM ports above COM9 are supported out of the box.
+	/// Names that are already full device paths, like `\\.\COM12` or device interface paths starting with `\\?\`,
+	/// are opened as-is.
 	///
 	/// # Example 1: Open a serial port with a specific baud rate and default settings.
 	/// ```
@@ -51,8 +299,21 @@ impl SerialPort {
 	/// # }
 	/// ```
 	pub fn open(name: impl AsRef<Path>, settings: impl IntoSettings) -> std::io::Result<Self> {
-		let mut serial_port = Self {
-			inner: sys::SerialPort::open(name.as_ref())?,
+		Self::open_with_options(name.as_ref(), &crate::OpenOptions::new(), settings)
+	}
+
+	/// Open and configure a serial port with the given options.
+	///
+	/// This is the implementation of [`Self::open()`] and [`crate::OpenOptions::open()`].
+	pub(crate) fn open_with_options(
+		name: &Path,
+		options: &crate::OpenOptions,
+		settings: impl IntoSettings,
+	) -> std::io::Result<Self> {
+		let serial_port = Self {
+			inner: sys::SerialPort::open_with_options(name, options)?,
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
 		};
 		let mut port_settings = serial_port.get_configuration()?;
 		settings.apply_to_settings(&mut port_settings)?;
@@ -60,26 +321,83 @@ impl SerialPort {
 		Ok(serial_port)
 	}
 
-	/// Open a connected pair of pseudo-terminals.
+	/// Open and configure a serial port, reporting errors with additional context.
+	///
+	/// This does the same as [`Self::open()`], but the returned [`Error`][crate::Error] includes the name of the serial port,
+	/// and whether the failure happened while opening or while configuring the port.
+	///
+	/// # Example
+	/// ```
+	/// # use serial2::SerialPort;
+	/// # fn foo() -> Result<(), serial2::Error> {
+	/// let port = SerialPort::open_detailed("/dev/ttyUSB0", 115200)?;
+	/// #   Ok(())
+	/// # }
+	/// ```
+	pub fn open_detailed(name: impl AsRef<Path>, settings: impl IntoSettings) -> Result<Self, crate::Error> {
+		let name = name.as_ref();
+		let serial_port = Self {
+			inner: sys::SerialPort::open(name).map_err(|source| crate::Error::Open {
+				port: name.to_owned(),
+				source,
+			})?,
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
+		};
+		let mut port_settings = serial_port.get_configuration().map_err(|e| serial_port.configure_error(None, e))?;
+		settings
+			.apply_to_settings(&mut port_settings)
+			.map_err(|e| serial_port.configure_error(None, e))?;
+		serial_port.set_configuration_detailed(&port_settings)?;
+		Ok(serial_port)
+	}
+
+	/// Open a connected pair of virtual serial ports.
+	///
+	/// Data written to one port can be read from the other port, and vice versa.
+	/// This is mainly useful for testing.
+	///
+	/// On Unix, this opens a pair of pseudo-terminals, configured in raw mode.
+	///
+	/// On Windows, this creates a connected pair of named pipes.
+	/// Reading, writing and timeouts work as normal,
+	/// but the named pipes can not be configured and they do not have control lines.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix"), all(windows, feature = "windows")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(any(feature = "unix", feature = "windows"))))]
+	pub fn pair() -> std::io::Result<(Self, Self)> {
+		let (a, b) = sys::SerialPort::pair()?;
+		let a = Self {
+			inner: a,
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
+		};
+		let b = Self {
+			inner: b,
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
+		};
+
+		#[cfg(unix)]
+		for pty in [&a, &b] {
+			let mut settings = pty.get_configuration()?;
+			settings.set_raw();
+			pty.set_configuration(&settings)?;
+		}
+
+		Ok((a, b))
+	}
+
+	/// Get the path of the slave device of a pseudo-terminal master.
+	///
+	/// For a pair opened with [`Self::pair()`], call this on the first port to get the path of the second port.
+	/// You can pass the path to other programs, to let them communicate with the first port.
+	///
+	/// This function returns an error if the serial port is not a pseudo-terminal master.
 	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
 	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
-	pub fn pair() -> std::io::Result<(Self, Self)> {
+	pub fn pts_name(&self) -> std::io::Result<PathBuf> {
 		#[cfg(unix)] {
-			let (pty_a, pty_b) = sys::SerialPort::pair()?;
-			let mut pty_a = Self { inner: pty_a };
-			let mut pty_b = Self { inner: pty_b };
-			{
-				let mut settings = pty_a.get_configuration()?;
-				settings.set_raw();
-				pty_a.set_configuration(&settings)?;
-			}
-			{
-				let mut settings = pty_b.get_configuration()?;
-				settings.set_raw();
-				pty_b.set_configuration(&settings)?;
-			}
-
-			Ok((pty_a, pty_b))
+			self.inner.pts_name()
 		}
 		#[cfg(windows)] {
 			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
@@ -94,11 +412,178 @@ impl SerialPort {
 		sys::enumerate()
 	}
 
+	/// Get a list of available serial ports with additional information.
+	///
+	/// This returns the same ports as [`Self::available_ports()`],
+	/// but each port is annotated with its type (see [`PortKind`][crate::PortKind]) where possible,
+	/// so callers can filter out Bluetooth or modem ports.
+	///
+	/// On Windows, this also returns the registered Bluetooth and modem ports that are not active,
+	/// for example because the device is not connected.
+	/// Opening those ports may fail.
+	///
+	/// Not currently supported on all platforms.
+	/// On unsupported platforms, this function always returns an error.
+	pub fn available_ports_info() -> std::io::Result<Vec<crate::PortInfo>> {
+		sys::enumerate_info()
+	}
+
+	/// Call a function for each available serial port.
+	///
+	/// This reports the same ports as [`Self::available_ports_info()`], but without collecting them in a [`Vec`].
+	/// On Linux and Android, enumeration through `/sys/class/tty` does not allocate any memory at all,
+	/// which makes it suitable for daemons that enumerate frequently while watching for devices.
+	/// On other platforms, the ports are still collected internally.
+	///
+	/// Not currently supported on all platforms.
+	/// On unsupported platforms, this function always returns an error.
+	pub fn for_each_port<F>(mut f: F) -> std::io::Result<()>
+	where
+		F: FnMut(crate::PortInfoRef<'_>),
+	{
+		sys::for_each_port(&mut f)
+	}
+
+	/// Open and configure the serial port with the given stable identifier.
+	///
+	/// The identifier is compared with [`PortInfo::id()`][crate::PortInfo::id] of all ports reported by [`Self::available_ports_info()`].
+	/// This allows you to reconnect to the same device after it was unplugged,
+	/// even if the operating system assigned a different name to the port.
+	///
+	/// If no port with the given identifier is found, an error of kind [`std::io::ErrorKind::NotFound`] is returned.
+	pub fn open_by_id(id: &str, settings: impl IntoSettings) -> std::io::Result<Self> {
+		let port = Self::available_ports_info()?
+			.into_iter()
+			.find(|port| port.id() == Some(id));
+		match port {
+			Some(port) => Self::open(port.path(), settings),
+			None => Err(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no serial port found with ID {id:?}"),
+			)),
+		}
+	}
+
+	/// Get diagnostics for all candidates considered while listing the available serial ports.
+	///
+	/// Instead of silently skipping entries that can not be inspected or that are filtered out,
+	/// this reports why each candidate was skipped (see [`PortStatus`][crate::PortStatus]).
+	/// This is useful to debug why a serial port is missing from [`Self::available_ports()`].
+	///
+	/// Currently, detailed diagnostics are only available on Linux and Android.
+	/// On Windows, the registered Bluetooth and modem ports that are not active are reported as filtered.
+	/// On other platforms, only the available ports are reported.
+	pub fn available_ports_verbose() -> std::io::Result<Vec<crate::PortDiagnostic>> {
+		sys::enumerate_verbose()
+	}
+
+	/// Check if an error indicates that the serial port device was disconnected.
+	///
+	/// Reads and writes report a disconnected device (for example, an unplugged USB serial adapter)
+	/// with an error of kind [`std::io::ErrorKind::NotConnected`].
+	/// The original OS error is available through [`std::io::Error::get_ref()`].
+	///
+	/// Other I/O on an open serial port, like reading the control lines, may report the raw OS error instead.
+	/// This function recognizes both, so it can be used to check errors from any function that uses an open [`SerialPort`].
+	///
+	/// It is not meant for errors from opening a serial port, like [`Self::open()`] or [`Self::reopen()`].
+	/// On Windows, opening a serial port that is in use fails with `ERROR_ACCESS_DENIED`,
+	/// which some drivers also report for I/O on a disconnected device.
+	/// That error is only recognized as a disconnect if it was reported as [`std::io::ErrorKind::NotConnected`] by I/O on an open port.
+	///
+	/// Once a serial port device is disconnected, all further I/O on the [`SerialPort`] object will fail.
+	/// You need to open the device again after it has been reconnected.
+	pub fn is_disconnected_error(error: &std::io::Error) -> bool {
+		if error.kind() == std::io::ErrorKind::NotConnected {
+			return true;
+		}
+		match error.raw_os_error() {
+			Some(code) => sys::is_disconnect_os_error(code),
+			None => false,
+		}
+	}
+
 	/// Configure (or reconfigure) the serial port.
-	pub fn set_configuration(&mut self, settings: &Settings) -> std::io::Result<()> {
+	///
+	/// This function takes a shared reference, so you can reconfigure a serial port
+	/// while it is shared between threads (for example, inside an [`Arc`][std::sync::Arc]).
+	/// Concurrent calls on the same [`SerialPort`] object are serialized,
+	/// but different handles created with [`Self::try_clone()`] are not synchronized with each other.
+	///
+	/// The settings are read back after applying them.
+	/// If the settings could not be applied, or if the driver did not apply them as requested,
+	/// the previous configuration is restored and an error is returned.
+	/// This way, the serial port is never left in a partially applied configuration.
+	/// On Unix, if the compatibility mode of `SerialPort::set_cdc_acm_quirks()` is enabled,
+	/// the settings are not verified and the previous configuration is not restored.
+	///
+	/// Use [`Self::set_apply_mode()`] to choose if pending output is transmitted before the new settings are applied.
+	pub fn set_configuration(&self, settings: &Settings) -> std::io::Result<()> {
 		self.inner.set_configuration(&settings.inner)
 	}
 
+	/// Configure (or reconfigure) the serial port, reporting errors with additional context.
+	///
+	/// This does the same as [`Self::set_configuration()`], but the returned [`Error`][crate::Error]
+	/// includes the name of the serial port and the first setting that was not applied as requested, if it could be determined.
+	pub fn set_configuration_detailed(&self, settings: &Settings) -> Result<(), crate::Error> {
+		self.set_configuration(settings).map_err(|e| {
+			// The previous configuration may have been restored already,
+			// so prefer the mismatch that was detected while applying the settings.
+			let setting = crate::error::MismatchError::setting_from_io_error(&e).unwrap_or_else(|| {
+				self.get_configuration()
+					.ok()
+					.and_then(|applied| crate::Setting::find_mismatch(&applied, settings))
+			});
+			self.configure_error(setting, e)
+		})
+	}
+
+	/// Modify the configuration of the serial port.
+	///
+	/// This reads the current configuration, passes it to the `modify` function,
+	/// and then applies and verifies the modified settings.
+	/// If the `modify` function returns an error, the configuration is left unchanged.
+	/// Like with [`Self::set_configuration()`], the previous configuration is restored if the modified settings can not be applied.
+	///
+	/// Other calls to [`Self::set_configuration()`] and [`Self::modify_configuration()`] on the same [`SerialPort`] object
+	/// are blocked until the new configuration has been applied.
+	///
+	/// # Example
+	/// ```
+	/// # use serial2::{SerialPort, StopBits};
+	/// # fn foo(port: &SerialPort) -> std::io::Result<()> {
+	/// port.modify_configuration(|settings| {
+	///     settings.set_baud_rate(921600)?;
+	///     settings.set_stop_bits(StopBits::Two);
+	///     Ok(())
+	/// })?;
+	/// #   Ok(())
+	/// # }
+	/// ```
+	pub fn modify_configuration<F>(&self, modify: F) -> std::io::Result<()>
+	where
+		F: FnOnce(&mut Settings) -> std::io::Result<()>,
+	{
+		self.inner.modify_configuration(|inner| {
+			let mut settings = Settings { inner: inner.clone() };
+			modify(&mut settings)?;
+			*inner = settings.inner;
+			Ok(())
+		})
+	}
+
+	/// Change the baud rate of the serial port, keeping all other settings.
+	///
+	/// This is a shorthand for calling [`Settings::set_baud_rate()`] from [`Self::modify_configuration()`].
+	/// It can be used for protocols that switch to a different baud rate after the initial handshake.
+	///
+	/// Note that any data still in the OS buffers may be sent or received with the new baud rate.
+	/// You may want to call [`Self::flush()`] before changing the baud rate.
+	pub fn set_baud_rate(&self, baud_rate: u32) -> std::io::Result<()> {
+		self.modify_configuration(|settings| settings.set_baud_rate(baud_rate))
+	}
+
 	/// Get the current configuration of the serial port.
 	///
 	/// This function can fail if the underlying syscall fails,
@@ -109,18 +594,286 @@ impl SerialPort {
 		})
 	}
 
+	/// Set when new settings take effect, and what happens to data in the OS buffers.
+	///
+	/// This is used by [`Self::set_configuration()`], [`Self::modify_configuration()`] and all functions that use them,
+	/// like [`Self::set_baud_rate()`].
+	/// See [`ApplyMode`] for the available modes.
+	///
+	/// The new mode applies to all handles for the same [`SerialPort`] object,
+	/// including those shared through an [`Arc`][std::sync::Arc].
+	/// Objects created with [`Self::try_clone()`] start with the mode of the original object.
+	pub fn set_apply_mode(&self, mode: ApplyMode) {
+		self.inner.set_apply_mode(mode)
+	}
+
+	/// Get when new settings take effect, and what happens to data in the OS buffers.
+	pub fn get_apply_mode(&self) -> ApplyMode {
+		self.inner.get_apply_mode()
+	}
+
+	/// Take a snapshot of the complete configuration of the serial port.
+	///
+	/// Use [`Self::restore_settings()`] to put the serial port back in the saved state,
+	/// for example after temporarily changing the baud rate for a firmware upload.
+	///
+	/// The timeouts and read mode are saved for this [`SerialPort`] object only:
+	/// objects created with [`Self::try_clone()`] have their own timeouts.
+	pub fn save_settings(&self) -> std::io::Result<SavedSettings> {
+		Ok(SavedSettings {
+			settings: self.get_configuration()?,
+			timeouts: self.inner.save_timeouts(),
+			timeout_behavior: self.get_timeout_behavior(),
+			apply_mode: self.get_apply_mode(),
+		})
+	}
+
+	/// Restore a snapshot taken with [`Self::save_settings()`].
+	///
+	/// The timeouts are restored even if applying the saved [`Settings`] fails,
+	/// so as much of the saved state as possible is put back.
+	/// The first error is returned.
+	pub fn restore_settings(&self, saved: &SavedSettings) -> std::io::Result<()> {
+		self.set_apply_mode(saved.apply_mode);
+		let settings_result = self.set_configuration(&saved.settings);
+		let timeouts_result = self.inner.restore_timeouts(&saved.timeouts);
+		self.set_timeout_behavior(saved.timeout_behavior);
+		settings_result.and(timeouts_result)
+	}
+
+	/// Enable or disable the compatibility mode for USB CDC-ACM gadget serial ports.
+	///
+	/// The serial port of a USB gadget (such as `/dev/ttyGS0` on Linux) and some USB CDC-ACM devices
+	/// accept any configuration, but the settings have no effect on the data transfer.
+	/// Some of them also do not report the applied settings correctly,
+	/// which causes [`Self::set_configuration()`] to report an error.
+	///
+	/// With the compatibility mode enabled, the settings are still applied,
+	/// but they are not read back and verified, and the previous settings are not restored if applying them fails.
+	/// Reading and writing is not affected.
+	///
+	/// The compatibility mode is enabled automatically by [`Self::open()`] for `/dev/ttyGS*` devices on Linux.
+	/// For other devices, open the port with [`KeepSettings`][crate::KeepSettings],
+	/// enable the compatibility mode and then configure the port.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn set_cdc_acm_quirks(&self, enable: bool) {
+		#[cfg(unix)] {
+			self.inner.cdc_acm_quirks.store(enable, std::sync::atomic::Ordering::Relaxed)
+		}
+		#[cfg(windows)] {
+			let _ = enable;
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Check if the compatibility mode for USB CDC-ACM gadget serial ports is enabled.
+	///
+	/// See [`Self::set_cdc_acm_quirks()`] for more information.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn cdc_acm_quirks(&self) -> bool {
+		#[cfg(unix)] {
+			self.inner.cdc_acm_quirks.load(std::sync::atomic::Ordering::Relaxed)
+		}
+		#[cfg(windows)] {
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Get the name of the serial port, as it was passed to [`Self::open()`].
+	///
+	/// Returns `None` if the serial port was not opened by name,
+	/// for example if it was created from a file descriptor or handle.
+	///
+	/// Note that the name is not updated if the device is renamed or reconnected under a different name.
+	pub fn name(&self) -> Option<&Path> {
+		self.inner.name.as_deref()
+	}
+
 	/// Try to clone the serial port handle.
 	///
 	/// The cloned object refers to the same serial port.
+	/// The settings of the serial port itself (as reported by [`Self::get_configuration()`]) are shared with the clone,
+	/// because they are a property of the device.
+	///
+	/// The state kept by this library is handled as follows:
+	/// * The read and write timeouts and the [`ReadMode`] start as a copy of the original.
+	///   On Unix, they are enforced by this library for each object separately, so later changes are not shared.
+	///   On Windows, they are configured on the device, so they are shared by the original and all clones.
+	/// * The [`TimeoutBehavior`], [`ApplyMode`] and control line verification start as a copy of the original,
+	///   but later changes are not shared.
+	/// * The statistics counters (if enabled) are shared.
+	/// * [`Self::abort_pending_io()`] only aborts operations on the object it is called on,
+	///   and calls to [`Self::set_configuration()`] are only serialized with other calls on the same object.
 	///
 	/// Mixing reads and writes on different handles to the same serial port from different threads may lead to unexpect results.
 	/// The data may end up interleaved in unpredictable ways.
 	pub fn try_clone(&self) -> std::io::Result<Self> {
 		Ok(Self {
 			inner: self.inner.try_clone()?,
+			stats: self.stats.clone(),
+			timeout_behavior: AtomicU8::new(self.timeout_behavior.load(Ordering::Relaxed)),
 		})
 	}
 
+	/// Split the serial port into a file descriptor and the state kept by this library.
+	///
+	/// Use [`Self::from_raw_parts()`] to put them back together, possibly in a different process.
+	/// Unlike converting the serial port into an [`OwnedFd`][std::os::unix::io::OwnedFd] directly,
+	/// this preserves the timeouts, read mode and other state that is not stored in the file descriptor.
+	///
+	/// The statistics counters are not part of the state.
+	#[cfg(unix)]
+	pub fn into_raw_parts(self) -> (std::os::unix::io::OwnedFd, PortState) {
+		let state = self.port_state();
+		(self.inner.file.into(), state)
+	}
+
+	/// Split the serial port into a handle and the state kept by this library.
+	///
+	/// Use [`Self::from_raw_parts()`] to put them back together, possibly in a different process.
+	/// Unlike converting the serial port into an [`OwnedHandle`][std::os::windows::io::OwnedHandle] directly,
+	/// this preserves the timeouts, read mode and other state that is not stored in the handle.
+	///
+	/// The statistics counters are not part of the state.
+	#[cfg(windows)]
+	pub fn into_raw_parts(self) -> (std::os::windows::io::OwnedHandle, PortState) {
+		let state = self.port_state();
+		(self.inner.file.into(), state)
+	}
+
+	/// Create a serial port from a file descriptor and the state returned by [`Self::into_raw_parts()`].
+	///
+	/// The settings of the serial port itself are stored in the device, so they are not changed.
+	/// The timeouts, read mode and other state are applied as given.
+	#[cfg(unix)]
+	pub fn from_raw_parts(fd: std::os::unix::io::OwnedFd, state: &PortState) -> std::io::Result<Self> {
+		Self::from(fd).with_port_state(state)
+	}
+
+	/// Create a serial port from a handle and the state returned by [`Self::into_raw_parts()`].
+	///
+	/// The settings of the serial port itself are stored in the device, so they are not changed.
+	/// The timeouts, read mode and other state are applied as given.
+	///
+	/// The handle must have been created with the `FILE_FLAG_OVERLAPPED` flag for the serial port to function correctly.
+	#[cfg(windows)]
+	pub fn from_raw_parts(handle: std::os::windows::io::OwnedHandle, state: &PortState) -> std::io::Result<Self> {
+		Self::from(handle).with_port_state(state)
+	}
+
+	/// Get the state kept by this library for [`Self::into_raw_parts()`] and [`Self::reopen()`].
+	fn port_state(&self) -> PortState {
+		// The getters only return a `Result` for consistency with other backends, they do not fail.
+		let default = PortState::default();
+		PortState {
+			name: self.inner.name.clone(),
+			read_timeout: self.get_read_timeout().unwrap_or(default.read_timeout),
+			write_timeout: self.get_write_timeout().unwrap_or(default.write_timeout),
+			read_mode: self.get_read_mode().unwrap_or(default.read_mode),
+			timeout_behavior: self.get_timeout_behavior(),
+			apply_mode: self.get_apply_mode(),
+			verify_control_lines: self.get_verify_control_lines(),
+			#[cfg(unix)]
+			cdc_acm_quirks: self.inner.cdc_acm_quirks.load(Ordering::Relaxed),
+			#[cfg(not(unix))]
+			cdc_acm_quirks: false,
+		}
+	}
+
+	/// Apply the state returned by [`Self::port_state()`] for [`Self::from_raw_parts()`] and [`Self::reopen()`].
+	fn with_port_state(mut self, state: &PortState) -> std::io::Result<Self> {
+		self.inner.name = state.name.clone();
+		self.set_read_timeout(state.read_timeout)?;
+		self.set_write_timeout(state.write_timeout)?;
+		self.set_read_mode(state.read_mode)?;
+		self.set_timeout_behavior(state.timeout_behavior);
+		self.set_apply_mode(state.apply_mode);
+		if state.verify_control_lines {
+			self.set_verify_control_lines(true)?;
+		}
+		#[cfg(unix)]
+		self.inner.cdc_acm_quirks.store(state.cdc_acm_quirks, Ordering::Relaxed);
+		Ok(self)
+	}
+
+	/// Open the serial port again with the same configuration.
+	///
+	/// Some drivers can end up in a state that is only cleared by closing and opening the device again.
+	/// This function opens the port again by name, and applies the current settings of this port.
+	/// The timeouts, read mode and other state kept by this library are copied too, like for [`Self::into_raw_parts()`].
+	/// Drop this port (and all handles created with [`Self::try_clone()`]) once the new port is opened,
+	/// so that the old file descriptor or handle is closed.
+	///
+	/// The new port is opened while this port is still open, so this port is left unchanged if opening the new port fails.
+	/// Windows allows only one handle to a serial port,
+	/// so on Windows this always returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	///
+	/// The port is opened again with the default options, even if it was opened with [`OpenOptions`][crate::OpenOptions].
+	/// The statistics counters (if enabled) are shared with the new port.
+	///
+	/// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] if the port was not opened by name,
+	/// for example because it was created from a raw file descriptor or handle.
+	pub fn reopen(&self) -> std::io::Result<Self> {
+		if cfg!(windows) {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::Unsupported,
+				"reopening a serial port is not supported on Windows",
+			));
+		}
+
+		let name = match self.name() {
+			Some(name) => name,
+			None => {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::InvalidInput,
+					"can not reopen a serial port without a name",
+				))
+			},
+		};
+		let settings = self.get_configuration()?;
+
+		// Restore the state first, because the apply mode and the CDC-ACM quirks affect how the settings are applied.
+		let serial_port = Self {
+			inner: sys::SerialPort::open(name)?,
+			stats: self.stats.clone(),
+			timeout_behavior: AtomicU8::new(0),
+		};
+		let serial_port = serial_port.with_port_state(&self.port_state())?;
+		serial_port.set_configuration(&settings)?;
+		Ok(serial_port)
+	}
+
+	/// Start collecting statistics of the reads and writes on this serial port.
+	///
+	/// Statistics are disabled by default, so that they do not cost anything if they are not used.
+	/// Once enabled, they can be retrieved with [`Self::statistics()`].
+	/// Enabling the statistics when they are already enabled does nothing.
+	///
+	/// Handles created with [`Self::try_clone()`] after this call share the same counters,
+	/// so the statistics include the reads and writes done on all of them.
+	pub fn enable_statistics(&self) {
+		self.stats.get_or_init(Default::default);
+	}
+
+	/// Get the statistics of the reads and writes on this serial port.
+	///
+	/// The statistics count all reads and writes since [`Self::enable_statistics()`] or [`Self::reset_statistics()`] was called.
+	/// Returns `None` if statistics have not been enabled.
+	pub fn statistics(&self) -> Option<crate::PortStats> {
+		self.stats.get().map(|stats| stats.get())
+	}
+
+	/// Reset all statistics to zero.
+	///
+	/// This does nothing if statistics have not been enabled.
+	pub fn reset_statistics(&self) {
+		if let Some(stats) = self.stats.get() {
+			stats.reset();
+		}
+	}
+
 	/// Read bytes from the serial port.
 	///
 	/// This is identical to [`std::io::Read::read()`], except that this function takes a const reference `&self`.
@@ -128,8 +881,19 @@ impl SerialPort {
 	///
 	/// Note that there are no guarantees on which thread receives what data when multiple threads are reading from the serial port.
 	/// You should normally limit yourself to a single reading thread and a single writing thread.
+	///
+	/// If the read timeout expires, the result depends on the [`TimeoutBehavior`] (see [`Self::set_timeout_behavior()`]).
 	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
-		self.inner.read(buf)
+		self.apply_timeout_behavior(self.read_raw(buf))
+	}
+
+	/// Read bytes from the serial port, always reporting timeouts as error.
+	pub(crate) fn read_raw(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let result = self.inner.read(buf);
+		if let Some(stats) = self.stats.get() {
+			stats.record_read(&result);
+		}
+		result
 	}
 
 	/// Read bytes from the serial port into a slice of buffers.
@@ -139,8 +903,14 @@ impl SerialPort {
 	///
 	/// Note that there are no guarantees on which thread receives what data when multiple threads are reading from the serial port.
 	/// You should normally limit yourself to a single reading thread and a single writing thread.
+	///
+	/// If the read timeout expires, the result depends on the [`TimeoutBehavior`] (see [`Self::set_timeout_behavior()`]).
 	pub fn read_vectored(&self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
-		self.inner.read_vectored(buf)
+		let result = self.inner.read_vectored(buf);
+		if let Some(stats) = self.stats.get() {
+			stats.record_read(&result);
+		}
+		self.apply_timeout_behavior(result)
 	}
 
 	/// Check if the implementation supports vectored reads.
@@ -151,25 +921,132 @@ impl SerialPort {
 		self.inner.is_read_vectored()
 	}
 
-	/// Read the exact number of bytes required to fill the buffer from the serial port.
+	/// Read the exact number of bytes required to fill the buffer from the serial port.
+	///
+	/// This will repeatedly call `read()` until the entire buffer is filled.
+	/// Errors of the type [`std::io::ErrorKind::Interrupted`] are silently ignored.
+	/// Any other errors (including timeouts) will be returned immediately.
+	///
+	/// If this function returns an error, it may already have read some data from the serial port into the provided buffer.
+	///
+	/// This function is identical to [`std::io::Read::read_exact()`], except that this function takes a const reference `&self`.
+	/// This allows you to use the serial port concurrently from multiple threads.
+	///
+	/// Note that there are no guarantees on which thread receives what data when multiple threads are reading from the serial port.
+	/// You should normally limit yourself to a single reading thread and a single writing thread.
+	pub fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+		let mut buf = buf;
+		while !buf.is_empty() {
+			match self.read_raw(buf) {
+				Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
+				Ok(n) => buf = &mut buf[n..],
+				Err(e) => {
+					if e.kind() != std::io::ErrorKind::Interrupted {
+						return Err(e);
+					} else {
+						continue;
+					}
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Read the exact number of bytes required to fill the buffer, giving up when a deadline passes.
+	///
+	/// This is like [`Self::read_exact()`], but it bounds the total time spent in all the underlying reads.
+	/// Each read still uses the configured read timeout, but it never waits past the deadline.
+	/// If the deadline passes before the buffer is filled, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	///
+	/// If this function returns an error, it may already have read some data from the serial port into the provided buffer.
+	///
+	/// # Example
+	/// ```
+	/// # use serial2::SerialPort;
+	/// # use std::time::{Duration, Instant};
+	/// # fn foo(port: &SerialPort) -> std::io::Result<()> {
+	/// let mut response = [0; 64];
+	/// port.read_exact_deadline(&mut response, Instant::now() + Duration::from_secs(1))?;
+	/// #   Ok(())
+	/// # }
+	/// ```
+	pub fn read_exact_deadline(&self, buf: &mut [u8], deadline: Instant) -> std::io::Result<()> {
+		let mut buf = buf;
+		while !buf.is_empty() {
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			if remaining.is_zero() {
+				return Err(std::io::ErrorKind::TimedOut.into());
+			}
+			let result = self.inner.read_with_max_timeout(buf, remaining);
+			if let Some(stats) = self.stats.get() {
+				stats.record_read(&result);
+			}
+			match result {
+				Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
+				Ok(n) => buf = &mut buf[n..],
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Get an iterator over the lines received on the serial port.
+	///
+	/// Each item is a line without the line terminator (`\n` or `\r\n`).
+	/// If no complete line is received within the read timeout, the iterator yields an error of kind [`std::io::ErrorKind::TimedOut`],
+	/// but you can keep using the iterator afterwards.
+	/// See [`Lines`][crate::Lines] for the details.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use serial2::SerialPort;
+	/// # fn foo(port: &SerialPort) -> std::io::Result<()> {
+	/// port.write_all(b"help\r\n")?;
+	/// for line in port.lines() {
+	///     println!("{}", line?);
+	/// }
+	/// #   Ok(())
+	/// # }
+	/// ```
+	pub fn lines(&self) -> crate::Lines<'_> {
+		crate::Lines::new(self)
+	}
+
+	/// Write bytes to the serial port.
+	///
+	/// This is identical to [`std::io::Write::write()`], except that this function takes a const reference `&self`.
+	/// This allows you to use the serial port concurrently from multiple threads.
+	///
+	/// Note that data written to the same serial port from multiple threads may end up interleaved at the receiving side.
+	/// You should normally limit yourself to a single reading thread and a single writing thread.
+	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		let result = self.inner.write(buf);
+		if let Some(stats) = self.stats.get() {
+			stats.record_write(&result);
+		}
+		result
+	}
+
+	/// Write all bytes to the serial port.
 	///
-	/// This will repeatedly call `read()` until the entire buffer is filled.
+	/// This will repeatedly call [`Self::write()`] until the entire buffer has been written.
 	/// Errors of the type [`std::io::ErrorKind::Interrupted`] are silently ignored.
 	/// Any other errors (including timeouts) will be returned immediately.
 	///
-	/// If this function returns an error, it may already have read some data from the serial port into the provided buffer.
+	/// If this function returns an error, it may already have transmitted some data from the buffer over the serial port.
 	///
-	/// This function is identical to [`std::io::Read::read_exact()`], except that this function takes a const reference `&self`.
+	/// This is identical to [`std::io::Write::write_all()`], except that this function takes a const reference `&self`.
 	/// This allows you to use the serial port concurrently from multiple threads.
 	///
-	/// Note that there are no guarantees on which thread receives what data when multiple threads are reading from the serial port.
+	/// Note that data written to the same serial port from multiple threads may end up interleaved at the receiving side.
 	/// You should normally limit yourself to a single reading thread and a single writing thread.
-	pub fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+	pub fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
 		let mut buf = buf;
 		while !buf.is_empty() {
-			match self.read(buf) {
-				Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
-				Ok(n) => buf = &mut buf[n..],
+			match self.write(buf) {
+				Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "failed to write whole buffer")),
+				Ok(n) => buf = &buf[n..],
 				Err(e) => {
 					if e.kind() != std::io::ErrorKind::Interrupted {
 						return Err(e);
@@ -182,36 +1059,108 @@ impl SerialPort {
 		Ok(())
 	}
 
-	/// Write bytes to the serial port.
+	/// Write all bytes to the serial port, giving up when a deadline passes.
 	///
-	/// This is identical to [`std::io::Write::write()`], except that this function takes a const reference `&self`.
+	/// This is like [`Self::write_all()`], but it bounds the total time spent in all the underlying writes.
+	/// Each write still uses the configured write timeout, but it never waits past the deadline.
+	/// If the deadline passes before all data is written, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	///
+	/// If this function returns an error, it may already have transmitted some data from the buffer over the serial port.
+	pub fn write_all_deadline(&self, buf: &[u8], deadline: Instant) -> std::io::Result<()> {
+		let mut buf = buf;
+		while !buf.is_empty() {
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			if remaining.is_zero() {
+				return Err(std::io::ErrorKind::TimedOut.into());
+			}
+			let result = self.inner.write_with_max_timeout(buf, remaining);
+			if let Some(stats) = self.stats.get() {
+				stats.record_write(&result);
+			}
+			match result {
+				Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "failed to write whole buffer")),
+				Ok(n) => buf = &buf[n..],
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Write all bytes to the serial port in chunks, reporting progress after each chunk.
+	///
+	/// This writes the buffer in chunks of at most `chunk_size` bytes using [`Self::write_all()`].
+	/// After each chunk, the `progress` function is called with the number of bytes written so far and the total number of bytes.
+	/// If it returns an error, no more data is written and the error is returned.
+	/// This can be used to cancel long transfers, such as firmware uploads.
+	///
+	/// Note that the reported progress is the data handed to the OS, which may not have been transmitted yet.
+	/// Use [`Self::flush()`] if you need to wait for the transmission to finish.
+	///
+	/// A `chunk_size` of zero is treated as one.
+	///
+	/// # Example
+	/// ```
+	/// # use serial2::SerialPort;
+	/// # fn foo(port: &SerialPort, firmware: &[u8]) -> std::io::Result<()> {
+	/// port.write_all_with_progress(firmware, 4096, |sent, total| {
+	///     println!("Uploaded {sent} of {total} bytes");
+	///     Ok(())
+	/// })?;
+	/// #   Ok(())
+	/// # }
+	/// ```
+	pub fn write_all_with_progress<F>(&self, buf: &[u8], chunk_size: usize, mut progress: F) -> std::io::Result<()>
+	where
+		F: FnMut(usize, usize) -> std::io::Result<()>,
+	{
+		let mut sent = 0;
+		for chunk in buf.chunks(chunk_size.max(1)) {
+			self.write_all(chunk)?;
+			sent += chunk.len();
+			progress(sent, buf.len())?;
+		}
+		Ok(())
+	}
+
+	/// Write bytes to the serial port from a slice of buffers.
+	///
+	/// This is identical to [`std::io::Write::write_vectored()`], except that this function takes a const reference `&self`.
 	/// This allows you to use the serial port concurrently from multiple threads.
 	///
 	/// Note that data written to the same serial port from multiple threads may end up interleaved at the receiving side.
 	/// You should normally limit yourself to a single reading thread and a single writing thread.
-	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
-		self.inner.write(buf)
+	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		let result = self.inner.write_vectored(buf);
+		if let Some(stats) = self.stats.get() {
+			stats.record_write(&result);
+		}
+		result
 	}
 
-	/// Write all bytes to the serial port.
+	/// Write all bytes from a slice of buffers to the serial port.
 	///
-	/// This will repeatedly call [`Self::write()`] until the entire buffer has been written.
+	/// This will repeatedly call [`Self::write_vectored()`] until all buffers have been written completely.
 	/// Errors of the type [`std::io::ErrorKind::Interrupted`] are silently ignored.
 	/// Any other errors (including timeouts) will be returned immediately.
 	///
-	/// If this function returns an error, it may already have transmitted some data from the buffer over the serial port.
+	/// The slice of buffers is modified to keep track of the data that still needs to be written.
+	/// When this function returns, the contents of the slice are unspecified.
 	///
-	/// This is identical to [`std::io::Write::write_all()`], except that this function takes a const reference `&self`.
+	/// If this function returns an error, it may already have transmitted some data from the buffers over the serial port.
+	///
+	/// This is similar to the unstable `std::io::Write::write_all_vectored()`, except that this function takes a const reference `&self`.
 	/// This allows you to use the serial port concurrently from multiple threads.
 	///
 	/// Note that data written to the same serial port from multiple threads may end up interleaved at the receiving side.
 	/// You should normally limit yourself to a single reading thread and a single writing thread.
-	pub fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
-		let mut buf = buf;
+	pub fn write_all_vectored(&self, mut buf: &mut [IoSlice<'_>]) -> std::io::Result<()> {
+		// Skip empty buffers up front, so we don't report `WriteZero` for them.
+		IoSlice::advance_slices(&mut buf, 0);
 		while !buf.is_empty() {
-			match self.write(buf) {
+			match self.write_vectored(buf) {
 				Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "failed to write whole buffer")),
-				Ok(n) => buf = &buf[n..],
+				Ok(n) => IoSlice::advance_slices(&mut buf, n),
 				Err(e) => {
 					if e.kind() != std::io::ErrorKind::Interrupted {
 						return Err(e);
@@ -224,15 +1173,59 @@ impl SerialPort {
 		Ok(())
 	}
 
-	/// Write bytes to the serial port from a slice of buffers.
+	/// Read bytes from the serial port into the spare capacity of a [`bytes::BytesMut`].
 	///
-	/// This is identical to [`std::io::Write::write_vectored()`], except that this function takes a const reference `&self`.
-	/// This allows you to use the serial port concurrently from multiple threads.
+	/// The received data is appended to the buffer, and the number of bytes read is returned.
+	/// If the buffer has no spare capacity, space for at least 4 KiB is reserved first.
+	/// At most 4 KiB is read per call, even if the buffer has more spare capacity.
 	///
-	/// Note that data written to the same serial port from multiple threads may end up interleaved at the receiving side.
-	/// You should normally limit yourself to a single reading thread and a single writing thread.
-	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
-		self.inner.write_vectored(buf)
+	/// This behaves like [`Self::read()`] otherwise.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use serial2::SerialPort;
+	/// # fn foo(port: &SerialPort) -> std::io::Result<()> {
+	/// let mut buffer = bytes::BytesMut::with_capacity(1024);
+	/// port.read_bytes(&mut buffer)?;
+	/// let message = buffer.split().freeze();
+	/// #   Ok(())
+	/// # }
+	/// ```
+	#[cfg(feature = "bytes")]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "bytes")))]
+	pub fn read_bytes(&self, buf: &mut bytes::BytesMut) -> std::io::Result<usize> {
+		if buf.len() == buf.capacity() {
+			buf.reserve(READ_BYTES_CHUNK_SIZE);
+		}
+		// Initialize part of the spare capacity, since the OS read functions need an initialized buffer.
+		// This does not allocate, because the buffer is only resized up to its capacity.
+		// Limit the size, so a large buffer is not filled with zeroes for every read.
+		let len = buf.len();
+		let read_len = (buf.capacity() - len).min(READ_BYTES_CHUNK_SIZE);
+		buf.resize(len + read_len, 0);
+		let result = self.read(&mut buf[len..]);
+		buf.truncate(len + result.as_ref().copied().unwrap_or(0));
+		result
+	}
+
+	/// Write bytes from a [`bytes::Buf`] to the serial port.
+	///
+	/// The data is written directly from the chunks of the buffer, using [`Self::write_vectored()`].
+	/// The buffer is advanced past the written data, and the number of bytes written is returned.
+	///
+	/// This behaves like [`Self::write()`] otherwise.
+	/// Call this function in a loop until [`bytes::Buf::has_remaining()`] returns false to write all data.
+	#[cfg(feature = "bytes")]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "bytes")))]
+	pub fn write_bytes(&self, buf: &mut impl bytes::Buf) -> std::io::Result<usize> {
+		if !buf.has_remaining() {
+			return Ok(0);
+		}
+		let mut chunks = [IoSlice::new(&[]); 16];
+		let count = buf.chunks_vectored(&mut chunks);
+		let written = self.write_vectored(&chunks[..count])?;
+		buf.advance(written);
+		Ok(written)
 	}
 
 	/// Check if the implementation supports vectored writes.
@@ -252,12 +1245,156 @@ impl SerialPort {
 		self.inner.flush_output()
 	}
 
+	/// Flush all data queued to be written, with a timeout.
+	///
+	/// This will block until the OS buffer has been fully transmitted, or until the timeout expires.
+	/// If the timeout expires first, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	/// Unlike [`Self::flush()`], this can not block forever if the transmission is stalled by flow control.
+	///
+	/// This is implemented by polling the size of the OS output queue.
+	/// Depending on the driver, a few bytes may still be in the hardware buffer of the serial port when this function returns.
+	///
+	/// Not all platforms support this function.
+	/// On unsupported platforms, this function always returns an error.
+	pub fn flush_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		let deadline = std::time::Instant::now().checked_add(timeout);
+		loop {
+			if self.output_drained()? {
+				return Ok(());
+			}
+			let remaining = match deadline {
+				None => FLUSH_POLL_INTERVAL,
+				Some(deadline) => deadline.saturating_duration_since(std::time::Instant::now()),
+			};
+			if remaining.is_zero() {
+				return Err(std::io::ErrorKind::TimedOut.into());
+			}
+			std::thread::sleep(remaining.min(FLUSH_POLL_INTERVAL));
+		}
+	}
+
+	/// Get the baud rates supported by the serial port.
+	///
+	/// This is a best-effort check: it reports which of the [`COMMON_BAUD_RATES`][crate::COMMON_BAUD_RATES] are supported,
+	/// and whether custom baud rates are supported.
+	///
+	/// On Windows, this uses the capabilities reported by the driver.
+	/// On Linux, *BSD and Apple platforms, the baud rate is passed to the driver as a number,
+	/// so all baud rates are reported as supported, even though the device may not be able to generate all of them.
+	/// On other platforms, this reports the baud rates that can be represented in the platform `termios` struct.
+	pub fn supported_baud_rates(&self) -> std::io::Result<crate::SupportedBaudRates> {
+		self.inner.supported_baud_rates()
+	}
+
+	/// Request a size for the OS input and output buffers of the serial port, in bytes.
+	///
+	/// Larger buffers can prevent data loss when receiving large amounts of data at high baud rates,
+	/// if the application can not always read the data fast enough.
+	///
+	/// On Windows, this uses `SetupComm`.
+	/// The driver is free to ignore the request or to use different sizes.
+	///
+	/// On other platforms, there is no way to change the size of the kernel buffers,
+	/// and an error of kind [`std::io::ErrorKind::Unsupported`] is returned.
+	pub fn set_os_buffer_sizes(&self, receive: u32, transmit: u32) -> std::io::Result<()> {
+		self.inner.set_os_buffer_sizes(receive, transmit)
+	}
+
+	/// Check if all data queued to be written has been handed to the serial port hardware.
+	///
+	/// This returns `true` if the OS output queue is empty, without blocking.
+	/// It can be used to poll for transmit completion, for example to switch the direction of a half-duplex bus.
+	/// Depending on the driver, a few bytes may still be in the hardware buffer of the serial port.
+	///
+	/// Not all platforms support this function.
+	/// On unsupported platforms, this function always returns an error.
+	pub fn output_drained(&self) -> std::io::Result<bool> {
+		Ok(self.inner.output_queue_len()? == 0)
+	}
+
+	/// Abort all pending read and write operations on this serial port.
+	///
+	/// Reads and writes that are blocked on this [`SerialPort`] object will return an error of kind [`std::io::ErrorKind::Other`].
+	/// Data that was already transferred is not affected, and the OS buffers are not discarded.
+	/// To discard the buffers, use [`Self::discard_buffers()`].
+	///
+	/// This can be used by a different thread to unblock a task that is waiting for the serial port.
+	/// Operations that start after this function returns are not affected.
+	///
+	/// Depending on the platform, operations on other objects created with [`Self::try_clone()`] may or may not be aborted too.
+	pub fn abort_pending_io(&self) -> std::io::Result<()> {
+		self.inner.abort_pending_io()
+	}
+
+	/// Check if the serial port device is still connected.
+	///
+	/// This performs a cheap check without reading or writing any data,
+	/// so idle applications can detect that a device (such as a USB serial adapter) has been unplugged.
+	///
+	/// Returns `Ok(false)` if the device has been disconnected.
+	/// Once this function returns `Ok(false)`, you need to open the device again after it has been reconnected.
+	///
+	/// Note that not all drivers report a disconnected device in the same way.
+	/// For some devices, a disconnect may only be detected on the next read or write.
+	pub fn is_connected(&self) -> std::io::Result<bool> {
+		self.inner.is_connected()
+	}
+
+	/// Set how [`Self::read()`] and [`Self::read_vectored()`] report a timeout.
+	///
+	/// By default, a timeout is reported as an error of kind [`std::io::ErrorKind::TimedOut`].
+	/// Some consumers of [`std::io::Read`] treat all errors as fatal,
+	/// and they work better if a timeout is reported as `Ok(0)` or as [`std::io::ErrorKind::WouldBlock`].
+	///
+	/// This only affects single reads.
+	/// Functions that perform multiple reads, like [`Self::read_exact()`], always report a timeout as an error.
+	///
+	/// The new behavior applies to all handles for the same [`SerialPort`] object,
+	/// including those shared through an [`Arc`][std::sync::Arc].
+	/// Objects created with [`Self::try_clone()`] start with the behavior of the original object.
+	pub fn set_timeout_behavior(&self, behavior: TimeoutBehavior) {
+		self.timeout_behavior.store(behavior.as_u8(), Ordering::Relaxed);
+	}
+
+	/// Get how [`Self::read()`] and [`Self::read_vectored()`] report a timeout.
+	pub fn get_timeout_behavior(&self) -> TimeoutBehavior {
+		TimeoutBehavior::from_u8(self.timeout_behavior.load(Ordering::Relaxed))
+	}
+
+	/// Set when [`Self::read()`] returns.
+	///
+	/// By default, a read returns as soon as any data is available.
+	/// With [`ReadMode::FillBuffer`], a read waits until the buffer is full or until the line has been idle for the inter-byte timeout.
+	/// This is useful for protocols that mark the end of a message with a gap in the transmission, such as Modbus RTU.
+	///
+	/// On Windows, this is enforced by the driver through the `COMMTIMEOUTS` of the port.
+	/// On Unix, the port is used in non-blocking mode so `VMIN` and `VTIME` have no effect,
+	/// and the inter-byte timeout is enforced by the library instead.
+	///
+	/// The new mode applies to all handles for the same [`SerialPort`] object,
+	/// including those shared through an [`Arc`][std::sync::Arc].
+	/// On Windows, it also affects other [`SerialPort`] objects created with [`Self::try_clone()`],
+	/// because the timeouts are a property of the device.
+	pub fn set_read_mode(&self, mode: ReadMode) -> std::io::Result<()> {
+		self.inner.set_read_mode(mode)
+	}
+
+	/// Get when [`Self::read()`] returns.
+	pub fn get_read_mode(&self) -> std::io::Result<ReadMode> {
+		self.inner.get_read_mode()
+	}
+
 	/// Set the read timeout for the serial port.
 	///
 	/// The timeout set by this function is an upper bound on individual calls to [`read()`][Self::read].
 	/// Other platform specific time-outs may trigger before this timeout does.
 	/// Additionally, some functions (like [`Self::read_exact`]) perform multiple calls to `read()`.
-	pub fn set_read_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
+	///
+	/// The new timeout applies to all handles for the same [`SerialPort`] object,
+	/// including those shared through an [`Arc`][std::sync::Arc].
+	/// On Unix, it does not affect other [`SerialPort`] objects created with [`Self::try_clone()`].
+	/// On Windows, it also affects those objects, because the timeouts are a property of the device.
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
 		self.inner.set_read_timeout(timeout)
 	}
 
@@ -275,7 +1412,12 @@ impl SerialPort {
 	/// The timeout set by this function is an upper bound on individual calls to [`write()`][Self::write].
 	/// Other platform specific time-outs may trigger before this timeout does.
 	/// Additionally, some functions (like [`Self::write_all`]) perform multiple calls to `write()`.
-	pub fn set_write_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
+	///
+	/// The new timeout applies to all handles for the same [`SerialPort`] object,
+	/// including those shared through an [`Arc`][std::sync::Arc].
+	/// On Unix, it does not affect other [`SerialPort`] objects created with [`Self::try_clone()`].
+	/// On Windows, it also affects those objects, because the timeouts are a property of the device.
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
 		self.inner.set_write_timeout(timeout)
 	}
 
@@ -329,6 +1471,24 @@ impl SerialPort {
 		}
 	}
 
+	/// Get the capabilities of the serial port driver on Windows.
+	///
+	/// This uses `GetCommProperties` to query things like the maximum baud rate,
+	/// the settable parameters and the type of the device.
+	/// This can be used to disable unsupported options in a user interface.
+	///
+	/// See [`crate::os::windows::CommProperties`] for more information.
+	#[cfg(any(feature = "doc", all(feature = "windows", windows)))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn get_capabilities(&self) -> std::io::Result<crate::os::windows::CommProperties> {
+		#[cfg(windows)] {
+			self.inner.get_capabilities()
+		}
+		#[cfg(not(windows))] {
+			unreachable!("this code is only enabled on Windows or during documentation generation")
+		}
+	}
+
 	/// Discard the kernel input and output buffers for the serial port.
 	///
 	/// When you write to a serial port, the data may be put in a buffer by the OS to be transmitted by the actual device later.
@@ -357,6 +1517,37 @@ impl SerialPort {
 		self.inner.discard_buffers(false, true)
 	}
 
+	/// Suspend the transmission of data, as if an XOFF character was received.
+	///
+	/// Data written to the serial port will be buffered by the OS until [`Self::resume_output()`] is called.
+	/// This can be used to implement manual software flow control.
+	pub fn suspend_output(&self) -> std::io::Result<()> {
+		self.inner.suspend_output()
+	}
+
+	/// Resume the transmission of data, as if an XON character was received.
+	///
+	/// This undoes the effect of [`Self::suspend_output()`].
+	pub fn resume_output(&self) -> std::io::Result<()> {
+		self.inner.resume_output()
+	}
+
+	/// Send an XOFF character to ask the other side to stop transmitting.
+	///
+	/// The character is transmitted ahead of any data that is still waiting in the output buffer.
+	/// On Unix, the configured STOP character is sent, which is XOFF (`0x13`) unless explicitly changed.
+	pub fn send_xoff(&self) -> std::io::Result<()> {
+		self.inner.send_xoff()
+	}
+
+	/// Send an XON character to ask the other side to resume transmitting.
+	///
+	/// The character is transmitted ahead of any data that is still waiting in the output buffer.
+	/// On Unix, the configured START character is sent, which is XON (`0x11`) unless explicitly changed.
+	pub fn send_xon(&self) -> std::io::Result<()> {
+		self.inner.send_xon()
+	}
+
 	/// Set the state of the Ready To Send line.
 	///
 	/// If hardware flow control is enabled on the serial port, it is platform specific what will happen.
@@ -366,6 +1557,40 @@ impl SerialPort {
 		self.inner.set_rts(state)
 	}
 
+	/// Enable or disable verification of changes to the RTS and DTR lines.
+	///
+	/// Some USB serial adapters (like cheap CH340 clones) silently ignore requests to change the RTS and DTR lines.
+	/// When verification is enabled, [`Self::set_rts()`] and [`Self::set_dtr()`] read back the state of the line
+	/// after changing it, and return an error if it does not have the requested state.
+	///
+	/// Note that some drivers report the requested state even if the device ignored it,
+	/// in which case the verification can not detect the problem.
+	///
+	/// This is only supported on Unix platforms.
+	/// On other platforms, enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	pub fn set_verify_control_lines(&self, enable: bool) -> std::io::Result<()> {
+		self.inner.set_verify_control_lines(enable)
+	}
+
+	/// Check if verification of changes to the RTS and DTR lines is enabled.
+	///
+	/// See [`Self::set_verify_control_lines()`] for more information.
+	pub fn get_verify_control_lines(&self) -> bool {
+		self.inner.get_verify_control_lines()
+	}
+
+	/// Pulse the Ready To Send line: set it high, wait for the given duration, and set it low again.
+	///
+	/// This is commonly used to reset a device that has its reset pin connected to the RTS line.
+	///
+	/// If setting the line high fails, the error is returned immediately.
+	/// This function blocks the current thread for the duration of the pulse.
+	pub fn pulse_rts(&self, duration: Duration) -> std::io::Result<()> {
+		self.set_rts(true)?;
+		std::thread::sleep(duration);
+		self.set_rts(false)
+	}
+
 	/// Read the state of the Clear To Send line.
 	///
 	/// If hardware flow control is enabled on the serial port, it is platform specific what will happen.
@@ -382,6 +1607,18 @@ impl SerialPort {
 		self.inner.set_dtr(state)
 	}
 
+	/// Pulse the Data Terminal Ready line: set it high, wait for the given duration, and set it low again.
+	///
+	/// This is commonly used to reset a device that has its reset pin connected to the DTR line.
+	///
+	/// If setting the line high fails, the error is returned immediately.
+	/// This function blocks the current thread for the duration of the pulse.
+	pub fn pulse_dtr(&self, duration: Duration) -> std::io::Result<()> {
+		self.set_dtr(true)?;
+		std::thread::sleep(duration);
+		self.set_dtr(false)
+	}
+
 	/// Read the state of the Data Set Ready line.
 	///
 	/// If hardware flow control is enabled on the serial port, it is platform specific what will happen.
@@ -405,6 +1642,230 @@ impl SerialPort {
 		self.inner.read_cd()
 	}
 
+	/// Wait until the Carrier Detect line is asserted, with a timeout.
+	///
+	/// Dial-up modems and many radio modems assert the Carrier Detect line when a link is established.
+	/// This returns immediately if the line is already asserted.
+	/// If the timeout expires first, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	/// Use [`Duration::MAX`] to wait forever.
+	///
+	/// On Windows, this uses `WaitCommEvent()` with `EV_RLSD`.
+	/// The event mask of the serial port is replaced while waiting and restored afterwards,
+	/// so this can not be used at the same time as other users of `WaitCommEvent()`.
+	///
+	/// On Unix platforms, the line is watched by polling its state every 10 milliseconds.
+	/// This works regardless of the `CLOCAL` flag, but the serial port must already be open.
+	/// The kernel functions that wait for modem status changes do not support a timeout,
+	/// so they can not be used to implement this function.
+	pub fn wait_for_carrier(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.wait_for_carrier(timeout)
+	}
+
+	/// Get the number of Ring Indicator pulses counted by the driver.
+	///
+	/// This is only supported on Linux and Android, where it uses the `TIOCGICOUNT` ioctl.
+	/// The counter is maintained by the kernel, so it also counts pulses that are too short to be seen with [`Self::read_ri()`].
+	/// Compare the counter between calls to detect new pulses: the absolute value has no meaning.
+	///
+	/// Not all drivers maintain the counter. For those, an error is returned.
+	/// On other platforms, an error of kind [`std::io::ErrorKind::Unsupported`] is returned.
+	pub fn ring_count(&self) -> std::io::Result<u32> {
+		self.inner.ring_count()
+	}
+
+	/// Wait for a pulse on the Ring Indicator line, with a timeout.
+	///
+	/// Modems pulse the Ring Indicator line for incoming calls.
+	/// This only returns when a new pulse is detected after the function is called.
+	/// If the timeout expires first, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	/// Use [`Duration::MAX`] to wait forever.
+	///
+	/// On Linux and Android, this polls the ring counter of [`Self::ring_count()`] every 10 milliseconds,
+	/// so even short pulses are detected.
+	/// On Windows, this uses `WaitCommEvent()` with `EV_RING`.
+	/// The event mask of the serial port is replaced while waiting and restored afterwards,
+	/// so this can not be used at the same time as other users of `WaitCommEvent()`.
+	/// On other platforms, the state of the line is polled every 10 milliseconds,
+	/// which can miss very short pulses.
+	pub fn wait_for_ring(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.wait_for_ring(timeout)
+	}
+
+	/// Start or stop transmitting a break condition.
+	///
+	/// While the break condition is active, the transmit line is held in the spacing (logical 0) state.
+	/// Data written while the break condition is active is not transmitted until the break is stopped.
+	pub fn set_break(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_break(state)
+	}
+
+	/// Transmit a break condition for the given duration.
+	///
+	/// This starts a break condition, waits for the given duration, and stops it again.
+	/// The actual duration can be longer than requested, depending on the scheduler of the OS.
+	///
+	/// The break is started immediately, so it can interrupt data that is still being transmitted.
+	/// Use [`Self::flush()`] first if the break must follow previously written data.
+	///
+	/// If starting the break fails, the error is returned immediately.
+	/// This function blocks the current thread for the duration of the break.
+	pub fn send_break(&self, duration: Duration) -> std::io::Result<()> {
+		self.set_break(true)?;
+		std::thread::sleep(duration);
+		self.set_break(false)
+	}
+
+	/// Check if the serial port supports transmitting a break condition.
+	///
+	/// This stops the break condition on the serial port, and reports if that succeeded.
+	/// It does not affect the data being transmitted or received,
+	/// but a break condition that was started with [`Self::set_break()`] is stopped.
+	///
+	/// Some drivers accept the request even if the hardware can not transmit a break,
+	/// so a return value of `true` does not guarantee that [`Self::send_break()`] has any effect.
+	pub fn is_break_supported(&self) -> bool {
+		self.inner.set_break(false).is_ok()
+	}
+
+	/// Check that data written to the serial port is received back, to validate a loopback plug or null-modem wiring.
+	///
+	/// This discards any pending input, writes the pattern and reads back the same number of bytes.
+	/// The whole test must complete within the given timeout.
+	/// Use [`Duration::MAX`] to wait forever.
+	///
+	/// If the received data differs from the pattern, an error of kind [`std::io::ErrorKind::InvalidData`] is returned.
+	/// If not all data is received in time, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use serial2::SerialPort;
+	/// # use std::time::Duration;
+	/// # fn foo(port: &SerialPort) -> std::io::Result<()> {
+	/// port.loopback_test(b"The quick brown fox jumps over the lazy dog", Duration::from_secs(1))?;
+	/// #   Ok(())
+	/// # }
+	/// ```
+	pub fn loopback_test(&self, pattern: &[u8], timeout: Duration) -> std::io::Result<()> {
+		let deadline = deadline_after(timeout);
+		self.discard_input_buffer()?;
+		self.write_all_deadline(pattern, deadline)?;
+
+		let mut received = vec![0; pattern.len()];
+		self.read_exact_deadline(&mut received, deadline)?;
+		match pattern.iter().zip(&received).position(|(sent, received)| sent != received) {
+			None => Ok(()),
+			Some(i) => Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidData,
+				format!(
+					"loopback data mismatch at byte {i}: sent 0x{:02X}, received 0x{:02X}",
+					pattern[i], received[i]
+				),
+			)),
+		}
+	}
+
+	/// Check which output control lines are looped back to input control lines.
+	///
+	/// This toggles the RTS and DTR lines and checks if the CTS, DSR and CD lines follow them,
+	/// waiting for `settle` after each change before reading the input lines.
+	/// A line pair is only reported as connected if the input follows the output both in the high and in the low state.
+	///
+	/// A typical loopback plug connects RTS to CTS and DTR to DSR and CD.
+	/// A null-modem cable connects the lines to the other side, in which case you should run the test on the other port.
+	///
+	/// Afterwards, RTS and DTR are both left asserted.
+	/// Do not use this function while hardware flow control is enabled.
+	pub fn loopback_test_control_lines(&self, settle: Duration) -> std::io::Result<ControlLineLoopback> {
+		self.set_rts(false)?;
+		self.set_dtr(false)?;
+
+		self.set_rts(true)?;
+		std::thread::sleep(settle);
+		let cts_high = self.read_cts()?;
+		self.set_rts(false)?;
+		std::thread::sleep(settle);
+		let cts_low = self.read_cts()?;
+
+		self.set_dtr(true)?;
+		std::thread::sleep(settle);
+		let dsr_high = self.read_dsr()?;
+		let cd_high = self.read_cd()?;
+		self.set_dtr(false)?;
+		std::thread::sleep(settle);
+		let dsr_low = self.read_dsr()?;
+		let cd_low = self.read_cd()?;
+
+		self.set_rts(true)?;
+		self.set_dtr(true)?;
+
+		Ok(ControlLineLoopback {
+			rts_cts: cts_high && !cts_low,
+			dtr_dsr: dsr_high && !dsr_low,
+			dtr_cd: cd_high && !cd_low,
+		})
+	}
+
+	/// Enable or disable low latency mode.
+	///
+	/// This is only supported on Linux and Android.
+	///
+	/// Many USB serial adapters buffer received data for a while before passing it to the host,
+	/// which adds latency to every message.
+	/// For example, FTDI devices wait up to 16 milliseconds by default.
+	/// Enabling low latency mode reduces this delay, at the cost of higher CPU and USB bus usage.
+	///
+	/// This sets the `ASYNC_LOW_LATENCY` flag of the serial port.
+	/// Many drivers ignore this flag, so for FTDI devices this also sets the `latency_timer` in sysfs
+	/// to 1 millisecond (or back to the default of 16 milliseconds when disabling low latency mode).
+	/// Writing to sysfs usually requires elevated permissions or a udev rule,
+	/// which can also be used to make the setting persistent.
+	///
+	/// An error is returned only if neither method succeeded.
+	#[cfg(any(feature = "doc", all(feature = "unix", any(target_os = "linux", target_os = "android"))))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "unix", any(target_os = "linux", target_os = "android")))))]
+	pub fn set_low_latency(&self, enable: bool) -> std::io::Result<()> {
+		#[cfg(all(feature = "unix", any(target_os = "linux", target_os = "android")))]
+		return sys::set_low_latency(&self.inner, enable);
+		#[allow(unreachable_code)] {
+			let _ = enable;
+			panic!("unsupported platform");
+		}
+	}
+
+	/// Get the Linux specific serial port information.
+	///
+	/// This uses the `TIOCGSERIAL` ioctl to get the UART type, base baud rate, custom divisor and flags of the serial port.
+	/// See [`crate::os::unix::SerialStruct`] for more information.
+	///
+	/// Many USB serial drivers do not support this.
+	#[cfg(any(feature = "doc", all(feature = "unix", any(target_os = "linux", target_os = "android"))))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "unix", any(target_os = "linux", target_os = "android")))))]
+	pub fn get_serial_struct(&self) -> std::io::Result<crate::os::unix::SerialStruct> {
+		#[cfg(all(feature = "unix", any(target_os = "linux", target_os = "android")))]
+		return sys::get_serial_struct(&self.inner);
+		#[allow(unreachable_code)] {
+			panic!("unsupported platform");
+		}
+	}
+
+	/// Apply Linux specific serial port information.
+	///
+	/// This uses the `TIOCSSERIAL` ioctl.
+	/// It can be used for legacy custom baud rates by setting the `ASYNC_SPD_CUST` flag and a custom divisor,
+	/// although [`Settings::set_baud_rate()`] supports arbitrary baud rates on Linux already.
+	///
+	/// Many USB serial drivers do not support this, and changing most fields requires elevated permissions.
+	#[cfg(any(feature = "doc", all(feature = "unix", any(target_os = "linux", target_os = "android"))))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(all(feature = "unix", any(target_os = "linux", target_os = "android")))))]
+	pub fn set_serial_struct(&self, serial: &crate::os::unix::SerialStruct) -> std::io::Result<()> {
+		#[cfg(all(feature = "unix", any(target_os = "linux", target_os = "android")))]
+		return sys::set_serial_struct(&self.inner, serial);
+		#[allow(unreachable_code)] {
+			let _ = serial;
+			panic!("unsupported platform");
+		}
+	}
+
 	/// Get the RS-4xx mode of the serial port transceiver.
 	///
 	/// This is currently only supported on Linux.
@@ -449,6 +1910,46 @@ impl SerialPort {
 			panic!("unsupported platform");
 		}
 	}
+
+	/// Check if the serial port supports configuring the RS-4xx mode of the transceiver.
+	///
+	/// This reads the RS-485 configuration of the serial port, without changing it.
+	/// If that succeeds, `SerialPort::get_rs4xx_mode()` and `SerialPort::set_rs4xx_mode()` can be used.
+	///
+	/// This is currently only supported on Linux with the `rs4xx` feature enabled.
+	/// On other platforms, it always returns `false`.
+	///
+	/// Note that serial ports that are always in RS-485 or RS-422 mode may still report `false`.
+	/// Use `rs4xx::supported_features()` to check which options the driver honors.
+	pub fn is_rs485_supported(&self) -> bool {
+		#[cfg(all(feature = "rs4xx", target_os = "linux"))] {
+			sys::get_rs4xx_mode(&self.inner).is_ok()
+		}
+		#[cfg(not(all(feature = "rs4xx", target_os = "linux")))] {
+			false
+		}
+	}
+
+	/// Report a read timeout according to the configured [`TimeoutBehavior`].
+	fn apply_timeout_behavior(&self, result: std::io::Result<usize>) -> std::io::Result<usize> {
+		match result {
+			Err(e) if e.kind() == std::io::ErrorKind::TimedOut => match self.get_timeout_behavior() {
+				TimeoutBehavior::Error => Err(e),
+				TimeoutBehavior::ReturnZero => Ok(0),
+				TimeoutBehavior::WouldBlock => Err(std::io::ErrorKind::WouldBlock.into()),
+			},
+			result => result,
+		}
+	}
+
+	/// Wrap an error from configuring the serial port with the name of the port.
+	fn configure_error(&self, setting: Option<crate::Setting>, source: std::io::Error) -> crate::Error {
+		crate::Error::Configure {
+			port: self.name().map(Path::to_owned),
+			setting,
+			source,
+		}
+	}
 }
 
 impl std::fmt::Debug for SerialPort {
@@ -517,6 +2018,8 @@ impl From<std::os::unix::io::OwnedFd> for SerialPort {
 	fn from(value: std::os::unix::io::OwnedFd) -> Self {
 		Self {
 			inner: sys::SerialPort::from_file(value.into()),
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
 		}
 	}
 }
@@ -548,6 +2051,8 @@ impl std::os::unix::io::FromRawFd for SerialPort {
 		use std::fs::File;
 		Self {
 			inner: sys::SerialPort::from_file(File::from_raw_fd(fd)),
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
 		}
 	}
 }
@@ -567,6 +2072,8 @@ impl From<std::os::windows::io::OwnedHandle> for SerialPort {
 	fn from(value: std::os::windows::io::OwnedHandle) -> Self {
 		Self {
 			inner: sys::SerialPort::from_file(value.into()),
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
 		}
 	}
 }
@@ -601,6 +2108,22 @@ impl std::os::windows::io::FromRawHandle for SerialPort {
 		use std::fs::File;
 		Self {
 			inner: sys::SerialPort::from_file(File::from_raw_handle(handle)),
+			stats: OnceLock::new(),
+			timeout_behavior: AtomicU8::new(0),
+		}
+	}
+}
+
+/// Get the deadline for a timeout starting now.
+///
+/// If the deadline can not be represented, a shorter timeout that can be represented is used instead.
+fn deadline_after(timeout: Duration) -> Instant {
+	let now = Instant::now();
+	let mut timeout = timeout;
+	loop {
+		if let Some(deadline) = now.checked_add(timeout) {
+			return deadline;
 		}
+		timeout /= 2;
 	}
 }
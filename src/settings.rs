@@ -14,6 +14,122 @@ pub const COMMON_BAUD_RATES: &[u32] = &[
 	4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 500000, 576000, 921600, 1000000, 1500000, 2000000,
 ];
 
+/// Get the baud rates that the platform defines a constant for.
+///
+/// Unlike [`COMMON_BAUD_RATES`], this is the full list of baud rates the OS supports natively,
+/// in increasing order:
+/// * On Linux, Android, Solaris, Illumos and Haiku, these are the baud rates with a `B*` constant in `termios.h`.
+/// * On Apple platforms and the BSDs, these are the baud rates with a `B*` constant too,
+///   but these platforms also support other baud rates.
+/// * On Windows, these are the baud rates with a `CBR_*` constant.
+///
+/// Many platforms also support custom baud rates,
+/// and a specific device may not support all of these baud rates.
+/// Use [`SerialPort::supported_baud_rates()`][crate::SerialPort::supported_baud_rates] to check what a device supports.
+///
+/// # Example
+/// ```
+/// let baud_rates = serial2::standard_baud_rates();
+/// assert!(baud_rates.contains(&9600));
+/// assert!(baud_rates.windows(2).all(|pair| pair[0] < pair[1]));
+/// ```
+pub fn standard_baud_rates() -> &'static [u32] {
+	&crate::sys::STANDARD_BAUD_RATES
+}
+
+/// Find the baud rate from [`COMMON_BAUD_RATES`] that is closest to the requested baud rate.
+///
+/// Returns the closest baud rate and the relative error of that baud rate compared to the requested rate.
+/// For example, an error of `0.02` means that the returned baud rate is 2% higher than requested,
+/// and an error of `-0.02` means that it is 2% lower.
+///
+/// # Example
+/// ```
+/// let (baud_rate, error) = serial2::closest_standard_baud_rate(115000);
+/// assert_eq!(baud_rate, 115200);
+/// assert!(error.abs() < 0.01);
+/// ```
+pub fn closest_standard_baud_rate(requested: u32) -> (u32, f32) {
+	let closest = COMMON_BAUD_RATES
+		.iter()
+		.copied()
+		.min_by_key(|&rate| rate.abs_diff(requested))
+		.unwrap_or(requested);
+	(closest, relative_error(closest, requested))
+}
+
+/// Estimate the relative error of a baud rate generated by a UART with the given clock frequency.
+///
+/// Most UARTs generate the baud rate by dividing their clock by 16 times an integer divisor.
+/// If the requested baud rate does not divide the clock evenly, the actual baud rate will be slightly off.
+/// Serial communication usually tolerates an error of about 2%, but less is better.
+///
+/// This function assumes 16 times oversampling and an integer divisor, rounded to the nearest value.
+/// Returns the actual baud rate and the relative error compared to the requested rate.
+/// If the requested baud rate is too high for the clock, the returned baud rate is the highest one the clock can generate.
+///
+/// Common UART clocks include 1.8432 MHz (the classic 16550 UART, with a base baud rate of 115200),
+/// 14.7456 MHz and 48 MHz (many USB serial converters).
+/// Note that some devices, like FTDI and CP210x converters, support fractional divisors and can be more accurate than this estimate.
+///
+/// # Example
+/// ```
+/// let (actual, error) = serial2::estimate_baud_rate_error(1_843_200, 115200);
+/// assert_eq!(actual, 115200);
+/// assert_eq!(error, 0.0);
+///
+/// let (actual, error) = serial2::estimate_baud_rate_error(1_843_200, 250_000);
+/// assert_eq!(actual, 115200);
+/// assert!(error < -0.5);
+/// ```
+pub fn estimate_baud_rate_error(uart_clock: u32, baud_rate: u32) -> (u32, f32) {
+	let base = u64::from(uart_clock) / 16;
+	let baud_rate_u64 = u64::from(baud_rate.max(1));
+	let divisor = ((base + baud_rate_u64 / 2) / baud_rate_u64).max(1);
+	let actual = (base / divisor) as u32;
+	(actual, relative_error(actual, baud_rate))
+}
+
+/// Compute the relative error of `actual` compared to `expected`.
+fn relative_error(actual: u32, expected: u32) -> f32 {
+	if expected == 0 {
+		return 0.0;
+	}
+	((f64::from(actual) - f64::from(expected)) / f64::from(expected)) as f32
+}
+
+/// The baud rates supported by a serial port.
+///
+/// Use [`SerialPort::supported_baud_rates()`][crate::SerialPort::supported_baud_rates] to query the supported baud rates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedBaudRates {
+	pub(crate) common_rates: Vec<u32>,
+	pub(crate) custom_rates: bool,
+}
+
+impl SupportedBaudRates {
+	/// Create a [`SupportedBaudRates`] that supports custom baud rates and all [`COMMON_BAUD_RATES`].
+	#[allow(dead_code)] // Not used on all platforms.
+	pub(crate) fn all() -> Self {
+		Self {
+			common_rates: COMMON_BAUD_RATES.to_vec(),
+			custom_rates: true,
+		}
+	}
+
+	/// Get the baud rates from [`COMMON_BAUD_RATES`] that are supported.
+	pub fn common_rates(&self) -> &[u32] {
+		&self.common_rates
+	}
+
+	/// Check if custom baud rates are supported.
+	///
+	/// If this is true, you can use any baud rate, but the device may still round it to the closest rate it can generate.
+	pub fn custom_rates(&self) -> bool {
+		self.custom_rates
+	}
+}
+
 /// Implement a trait be delegating to an existing implementation.
 macro_rules! delegate_impl {
 	(TryFrom<$from:ty> for $for:ty as u8) => {
@@ -229,10 +345,38 @@ pub enum Parity {
 	/// This variant is (de)serialized as the string <code>"even"</code>.
 	/// </div>
 	Even,
+
+	/// Add a parity bit that is always set (1).
+	///
+	/// Received characters are also expected to have a parity bit that is set.
+	/// This is used by 9-bit protocols to mark address bytes, see [`NineBit`][crate::NineBit].
+	///
+	/// Mark parity is only supported on Linux, Android and Windows.
+	/// On other platforms, applying settings with this parity fails with [`std::io::ErrorKind::Unsupported`].
+	///
+	/// <div class="item-info" style="margin-left: 0">
+	/// <span class="stab portability" style="display: inline">Available on <strong>crate feature <code>serde</code></strong> only:</span>
+	/// This variant is (de)serialized as the string <code>"mark"</code>.
+	/// </div>
+	Mark,
+
+	/// Add a parity bit that is always cleared (0).
+	///
+	/// Received characters are also expected to have a parity bit that is cleared.
+	/// This is used by 9-bit protocols for data bytes, see [`NineBit`][crate::NineBit].
+	///
+	/// Space parity is only supported on Linux, Android and Windows.
+	/// On other platforms, applying settings with this parity fails with [`std::io::ErrorKind::Unsupported`].
+	///
+	/// <div class="item-info" style="margin-left: 0">
+	/// <span class="stab portability" style="display: inline">Available on <strong>crate feature <code>serde</code></strong> only:</span>
+	/// This variant is (de)serialized as the string <code>"space"</code>.
+	/// </div>
+	Space,
 }
 
 impl Parity {
-	const EXPECTED: &'static str = "the string \"none\", \"odd\" or \"even\"";
+	const EXPECTED: &'static str = "the string \"none\", \"odd\", \"even\", \"mark\" or \"space\"";
 
 	/// Get the parity as lowercase [`&str`].
 	pub fn as_str(self) -> &'static str {
@@ -240,6 +384,8 @@ impl Parity {
 			Self::None => "none",
 			Self::Odd => "odd",
 			Self::Even => "even",
+			Self::Mark => "mark",
+			Self::Space => "space",
 		}
 	}
 
@@ -250,6 +396,8 @@ impl Parity {
 			"none" => Ok(Self::None),
 			"odd" => Ok(Self::Odd),
 			"even" => Ok(Self::Even),
+			"mark" => Ok(Self::Mark),
+			"space" => Ok(Self::Space),
 			unexpected => Err(TryFromError {
 				unexpected,
 				expected: Self::EXPECTED,
@@ -374,6 +522,61 @@ impl std::str::FromStr for FlowControl {
 	}
 }
 
+/// Translation of line endings by the operating system.
+///
+/// Console-style devices (like modems and network switches) often send and expect carriage return + newline (CRLF) line endings.
+/// These modes let the operating system translate them, so your application can work with plain newlines.
+///
+/// See [`Settings::set_newline_mode()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum NewlineMode {
+	/// Do not translate line endings.
+	///
+	/// This is the default, and the mode set by [`Settings::set_raw()`].
+	#[default]
+	None,
+
+	/// Translate received carriage returns to newlines.
+	///
+	/// This is the `ICRNL` flag on Unix platforms.
+	/// Note that this turns CRLF line endings into two newlines.
+	/// Use [`Settings::set_ignore_cr()`] instead to receive them as a single newline:
+	/// if both are enabled, received carriage returns are discarded before they can be translated.
+	InputCrToLf,
+
+	/// Translate sent newlines to a carriage return followed by a newline.
+	///
+	/// This is the `ONLCR` flag on Unix platforms.
+	OutputLfToCrLf,
+
+	/// Translate line endings in both directions.
+	///
+	/// This combines [`Self::InputCrToLf`] and [`Self::OutputLfToCrLf`].
+	Text,
+}
+
+impl NewlineMode {
+	/// Check if received carriage returns are translated to newlines.
+	pub fn translates_input(self) -> bool {
+		matches!(self, Self::InputCrToLf | Self::Text)
+	}
+
+	/// Check if sent newlines are translated to a carriage return followed by a newline.
+	pub fn translates_output(self) -> bool {
+		matches!(self, Self::OutputLfToCrLf | Self::Text)
+	}
+
+	/// Get the mode that translates the given directions.
+	pub fn from_directions(input: bool, output: bool) -> Self {
+		match (input, output) {
+			(false, false) => Self::None,
+			(true, false) => Self::InputCrToLf,
+			(false, true) => Self::OutputLfToCrLf,
+			(true, true) => Self::Text,
+		}
+	}
+}
+
 impl Settings {
 	/// Disable all OS level input and output processing.
 	///
@@ -421,6 +624,10 @@ impl Settings {
 	}
 
 	/// Set the partity check.
+	///
+	/// [`Parity::Mark`] and [`Parity::Space`] are only supported on Linux, Android and Windows.
+	/// On other platforms, [`Self::validate()`] and [`SerialPort::set_configuration()`][crate::SerialPort::set_configuration]
+	/// return an error of kind [`std::io::ErrorKind::Unsupported`] for them, and [`Self::get_parity()`] reports the previous parity.
 	pub fn set_parity(&mut self, parity: Parity) {
 		self.inner.set_parity(parity)
 	}
@@ -442,6 +649,115 @@ impl Settings {
 		self.inner.get_flow_control()
 	}
 
+	/// Check for combinations of settings that the platform can not apply.
+	///
+	/// This catches mistakes before [`SerialPort::set_configuration()`][crate::SerialPort::set_configuration] fails with a less descriptive error,
+	/// or silently applies something else than what was requested.
+	/// For example, this rejects:
+	/// * hardware (RTS/CTS) and software (XON/XOFF) flow control enabled at the same time,
+	/// * a baud rate that can not be represented by the platform,
+	/// * on Windows: 5 data bits with 2 stop bits, or 1.5 stop bits with more than 5 data bits.
+	///
+	/// These combinations can only be created through the raw platform specific settings.
+	///
+	/// If the settings are invalid, an error of kind [`std::io::ErrorKind::InvalidInput`] is returned that describes the problem.
+	/// If the settings contain an option that is not supported on this platform, like [`Parity::Mark`] on macOS,
+	/// an error of kind [`std::io::ErrorKind::Unsupported`] is returned.
+	/// Note that valid settings may still be rejected by the device driver.
+	pub fn validate(&self) -> std::io::Result<()> {
+		self.inner.validate()
+	}
+
+	/// Set whether received carriage return characters are discarded.
+	///
+	/// This is the `IGNCR` flag on Unix platforms.
+	/// It is disabled by [`Self::set_raw()`].
+	///
+	/// This option is not supported on Windows.
+	/// There, disabling it does nothing and enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	pub fn set_ignore_cr(&mut self, enable: bool) -> std::io::Result<()> {
+		self.inner.set_ignore_cr(enable)
+	}
+
+	/// Check whether received carriage return characters are discarded.
+	///
+	/// This always returns `false` on Windows.
+	pub fn get_ignore_cr(&self) -> bool {
+		self.inner.get_ignore_cr()
+	}
+
+	/// Set whether received newline characters are translated to carriage returns.
+	///
+	/// This is the `INLCR` flag on Unix platforms.
+	/// It is disabled by [`Self::set_raw()`].
+	///
+	/// This option is not supported on Windows.
+	/// There, disabling it does nothing and enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	pub fn set_map_nl_to_cr(&mut self, enable: bool) -> std::io::Result<()> {
+		self.inner.set_map_nl_to_cr(enable)
+	}
+
+	/// Check whether received newline characters are translated to carriage returns.
+	///
+	/// This always returns `false` on Windows.
+	pub fn get_map_nl_to_cr(&self) -> bool {
+		self.inner.get_map_nl_to_cr()
+	}
+
+	/// Set whether the highest bit of received bytes is cleared, leaving only 7-bit characters.
+	///
+	/// This is the `ISTRIP` flag on Unix platforms.
+	/// It is disabled by [`Self::set_raw()`].
+	///
+	/// This option is not supported on Windows.
+	/// There, disabling it does nothing and enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	pub fn set_strip_high_bit(&mut self, enable: bool) -> std::io::Result<()> {
+		self.inner.set_strip_high_bit(enable)
+	}
+
+	/// Check whether the highest bit of received bytes is cleared.
+	///
+	/// This always returns `false` on Windows.
+	pub fn get_strip_high_bit(&self) -> bool {
+		self.inner.get_strip_high_bit()
+	}
+
+	/// Set the translation of line endings.
+	///
+	/// This is disabled by [`Self::set_raw()`].
+	/// See [`NewlineMode`] for the available modes.
+	///
+	/// Line ending translation is not supported on Windows.
+	/// There, [`NewlineMode::None`] does nothing and other modes return an error of kind [`std::io::ErrorKind::Unsupported`].
+	pub fn set_newline_mode(&mut self, mode: NewlineMode) -> std::io::Result<()> {
+		self.inner.set_newline_mode(mode)
+	}
+
+	/// Get the translation of line endings.
+	///
+	/// This always returns [`NewlineMode::None`] on Windows.
+	pub fn get_newline_mode(&self) -> NewlineMode {
+		self.inner.get_newline_mode()
+	}
+
+	/// Set whether received null bytes are discarded.
+	///
+	/// This is the `fNull` option of the `DCB` on Windows.
+	/// It is disabled by [`Self::set_raw()`].
+	///
+	/// This option is only supported on Windows.
+	/// On other platforms, disabling it does nothing and enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	pub fn set_discard_null(&mut self, enable: bool) -> std::io::Result<()> {
+		self.inner.set_discard_null(enable)
+	}
+
+	/// Check whether received null bytes are discarded.
+	///
+	/// This always returns `false` on platforms other than Windows.
+	pub fn get_discard_null(&self) -> bool {
+		self.inner.get_discard_null()
+	}
+
 	/// Get a reference to the raw `termios` struct.
 	///
 	/// On Linux and Android this is actually a `termios2` struct.
@@ -478,6 +794,207 @@ impl Settings {
 		}
 	}
 
+	/// Get the input mode flags (`c_iflag`) of the raw `termios` struct.
+	///
+	/// This gives typed access to the flags, without manipulating the raw `termios` struct.
+	/// Your code will not be cross-platform anymore if you use this.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn get_input_flags(&self) -> crate::os::unix::InputFlags {
+		#[cfg(unix)] {
+			#[allow(clippy::unnecessary_cast)] // Not unnecessary on all platforms.
+			let bits = self.inner.termios.c_iflag as u64;
+			crate::os::unix::InputFlags::from_bits(bits)
+		}
+		#[cfg(not(unix))] {
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Set the input mode flags (`c_iflag`) of the raw `termios` struct.
+	///
+	/// Note that other functions like [`Self::set_raw()`] and [`Self::set_flow_control()`] also change the input flags.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn set_input_flags(&mut self, flags: crate::os::unix::InputFlags) {
+		#[cfg(unix)] {
+			#[allow(clippy::unnecessary_cast)] // Not unnecessary on all platforms.
+			let bits = flags.bits() as libc::tcflag_t;
+			self.inner.termios.c_iflag = bits;
+		}
+		#[cfg(not(unix))] {
+			let _ = flags;
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Get the output mode flags (`c_oflag`) of the raw `termios` struct.
+	///
+	/// This gives typed access to the flags, without manipulating the raw `termios` struct.
+	/// Your code will not be cross-platform anymore if you use this.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn get_output_flags(&self) -> crate::os::unix::OutputFlags {
+		#[cfg(unix)] {
+			#[allow(clippy::unnecessary_cast)] // Not unnecessary on all platforms.
+			let bits = self.inner.termios.c_oflag as u64;
+			crate::os::unix::OutputFlags::from_bits(bits)
+		}
+		#[cfg(not(unix))] {
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Set the output mode flags (`c_oflag`) of the raw `termios` struct.
+	///
+	/// Note that other functions like [`Self::set_raw()`] and [`Self::set_newline_mode()`] also change the output flags.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn set_output_flags(&mut self, flags: crate::os::unix::OutputFlags) {
+		#[cfg(unix)] {
+			#[allow(clippy::unnecessary_cast)] // Not unnecessary on all platforms.
+			let bits = flags.bits() as libc::tcflag_t;
+			self.inner.termios.c_oflag = bits;
+		}
+		#[cfg(not(unix))] {
+			let _ = flags;
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Get the control characters (`c_cc`) of the raw `termios` struct.
+	///
+	/// Index the slice with the `V*` constants from the `libc` crate, like `libc::VMIN` or `libc::VSTART`.
+	/// The number of control characters differs between platforms.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn control_chars(&self) -> &[u8] {
+		#[cfg(unix)] {
+			&self.inner.termios.c_cc
+		}
+		#[cfg(not(unix))] {
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Get a mutable reference to the control characters (`c_cc`) of the raw `termios` struct.
+	///
+	/// Index the slice with the `V*` constants from the `libc` crate, like `libc::VMIN` or `libc::VSTART`.
+	/// The number of control characters differs between platforms.
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unix")))]
+	pub fn control_chars_mut(&mut self) -> &mut [u8] {
+		#[cfg(unix)] {
+			&mut self.inner.termios.c_cc
+		}
+		#[cfg(not(unix))] {
+			unreachable!("this code is only enabled on Unix platforms or during documentation generation")
+		}
+	}
+
+	/// Get the single bit flags of the raw `DCB` struct.
+	///
+	/// This gives typed access to the flags, without manipulating the raw `DCB` struct.
+	/// Your code will not be cross-platform anymore if you use this.
+	#[cfg(any(feature = "doc", all(windows, feature = "windows")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn get_dcb_flags(&self) -> crate::os::windows::DcbFlags {
+		#[cfg(windows)] {
+			let mut flags = crate::os::windows::DcbFlags::empty();
+			for (flag, get, _) in crate::os::windows::DcbFlags::ACCESSORS {
+				flags.set(flag, get(&self.inner.dcb) != 0);
+			}
+			flags
+		}
+		#[cfg(not(windows))] {
+			unreachable!("this code is only enabled on Windows or during documentation generation")
+		}
+	}
+
+	/// Set the single bit flags of the raw `DCB` struct.
+	///
+	/// The DTR and RTS control modes are left unchanged.
+	/// Note that other functions like [`Self::set_parity()`] and [`Self::set_flow_control()`] also change these flags.
+	#[cfg(any(feature = "doc", all(windows, feature = "windows")))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn set_dcb_flags(&mut self, flags: crate::os::windows::DcbFlags) {
+		#[cfg(windows)] {
+			for (flag, _, set) in crate::os::windows::DcbFlags::ACCESSORS {
+				set(&mut self.inner.dcb, flags.contains(flag) as u32);
+			}
+		}
+		#[cfg(not(windows))] {
+			let _ = flags;
+			unreachable!("this code is only enabled on Windows or during documentation generation")
+		}
+	}
+
+	/// Set whether reads and writes are aborted when a communication error occurs (`fAbortOnError` in the Windows `DCB`).
+	///
+	/// If enabled, all pending and future reads and writes fail after an error like a parity or framing error,
+	/// until the error state is cleared by calling `ClearCommError()` on the raw handle of the serial port.
+	///
+	/// This option only exists on Windows.
+	/// On other platforms, disabling it does nothing and enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	#[cfg(any(feature = "doc", feature = "windows"))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn set_abort_on_error(&mut self, enable: bool) -> std::io::Result<()> {
+		#[cfg(windows)] {
+			self.inner.dcb.set_fAbortOnError(enable as u32);
+			Ok(())
+		}
+		#[cfg(not(windows))] {
+			unsupported_option(enable, "fAbortOnError")
+		}
+	}
+
+	/// Check whether reads and writes are aborted when a communication error occurs (`fAbortOnError` in the Windows `DCB`).
+	///
+	/// This always returns `false` on platforms other than Windows.
+	#[cfg(any(feature = "doc", feature = "windows"))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn get_abort_on_error(&self) -> bool {
+		#[cfg(windows)] {
+			self.inner.dcb.fAbortOnError() != 0
+		}
+		#[cfg(not(windows))] {
+			false
+		}
+	}
+
+	/// Set whether transmission continues after the driver sent XOFF because its input buffer is almost full (`fTXContinueOnXoff` in the Windows `DCB`).
+	///
+	/// This only has an effect with [`FlowControl::XonXoff`].
+	/// If disabled, transmission stops until the driver sends XON again.
+	///
+	/// This option only exists on Windows.
+	/// On other platforms, disabling it does nothing and enabling it returns an error of kind [`std::io::ErrorKind::Unsupported`].
+	#[cfg(any(feature = "doc", feature = "windows"))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn set_tx_continue_on_xoff(&mut self, enable: bool) -> std::io::Result<()> {
+		#[cfg(windows)] {
+			self.inner.dcb.set_fTXContinueOnXoff(enable as u32);
+			Ok(())
+		}
+		#[cfg(not(windows))] {
+			unsupported_option(enable, "fTXContinueOnXoff")
+		}
+	}
+
+	/// Check whether transmission continues after the driver sent XOFF (`fTXContinueOnXoff` in the Windows `DCB`).
+	///
+	/// This always returns `false` on platforms other than Windows.
+	#[cfg(any(feature = "doc", feature = "windows"))]
+	#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "windows")))]
+	pub fn get_tx_continue_on_xoff(&self) -> bool {
+		#[cfg(windows)] {
+			self.inner.dcb.fTXContinueOnXoff() != 0
+		}
+		#[cfg(not(windows))] {
+			false
+		}
+	}
+
 	/// Get a reference to the raw `DCB` struct.
 	///
 	/// You can use this function to access Windows specific features of the serial port.
@@ -509,6 +1026,18 @@ impl Settings {
 	}
 }
 
+/// Handle an option that the platform does not support: disabling it is a no-op, enabling it is an error.
+pub(crate) fn unsupported_option(enable: bool, name: &str) -> std::io::Result<()> {
+	if enable {
+		Err(std::io::Error::new(
+			std::io::ErrorKind::Unsupported,
+			format!("{name} is not supported on this platform"),
+		))
+	} else {
+		Ok(())
+	}
+}
+
 impl std::fmt::Debug for Settings {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_struct("Settings")
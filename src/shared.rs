@@ -0,0 +1,192 @@
+use std::io::{IoSlice, IoSliceMut};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{SerialPort, SerialTransport};
+
+/// A serial port that can be cheaply cloned and shared between threads.
+///
+/// All clones refer to the same [`SerialPort`] object, so they also share the timeouts and other per-object state.
+/// This is different from [`SerialPort::try_clone()`], which creates a new handle with its own state.
+///
+/// All functions of [`SerialPort`] that take `&self` are available through [`Deref`][std::ops::Deref].
+/// They are safe to call from multiple threads at the same time.
+/// In particular, [`SerialPort::modify_configuration()`] applies a read-modify-write of the settings atomically,
+/// so there is no need to protect the settings with a separate [`Mutex`][std::sync::Mutex].
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{SerialPort, SharedSerialPort};
+///
+/// let port = SharedSerialPort::new(SerialPort::open("/dev/ttyUSB0", 115200)?);
+/// let reader = port.clone();
+/// std::thread::spawn(move || {
+///     let mut buffer = [0; 256];
+///     while let Ok(read) = reader.read(&mut buffer) {
+///         println!("received: {:?}", &buffer[..read]);
+///     }
+/// });
+/// port.write_all(b"Hello!")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedSerialPort {
+	inner: Arc<SerialPort>,
+}
+
+impl SharedSerialPort {
+	/// Wrap a serial port so it can be shared.
+	pub fn new(serial_port: SerialPort) -> Self {
+		Self {
+			inner: Arc::new(serial_port),
+		}
+	}
+
+	/// Get the number of clones that refer to the same serial port, including this one.
+	pub fn handle_count(&self) -> usize {
+		Arc::strong_count(&self.inner)
+	}
+
+	/// Get the serial port back, if this is the last clone.
+	///
+	/// If there are other clones, this returns `Err(self)`.
+	pub fn into_inner(self) -> Result<SerialPort, Self> {
+		Arc::try_unwrap(self.inner).map_err(|inner| Self { inner })
+	}
+}
+
+impl From<SerialPort> for SharedSerialPort {
+	fn from(value: SerialPort) -> Self {
+		Self::new(value)
+	}
+}
+
+impl std::ops::Deref for SharedSerialPort {
+	type Target = SerialPort;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+impl AsRef<SerialPort> for SharedSerialPort {
+	fn as_ref(&self) -> &SerialPort {
+		&self.inner
+	}
+}
+
+impl std::io::Read for SharedSerialPort {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		self.inner.read(buf)
+	}
+
+	fn read_vectored(&mut self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		self.inner.read_vectored(buf)
+	}
+}
+
+impl std::io::Read for &'_ SharedSerialPort {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		self.inner.read(buf)
+	}
+
+	fn read_vectored(&mut self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		self.inner.read_vectored(buf)
+	}
+}
+
+impl std::io::Write for SharedSerialPort {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		self.inner.write(buf)
+	}
+
+	fn write_vectored(&mut self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		self.inner.write_vectored(buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+impl std::io::Write for &'_ SharedSerialPort {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		self.inner.write(buf)
+	}
+
+	fn write_vectored(&mut self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		self.inner.write_vectored(buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+impl SerialTransport for SharedSerialPort {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		self.inner.read(buf)
+	}
+
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		self.inner.write(buf)
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+
+	fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+		self.inner.read_exact(buf)
+	}
+
+	fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+		self.inner.write_all(buf)
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_read_timeout(timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_read_timeout()
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.inner.set_write_timeout(timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		self.inner.get_write_timeout()
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		self.inner.discard_buffers()
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_rts(state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		self.inner.read_cts()
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.inner.set_dtr(state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		self.inner.read_dsr()
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		self.inner.read_ri()
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		self.inner.read_cd()
+	}
+}
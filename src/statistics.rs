@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Statistics of the I/O done on a serial port.
+///
+/// Use [`SerialPort::enable_statistics()`][crate::SerialPort::enable_statistics] to start collecting statistics,
+/// and [`SerialPort::statistics()`][crate::SerialPort::statistics] to retrieve them.
+///
+/// Timeouts are counted separately from other errors.
+/// Interrupted calls are not counted at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortStats {
+	pub(crate) bytes_read: u64,
+	pub(crate) bytes_written: u64,
+	pub(crate) reads: u64,
+	pub(crate) writes: u64,
+	pub(crate) timeouts: u64,
+	pub(crate) errors: u64,
+}
+
+impl PortStats {
+	/// Get the total number of bytes read.
+	pub fn bytes_read(&self) -> u64 {
+		self.bytes_read
+	}
+
+	/// Get the total number of bytes written.
+	pub fn bytes_written(&self) -> u64 {
+		self.bytes_written
+	}
+
+	/// Get the number of successful read calls.
+	pub fn reads(&self) -> u64 {
+		self.reads
+	}
+
+	/// Get the number of successful write calls.
+	pub fn writes(&self) -> u64 {
+		self.writes
+	}
+
+	/// Get the number of reads and writes that timed out.
+	pub fn timeouts(&self) -> u64 {
+		self.timeouts
+	}
+
+	/// Get the number of reads and writes that failed with an error other than a timeout.
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+}
+
+/// Counters for [`PortStats`] that can be updated from multiple threads.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+	bytes_read: AtomicU64,
+	bytes_written: AtomicU64,
+	reads: AtomicU64,
+	writes: AtomicU64,
+	timeouts: AtomicU64,
+	errors: AtomicU64,
+}
+
+impl Counters {
+	/// Count the result of a read call.
+	pub fn record_read(&self, result: &std::io::Result<usize>) {
+		self.record(result, &self.reads, &self.bytes_read)
+	}
+
+	/// Count the result of a write call.
+	pub fn record_write(&self, result: &std::io::Result<usize>) {
+		self.record(result, &self.writes, &self.bytes_written)
+	}
+
+	fn record(&self, result: &std::io::Result<usize>, calls: &AtomicU64, bytes: &AtomicU64) {
+		match result {
+			Ok(n) => {
+				calls.fetch_add(1, Ordering::Relaxed);
+				bytes.fetch_add(*n as u64, Ordering::Relaxed);
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+			Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+				self.timeouts.fetch_add(1, Ordering::Relaxed);
+			},
+			Err(_) => {
+				self.errors.fetch_add(1, Ordering::Relaxed);
+			},
+		}
+	}
+
+	/// Get a snapshot of the counters.
+	pub fn get(&self) -> PortStats {
+		PortStats {
+			bytes_read: self.bytes_read.load(Ordering::Relaxed),
+			bytes_written: self.bytes_written.load(Ordering::Relaxed),
+			reads: self.reads.load(Ordering::Relaxed),
+			writes: self.writes.load(Ordering::Relaxed),
+			timeouts: self.timeouts.load(Ordering::Relaxed),
+			errors: self.errors.load(Ordering::Relaxed),
+		}
+	}
+
+	/// Reset all counters to zero.
+	pub fn reset(&self) {
+		for counter in [&self.bytes_read, &self.bytes_written, &self.reads, &self.writes, &self.timeouts, &self.errors] {
+			counter.store(0, Ordering::Relaxed);
+		}
+	}
+}
@@ -10,4 +10,4 @@ mod windows;
 #[cfg(windows)]
 pub use windows::*;
 
-const DEFAULT_TIMEOUT_MS: u32 = 3000;
+pub(crate) const DEFAULT_TIMEOUT_MS: u32 = 3000;
@@ -1,6 +1,16 @@
 use std::path::PathBuf;
 use std::os::unix::io::RawFd;
 
+/// The baud rates that have a `B*` constant in `termios.h`.
+///
+/// Other baud rates can be set with the `IOSSIOSPEED` ioctl.
+pub const STANDARD_BAUD_RATES: [u32; 22] = [
+	// POSIX 2017.1 defines the baud rates up to 38400: https://pubs.opengroup.org/onlinepubs/9699919799
+	// The BSDs add 7200, 14400, 28800 and the baud rates above 38400.
+	50, 75, 110, 134, 150, 200, 300, 600, 1200, 1800, 2400, 4800, 7200, 9600, 14400, 19200, 28800, 38400,
+	57600, 76800, 115200, 230400,
+];
+
 /// A ioctl to set the baud rate of a serial port.
 ///
 /// Value taken from random forum because there is no public documentation.
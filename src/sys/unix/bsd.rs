@@ -1,5 +1,15 @@
 use std::path::PathBuf;
 
+/// The baud rates that have a `B*` constant in `termios.h` on all supported BSDs.
+///
+/// The constants are the baud rate itself, so other baud rates can be used too.
+pub const STANDARD_BAUD_RATES: [u32; 22] = [
+	// POSIX 2017.1 defines the baud rates up to 38400: https://pubs.opengroup.org/onlinepubs/9699919799
+	// The BSDs add 7200, 14400, 28800 and the baud rates above 38400.
+	50, 75, 110, 134, 150, 200, 300, 600, 1200, 1800, 2400, 4800, 7200, 9600, 14400, 19200, 28800, 38400,
+	57600, 76800, 115200, 230400,
+];
+
 pub fn enumerate() -> std::io::Result<Vec<PathBuf>> {
 	use std::os::unix::ffi::OsStrExt;
 	use std::os::unix::fs::FileTypeExt;
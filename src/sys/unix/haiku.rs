@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+// All values taken from:
+// https://github.com/haiku/haiku/blob/master/headers/posix/termios.h
+
+pub const BAUD_RATES: [(libc::speed_t, u32); 19] = [
+	(libc::B50, 50),
+	(libc::B75, 75),
+	(libc::B110, 110),
+	(libc::B134, 134),
+	(libc::B150, 150),
+	(libc::B200, 200),
+	(libc::B300, 300),
+	(libc::B600, 600),
+	(libc::B1200, 1200),
+	(libc::B1800, 1800),
+	(libc::B2400, 2400),
+	(libc::B4800, 4800),
+	(libc::B9600, 9600),
+	(libc::B19200, 19200),
+	(libc::B31250, 31250),
+	(libc::B38400, 38400),
+	(libc::B57600, 57600),
+	(libc::B115200, 115200),
+	(libc::B230400, 230400),
+];
+
+pub fn enumerate() -> std::io::Result<Vec<PathBuf>> {
+	// Serial port drivers publish their devices in /dev/ports,
+	// for example /dev/ports/pc_serial0 or /dev/ports/usb0.
+	let serial_ports = std::fs::read_dir("/dev/ports")?
+		.filter_map(|entry| {
+			let entry = entry.ok()?;
+			if entry.file_type().ok()?.is_dir() {
+				None
+			} else {
+				Some(entry.path())
+			}
+		})
+		.collect();
+	Ok(serial_ports)
+}
@@ -1,12 +1,20 @@
 use cfg_if::cfg_if;
 use std::path::{Path, PathBuf};
 
+use crate::{PortDiagnostic, PortStatus};
+
 #[cfg(feature = "rs4xx")]
 mod rs4xx;
 
 #[cfg(feature = "rs4xx")]
 pub use rs4xx::*;
 
+#[cfg(feature = "unix")]
+mod serial;
+
+#[cfg(feature = "unix")]
+pub use serial::*;
+
 cfg_if! {
 	if #[cfg(any(target_arch = "sparc", target_arch = "sparc64"))] {
 		pub const BAUD_RATES: [(u32, u32); 30] = [
@@ -78,6 +86,74 @@ cfg_if! {
 }
 
 pub fn enumerate() -> std::io::Result<Vec<PathBuf>> {
+	let entries = enumerate_verbose()?
+		.into_iter()
+		.filter(|entry| entry.is_available())
+		.map(|entry| entry.path)
+		.collect();
+	Ok(entries)
+}
+
+pub fn enumerate_verbose() -> std::io::Result<Vec<PortDiagnostic>> {
+	// If /sys is not available (for example in containers or on locked-down Android devices),
+	// fall back to the TTY drivers listed in /proc.
+	// If that fails too, report the original error.
+	match enumerate_sysfs() {
+		Ok(entries) => Ok(entries),
+		Err(sysfs_error) => enumerate_proc_tty_drivers().map_err(|_| sysfs_error),
+	}
+}
+
+/// Get a stable identifier for the device behind a serial port.
+///
+/// This uses the name of the matching link in `/dev/serial/by-id` if there is one.
+/// Otherwise, an identifier is made from the sysfs attributes of the USB device.
+pub fn port_id(path: &Path) -> Option<String> {
+	port_id_from_by_id(path).or_else(|| port_id_from_sysfs(path))
+}
+
+/// Find the name of the link in `/dev/serial/by-id` that points to the given device.
+///
+/// These links are created by udev for USB serial devices.
+fn port_id_from_by_id(path: &Path) -> Option<String> {
+	let target = path.canonicalize().ok()?;
+	for entry in std::fs::read_dir("/dev/serial/by-id").ok()? {
+		let entry = match entry {
+			Ok(x) => x,
+			Err(_) => continue,
+		};
+		if entry.path().canonicalize().ok().as_deref() == Some(target.as_path()) {
+			return entry.file_name().into_string().ok();
+		}
+	}
+	None
+}
+
+/// Make an identifier from the vendor ID, product ID, serial number and interface number of a USB serial device.
+///
+/// Returns `None` if the port does not belong to a USB device with a serial number.
+fn port_id_from_sysfs(path: &Path) -> Option<String> {
+	let name = path.file_name()?;
+	let device = Path::new("/sys/class/tty").join(name).join("device").canonicalize().ok()?;
+
+	// The device is either the USB interface itself (cdc-acm), or a child of the interface (usb-serial drivers).
+	let interface = device.ancestors().find(|dir| dir.join("bInterfaceNumber").is_file())?;
+	let usb_device = interface.parent()?;
+	let read = |dir: &Path, name: &str| {
+		std::fs::read_to_string(dir.join(name))
+			.ok()
+			.map(|value| value.trim().to_owned())
+	};
+
+	let vendor = read(usb_device, "idVendor")?;
+	let product = read(usb_device, "idProduct")?;
+	let serial = read(usb_device, "serial")?;
+	let interface_number = read(interface, "bInterfaceNumber")?;
+	Some(format!("usb-{vendor}:{product}-{serial}-if{interface_number}"))
+}
+
+/// Enumerate serial ports using `/sys/class/tty`.
+fn enumerate_sysfs() -> std::io::Result<Vec<PortDiagnostic>> {
 	use std::os::unix::ffi::OsStrExt;
 	use std::os::unix::fs::FileTypeExt;
 
@@ -85,10 +161,13 @@ pub fn enumerate() -> std::io::Result<Vec<PathBuf>> {
 	let mut entries = Vec::with_capacity(32);
 
 	for entry in dir {
-		// Skip entries we can't stat.
+		// Report entries we can't stat.
 		let entry = match entry {
 			Ok(x) => x,
-			Err(_) => continue,
+			Err(e) => {
+				entries.push(PortDiagnostic::new("/sys/class/tty", PortStatus::Inaccessible(e)));
+				continue;
+			},
 		};
 
 		let name = entry.file_name();
@@ -96,36 +175,306 @@ pub fn enumerate() -> std::io::Result<Vec<PathBuf>> {
 		// Skip everything that doesn't have a matching device node in /dev
 		let dev_path = Path::new("/dev").join(&name);
 		match dev_path.metadata() {
-			Err(_) => continue,
+			Err(e) => {
+				entries.push(PortDiagnostic::new(dev_path, PortStatus::Inaccessible(e)));
+				continue;
+			},
 			Ok(metadata) => {
 				if !metadata.file_type().is_char_device() {
+					entries.push(PortDiagnostic::new(dev_path, PortStatus::NotCharDevice));
 					continue;
 				}
 			},
 		}
 
-		match name.as_bytes().strip_prefix(b"tty") {
-			// Skip entries called "tty";
-			Some(b"") => continue,
-			// Skip "tty1", "tty2", etc (they are virtual terminals, not serial ports).
-			Some(&[c, ..]) if c.is_ascii_digit() => continue,
-			// Skip everything that doesn't start with "tty", they are almost certainly not serial ports.
-			None => continue,
-			// Accept the rest.
-			Some(_) => (),
-		};
+		if let Some(reason) = filter_tty_name(name.as_bytes()) {
+			entries.push(PortDiagnostic::new(dev_path, PortStatus::Filtered(reason)));
+			continue;
+		}
 
 		// There's a bunch of ttyS* ports that are not really serial ports.
 		//
 		// They have a file called `device/driver_override` set to "(null)".
 		if let Ok(driver_override) = std::fs::read(entry.path().join("device/driver_override")) {
-			if driver_override == b"(null)\n" {
+			if driver_override == NULL_DRIVER_OVERRIDE {
+				let reason = "the port has no hardware (driver_override is \"(null)\")";
+				entries.push(PortDiagnostic::new(dev_path, PortStatus::Filtered(reason)));
+				continue;
+			}
+		}
+
+		let kind = super::port_kind_from_path(&dev_path);
+		entries.push(PortDiagnostic::new(dev_path, PortStatus::Available(kind)));
+	}
+
+	Ok(entries)
+}
+
+/// The contents of `device/driver_override` for `ttyS*` ports without actual hardware.
+const NULL_DRIVER_OVERRIDE: &[u8] = b"(null)\n";
+
+/// Check if an entry in `/sys/class/tty` should be skipped based on its name.
+///
+/// Returns the reason to skip the entry, or `None` if it may be a serial port.
+fn filter_tty_name(name: &[u8]) -> Option<&'static str> {
+	match name.strip_prefix(b"tty") {
+		// Skip entries called "tty";
+		Some(b"") => Some("the controlling terminal is not a serial port"),
+		// Skip "tty1", "tty2", etc (they are virtual terminals, not serial ports).
+		Some(&[c, ..]) if c.is_ascii_digit() => Some("virtual terminals are not serial ports"),
+		// Skip everything that doesn't start with "tty", they are almost certainly not serial ports.
+		None => Some("the name does not start with \"tty\""),
+		// Accept the rest.
+		Some(_) => None,
+	}
+}
+
+/// Call a function for each available serial port.
+///
+/// This applies the same filters as [`enumerate_sysfs()`], but uses `readdir()` and fixed size buffers,
+/// so it does not allocate any memory.
+/// If `/sys/class/tty` can not be opened, this collects the ports from `/proc/tty/drivers` instead.
+pub fn for_each_port(f: &mut dyn FnMut(crate::PortInfoRef<'_>)) -> std::io::Result<()> {
+	use std::ffi::{CStr, OsStr};
+	use std::os::unix::ffi::OsStrExt;
+
+	#[allow(clippy::manual_c_str_literals)] // C string literals require Rust 1.77
+	let sysfs = unsafe { CStr::from_bytes_with_nul_unchecked(b"/sys/class/tty\0") };
+	let dir = unsafe { libc::opendir(sysfs.as_ptr()) };
+	if dir.is_null() {
+		let sysfs_error = std::io::Error::last_os_error();
+		let entries = enumerate_proc_tty_drivers().map_err(|_| sysfs_error)?;
+		for entry in &entries {
+			if let PortStatus::Available(kind) = entry.status {
+				f(crate::PortInfoRef::new(&entry.path, kind));
+			}
+		}
+		return Ok(());
+	}
+	let dir = Dir(dir);
+
+	// Device names are limited to 255 bytes, so these buffers are always large enough.
+	let mut dev_path = [0u8; 320];
+	let mut override_path = [0u8; 320];
+	let mut driver_override = [0u8; 16];
+	loop {
+		let entry = unsafe { libc::readdir(dir.0) };
+		if entry.is_null() {
+			break;
+		}
+		let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) }.to_bytes();
+		if filter_tty_name(name).is_some() {
+			continue;
+		}
+
+		// Skip everything that doesn't have a matching character device in /dev.
+		let dev = match join_cstr(&mut dev_path, &[b"/dev/", name]) {
+			Some(x) => x,
+			None => continue,
+		};
+		let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+		if unsafe { libc::stat(dev.as_ptr(), &mut stat) } != 0 || stat.st_mode & libc::S_IFMT != libc::S_IFCHR {
+			continue;
+		}
+
+		// Skip ttyS* ports without actual hardware.
+		if let Some(path) = join_cstr(&mut override_path, &[b"/sys/class/tty/", name, b"/device/driver_override"]) {
+			if read_small_file(path, &mut driver_override) == Some(NULL_DRIVER_OVERRIDE) {
 				continue;
 			}
 		}
 
-		entries.push(dev_path);
+		let path = Path::new(OsStr::from_bytes(dev.to_bytes()));
+		f(crate::PortInfoRef::new(path, super::port_kind_from_path(path)));
+	}
+	Ok(())
+}
+
+/// A directory stream from `opendir()` that is closed on drop.
+struct Dir(*mut libc::DIR);
+
+impl Drop for Dir {
+	fn drop(&mut self) {
+		unsafe {
+			libc::closedir(self.0);
+		}
+	}
+}
+
+/// Concatenate byte strings into a buffer as a nul-terminated string.
+///
+/// Returns `None` if the result does not fit in the buffer or if the parts contain a nul byte.
+fn join_cstr<'a>(buffer: &'a mut [u8], parts: &[&[u8]]) -> Option<&'a std::ffi::CStr> {
+	let mut len = 0;
+	for part in parts {
+		buffer.get_mut(len..len + part.len())?.copy_from_slice(part);
+		len += part.len();
+	}
+	*buffer.get_mut(len)? = 0;
+	std::ffi::CStr::from_bytes_with_nul(&buffer[..=len]).ok()
+}
+
+/// Read a small file into a buffer, without allocating.
+///
+/// Returns `None` if the file can not be read.
+/// If the file is larger than the buffer, only the start of the file is returned.
+fn read_small_file<'a>(path: &std::ffi::CStr, buffer: &'a mut [u8]) -> Option<&'a [u8]> {
+	unsafe {
+		let fd = libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC);
+		if fd < 0 {
+			return None;
+		}
+		let read = libc::read(fd, buffer.as_mut_ptr().cast(), buffer.len());
+		libc::close(fd);
+		let read = usize::try_from(read).ok()?;
+		Some(&buffer[..read])
 	}
+}
+
+/// Enumerate serial ports using `/proc/tty/drivers` and the device nodes in `/dev`.
+///
+/// This reports all character devices that belong to a TTY driver of type "serial".
+/// Unlike [`enumerate_sysfs()`], it can not filter out `ttyS*` ports without actual hardware.
+///
+/// Only entries in `/dev` with a name that matches one of the serial drivers are included in the diagnostics.
+fn enumerate_proc_tty_drivers() -> std::io::Result<Vec<PortDiagnostic>> {
+	use std::os::unix::ffi::OsStrExt;
+	use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+	let drivers = std::fs::read_to_string("/proc/tty/drivers")?;
+	let drivers: Vec<TtyDriver> = drivers.lines().filter_map(TtyDriver::parse).collect();
+
+	let mut entries = Vec::with_capacity(32);
+	for entry in std::fs::read_dir("/dev")? {
+		// Report entries we can't stat.
+		let entry = match entry {
+			Ok(x) => x,
+			Err(e) => {
+				entries.push(PortDiagnostic::new("/dev", PortStatus::Inaccessible(e)));
+				continue;
+			},
+		};
 
+		let name = entry.file_name();
+		let is_candidate = drivers
+			.iter()
+			.any(|driver| name.as_bytes().starts_with(driver.name_prefix.as_bytes()));
+		if !is_candidate {
+			continue;
+		}
+
+		let metadata = match entry.metadata() {
+			Ok(x) => x,
+			Err(e) => {
+				entries.push(PortDiagnostic::new(entry.path(), PortStatus::Inaccessible(e)));
+				continue;
+			},
+		};
+		if !metadata.file_type().is_char_device() {
+			entries.push(PortDiagnostic::new(entry.path(), PortStatus::NotCharDevice));
+			continue;
+		}
+
+		let (major, minor) = split_device_number(metadata.rdev());
+		let is_serial = drivers.iter().any(|driver| {
+			driver.major == major
+				&& driver.minor.contains(&minor)
+				&& name.as_bytes().starts_with(driver.name_prefix.as_bytes())
+		});
+		let path = entry.path();
+		if is_serial {
+			let kind = super::port_kind_from_path(&path);
+			entries.push(PortDiagnostic::new(path, PortStatus::Available(kind)));
+		} else {
+			let reason = "the device number does not belong to a serial driver";
+			entries.push(PortDiagnostic::new(path, PortStatus::Filtered(reason)));
+		}
+	}
+
+	entries.sort_by(|a, b| a.path.cmp(&b.path));
 	Ok(entries)
 }
+
+/// A serial TTY driver from `/proc/tty/drivers`.
+struct TtyDriver<'a> {
+	/// The prefix of the device names, without the `/dev/` directory.
+	name_prefix: &'a str,
+
+	/// The major device number.
+	major: u32,
+
+	/// The range of minor device numbers.
+	minor: std::ops::RangeInclusive<u32>,
+}
+
+impl<'a> TtyDriver<'a> {
+	/// Parse a line from `/proc/tty/drivers`.
+	///
+	/// Each line has the driver name, the device node prefix, the major number, the minor number range and the driver type:
+	/// `usbserial /dev/ttyUSB 188 0-511 serial`.
+	///
+	/// Returns `None` for invalid lines and for drivers that are not of type "serial".
+	fn parse(line: &'a str) -> Option<Self> {
+		let mut fields = line.split_whitespace();
+		let _driver = fields.next()?;
+		let name_prefix = fields.next()?.strip_prefix("/dev/")?;
+		let major = fields.next()?.parse().ok()?;
+		let minor = fields.next()?;
+		let kind = fields.next()?;
+		if kind != "serial" {
+			return None;
+		}
+		let minor = match minor.split_once('-') {
+			Some((start, end)) => start.parse().ok()?..=end.parse().ok()?,
+			None => {
+				let minor = minor.parse().ok()?;
+				minor..=minor
+			},
+		};
+		Some(Self {
+			name_prefix,
+			major,
+			minor,
+		})
+	}
+}
+
+/// Split a device number into the major and minor number.
+///
+/// This uses the same encoding as the `major()` and `minor()` functions of glibc and musl.
+fn split_device_number(device: u64) -> (u32, u32) {
+	let major = ((device >> 8) & 0xfff) | ((device >> 32) & !0xfff);
+	let minor = (device & 0xff) | ((device >> 12) & !0xff);
+	(major as u32, minor as u32)
+}
+
+/// The interrupt counters of a serial port, as reported by `TIOCGICOUNT`.
+///
+/// This is `struct serial_icounter_struct` from `linux/serial.h`, which is not exposed by the `libc` crate.
+/// Only the ring counter is used, the other fields are needed for the layout.
+#[repr(C)]
+#[allow(dead_code)]
+struct SerialIcounter {
+	cts: libc::c_int,
+	dsr: libc::c_int,
+	rng: libc::c_int,
+	dcd: libc::c_int,
+	rx: libc::c_int,
+	tx: libc::c_int,
+	frame: libc::c_int,
+	overrun: libc::c_int,
+	parity: libc::c_int,
+	brk: libc::c_int,
+	buf_overrun: libc::c_int,
+	reserved: [libc::c_int; 9],
+}
+
+/// Get the number of Ring Indicator pulses counted by the driver with `TIOCGICOUNT`.
+pub fn get_ring_count(file: &std::fs::File) -> std::io::Result<u32> {
+	use std::os::unix::io::AsRawFd;
+	unsafe {
+		let mut counter: SerialIcounter = std::mem::zeroed();
+		super::check(libc::ioctl(file.as_raw_fd(), libc::TIOCGICOUNT as _, &mut counter))?;
+		Ok(counter.rng as u32)
+	}
+}
@@ -1,7 +1,7 @@
 use std::os::unix::io::{AsFd, AsRawFd};
 use std::time::Duration;
 
-use crate::rs4xx::{Rs485Config, TransceiverMode};
+use crate::rs4xx::{Rs485Config, Rs4xxFeatures, TransceiverMode};
 use crate::sys::unix::{check, SerialPort};
 
 /// Get the RS-485/422 mode of the serial port transceiver.
@@ -22,6 +22,57 @@ pub fn set_rs4xx_mode(port: &SerialPort, mode: &crate::rs4xx::TransceiverMode) -
 	config.set_on_fd(&port.file)
 }
 
+/// Probe which RS-485/422 features are supported by the driver of the serial port.
+///
+/// The kernel clears unsupported flags and delays when a configuration is applied,
+/// so we apply configurations with all features enabled and read back what is left.
+/// The original configuration is restored afterwards.
+pub fn get_rs4xx_supported_features(port: &SerialPort) -> std::io::Result<Rs4xxFeatures> {
+	let original = SerialRs485::get_from_fd(&port.file)?;
+	let result = probe_rs4xx_features(port);
+	let restored = original.set_on_fd(&port.file);
+	let features = result?;
+	restored?;
+	Ok(features)
+}
+
+fn probe_rs4xx_features(port: &SerialPort) -> std::io::Result<Rs4xxFeatures> {
+	let probe = |config: SerialRs485| -> std::io::Result<SerialRs485> {
+		config.set_on_fd(&port.file)?;
+		SerialRs485::get_from_fd(&port.file)
+	};
+
+	let mut features = Rs4xxFeatures::default();
+
+	let mut config = SerialRs485::new_with_flags(
+		flags::SER_RS485_ENABLED
+			| flags::SER_RS485_RTS_ON_SEND
+			| flags::SER_RS485_RX_DURING_TX
+			| flags::SER_RS485_TERMINATE_BUS
+			| flags::SER_RS485_ADDRB,
+	);
+	config.delay_rts_before_send_ms = 1;
+	config.delay_rts_after_send_ms = 1;
+	let config = probe(config)?;
+	if config.flags & flags::SER_RS485_ENABLED == 0 {
+		return Ok(features);
+	}
+	features.rs485 = true;
+	features.full_duplex = config.flags & flags::SER_RS485_RX_DURING_TX != 0;
+	features.bus_termination = config.flags & flags::SER_RS485_TERMINATE_BUS != 0;
+	features.addressing = config.flags & flags::SER_RS485_ADDRB != 0;
+	features.delay_before_send = config.delay_rts_before_send_ms != 0;
+	features.delay_after_send = config.delay_rts_after_send_ms != 0;
+
+	let config = probe(SerialRs485::new_with_flags(flags::SER_RS485_ENABLED | flags::SER_RS485_RTS_AFTER_SEND))?;
+	features.invert_rts = config.flags & flags::SER_RS485_RTS_ON_SEND == 0;
+
+	let config = probe(SerialRs485::new_rs422())?;
+	features.rs422 = config.flags & flags::SER_RS485_MODE_RS422 != 0;
+
+	Ok(features)
+}
+
 #[rustfmt::skip]
 #[allow(dead_code)]
 mod flags {
@@ -123,6 +174,20 @@ impl From<&'_ Rs485Config> for SerialRs485 {
 			flags |= flags::SER_RS485_RTS_ON_SEND;
 		}
 
+		let mut addr_recv = 0;
+		let mut addr_dest = 0;
+		if config.get_addressing() {
+			flags |= flags::SER_RS485_ADDRB;
+			if let Some(address) = config.get_receive_address() {
+				flags |= flags::SER_RS485_ADDR_RECV;
+				addr_recv = address;
+			}
+			if let Some(address) = config.get_destination_address() {
+				flags |= flags::SER_RS485_ADDR_DEST;
+				addr_dest = address;
+			}
+		}
+
 		let delay_rts_before_send_ms = config
 			.get_delay_before_send()
 			.as_millis()
@@ -134,8 +199,8 @@ impl From<&'_ Rs485Config> for SerialRs485 {
 			flags,
 			delay_rts_before_send_ms,
 			delay_rts_after_send_ms,
-			addr_recv: 0,
-			addr_dest: 0,
+			addr_recv,
+			addr_dest,
 			_padding0: [0; 2],
 			_padding1: [0; 4],
 		}
@@ -157,6 +222,13 @@ impl From<&SerialRs485> for TransceiverMode {
 		config.set_invert_rts(other.flags & flags::SER_RS485_RTS_ON_SEND == 0);
 		config.set_delay_before_send(Duration::from_millis(other.delay_rts_before_send_ms.into()));
 		config.set_delay_after_send(Duration::from_millis(other.delay_rts_after_send_ms.into()));
+		config.set_addressing(other.flags & flags::SER_RS485_ADDRB != 0);
+		if other.flags & flags::SER_RS485_ADDR_RECV != 0 {
+			config.set_receive_address(Some(other.addr_recv));
+		}
+		if other.flags & flags::SER_RS485_ADDR_DEST != 0 {
+			config.set_destination_address(Some(other.addr_dest));
+		}
 		TransceiverMode::Rs485(config)
 	}
 }
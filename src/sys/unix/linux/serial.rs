@@ -0,0 +1,69 @@
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+
+use crate::os::unix::SerialStruct;
+use crate::sys::unix::{check, SerialPort};
+
+/// The `ASYNC_LOW_LATENCY` flag of [`SerialStruct::flags`].
+const ASYNC_LOW_LATENCY: libc::c_int = 1 << 13;
+
+/// The default latency timer of FTDI devices in milliseconds.
+const FTDI_DEFAULT_LATENCY_TIMER: u8 = 16;
+
+/// Get the serial port information with `TIOCGSERIAL`.
+pub fn get_serial_struct(port: &SerialPort) -> std::io::Result<SerialStruct> {
+	unsafe {
+		let mut serial: SerialStruct = std::mem::zeroed();
+		check(libc::ioctl(port.file.as_raw_fd(), libc::TIOCGSERIAL as _, &mut serial))?;
+		Ok(serial)
+	}
+}
+
+/// Apply serial port information with `TIOCSSERIAL`.
+pub fn set_serial_struct(port: &SerialPort, serial: &SerialStruct) -> std::io::Result<()> {
+	unsafe {
+		check(libc::ioctl(port.file.as_raw_fd(), libc::TIOCSSERIAL as _, serial))?;
+		Ok(())
+	}
+}
+
+/// Enable or disable low latency mode.
+///
+/// This sets the `ASYNC_LOW_LATENCY` flag with `TIOCSSERIAL`.
+/// Many USB serial drivers ignore that flag, so for FTDI devices we also set the latency timer through sysfs.
+/// An error is only reported if both methods fail.
+pub fn set_low_latency(port: &SerialPort, enable: bool) -> std::io::Result<()> {
+	let serial_result = get_serial_struct(port).and_then(|mut serial| {
+		if enable {
+			serial.flags |= ASYNC_LOW_LATENCY;
+		} else {
+			serial.flags &= !ASYNC_LOW_LATENCY;
+		}
+		set_serial_struct(port, &serial)
+	});
+
+	let latency_timer = if enable { 1 } else { FTDI_DEFAULT_LATENCY_TIMER };
+	match set_ftdi_latency_timer(port, latency_timer) {
+		Ok(true) => Ok(()),
+		Ok(false) => serial_result,
+		Err(e) => serial_result.map_err(|_| e),
+	}
+}
+
+/// Set the latency timer of an FTDI device in milliseconds.
+///
+/// Returns `Ok(false)` if the serial port is not an FTDI device.
+fn set_ftdi_latency_timer(port: &SerialPort, milliseconds: u8) -> std::io::Result<bool> {
+	// Find the sysfs directory of the device through its device number.
+	let rdev = port.file.metadata()?.rdev();
+	let path = format!(
+		"/sys/dev/char/{}:{}/device/latency_timer",
+		libc::major(rdev),
+		libc::minor(rdev),
+	);
+	match std::fs::write(path, format!("{milliseconds}\n")) {
+		Ok(()) => Ok(true),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+		Err(e) => Err(e),
+	}
+}
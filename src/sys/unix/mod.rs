@@ -3,17 +3,35 @@ use std::io::{IoSlice, IoSliceMut};
 use std::os::raw::c_int;
 use std::os::unix::io::AsRawFd;
 use std::path::Path;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
 
 pub struct SerialPort {
 	pub file: std::fs::File,
-	pub read_timeout_ms: u32,
-	pub write_timeout_ms: u32,
+	pub read_timeout_ms: AtomicU32,
+	/// The inter-byte timeout for [`crate::ReadMode::FillBuffer`], or [`NO_INTER_BYTE_TIMEOUT`].
+	pub inter_byte_timeout_ms: AtomicU32,
+	pub write_timeout_ms: AtomicU32,
+	pub config_lock: std::sync::Mutex<()>,
+	pub abort: AbortSignal,
+	pub name: Option<std::path::PathBuf>,
+	pub cdc_acm_quirks: AtomicBool,
+	/// The last read filled the whole buffer, so more data is probably available already.
+	pub read_filled_buffer: AtomicBool,
+	/// The file is in non-blocking mode, so a transfer can be attempted before `poll()` without blocking.
+	///
+	/// This is false for file descriptors passed in by the user that are in blocking mode, like the master side of a pseudo terminal.
+	pub nonblocking: bool,
+	/// Read back the state of RTS and DTR after changing them.
+	pub verify_control_lines: AtomicBool,
+	/// The [`crate::ApplyMode`] for new settings, stored as `u8`.
+	pub apply_mode: AtomicU8,
 }
 
 impl std::fmt::Debug for SerialPort {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_struct("SerialPort")
+			.field("name", &self.name)
 			.field("fd", &self.file.as_raw_fd())
 			.finish()
 	}
@@ -50,6 +68,10 @@ cfg_if! {
 		mod solarish;
 		pub use solarish::*;
 
+	} else if #[cfg(target_os = "haiku")] {
+		mod haiku;
+		pub use haiku::*;
+
 	} else {
 		mod other;
 		pub use other::*;
@@ -67,6 +89,9 @@ cfg_if! {
 		#[derive(Clone)]
 		pub struct Settings {
 			pub(crate) termios: RawTermios,
+
+			/// A parity that was requested but can not be configured on this platform.
+			pub(crate) unsupported_parity: Option<crate::Parity>,
 		}
 
 		impl Settings {
@@ -74,13 +99,18 @@ cfg_if! {
 				unsafe {
 					let mut termios = std::mem::zeroed();
 					check(libc::ioctl(file.as_raw_fd(), libc::TCGETS2 as _, &mut termios))?;
-					Ok(Settings { termios })
+					Ok(Settings { termios, unsupported_parity: None })
 				}
 			}
 
-			fn set_on_file(&self, file: &mut std::fs::File) -> std::io::Result<()> {
+			fn set_on_file(&self, file: &std::fs::File, mode: crate::ApplyMode) -> std::io::Result<()> {
+				let request = match mode {
+					crate::ApplyMode::Drain => libc::TCSETSW2,
+					crate::ApplyMode::DrainAndDiscardInput => libc::TCSETSF2,
+					crate::ApplyMode::Immediate | crate::ApplyMode::Discard => libc::TCSETS2,
+				};
 				unsafe {
-					check(libc::ioctl(file.as_raw_fd(), libc::TCSETSW2 as _, &self.termios))?;
+					check(libc::ioctl(file.as_raw_fd(), request as _, &self.termios))?;
 				}
 				Ok(())
 			}
@@ -91,6 +121,9 @@ cfg_if! {
 		#[derive(Clone)]
 		pub struct Settings {
 			pub termios: RawTermios,
+
+			/// A parity that was requested but can not be configured on this platform.
+			pub(crate) unsupported_parity: Option<crate::Parity>,
 		}
 
 		impl Settings {
@@ -98,13 +131,18 @@ cfg_if! {
 				unsafe {
 					let mut termios = std::mem::zeroed();
 					check(libc::tcgetattr(file.as_raw_fd(), &mut termios))?;
-					Ok(Settings { termios })
+					Ok(Settings { termios, unsupported_parity: None })
 				}
 			}
 
-			fn set_on_file(&self, file: &mut std::fs::File) -> std::io::Result<()> {
+			fn set_on_file(&self, file: &std::fs::File, mode: crate::ApplyMode) -> std::io::Result<()> {
+				let action = match mode {
+					crate::ApplyMode::Drain => libc::TCSADRAIN,
+					crate::ApplyMode::DrainAndDiscardInput => libc::TCSAFLUSH,
+					crate::ApplyMode::Immediate | crate::ApplyMode::Discard => libc::TCSANOW,
+				};
 				unsafe {
-					check(libc::tcsetattr(file.as_raw_fd(), libc::TCSADRAIN, &self.termios))?;
+					check(libc::tcsetattr(file.as_raw_fd(), action, &self.termios))?;
 					Ok(())
 				}
 			}
@@ -114,15 +152,39 @@ cfg_if! {
 
 impl SerialPort {
 	pub fn open(path: &Path) -> std::io::Result<Self> {
+		Self::open_with_options(path, &crate::OpenOptions::new())
+	}
+
+	pub fn open_with_options(path: &Path, options: &crate::OpenOptions) -> std::io::Result<Self> {
 		use std::os::unix::fs::OpenOptionsExt;
+		let mut flags = options.custom_flags;
+		if options.no_ctty {
+			flags |= libc::O_NOCTTY;
+		}
+		if options.open_nonblocking {
+			flags |= libc::O_NONBLOCK;
+		}
 		let file = std::fs::OpenOptions::new()
-			.read(true)
-			.write(true)
+			.read(options.read)
+			.write(options.write)
 			.create(false)
-			.custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+			.custom_flags(flags)
 			.open(path)?;
 
-		Ok(Self::from_file(file))
+		// We need non-blocking I/O to implement timeouts, even if the device was opened in blocking mode.
+		if !options.open_nonblocking {
+			unsafe {
+				let flags = check(libc::fcntl(file.as_raw_fd(), libc::F_GETFL))?;
+				check(libc::fcntl(file.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+			}
+		}
+
+		let mut serial_port = Self::from_file(file);
+		serial_port.name = Some(path.to_owned());
+		if is_usb_gadget_path(path) {
+			serial_port.cdc_acm_quirks = AtomicBool::new(true);
+		}
+		Ok(serial_port)
 	}
 
 	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
@@ -131,6 +193,9 @@ impl SerialPort {
 		unsafe {
 			let pty_a = check(libc::posix_openpt(libc::O_RDWR | libc::O_CLOEXEC | libc::O_NOCTTY))?;
 			let pty_a = std::fs::File::from_raw_fd(pty_a);
+			// Use non-blocking I/O for the master side too, like for ports opened by path.
+			let flags = check(libc::fcntl(pty_a.as_raw_fd(), libc::F_GETFL))?;
+			check(libc::fcntl(pty_a.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK))?;
 			let pty_a = Self::from_file(pty_a);
 			let pty_b_name = pts_name(&pty_a)?;
 			check(libc::unlockpt(pty_a.file.as_raw_fd()))?;
@@ -140,19 +205,43 @@ impl SerialPort {
 		}
 	}
 
+	#[cfg(any(feature = "doc", all(unix, feature = "unix")))]
+	pub fn pts_name(&self) -> std::io::Result<std::path::PathBuf> {
+		pts_name(self)
+	}
+
 	pub fn from_file(file: std::fs::File) -> Self {
+		let nonblocking = is_nonblocking(&file);
 		Self {
 			file,
-			read_timeout_ms: super::DEFAULT_TIMEOUT_MS,
-			write_timeout_ms: super::DEFAULT_TIMEOUT_MS,
+			read_timeout_ms: AtomicU32::new(super::DEFAULT_TIMEOUT_MS),
+			inter_byte_timeout_ms: AtomicU32::new(NO_INTER_BYTE_TIMEOUT),
+			write_timeout_ms: AtomicU32::new(super::DEFAULT_TIMEOUT_MS),
+			config_lock: std::sync::Mutex::new(()),
+			abort: AbortSignal::new(),
+			name: None,
+			cdc_acm_quirks: AtomicBool::new(false),
+			read_filled_buffer: AtomicBool::new(false),
+			nonblocking,
+			verify_control_lines: AtomicBool::new(false),
+			apply_mode: AtomicU8::new(0),
 		}
 	}
 
 	pub fn try_clone(&self) -> std::io::Result<Self> {
 		Ok(Self {
 			file: self.file.try_clone()?,
-			read_timeout_ms: self.read_timeout_ms,
-			write_timeout_ms: self.write_timeout_ms,
+			read_timeout_ms: AtomicU32::new(self.read_timeout_ms.load(Ordering::Relaxed)),
+			inter_byte_timeout_ms: AtomicU32::new(self.inter_byte_timeout_ms.load(Ordering::Relaxed)),
+			write_timeout_ms: AtomicU32::new(self.write_timeout_ms.load(Ordering::Relaxed)),
+			config_lock: std::sync::Mutex::new(()),
+			abort: AbortSignal::new(),
+			name: self.name.clone(),
+			cdc_acm_quirks: AtomicBool::new(self.cdc_acm_quirks.load(Ordering::Relaxed)),
+			read_filled_buffer: AtomicBool::new(false),
+			nonblocking: self.nonblocking,
+			verify_control_lines: AtomicBool::new(self.verify_control_lines.load(Ordering::Relaxed)),
+			apply_mode: AtomicU8::new(self.apply_mode.load(Ordering::Relaxed)),
 		})
 	}
 
@@ -160,7 +249,55 @@ impl SerialPort {
 		Settings::get_from_file(&self.file)
 	}
 
-	pub fn set_configuration(&mut self, settings: &Settings) -> std::io::Result<()> {
+	pub fn set_configuration(&self, settings: &Settings) -> std::io::Result<()> {
+		// Applying and verifying the settings takes multiple syscalls,
+		// so don't let other threads change the settings in the mean time.
+		let _guard = self.config_lock.lock().unwrap_or_else(|e| e.into_inner());
+		self.set_configuration_locked(settings)
+	}
+
+	pub fn modify_configuration<F>(&self, modify: F) -> std::io::Result<()>
+	where
+		F: FnOnce(&mut Settings) -> std::io::Result<()>,
+	{
+		let _guard = self.config_lock.lock().unwrap_or_else(|e| e.into_inner());
+		let mut settings = self.get_configuration()?;
+		modify(&mut settings)?;
+		self.set_configuration_locked(&settings)
+	}
+
+	/// Apply and verify the settings.
+	///
+	/// If the settings can not be applied or verified, the previous settings are restored.
+	/// If the previous settings can not be read, or if the CDC-ACM quirks are enabled, nothing is restored.
+	///
+	/// The caller must hold the configuration lock.
+	fn set_configuration_locked(&self, settings: &Settings) -> std::io::Result<()> {
+		settings.check_supported()?;
+
+		// USB CDC-ACM gadgets may fail to report the settings, and the settings have no effect anyway.
+		let previous = if self.cdc_acm_quirks.load(Ordering::Relaxed) {
+			None
+		} else {
+			self.get_configuration().ok()
+		};
+		let mode = self.get_apply_mode();
+		let result = self
+			.write_configuration(settings, mode)
+			.and_then(|()| self.verify_configuration(settings));
+		if let (Err(_), Some(previous)) = (&result, &previous) {
+			// Report the original error, even if restoring the previous settings fails.
+			let _ = self.write_configuration(previous, crate::ApplyMode::Immediate);
+		}
+		result
+	}
+
+	/// Apply the settings without verifying them.
+	fn write_configuration(&self, settings: &Settings, mode: crate::ApplyMode) -> std::io::Result<()> {
+		if mode == crate::ApplyMode::Discard {
+			self.discard_buffers(true, true)?;
+		}
+
 		// On iOS and macOS we set the baud rate with the IOSSIOSPEED ioctl.
 		// But we also need to ensure the `set_on_file()` doesn't fail.
 		// So fill in a safe speed in the termios struct which we will override shortly after.
@@ -175,74 +312,186 @@ impl SerialPort {
 			}
 		}
 
-		apply_settings.set_on_file(&mut self.file)?;
+		apply_settings.set_on_file(&self.file, mode)?;
 
 		// On iOS and macOS, override the speed with the IOSSIOSPEED ioctl.
 		#[cfg(any(target_os = "ios", target_os = "macos"))]
 		ioctl_iossiospeed(self.file.as_raw_fd(), settings.termios.c_ospeed)?;
 
+		Ok(())
+	}
+
+	/// Check that the applied settings match the requested settings.
+	fn verify_configuration(&self, settings: &Settings) -> std::io::Result<()> {
+		// USB CDC-ACM gadgets accept any settings, but the settings have no effect.
+		// Some of them also fail to report the settings back, so don't verify them at all.
+		if self.cdc_acm_quirks.load(Ordering::Relaxed) {
+			return Ok(());
+		}
+
 		let applied_settings = self.get_configuration()?;
 		if !applied_settings.matches_requested(settings) {
-			Err(other_error("failed to apply some or all settings"))
+			Err(crate::error::MismatchError::new_io_error(
+				&crate::Settings { inner: applied_settings },
+				&crate::Settings { inner: settings.clone() },
+			))
 		} else {
 			Ok(())
 		}
 	}
 
-	pub fn set_read_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
-		self.read_timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
+	pub fn set_apply_mode(&self, mode: crate::ApplyMode) {
+		self.apply_mode.store(mode.as_u8(), Ordering::Relaxed);
+	}
+
+	pub fn get_apply_mode(&self) -> crate::ApplyMode {
+		crate::ApplyMode::from_u8(self.apply_mode.load(Ordering::Relaxed))
+	}
+
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		let timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
+		self.read_timeout_ms.store(timeout_ms, Ordering::Relaxed);
 		Ok(())
 	}
 
 	pub fn get_read_timeout(&self) -> std::io::Result<Duration> {
-		Ok(Duration::from_millis(self.read_timeout_ms.into()))
+		Ok(Duration::from_millis(self.read_timeout_ms.load(Ordering::Relaxed).into()))
 	}
 
-	pub fn set_write_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
-		self.write_timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
+	pub fn set_read_mode(&self, mode: crate::ReadMode) -> std::io::Result<()> {
+		let inter_byte_timeout_ms = match mode {
+			crate::ReadMode::FirstByte => NO_INTER_BYTE_TIMEOUT,
+			crate::ReadMode::FillBuffer { inter_byte_timeout } => {
+				let timeout_ms = inter_byte_timeout.as_millis().try_into().unwrap_or(u32::MAX);
+				timeout_ms.min(NO_INTER_BYTE_TIMEOUT - 1)
+			},
+		};
+		self.inter_byte_timeout_ms.store(inter_byte_timeout_ms, Ordering::Relaxed);
+		Ok(())
+	}
+
+	pub fn get_read_mode(&self) -> std::io::Result<crate::ReadMode> {
+		match self.inter_byte_timeout_ms.load(Ordering::Relaxed) {
+			NO_INTER_BYTE_TIMEOUT => Ok(crate::ReadMode::FirstByte),
+			timeout_ms => Ok(crate::ReadMode::FillBuffer {
+				inter_byte_timeout: Duration::from_millis(timeout_ms.into()),
+			}),
+		}
+	}
+
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		let timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
+		self.write_timeout_ms.store(timeout_ms, Ordering::Relaxed);
 		Ok(())
 	}
 
 	pub fn get_write_timeout(&self) -> std::io::Result<Duration> {
-		Ok(Duration::from_millis(self.write_timeout_ms.into()))
+		Ok(Duration::from_millis(self.write_timeout_ms.load(Ordering::Relaxed).into()))
+	}
+
+	pub fn save_timeouts(&self) -> SavedTimeouts {
+		SavedTimeouts {
+			read_timeout_ms: self.read_timeout_ms.load(Ordering::Relaxed),
+			inter_byte_timeout_ms: self.inter_byte_timeout_ms.load(Ordering::Relaxed),
+			write_timeout_ms: self.write_timeout_ms.load(Ordering::Relaxed),
+		}
+	}
+
+	pub fn restore_timeouts(&self, saved: &SavedTimeouts) -> std::io::Result<()> {
+		self.read_timeout_ms.store(saved.read_timeout_ms, Ordering::Relaxed);
+		self.inter_byte_timeout_ms.store(saved.inter_byte_timeout_ms, Ordering::Relaxed);
+		self.write_timeout_ms.store(saved.write_timeout_ms, Ordering::Relaxed);
+		Ok(())
 	}
 
 	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
-		if !poll(&self.file, libc::POLLIN, self.read_timeout_ms)? {
-			return Err(std::io::ErrorKind::TimedOut.into());
+		self.read_impl(buf, self.read_timeout_ms.load(Ordering::Relaxed))
+	}
+
+	/// Read with the configured read timeout, but wait no longer than `max_timeout`.
+	pub fn read_with_max_timeout(&self, buf: &mut [u8], max_timeout: Duration) -> std::io::Result<usize> {
+		let max_timeout_ms = max_timeout.as_millis().try_into().unwrap_or(u32::MAX);
+		let timeout_ms = self.read_timeout_ms.load(Ordering::Relaxed).min(max_timeout_ms);
+		self.read_impl(buf, timeout_ms)
+	}
+
+	/// Read according to the configured read mode, waiting at most `timeout_ms` in total.
+	fn read_impl(&self, buf: &mut [u8], timeout_ms: u32) -> std::io::Result<usize> {
+		let inter_byte_timeout_ms = self.inter_byte_timeout_ms.load(Ordering::Relaxed);
+		if inter_byte_timeout_ms == NO_INTER_BYTE_TIMEOUT {
+			return self.read_once(buf, timeout_ms);
 		}
-		unsafe {
-			loop {
-				let result = check_isize(libc::read(
-					self.file.as_raw_fd(),
-					buf.as_mut_ptr().cast(),
-					buf.len() as _,
-				));
-				match result {
-					Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => continue,
-					x => return x,
-				}
+
+		// The port is opened with O_NONBLOCK, so VMIN and VTIME are ignored by the kernel.
+		// Keep reading until the buffer is full or the line stays idle for the inter-byte timeout.
+		let deadline = Deadline::after_ms(timeout_ms);
+		let mut total = self.read_once(buf, timeout_ms)?;
+		while total > 0 && total < buf.len() {
+			let timeout_ms = match deadline.remaining_ms() {
+				-1 => inter_byte_timeout_ms,
+				remaining_ms => inter_byte_timeout_ms.min(remaining_ms as u32),
+			};
+			match self.read_once(&mut buf[total..], timeout_ms) {
+				Ok(0) => break,
+				Ok(n) => total += n,
+				// Return the data we already have.
+				// If the error persists, it will be reported by the next read.
+				Err(_) => break,
 			}
 		}
+		Ok(total)
+	}
+
+	/// Perform a single read, waiting at most `timeout_ms` for data.
+	fn read_once(&self, buf: &mut [u8], timeout_ms: u32) -> std::io::Result<usize> {
+		let try_first = self.should_read_before_poll(timeout_ms);
+		let result = self.poll_and_transfer(libc::POLLIN, timeout_ms, try_first, || unsafe {
+			check_isize(libc::read(
+				self.file.as_raw_fd(),
+				buf.as_mut_ptr().cast(),
+				buf.len() as _,
+			))
+		});
+		self.update_read_filled_buffer(&result, buf.len());
+		result
+	}
+
+	/// Check if a read should be attempted before waiting for the serial port to become readable.
+	///
+	/// If the previous read filled the whole buffer, more data is probably available already.
+	/// If the timeout is infinite, a failed read costs nothing compared to the time we may have to wait anyway.
+	/// In both cases, trying the read first saves a `poll()` call if data is available.
+	fn should_read_before_poll(&self, timeout_ms: u32) -> bool {
+		Deadline::is_infinite_ms(timeout_ms) || self.read_filled_buffer.load(Ordering::Relaxed)
+	}
+
+	/// Remember if a read filled the whole buffer, for [`Self::should_read_before_poll()`].
+	fn update_read_filled_buffer(&self, result: &std::io::Result<usize>, buffer_len: usize) {
+		let filled = matches!(*result, Ok(n) if n > 0 && n == buffer_len);
+		self.read_filled_buffer.store(filled, Ordering::Relaxed);
 	}
 
 	pub fn read_vectored(&self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
-		if !poll(&self.file, libc::POLLIN, self.read_timeout_ms)? {
-			return Err(std::io::ErrorKind::TimedOut.into());
-		}
-		unsafe {
-			loop {
-				let result = check_isize(libc::readv(
-					self.file.as_raw_fd(),
-					buf.as_mut_ptr().cast(),
-					buf.len() as _,
-				));
-				match result {
-					Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => continue,
-					x => return x,
-				}
-			}
+		// Filling multiple buffers with an inter-byte timeout is not worth the complexity,
+		// so only fill the first non-empty buffer in that case.
+		if self.inter_byte_timeout_ms.load(Ordering::Relaxed) != NO_INTER_BYTE_TIMEOUT {
+			return match buf.iter_mut().find(|buf| !buf.is_empty()) {
+				Some(buf) => self.read(buf),
+				None => self.read(&mut []),
+			};
 		}
+		let timeout_ms = self.read_timeout_ms.load(Ordering::Relaxed);
+		let try_first = self.should_read_before_poll(timeout_ms);
+		let buffer_len = buf.iter().map(|buf| buf.len()).sum();
+		let result = self.poll_and_transfer(libc::POLLIN, timeout_ms, try_first, || unsafe {
+			check_isize(libc::readv(
+				self.file.as_raw_fd(),
+				buf.as_mut_ptr().cast(),
+				buf.len() as _,
+			))
+		});
+		self.update_read_filled_buffer(&result, buffer_len);
+		result
 	}
 
 	pub fn is_read_vectored(&self) -> bool {
@@ -250,62 +499,172 @@ impl SerialPort {
 	}
 
 	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
-		if !poll(&self.file, libc::POLLOUT, self.write_timeout_ms)? {
-			return Err(std::io::ErrorKind::TimedOut.into());
+		self.poll_and_transfer(libc::POLLOUT, self.write_timeout_ms.load(Ordering::Relaxed), true, || unsafe {
+			check_isize(libc::write(self.file.as_raw_fd(), buf.as_ptr().cast(), buf.len() as _))
+		})
+	}
+
+	/// Write with the configured write timeout, but wait no longer than `max_timeout`.
+	pub fn write_with_max_timeout(&self, buf: &[u8], max_timeout: Duration) -> std::io::Result<usize> {
+		let max_timeout_ms = max_timeout.as_millis().try_into().unwrap_or(u32::MAX);
+		let timeout_ms = self.write_timeout_ms.load(Ordering::Relaxed).min(max_timeout_ms);
+		self.poll_and_transfer(libc::POLLOUT, timeout_ms, true, || unsafe {
+			check_isize(libc::write(self.file.as_raw_fd(), buf.as_ptr().cast(), buf.len() as _))
+		})
+	}
+
+	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		self.poll_and_transfer(libc::POLLOUT, self.write_timeout_ms.load(Ordering::Relaxed), true, || unsafe {
+			check_isize(libc::writev(self.file.as_raw_fd(), buf.as_ptr().cast(), buf.len() as _))
+		})
+	}
+
+	pub fn is_write_vectored(&self) -> bool {
+		true
+	}
+
+	pub fn flush_output(&self) -> std::io::Result<()> {
+		unsafe {
+			check(libc::tcdrain(self.file.as_raw_fd()))?;
+			Ok(())
 		}
+	}
+
+	pub fn abort_pending_io(&self) -> std::io::Result<()> {
+		self.abort.trigger()
+	}
+
+	pub fn is_connected(&self) -> std::io::Result<bool> {
+		// A disconnected device or a closed pseudo terminal is reported as hung up by poll().
 		unsafe {
+			let mut poll_fd = libc::pollfd {
+				fd: self.file.as_raw_fd(),
+				events: 0,
+				revents: 0,
+			};
 			loop {
-				let result = check_isize(libc::write(self.file.as_raw_fd(), buf.as_ptr().cast(), buf.len() as _));
-				match result {
+				match check(libc::poll(&mut poll_fd, 1, 0)) {
 					Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => continue,
-					x => return x,
+					Err(e) => return Err(e),
+					Ok(_) => break,
 				}
 			}
+			if poll_fd.revents & (libc::POLLHUP | libc::POLLERR | libc::POLLNVAL) != 0 {
+				return Ok(false);
+			}
 		}
-	}
 
-	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
-		if !poll(&self.file, libc::POLLOUT, self.write_timeout_ms)? {
-			return Err(std::io::ErrorKind::TimedOut.into());
+		// Not all platforms report a hang up, but the terminal attributes can not be read from a removed device.
+		match Settings::get_from_file(&self.file) {
+			Ok(_) => Ok(true),
+			Err(ref e) if e.raw_os_error().is_some_and(is_disconnect_os_error) => Ok(false),
+			Err(e) => Err(e),
 		}
-		unsafe {
-			loop {
-				let result = check_isize(libc::writev(self.file.as_raw_fd(), buf.as_ptr().cast(), buf.len() as _));
-				match result {
-					Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => continue,
-					x => return x,
-				}
+	}
+
+	pub fn supported_baud_rates(&self) -> std::io::Result<crate::SupportedBaudRates> {
+		cfg_if! {
+			if #[cfg(any(
+				target_os = "dragonfly",
+				target_os = "freebsd",
+				target_os = "ios",
+				target_os = "macos",
+				target_os = "netbsd",
+				target_os = "openbsd",
+				all(
+					any(target_os = "android", target_os = "linux"),
+					not(any(target_arch = "powerpc", target_arch = "powerpc64"))
+				),
+			))] {
+				// The baud rate is passed to the driver as a number, so we can not know what it supports.
+				Ok(crate::SupportedBaudRates::all())
+			} else {
+				let common_rates = crate::COMMON_BAUD_RATES
+					.iter()
+					.copied()
+					.filter(|&rate| BAUD_RATES.iter().any(|&(_, bits_per_second)| bits_per_second == rate))
+					.collect();
+				Ok(crate::SupportedBaudRates {
+					common_rates,
+					custom_rates: false,
+				})
 			}
 		}
 	}
 
-	pub fn is_write_vectored(&self) -> bool {
-		true
+	pub fn set_os_buffer_sizes(&self, _receive: u32, _transmit: u32) -> std::io::Result<()> {
+		// There is no API to change the size of the kernel buffers of a terminal.
+		Err(std::io::Error::new(
+			std::io::ErrorKind::Unsupported,
+			"changing the size of the OS buffers is not supported on this platform",
+		))
 	}
 
-	pub fn flush_output(&self) -> std::io::Result<()> {
-		unsafe {
-			check(libc::tcdrain(self.file.as_raw_fd()))?;
-			Ok(())
+	pub fn output_queue_len(&self) -> std::io::Result<usize> {
+		cfg_if! {
+			if #[cfg(any(
+				target_os = "android",
+				target_os = "dragonfly",
+				target_os = "freebsd",
+				target_os = "haiku",
+				target_os = "illumos",
+				target_os = "ios",
+				target_os = "linux",
+				target_os = "macos",
+				target_os = "solaris",
+			))] {
+				unsafe {
+					let mut queued: c_int = 0;
+					check(libc::ioctl(self.file.as_raw_fd(), libc::TIOCOUTQ as _, &mut queued))?;
+					Ok(queued as usize)
+				}
+			} else if #[cfg(any(target_os = "netbsd", target_os = "openbsd"))] {
+				// _IOR('t', 115, int), but libc doesn't expose it for these platforms.
+				const TIOCOUTQ: libc::c_ulong = 0x40047473;
+				unsafe {
+					let mut queued: c_int = 0;
+					check(libc::ioctl(self.file.as_raw_fd(), TIOCOUTQ as _, &mut queued))?;
+					Ok(queued as usize)
+				}
+			} else {
+				Err(other_error("querying the output queue is not supported on this platform"))
+			}
 		}
 	}
 
 	pub fn discard_buffers(&self, discard_input: bool, discard_output: bool) -> std::io::Result<()> {
+		// The queue selectors are not bit flags: `TCIFLUSH | TCOFLUSH` is not the same as `TCIOFLUSH`.
+		let queue = match (discard_input, discard_output) {
+			(true, true) => libc::TCIOFLUSH,
+			(true, false) => libc::TCIFLUSH,
+			(false, true) => libc::TCOFLUSH,
+			(false, false) => return Ok(()),
+		};
 		unsafe {
-			let mut flags = 0;
-			if discard_input {
-				flags |= libc::TCIFLUSH;
-			}
-			if discard_output {
-				flags |= libc::TCOFLUSH;
-			}
-			check(libc::tcflush(self.file.as_raw_fd(), flags))?;
+			check(libc::tcflush(self.file.as_raw_fd(), queue))?;
 			Ok(())
 		}
 	}
 
+	pub fn suspend_output(&self) -> std::io::Result<()> {
+		tcflow(&self.file, libc::TCOOFF)
+	}
+
+	pub fn resume_output(&self) -> std::io::Result<()> {
+		tcflow(&self.file, libc::TCOON)
+	}
+
+	pub fn send_xoff(&self) -> std::io::Result<()> {
+		tcflow(&self.file, libc::TCIOFF)
+	}
+
+	pub fn send_xon(&self) -> std::io::Result<()> {
+		tcflow(&self.file, libc::TCION)
+	}
+
 	pub fn set_rts(&self, state: bool) -> std::io::Result<()> {
-		set_pin(&self.file, libc::TIOCM_RTS, state)
+		set_pin(&self.file, libc::TIOCM_RTS, state)?;
+		self.verify_pin(libc::TIOCM_RTS, state, "RTS")
 	}
 
 	pub fn read_cts(&self) -> std::io::Result<bool> {
@@ -313,7 +672,50 @@ impl SerialPort {
 	}
 
 	pub fn set_dtr(&self, state: bool) -> std::io::Result<()> {
-		set_pin(&self.file, libc::TIOCM_DTR, state)
+		set_pin(&self.file, libc::TIOCM_DTR, state)?;
+		self.verify_pin(libc::TIOCM_DTR, state, "DTR")
+	}
+
+	/// Change the RTS and DTR lines at the same time, with a single `TIOCMSET` call.
+	pub fn set_rts_dtr(&self, rts: Option<bool>, dtr: Option<bool>) -> std::io::Result<()> {
+		unsafe {
+			let mut bits: c_int = 0;
+			check(libc::ioctl(self.file.as_raw_fd(), libc::TIOCMGET as _, &mut bits))?;
+			for (pin, state) in [(libc::TIOCM_RTS, rts), (libc::TIOCM_DTR, dtr)] {
+				match state {
+					Some(true) => bits |= pin,
+					Some(false) => bits &= !pin,
+					None => (),
+				}
+			}
+			check(libc::ioctl(self.file.as_raw_fd(), libc::TIOCMSET as _, &bits))?;
+		}
+		if let Some(rts) = rts {
+			self.verify_pin(libc::TIOCM_RTS, rts, "RTS")?;
+		}
+		if let Some(dtr) = dtr {
+			self.verify_pin(libc::TIOCM_DTR, dtr, "DTR")?;
+		}
+		Ok(())
+	}
+
+	pub fn set_verify_control_lines(&self, enable: bool) -> std::io::Result<()> {
+		self.verify_control_lines.store(enable, Ordering::Relaxed);
+		Ok(())
+	}
+
+	pub fn get_verify_control_lines(&self) -> bool {
+		self.verify_control_lines.load(Ordering::Relaxed)
+	}
+
+	/// Check that an output pin has the requested state, if control line verification is enabled.
+	fn verify_pin(&self, pin: c_int, state: bool, name: &str) -> std::io::Result<()> {
+		if !self.verify_control_lines.load(Ordering::Relaxed) || read_pin(&self.file, pin)? == state {
+			return Ok(());
+		}
+		Err(other_error(format!(
+			"the {name} line did not change to the requested state, the driver may not support controlling it"
+		)))
 	}
 
 	pub fn read_dsr(&self) -> std::io::Result<bool> {
@@ -327,18 +729,475 @@ impl SerialPort {
 	pub fn read_cd(&self) -> std::io::Result<bool> {
 		read_pin(&self.file, libc::TIOCM_CD)
 	}
+
+	pub fn ring_count(&self) -> std::io::Result<u32> {
+		cfg_if! {
+			if #[cfg(any(target_os = "linux", target_os = "android"))] {
+				get_ring_count(&self.file)
+			} else {
+				Err(std::io::Error::new(
+					std::io::ErrorKind::Unsupported,
+					"counting Ring Indicator pulses is not supported on this platform",
+				))
+			}
+		}
+	}
+
+	/// Wait for the Carrier Detect line to be asserted by polling its state.
+	pub fn wait_for_carrier(&self, timeout: Duration) -> std::io::Result<()> {
+		let deadline = Instant::now().checked_add(timeout);
+		loop {
+			if self.read_cd()? {
+				return Ok(());
+			}
+			let remaining = match deadline {
+				None => CARRIER_POLL_INTERVAL,
+				Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+			};
+			if remaining.is_zero() {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::TimedOut,
+					"timed out waiting for the Carrier Detect line",
+				));
+			}
+			std::thread::sleep(remaining.min(CARRIER_POLL_INTERVAL));
+		}
+	}
+
+	/// Wait for a Ring Indicator pulse by polling the ring counter of the driver.
+	///
+	/// If the platform has no ring counter, the state of the Ring Indicator line is polled instead,
+	/// and the function returns when the line is asserted after being deasserted.
+	pub fn wait_for_ring(&self, timeout: Duration) -> std::io::Result<()> {
+		let deadline = Instant::now().checked_add(timeout);
+		let mut previous = match self.ring_count() {
+			Ok(count) => RingState::Count(count),
+			Err(e) if e.kind() == std::io::ErrorKind::Unsupported => RingState::Line(self.read_ri()?),
+			Err(e) => return Err(e),
+		};
+		loop {
+			let remaining = match deadline {
+				None => RING_POLL_INTERVAL,
+				Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+			};
+			if remaining.is_zero() {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::TimedOut,
+					"timed out waiting for the Ring Indicator line",
+				));
+			}
+			std::thread::sleep(remaining.min(RING_POLL_INTERVAL));
+			let current = match previous {
+				RingState::Count(_) => RingState::Count(self.ring_count()?),
+				RingState::Line(_) => RingState::Line(self.read_ri()?),
+			};
+			match (previous, current) {
+				(RingState::Count(a), RingState::Count(b)) if a != b => return Ok(()),
+				(RingState::Line(false), RingState::Line(true)) => return Ok(()),
+				_ => previous = current,
+			}
+		}
+	}
+
+	pub fn set_break(&self, state: bool) -> std::io::Result<()> {
+		unsafe {
+			if state {
+				check(libc::ioctl(self.file.as_raw_fd(), libc::TIOCSBRK as _))?;
+			} else {
+				check(libc::ioctl(self.file.as_raw_fd(), libc::TIOCCBRK as _))?;
+			}
+			Ok(())
+		}
+	}
+
+	/// Wait for the serial port to be readable or writable and then perform a non-blocking transfer.
+	///
+	/// If the wait or the transfer is interrupted by a signal, the operation is retried.
+	/// The remaining timeout is computed from a fixed deadline,
+	/// so signals can not extend the total time spent waiting beyond the configured timeout.
+	///
+	/// If [`Self::abort_pending_io()`] is called while waiting, an error is returned.
+	///
+	/// If `try_first` is true, the transfer is attempted once before waiting,
+	/// which saves a `poll()` call if the transfer can be performed immediately.
+	/// Writes always try first, because the OS output buffer is rarely full.
+	/// This is skipped if the file is in blocking mode, because the transfer could block indefinitely.
+	fn poll_and_transfer<F>(
+		&self,
+		events: std::os::raw::c_short,
+		timeout_ms: u32,
+		try_first: bool,
+		mut transfer: F,
+	) -> std::io::Result<usize>
+	where
+		F: FnMut() -> std::io::Result<usize>,
+	{
+		let deadline = Deadline::after_ms(timeout_ms);
+		if try_first && self.nonblocking {
+			match transfer() {
+				Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => (),
+				Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => (),
+				x => return x.map_err(map_disconnect_error),
+			}
+		}
+		let abort = self.abort.pipe()?;
+		loop {
+			if !poll(&self.file, events, &abort, &deadline).map_err(map_disconnect_error)? {
+				return Err(std::io::ErrorKind::TimedOut.into());
+			}
+			match transfer() {
+				Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => continue,
+				// The file may be reported as ready even if the transfer would block.
+				// In that case, wait again for the remaining time.
+				Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+				x => return x.map_err(map_disconnect_error),
+			}
+		}
+	}
+}
+
+cfg_if! {
+	if #[cfg(any(
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "ios",
+		target_os = "macos",
+		target_os = "netbsd",
+		target_os = "openbsd",
+	))] {
+		// The `B*` constants are the baud rate itself on these platforms,
+		// so there is no table to convert between them, only a list of the defined constants.
+	} else {
+		/// The baud rates that have a `B*` constant on this platform.
+		pub const STANDARD_BAUD_RATES: [u32; BAUD_RATES.len()] = baud_rate_values(&BAUD_RATES);
+
+		/// Get the baud rates from a table of `B*` constants and baud rates.
+		const fn baud_rate_values<T, const N: usize>(table: &[(T, u32); N]) -> [u32; N] {
+			let mut values = [0; N];
+			let mut i = 0;
+			while i < N {
+				values[i] = table[i].1;
+				i += 1;
+			}
+			values
+		}
+	}
+}
+
+/// Get the `B*` speed constant for a baud rate.
+#[cfg(any(feature = "doc", feature = "unix"))]
+pub fn baud_rate_to_speed(baud_rate: u32) -> Option<libc::speed_t> {
+	cfg_if! {
+		if #[cfg(any(
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "ios",
+			target_os = "macos",
+			target_os = "netbsd",
+			target_os = "openbsd",
+		))] {
+			#[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)] // Not useless on all platforms.
+			baud_rate.try_into().ok()
+		} else {
+			#[allow(clippy::unnecessary_cast)] // Not unnecessary on all platforms.
+			BAUD_RATES.iter()
+				.find(|&&(_, bits_per_second)| bits_per_second == baud_rate)
+				.map(|&(constant, _)| constant as libc::speed_t)
+		}
+	}
+}
+
+/// Get the baud rate for a `B*` speed constant.
+#[cfg(any(feature = "doc", feature = "unix"))]
+pub fn speed_to_baud_rate(speed: libc::speed_t) -> Option<u32> {
+	cfg_if! {
+		if #[cfg(any(
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "ios",
+			target_os = "macos",
+			target_os = "netbsd",
+			target_os = "openbsd",
+		))] {
+			#[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)] // Not useless on all platforms.
+			speed.try_into().ok()
+		} else {
+			#[allow(clippy::unnecessary_cast)] // Not unnecessary on all platforms.
+			BAUD_RATES.iter()
+				.find(|&&(constant, _)| constant as libc::speed_t == speed)
+				.map(|&(_, bits_per_second)| bits_per_second)
+		}
+	}
+}
+
+/// Get a list of available serial ports, annotated with their type.
+///
+/// On Unix platforms, the type is derived from the name of the device.
+/// A stable identifier is only available on Linux and Android.
+pub fn enumerate_info() -> std::io::Result<Vec<crate::PortInfo>> {
+	let ports = enumerate()?
+		.into_iter()
+		.map(|path| {
+			let kind = port_kind_from_path(&path);
+			let mut info = crate::PortInfo::new(path, kind);
+			#[cfg(any(target_os = "linux", target_os = "android"))] {
+				info.id = port_id(&info.path);
+			}
+			info
+		})
+		.collect();
+	Ok(ports)
+}
+
+/// Get diagnostics for all candidates considered during port enumeration.
+///
+/// Only Linux and Android have detailed diagnostics, other platforms just report the available ports.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn enumerate_verbose() -> std::io::Result<Vec<crate::PortDiagnostic>> {
+	let ports = enumerate_info()?
+		.into_iter()
+		.map(|port| crate::PortDiagnostic::new(port.path, crate::PortStatus::Available(port.kind)))
+		.collect();
+	Ok(ports)
+}
+
+/// Call a function for each available serial port.
+///
+/// Only Linux and Android have an implementation that does not allocate, other platforms collect the ports first.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn for_each_port(f: &mut dyn FnMut(crate::PortInfoRef<'_>)) -> std::io::Result<()> {
+	for port in enumerate_info()? {
+		f(crate::PortInfoRef::new(&port.path, port.kind));
+	}
+	Ok(())
+}
+
+/// Guess the type of a serial port from the name of the device.
+fn port_kind_from_path(path: &Path) -> crate::PortKind {
+	use std::os::unix::ffi::OsStrExt;
+	let name = match path.file_name() {
+		Some(name) => name.as_bytes(),
+		None => return crate::PortKind::Unknown,
+	};
+	if name.starts_with(b"rfcomm") || name.starts_with(b"tty.Bluetooth") || name.starts_with(b"cu.Bluetooth") {
+		crate::PortKind::Bluetooth
+	} else {
+		crate::PortKind::Unknown
+	}
+}
+
+/// The value of [`SerialPort::inter_byte_timeout_ms`] for [`crate::ReadMode::FirstByte`].
+const NO_INTER_BYTE_TIMEOUT: u32 = u32::MAX;
+
+/// The timeouts and read mode of a serial port, saved by [`SerialPort::save_timeouts()`].
+#[derive(Debug, Clone)]
+pub struct SavedTimeouts {
+	read_timeout_ms: u32,
+	inter_byte_timeout_ms: u32,
+	write_timeout_ms: u32,
+}
+
+/// The flag for mark and space parity (`CMSPAR`), or `0` if the platform does not support it.
+///
+/// With this flag, the parity bit is always set if `PARODD` is set, or always cleared otherwise.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const STICK_PARITY: libc::tcflag_t = libc::CMSPAR;
+
+/// The flag for mark and space parity (`CMSPAR`), or `0` if the platform does not support it.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+const STICK_PARITY: libc::tcflag_t = 0;
+
+/// The interval for polling the Carrier Detect line in [`SerialPort::wait_for_carrier()`].
+const CARRIER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The interval for polling the ring counter or the Ring Indicator line in [`SerialPort::wait_for_ring()`].
+const RING_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The last observed ring state for [`SerialPort::wait_for_ring()`].
+#[derive(Copy, Clone)]
+enum RingState {
+	/// The ring counter of the driver.
+	Count(u32),
+
+	/// The state of the Ring Indicator line, if the platform has no ring counter.
+	Line(bool),
+}
+
+/// A deadline for a timeout, or no deadline at all if the timeout is infinite.
+struct Deadline {
+	deadline: Option<std::time::Instant>,
+}
+
+impl Deadline {
+	/// Create a deadline `timeout_ms` milliseconds from now.
+	///
+	/// Timeouts that do not fit in a signed 32 bit integer are treated as infinite, like `poll()` does.
+	fn after_ms(timeout_ms: u32) -> Self {
+		let deadline = match i32::try_from(timeout_ms) {
+			Ok(timeout_ms) => Some(std::time::Instant::now() + Duration::from_millis(timeout_ms as u64)),
+			Err(_) => None,
+		};
+		Self { deadline }
+	}
+
+	/// Check if a timeout in milliseconds is treated as infinite.
+	fn is_infinite_ms(timeout_ms: u32) -> bool {
+		i32::try_from(timeout_ms).is_err()
+	}
+
+	/// Get the remaining time until the deadline in milliseconds, or -1 if there is no deadline.
+	///
+	/// The remaining time is rounded up, so we never wake up before the deadline.
+	fn remaining_ms(&self) -> i32 {
+		let deadline = match self.deadline {
+			Some(x) => x,
+			None => return -1,
+		};
+		let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+		let remaining_ms = remaining.as_nanos().div_ceil(1_000_000);
+		remaining_ms.try_into().unwrap_or(i32::MAX)
+	}
 }
 
 /// Wait for a file to be readable or writable.
-fn poll(file: &std::fs::File, events: std::os::raw::c_short, timeout_ms: u32) -> std::io::Result<bool> {
+///
+/// If the wait is interrupted by a signal, it is resumed with the time remaining until the deadline.
+/// If the `abort` pipe becomes readable, an error is returned.
+fn poll(
+	file: &std::fs::File,
+	events: std::os::raw::c_short,
+	abort: &AbortPipe,
+	deadline: &Deadline,
+) -> std::io::Result<bool> {
 	unsafe {
-		let mut poll_fd = libc::pollfd {
-			fd: file.as_raw_fd(),
-			events,
-			revents: 0,
-		};
-		check(libc::poll(&mut poll_fd, 1, timeout_ms as i32))?;
-		Ok(poll_fd.revents != 0)
+		let mut poll_fds = [
+			libc::pollfd {
+				fd: file.as_raw_fd(),
+				events,
+				revents: 0,
+			},
+			libc::pollfd {
+				fd: abort.read.as_raw_fd(),
+				events: libc::POLLIN,
+				revents: 0,
+			},
+		];
+		loop {
+			match check(libc::poll(poll_fds.as_mut_ptr(), 2, deadline.remaining_ms())) {
+				Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => continue,
+				Err(e) => return Err(e),
+				Ok(_) => {
+					if poll_fds[1].revents != 0 {
+						return Err(aborted_error());
+					}
+					return Ok(poll_fds[0].revents != 0);
+				},
+			}
+		}
+	}
+}
+
+/// A signal to abort pending I/O operations.
+///
+/// Pending operations wait for the read end of a pipe to become readable, in addition to the serial port itself.
+/// Triggering the signal writes to the pipe, which wakes up all operations waiting for it.
+///
+/// Once triggered, the pipe remains readable forever, so the signal replaces the pipe with a fresh one.
+/// Operations that started before the signal was triggered keep a reference to the old pipe.
+pub struct AbortSignal {
+	pipe: std::sync::Mutex<Option<std::sync::Arc<AbortPipe>>>,
+}
+
+/// A pipe used to wake up pending I/O operations.
+struct AbortPipe {
+	read: std::fs::File,
+	write: std::fs::File,
+}
+
+impl AbortSignal {
+	fn new() -> Self {
+		Self {
+			pipe: std::sync::Mutex::new(None),
+		}
+	}
+
+	/// Get the current pipe, creating it if necessary.
+	fn pipe(&self) -> std::io::Result<std::sync::Arc<AbortPipe>> {
+		let mut pipe = self.pipe.lock().unwrap_or_else(|e| e.into_inner());
+		if let Some(pipe) = &*pipe {
+			return Ok(pipe.clone());
+		}
+		let new_pipe = std::sync::Arc::new(AbortPipe::new()?);
+		*pipe = Some(new_pipe.clone());
+		Ok(new_pipe)
+	}
+
+	/// Abort all operations that are waiting for the current pipe.
+	fn trigger(&self) -> std::io::Result<()> {
+		// Take the pipe out so that new operations will create a fresh one.
+		// If there is no pipe, no operations can be pending.
+		let pipe = self.pipe.lock().unwrap_or_else(|e| e.into_inner()).take();
+		if let Some(pipe) = pipe {
+			use std::io::Write;
+			(&pipe.write).write_all(&[0])?;
+		}
+		Ok(())
+	}
+}
+
+impl AbortPipe {
+	fn new() -> std::io::Result<Self> {
+		use std::os::unix::io::FromRawFd;
+		unsafe {
+			let mut fds = [0; 2];
+			check(libc::pipe(fds.as_mut_ptr()))?;
+			let read = std::fs::File::from_raw_fd(fds[0]);
+			let write = std::fs::File::from_raw_fd(fds[1]);
+			check(libc::fcntl(read.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC))?;
+			check(libc::fcntl(write.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC))?;
+			Ok(Self { read, write })
+		}
+	}
+}
+
+/// Check if an error code indicates that the device was disconnected.
+pub fn is_disconnect_os_error(code: i32) -> bool {
+	// USB serial adapters report EIO or ENXIO when unplugged, depending on the platform and driver.
+	// The master side of a pseudo terminal reports EIO when the slave side is closed.
+	code == libc::EIO || code == libc::ENXIO || code == libc::ENODEV
+}
+
+/// Convert errors that indicate a disconnected device to [`std::io::ErrorKind::NotConnected`].
+///
+/// The original error is kept as inner error.
+fn map_disconnect_error(error: std::io::Error) -> std::io::Error {
+	match error.raw_os_error() {
+		Some(code) if is_disconnect_os_error(code) => std::io::Error::new(std::io::ErrorKind::NotConnected, error),
+		_ => error,
+	}
+}
+
+/// Check if a path refers to the serial port of a Linux USB gadget (`/dev/ttyGS*`).
+fn is_usb_gadget_path(path: &Path) -> bool {
+	if !cfg!(any(target_os = "linux", target_os = "android")) {
+		return false;
+	}
+	// Resolve symlinks like the ones created by udev, but fall back to the original path.
+	let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+	path.file_name()
+		.and_then(|name| name.to_str())
+		.is_some_and(|name| name.starts_with("ttyGS"))
+}
+
+/// Create the error returned for aborted I/O operations.
+fn aborted_error() -> std::io::Error {
+	other_error("I/O operation aborted")
+}
+
+fn tcflow(file: &std::fs::File, action: c_int) -> std::io::Result<()> {
+	unsafe {
+		check(libc::tcflow(file.as_raw_fd(), action))?;
+		Ok(())
 	}
 }
 
@@ -361,6 +1220,14 @@ fn read_pin(file: &std::fs::File, pin: c_int) -> std::io::Result<bool> {
 	}
 }
 
+/// Check if a file is in non-blocking mode.
+fn is_nonblocking(file: &std::fs::File) -> bool {
+	unsafe {
+		let flags = libc::fcntl(file.as_raw_fd(), libc::F_GETFL);
+		flags != -1 && flags & libc::O_NONBLOCK != 0
+	}
+}
+
 /// Check the return value of a syscall for errors.
 fn check(ret: i32) -> std::io::Result<i32> {
 	if ret == -1 {
@@ -384,7 +1251,7 @@ fn other_error<E>(msg: E) -> std::io::Error
 where
 	E: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-	std::io::Error::new(std::io::ErrorKind::Other, msg)
+	std::io::Error::other(msg)
 }
 
 #[cfg(any(doc, feature = "doc", all(unix, feature = "unix")))]
@@ -394,6 +1261,7 @@ fn pts_name(master: &SerialPort) -> std::io::Result<std::path::PathBuf> {
 
 	cfg_if! {
 		if #[cfg(any(
+				target_os = "haiku",
 				target_os = "ios",
 				target_os = "macos",
 				target_os = "netbsd",
@@ -561,17 +1429,34 @@ impl Settings {
 	}
 
 	pub fn set_parity(&mut self, parity: crate::Parity) {
-		match parity {
-			crate::Parity::None => self.termios.c_cflag = self.termios.c_cflag & !libc::PARODD & !libc::PARENB,
-			crate::Parity::Even => self.termios.c_cflag = self.termios.c_cflag & !libc::PARODD | libc::PARENB,
-			crate::Parity::Odd => self.termios.c_cflag = self.termios.c_cflag | libc::PARODD | libc::PARENB,
+		self.unsupported_parity = None;
+		let cflag = self.termios.c_cflag & !STICK_PARITY;
+		self.termios.c_cflag = match parity {
+			crate::Parity::None => cflag & !libc::PARODD & !libc::PARENB,
+			crate::Parity::Even => cflag & !libc::PARODD | libc::PARENB,
+			crate::Parity::Odd => cflag | libc::PARODD | libc::PARENB,
+			// Without `CMSPAR`, mark and space parity can not be configured.
+			// Remember the request, so that applying the settings fails instead of silently using the old parity.
+			crate::Parity::Mark | crate::Parity::Space if STICK_PARITY == 0 => {
+				self.unsupported_parity = Some(parity);
+				return;
+			},
+			crate::Parity::Mark => cflag | STICK_PARITY | libc::PARODD | libc::PARENB,
+			crate::Parity::Space => cflag & !libc::PARODD | STICK_PARITY | libc::PARENB,
 		};
 	}
 
 	pub fn get_parity(&self) -> std::io::Result<crate::Parity> {
-		if self.termios.c_cflag & libc::PARENB == 0 {
+		let cflag = self.termios.c_cflag;
+		if cflag & libc::PARENB == 0 {
 			Ok(crate::Parity::None)
-		} else if self.termios.c_cflag & libc::PARODD != 0 {
+		} else if STICK_PARITY != 0 && cflag & STICK_PARITY != 0 {
+			if cflag & libc::PARODD != 0 {
+				Ok(crate::Parity::Mark)
+			} else {
+				Ok(crate::Parity::Space)
+			}
+		} else if cflag & libc::PARODD != 0 {
 			Ok(crate::Parity::Odd)
 		} else {
 			Ok(crate::Parity::Even)
@@ -610,6 +1495,101 @@ impl Settings {
 			Err(other_error("unknown flow control configuration"))
 		}
 	}
+
+	pub fn set_ignore_cr(&mut self, enable: bool) -> std::io::Result<()> {
+		self.set_input_flag(libc::IGNCR, enable);
+		Ok(())
+	}
+
+	pub fn get_ignore_cr(&self) -> bool {
+		self.termios.c_iflag & libc::IGNCR != 0
+	}
+
+	pub fn set_map_nl_to_cr(&mut self, enable: bool) -> std::io::Result<()> {
+		self.set_input_flag(libc::INLCR, enable);
+		Ok(())
+	}
+
+	pub fn get_map_nl_to_cr(&self) -> bool {
+		self.termios.c_iflag & libc::INLCR != 0
+	}
+
+	pub fn set_strip_high_bit(&mut self, enable: bool) -> std::io::Result<()> {
+		self.set_input_flag(libc::ISTRIP, enable);
+		Ok(())
+	}
+
+	pub fn get_strip_high_bit(&self) -> bool {
+		self.termios.c_iflag & libc::ISTRIP != 0
+	}
+
+	pub fn set_newline_mode(&mut self, mode: crate::NewlineMode) -> std::io::Result<()> {
+		self.set_input_flag(libc::ICRNL, mode.translates_input());
+		if mode.translates_output() {
+			self.termios.c_oflag |= libc::OPOST | libc::ONLCR;
+		} else {
+			self.termios.c_oflag &= !libc::ONLCR;
+		}
+		Ok(())
+	}
+
+	pub fn get_newline_mode(&self) -> crate::NewlineMode {
+		let input = self.termios.c_iflag & libc::ICRNL != 0;
+		let output = self.termios.c_oflag & libc::OPOST != 0 && self.termios.c_oflag & libc::ONLCR != 0;
+		crate::NewlineMode::from_directions(input, output)
+	}
+
+	pub fn set_discard_null(&mut self, enable: bool) -> std::io::Result<()> {
+		crate::settings::unsupported_option(enable, "discarding null bytes")
+	}
+
+	pub fn get_discard_null(&self) -> bool {
+		false
+	}
+
+	fn set_input_flag(&mut self, flag: libc::tcflag_t, enable: bool) {
+		if enable {
+			self.termios.c_iflag |= flag;
+		} else {
+			self.termios.c_iflag &= !flag;
+		}
+	}
+
+	pub fn validate(&self) -> std::io::Result<()> {
+		self.check_supported()?;
+		let software_flow = self.termios.c_iflag & (libc::IXON | libc::IXOFF) != 0;
+		let hardware_flow = self.termios.c_cflag & libc::CRTSCTS != 0;
+		if software_flow && hardware_flow {
+			return Err(invalid_settings(
+				"hardware (RTS/CTS) and software (XON/XOFF) flow control can not be enabled at the same time",
+			));
+		}
+		if let Err(e) = self.get_baud_rate() {
+			return Err(invalid_settings(format!("invalid baud rate: {e}")));
+		}
+		Ok(())
+	}
+}
+
+impl Settings {
+	/// Check that the settings do not contain options that were requested but can not be configured on this platform.
+	fn check_supported(&self) -> std::io::Result<()> {
+		match self.unsupported_parity {
+			Some(parity) => Err(std::io::Error::new(
+				std::io::ErrorKind::Unsupported,
+				format!("{} parity is not supported on this platform", parity.as_str()),
+			)),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Create an error for invalid settings, as reported by [`Settings::validate()`].
+fn invalid_settings<E>(msg: E) -> std::io::Error
+where
+	E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+	std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
 }
 
 impl Settings {
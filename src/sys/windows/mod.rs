@@ -1,7 +1,8 @@
-use std::ffi::{CStr, OsString};
+use std::ffi::{CStr, CString, OsString};
 use std::io::{IoSlice, IoSliceMut};
 use std::os::windows::io::{AsRawHandle, RawHandle};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::time::Duration;
 
 use winapi::shared::minwindef::{BOOL, HKEY};
@@ -10,11 +11,21 @@ use winapi::um::{commapi, fileapi, handleapi, ioapiset, minwinbase, synchapi, wi
 
 pub struct SerialPort {
 	pub file: std::fs::File,
+	is_comm_device: bool,
+	/// The timeouts, shared with clones because the timeouts are a property of the device.
+	timeouts: std::sync::Arc<std::sync::Mutex<Timeouts>>,
+	config_lock: std::sync::Mutex<()>,
+	read_overlapped: OverlappedCache,
+	write_overlapped: OverlappedCache,
+	pub name: Option<PathBuf>,
+	/// The [`crate::ApplyMode`] for new settings, stored as `u8`.
+	apply_mode: AtomicU8,
 }
 
 impl std::fmt::Debug for SerialPort {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_struct("SerialPort")
+			.field("name", &self.name)
 			.field("handle", &self.file.as_raw_handle())
 			.finish()
 	}
@@ -27,44 +38,114 @@ pub struct Settings {
 
 impl SerialPort {
 	pub fn open(name: &Path) -> std::io::Result<Self> {
+		Self::open_with_options(name, &crate::OpenOptions::new())
+	}
+
+	pub fn open_with_options(name: &Path, options: &crate::OpenOptions) -> std::io::Result<Self> {
 		use std::os::windows::fs::OpenOptionsExt;
 
-		// Use the win32 device namespace, otherwise we're limited to COM1-9.
-		// This also works with higher numbers.
-		// https://docs.microsoft.com/en-us/windows/win32/fileio/naming-a-file#win32-device-namespaces
-		let mut path = OsString::from("\\\\.\\");
-		path.push(name.as_os_str());
+		let path = device_path(name);
+		let mut open_options = std::fs::OpenOptions::new();
+		open_options
+			.read(options.read)
+			.write(options.write)
+			.create(false)
+			.attributes(options.attributes)
+			.custom_flags(winbase::FILE_FLAG_OVERLAPPED);
+		if let Some(share_mode) = options.share_mode {
+			open_options.share_mode(share_mode);
+		}
+		let file = open_options.open(path)?;
+
+		let mut serial_port = Self::from_file(file);
+		serial_port.name = Some(name.to_owned());
+		let default_timeout = Duration::from_millis(super::DEFAULT_TIMEOUT_MS.into());
+		serial_port.set_read_timeout(default_timeout)?;
+		serial_port.set_write_timeout(default_timeout)?;
+		Ok(serial_port)
+	}
+
+	#[cfg(any(feature = "doc", feature = "windows"))]
+	pub fn pair() -> std::io::Result<(Self, Self)> {
+		use std::os::windows::fs::OpenOptionsExt;
+		use std::os::windows::io::FromRawHandle;
+		use std::sync::atomic::AtomicU32;
+
+		// Named pipes need a unique name, so combine the process ID with a counter.
+		static COUNTER: AtomicU32 = AtomicU32::new(0);
+		let name = format!(
+			"\\\\.\\pipe\\serial2-pair-{}-{}",
+			std::process::id(),
+			COUNTER.fetch_add(1, Ordering::Relaxed),
+		);
+		let c_name = std::ffi::CString::new(name.as_str()).unwrap();
+
+		let server = unsafe {
+			let handle = winbase::CreateNamedPipeA(
+				c_name.as_ptr(),
+				winbase::PIPE_ACCESS_DUPLEX | winbase::FILE_FLAG_OVERLAPPED | winbase::FILE_FLAG_FIRST_PIPE_INSTANCE,
+				winbase::PIPE_TYPE_BYTE | winbase::PIPE_READMODE_BYTE | winbase::PIPE_WAIT | winbase::PIPE_REJECT_REMOTE_CLIENTS,
+				1,
+				4096,
+				4096,
+				0,
+				std::ptr::null_mut(),
+			);
+			if handle == handleapi::INVALID_HANDLE_VALUE {
+				return Err(std::io::Error::last_os_error());
+			}
+			std::fs::File::from_raw_handle(handle)
+		};
 
-		let file = std::fs::OpenOptions::new()
+		// There is no need to call `ConnectNamedPipe()`:
+		// the connection is established as soon as the client opens the pipe.
+		let client = std::fs::OpenOptions::new()
 			.read(true)
 			.write(true)
 			.create(false)
 			.custom_flags(winbase::FILE_FLAG_OVERLAPPED)
-			.open(path)?;
-
-		unsafe {
-			let mut timeouts: winbase::COMMTIMEOUTS = std::mem::zeroed();
-			check_bool(commapi::GetCommTimeouts(file.as_raw_handle(), &mut timeouts))?;
-			// Mimic POSIX behaviour for reads.
-			// For more details, see:
-			// https://learn.microsoft.com/en-us/windows/win32/api/winbase/ns-winbase-commtimeouts#remarks
-			timeouts.ReadIntervalTimeout = u32::MAX;
-			timeouts.ReadTotalTimeoutMultiplier = u32::MAX;
-			timeouts.ReadTotalTimeoutConstant = super::DEFAULT_TIMEOUT_MS;
-			timeouts.WriteTotalTimeoutMultiplier = 0;
-			timeouts.WriteTotalTimeoutConstant = super::DEFAULT_TIMEOUT_MS;
-			check_bool(commapi::SetCommTimeouts(file.as_raw_handle(), &mut timeouts))?;
+			.open(name)?;
+
+		let server = Self::from_file(server);
+		let client = Self::from_file(client);
+		let default_timeout = Duration::from_millis(super::DEFAULT_TIMEOUT_MS.into());
+		for pipe in [&server, &client] {
+			pipe.set_read_timeout(default_timeout)?;
+			pipe.set_write_timeout(default_timeout)?;
 		}
-		Ok(Self::from_file(file))
+		Ok((server, client))
 	}
 
 	pub fn from_file(file: std::fs::File) -> Self {
-		Self { file }
+		// We don't know what timeouts are configured on the handle, so query them.
+		// If that fails, the handle is not a serial port (for example, it could be a named pipe),
+		// so we have to enforce the timeouts ourselves.
+		let mut timeouts: winbase::COMMTIMEOUTS = unsafe { std::mem::zeroed() };
+		let is_comm_device = unsafe { commapi::GetCommTimeouts(file.as_raw_handle(), &mut timeouts) != 0 };
+		Self {
+			file,
+			is_comm_device,
+			timeouts: std::sync::Arc::new(std::sync::Mutex::new(Timeouts::from_comm_timeouts(timeouts))),
+			config_lock: std::sync::Mutex::new(()),
+			read_overlapped: OverlappedCache::new(),
+			write_overlapped: OverlappedCache::new(),
+			name: None,
+			apply_mode: AtomicU8::new(0),
+		}
 	}
 
 	pub fn try_clone(&self) -> std::io::Result<Self> {
 		Ok(Self {
 			file: self.file.try_clone()?,
+			is_comm_device: self.is_comm_device,
+			// The timeouts configured on the device apply to all handles, so share our record of them too.
+			// Otherwise, the timeouts would be lost for handles that are not a serial port, like named pipes.
+			timeouts: self.timeouts.clone(),
+			config_lock: std::sync::Mutex::new(()),
+			read_overlapped: OverlappedCache::new(),
+			write_overlapped: OverlappedCache::new(),
+			name: self.name.clone(),
+			apply_mode: AtomicU8::new(self.apply_mode.load(Ordering::Relaxed)),
 		})
 	}
 
@@ -76,116 +157,276 @@ impl SerialPort {
 		}
 	}
 
-	pub fn set_configuration(&mut self, settings: &Settings) -> std::io::Result<()> {
+	pub fn set_configuration(&self, settings: &Settings) -> std::io::Result<()> {
+		let _guard = self.config_lock.lock().unwrap_or_else(|e| e.into_inner());
+		self.set_configuration_locked(settings)
+	}
+
+	pub fn modify_configuration<F>(&self, modify: F) -> std::io::Result<()>
+	where
+		F: FnOnce(&mut Settings) -> std::io::Result<()>,
+	{
+		// Don't let other threads change the settings between reading and applying them.
+		let _guard = self.config_lock.lock().unwrap_or_else(|e| e.into_inner());
+		let mut settings = self.get_configuration()?;
+		modify(&mut settings)?;
+		self.set_configuration_locked(&settings)
+	}
+
+	/// Apply and verify the settings.
+	///
+	/// If the driver did not apply the settings as requested, the previous settings are restored.
+	///
+	/// The caller must hold the configuration lock.
+	fn set_configuration_locked(&self, settings: &Settings) -> std::io::Result<()> {
+		let previous = self.get_configuration()?;
+
+		// `SetCommState()` applies the settings immediately, so drain or discard the buffers ourselves.
+		let mode = self.get_apply_mode();
+		match mode {
+			crate::ApplyMode::Drain | crate::ApplyMode::DrainAndDiscardInput => self.flush_output()?,
+			crate::ApplyMode::Discard => self.discard_buffers(true, true)?,
+			crate::ApplyMode::Immediate => (),
+		}
+
+		// `SetCommState()` either applies all settings or none, so there is nothing to restore if it fails.
+		self.write_configuration(settings)?;
+		if let Err(e) = self.verify_configuration(settings) {
+			// Report the original error, even if restoring the previous settings fails.
+			let _ = self.write_configuration(&previous);
+			return Err(e);
+		}
+
+		if mode == crate::ApplyMode::DrainAndDiscardInput {
+			self.discard_buffers(true, false)?;
+		}
+		Ok(())
+	}
+
+	/// Apply the settings without verifying them.
+	fn write_configuration(&self, settings: &Settings) -> std::io::Result<()> {
 		unsafe {
 			let mut settings = settings.clone();
 			check_bool(commapi::SetCommState(self.file.as_raw_handle(), &mut settings.dcb))
 		}
 	}
 
-	pub fn set_read_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
-		unsafe {
-			let mut timeouts = std::mem::zeroed();
-			// Mimic POSIX behaviour for reads.
-			// Timeout must be > 0 and < u32::MAX, so clamp it.
-			// For more details, see:
-			// https://learn.microsoft.com/en-us/windows/win32/api/winbase/ns-winbase-commtimeouts#remarks
-			let timeout_ms = timeout
-				.as_millis()
-				.try_into()
-				.unwrap_or(u32::MAX)
-				.clamp(1, u32::MAX - 1);
-			check_bool(commapi::GetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))?;
-			timeouts.ReadIntervalTimeout = u32::MAX;
-			timeouts.ReadTotalTimeoutMultiplier = u32::MAX;
-			timeouts.ReadTotalTimeoutConstant = timeout_ms;
-			check_bool(commapi::SetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))
+	/// Check that the applied settings match the requested settings.
+	///
+	/// Some USB serial drivers accept any settings, but silently clamp the baud rate to what the device supports.
+	fn verify_configuration(&self, settings: &Settings) -> std::io::Result<()> {
+		let applied_settings = self.get_configuration()?;
+		if !applied_settings.matches_requested(settings) {
+			Err(crate::error::MismatchError::new_io_error(
+				&crate::Settings { inner: applied_settings },
+				&crate::Settings { inner: settings.clone() },
+			))
+		} else {
+			Ok(())
 		}
 	}
 
+	pub fn set_apply_mode(&self, mode: crate::ApplyMode) {
+		self.apply_mode.store(mode.as_u8(), Ordering::Relaxed);
+	}
+
+	pub fn get_apply_mode(&self) -> crate::ApplyMode {
+		crate::ApplyMode::from_u8(self.apply_mode.load(Ordering::Relaxed))
+	}
+
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		let mut timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+		let read_mode = timeouts.read_mode;
+		self.apply_read_timeouts(&mut timeouts, timeout, read_mode)
+	}
+
 	pub fn get_read_timeout(&self) -> std::io::Result<Duration> {
-		unsafe {
-			let mut timeouts = std::mem::zeroed();
-			check_bool(commapi::GetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))?;
-			Ok(Duration::from_millis(timeouts.ReadTotalTimeoutConstant.into()))
+		Ok(self.timeouts.lock().unwrap_or_else(|e| e.into_inner()).read)
+	}
+
+	pub fn set_read_mode(&self, mode: crate::ReadMode) -> std::io::Result<()> {
+		let mut timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+		let timeout = timeouts.read;
+		self.apply_read_timeouts(&mut timeouts, timeout, mode)
+	}
+
+	pub fn get_read_mode(&self) -> std::io::Result<crate::ReadMode> {
+		Ok(self.timeouts.lock().unwrap_or_else(|e| e.into_inner()).read_mode)
+	}
+
+	/// Configure the read timeouts of the handle for the given read timeout and read mode.
+	fn apply_read_timeouts(
+		&self,
+		timeouts: &mut Timeouts,
+		timeout: Duration,
+		mode: crate::ReadMode,
+	) -> std::io::Result<()> {
+		// Timeout must be > 0 and < u32::MAX, so clamp it.
+		// For more details, see:
+		// https://learn.microsoft.com/en-us/windows/win32/api/winbase/ns-winbase-commtimeouts#remarks
+		let timeout_ms = timeout
+			.as_millis()
+			.try_into()
+			.unwrap_or(u32::MAX)
+			.clamp(1, u32::MAX - 1);
+		let mut comm = timeouts.comm;
+		match mode {
+			// Mimic POSIX behaviour for reads: return as soon as any data is available.
+			crate::ReadMode::FirstByte => {
+				comm.ReadIntervalTimeout = u32::MAX;
+				comm.ReadTotalTimeoutMultiplier = u32::MAX;
+			},
+			// Let the driver fill the buffer until the time between two bytes exceeds the interval timeout.
+			// Zero and u32::MAX have a special meaning for the interval timeout, so clamp it.
+			crate::ReadMode::FillBuffer { inter_byte_timeout } => {
+				comm.ReadIntervalTimeout = inter_byte_timeout
+					.as_millis()
+					.try_into()
+					.unwrap_or(u32::MAX)
+					.clamp(1, u32::MAX - 1);
+				comm.ReadTotalTimeoutMultiplier = 0;
+			},
 		}
+		comm.ReadTotalTimeoutConstant = timeout_ms;
+		if self.is_comm_device {
+			timeouts.apply(&self.file, comm)?;
+		}
+		timeouts.read = timeout;
+		timeouts.read_mode = mode;
+		Ok(())
 	}
 
-	pub fn set_write_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
-		unsafe {
-			let mut timeouts = std::mem::zeroed();
-			let timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
-			check_bool(commapi::GetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))?;
-			timeouts.WriteTotalTimeoutMultiplier = 0;
-			timeouts.WriteTotalTimeoutConstant = timeout_ms;
-			check_bool(commapi::SetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		let timeout_ms = timeout.as_millis().try_into().unwrap_or(u32::MAX);
+		let mut timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+		let mut comm = timeouts.comm;
+		comm.WriteTotalTimeoutMultiplier = 0;
+		comm.WriteTotalTimeoutConstant = timeout_ms;
+		if self.is_comm_device {
+			timeouts.apply(&self.file, comm)?;
 		}
+		timeouts.write = timeout;
+		Ok(())
 	}
 
 	pub fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		Ok(self.timeouts.lock().unwrap_or_else(|e| e.into_inner()).write)
+	}
+
+	fn get_comm_properties(&self) -> std::io::Result<winbase::COMMPROP> {
 		unsafe {
-			let mut timeouts = std::mem::zeroed();
-			check_bool(commapi::GetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))?;
-			Ok(Duration::from_millis(timeouts.WriteTotalTimeoutConstant.into()))
+			let mut prop: winbase::COMMPROP = std::mem::zeroed();
+			prop.wPacketLength = std::mem::size_of::<winbase::COMMPROP>() as u16;
+			check_bool(commapi::GetCommProperties(self.file.as_raw_handle(), &mut prop))?;
+			Ok(prop)
 		}
 	}
 
 	#[cfg(any(feature = "doc", all(feature = "windows", windows)))]
 	pub fn get_windows_timeouts(&self) -> std::io::Result<crate::os::windows::CommTimeouts> {
-		unsafe {
-			let mut timeouts = std::mem::zeroed();
-			check_bool(commapi::GetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))?;
-			Ok(crate::os::windows::CommTimeouts {
-				read_interval_timeout: timeouts.ReadIntervalTimeout,
-				read_total_timeout_multiplier: timeouts.ReadTotalTimeoutMultiplier,
-				read_total_timeout_constant: timeouts.ReadTotalTimeoutConstant,
-				write_total_timeout_multiplier: timeouts.WriteTotalTimeoutMultiplier,
-				write_total_timeout_constant: timeouts.WriteTotalTimeoutConstant,
-			})
-		}
+		let timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+		Ok(crate::os::windows::CommTimeouts {
+			read_interval_timeout: timeouts.comm.ReadIntervalTimeout,
+			read_total_timeout_multiplier: timeouts.comm.ReadTotalTimeoutMultiplier,
+			read_total_timeout_constant: timeouts.comm.ReadTotalTimeoutConstant,
+			write_total_timeout_multiplier: timeouts.comm.WriteTotalTimeoutMultiplier,
+			write_total_timeout_constant: timeouts.comm.WriteTotalTimeoutConstant,
+		})
+	}
+
+	#[cfg(any(feature = "doc", all(feature = "windows", windows)))]
+	pub fn get_capabilities(&self) -> std::io::Result<crate::os::windows::CommProperties> {
+		let prop = self.get_comm_properties()?;
+		Ok(crate::os::windows::CommProperties {
+			service_mask: prop.dwServiceMask,
+			max_tx_queue: prop.dwMaxTxQueue,
+			max_rx_queue: prop.dwMaxRxQueue,
+			max_baud: prop.dwMaxBaud,
+			provider_sub_type: prop.dwProvSubType,
+			provider_capabilities: prop.dwProvCapabilities,
+			settable_params: prop.dwSettableParams,
+			settable_baud: prop.dwSettableBaud,
+			settable_data: prop.wSettableData,
+			settable_stop_parity: prop.wSettableStopParity,
+			current_tx_queue: prop.dwCurrentTxQueue,
+			current_rx_queue: prop.dwCurrentRxQueue,
+		})
 	}
 
 	#[cfg(any(feature = "doc", all(feature = "windows", windows)))]
 	pub fn set_windows_timeouts(&self, timeouts: &crate::os::windows::CommTimeouts) -> std::io::Result<()> {
-		let mut timeouts = winapi::um::winbase::COMMTIMEOUTS {
+		let comm = winbase::COMMTIMEOUTS {
 			ReadIntervalTimeout: timeouts.read_interval_timeout,
 			ReadTotalTimeoutMultiplier: timeouts.read_total_timeout_multiplier,
 			ReadTotalTimeoutConstant: timeouts.read_total_timeout_constant,
 			WriteTotalTimeoutMultiplier: timeouts.write_total_timeout_multiplier,
 			WriteTotalTimeoutConstant: timeouts.write_total_timeout_constant,
 		};
-		unsafe {
-			check_bool(commapi::SetCommTimeouts(self.file.as_raw_handle(), &mut timeouts))?;
-			Ok(())
+		let mut timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+		timeouts.apply(&self.file, comm)?;
+		*timeouts = Timeouts::from_comm_timeouts(comm);
+		Ok(())
+	}
+
+	pub fn save_timeouts(&self) -> SavedTimeouts {
+		SavedTimeouts(self.timeouts.lock().unwrap_or_else(|e| e.into_inner()).clone())
+	}
+
+	pub fn restore_timeouts(&self, saved: &SavedTimeouts) -> std::io::Result<()> {
+		let mut timeouts = self.timeouts.lock().unwrap_or_else(|e| e.into_inner());
+		if self.is_comm_device {
+			timeouts.apply(&self.file, saved.0.comm)?;
 		}
+		*timeouts = saved.0.clone();
+		Ok(())
 	}
 
 	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
-		unsafe {
+		self.read_impl(buf, None)
+	}
+
+	/// Read with the configured read timeout, but wait no longer than `max_timeout`.
+	pub fn read_with_max_timeout(&self, buf: &mut [u8], max_timeout: Duration) -> std::io::Result<usize> {
+		self.read_impl(buf, Some(max_timeout))
+	}
+
+	fn read_impl(&self, buf: &mut [u8], max_timeout: Option<Duration>) -> std::io::Result<usize> {
+		// Serial ports enforce the read timeout through the COMMTIMEOUTS struct,
+		// but other handles need us to enforce the timeout ourselves.
+		let timeout_ms = if self.is_comm_device {
+			winbase::INFINITE
+		} else {
+			timeout_to_ms(self.get_read_timeout()?)
+		};
+		// A maximum timeout is always enforced by us, because it is not part of the COMMTIMEOUTS.
+		let timeout_ms = match max_timeout {
+			Some(max_timeout) => timeout_ms.min(timeout_to_ms_rounded_up(max_timeout)),
+			None => timeout_ms,
+		};
+		let result = unsafe {
 			let len = buf.len().try_into().unwrap_or(u32::MAX);
-			let event = Event::create(false, false)?;
+			let mut operation = Operation::new(&self.file, &self.read_overlapped)?;
 			let mut read = 0;
-			let mut overlapped: minwinbase::OVERLAPPED = std::mem::zeroed();
-			overlapped.hEvent = event.handle;
 			let ret = check_bool(fileapi::ReadFile(
 				self.file.as_raw_handle(),
 				buf.as_mut_ptr().cast(),
 				len,
 				&mut read,
-				&mut overlapped,
+				operation.overlapped(),
 			));
 			match ret {
 				// Windows reports timeouts as a succesfull transfer of 0 bytes.
-				Ok(()) if read == 0 => return Err(std::io::ErrorKind::TimedOut.into()),
-				Ok(()) => return Ok(read as usize),
+				Ok(()) if read == 0 => Err(std::io::ErrorKind::TimedOut.into()),
+				Ok(()) => Ok(read as usize),
 				// BrokenPipe with reads means EOF on Windows.
-				Err(ref e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(0),
-				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_IO_PENDING as i32) => (),
-				Err(e) => return Err(e),
+				Err(ref e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(0),
+				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_IO_PENDING as i32) => {
+					operation.wait(timeout_ms).or_else(map_broken_pipe)
+				},
+				Err(e) => Err(e),
 			}
-
-			wait_async_transfer(&self.file, &mut overlapped).or_else(map_broken_pipe)
-		}
+		};
+		result.map_err(map_disconnect_error)
 	}
 
 	pub fn read_vectored(&self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
@@ -201,29 +442,42 @@ impl SerialPort {
 	}
 
 	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
-		unsafe {
+		self.write_impl(buf, None)
+	}
+
+	/// Write with the configured write timeout, but wait no longer than `max_timeout`.
+	pub fn write_with_max_timeout(&self, buf: &[u8], max_timeout: Duration) -> std::io::Result<usize> {
+		self.write_impl(buf, Some(max_timeout))
+	}
+
+	fn write_impl(&self, buf: &[u8], max_timeout: Option<Duration>) -> std::io::Result<usize> {
+		// Not all drivers respect the write timeouts from the COMMTIMEOUTS struct,
+		// so we also enforce the write timeout ourselves.
+		let timeout_ms = timeout_to_ms(self.get_write_timeout()?);
+		let timeout_ms = match max_timeout {
+			Some(max_timeout) => timeout_ms.min(timeout_to_ms_rounded_up(max_timeout)),
+			None => timeout_ms,
+		};
+		let result = unsafe {
 			let len = buf.len().try_into().unwrap_or(u32::MAX);
-			let event = Event::create(false, false)?;
+			let mut operation = Operation::new(&self.file, &self.write_overlapped)?;
 			let mut written = 0;
-			let mut overlapped: minwinbase::OVERLAPPED = std::mem::zeroed();
-			overlapped.hEvent = event.handle;
 			let ret = check_bool(fileapi::WriteFile(
 				self.file.as_raw_handle(),
 				buf.as_ptr().cast(),
 				len,
 				&mut written,
-				&mut overlapped,
+				operation.overlapped(),
 			));
 			match ret {
 				// Windows reports timeouts as a succesfull transfer of 0 bytes.
-				Ok(()) if written == 0 => return Err(std::io::ErrorKind::TimedOut.into()),
-				Ok(()) => return Ok(written as usize),
-				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_IO_PENDING as i32) => (),
-				Err(e) => return Err(e),
+				Ok(()) if written == 0 => Err(std::io::ErrorKind::TimedOut.into()),
+				Ok(()) => Ok(written as usize),
+				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_IO_PENDING as i32) => operation.wait(timeout_ms),
+				Err(e) => Err(e),
 			}
-
-			wait_async_transfer(&self.file, &mut overlapped)
-		}
+		};
+		result.map_err(map_disconnect_error)
 	}
 
 	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
@@ -242,6 +496,87 @@ impl SerialPort {
 		unsafe { check_bool(winapi::um::fileapi::FlushFileBuffers(self.file.as_raw_handle())) }
 	}
 
+	pub fn abort_pending_io(&self) -> std::io::Result<()> {
+		unsafe {
+			match check_bool(ioapiset::CancelIoEx(self.file.as_raw_handle(), std::ptr::null_mut())) {
+				// There was nothing to cancel.
+				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_NOT_FOUND as i32) => Ok(()),
+				x => x,
+			}
+		}
+	}
+
+	pub fn is_connected(&self) -> std::io::Result<bool> {
+		unsafe {
+			let mut bits: u32 = 0;
+			match check_bool(commapi::GetCommModemStatus(self.file.as_raw_handle(), &mut bits)) {
+				Ok(()) => Ok(true),
+				Err(ref e) if e.raw_os_error().is_some_and(is_io_disconnect_os_error) => Ok(false),
+				Err(e) => Err(e),
+			}
+		}
+	}
+
+	pub fn output_queue_len(&self) -> std::io::Result<usize> {
+		unsafe {
+			let mut errors = 0;
+			let mut stat: winbase::COMSTAT = std::mem::zeroed();
+			check_bool(commapi::ClearCommError(self.file.as_raw_handle(), &mut errors, &mut stat))?;
+			Ok(stat.cbOutQue as usize)
+		}
+	}
+
+	/// Get and clear the communication errors (`CE_*` flags) reported by the driver since the last call.
+	///
+	/// Handles that are not serial ports (like named pipes) never report errors.
+	pub fn take_comm_errors(&self) -> std::io::Result<u32> {
+		if !self.is_comm_device {
+			return Ok(0);
+		}
+		unsafe {
+			let mut errors = 0;
+			let mut stat: winbase::COMSTAT = std::mem::zeroed();
+			check_bool(commapi::ClearCommError(self.file.as_raw_handle(), &mut errors, &mut stat))?;
+			Ok(errors)
+		}
+	}
+
+	pub fn supported_baud_rates(&self) -> std::io::Result<crate::SupportedBaudRates> {
+		// The `BAUD_*` flags for the baud rates in `COMMON_BAUD_RATES`.
+		const BAUD_FLAGS: [(u32, u32); 6] = [
+			(winbase::BAUD_4800, 4800),
+			(winbase::BAUD_9600, 9600),
+			(winbase::BAUD_19200, 19200),
+			(winbase::BAUD_38400, 38400),
+			(winbase::BAUD_57600, 57600),
+			(winbase::BAUD_115200, 115200),
+		];
+
+		let prop = self.get_comm_properties()?;
+		let custom_rates = prop.dwSettableBaud & winbase::BAUD_USER != 0;
+		let common_rates = crate::COMMON_BAUD_RATES
+			.iter()
+			.copied()
+			.filter(|&rate| {
+				custom_rates
+					|| BAUD_FLAGS
+						.iter()
+						.any(|&(flag, bits_per_second)| bits_per_second == rate && prop.dwSettableBaud & flag != 0)
+			})
+			.collect();
+		Ok(crate::SupportedBaudRates {
+			common_rates,
+			custom_rates,
+		})
+	}
+
+	pub fn set_os_buffer_sizes(&self, receive: u32, transmit: u32) -> std::io::Result<()> {
+		unsafe {
+			check_bool(commapi::SetupComm(self.file.as_raw_handle(), receive, transmit))?;
+		}
+		Ok(())
+	}
+
 	pub fn discard_buffers(&self, discard_input: bool, discard_output: bool) -> std::io::Result<()> {
 		unsafe {
 			let mut flags = 0;
@@ -255,6 +590,22 @@ impl SerialPort {
 		}
 	}
 
+	pub fn suspend_output(&self) -> std::io::Result<()> {
+		escape_comm_function(&self.file, winbase::SETXOFF)
+	}
+
+	pub fn resume_output(&self) -> std::io::Result<()> {
+		escape_comm_function(&self.file, winbase::SETXON)
+	}
+
+	pub fn send_xoff(&self) -> std::io::Result<()> {
+		transmit_comm_char(&self.file, XOFF)
+	}
+
+	pub fn send_xon(&self) -> std::io::Result<()> {
+		transmit_comm_char(&self.file, XON)
+	}
+
 	pub fn set_rts(&self, state: bool) -> std::io::Result<()> {
 		if state {
 			escape_comm_function(&self.file, winbase::SETRTS)
@@ -279,6 +630,28 @@ impl SerialPort {
 		read_pin(&self.file, winbase::MS_DSR_ON)
 	}
 
+	/// Change the RTS and DTR lines.
+	///
+	/// Windows has no function to change both lines at the same time, so they are changed one after the other.
+	pub fn set_rts_dtr(&self, rts: Option<bool>, dtr: Option<bool>) -> std::io::Result<()> {
+		if let Some(rts) = rts {
+			self.set_rts(rts)?;
+		}
+		if let Some(dtr) = dtr {
+			self.set_dtr(dtr)?;
+		}
+		Ok(())
+	}
+
+	pub fn set_verify_control_lines(&self, enable: bool) -> std::io::Result<()> {
+		// Windows can not report the state of the output control lines.
+		crate::settings::unsupported_option(enable, "verifying output control lines")
+	}
+
+	pub fn get_verify_control_lines(&self) -> bool {
+		false
+	}
+
 	pub fn read_ri(&self) -> std::io::Result<bool> {
 		read_pin(&self.file, winbase::MS_RING_ON)
 	}
@@ -289,6 +662,176 @@ impl SerialPort {
 		// I think.
 		read_pin(&self.file, winbase::MS_RLSD_ON)
 	}
+
+	pub fn ring_count(&self) -> std::io::Result<u32> {
+		Err(std::io::Error::new(
+			std::io::ErrorKind::Unsupported,
+			"counting Ring Indicator pulses is not supported on this platform",
+		))
+	}
+
+	/// Wait for the Carrier Detect line to become active with `WaitCommEvent()` and `EV_RLSD`.
+	///
+	/// This replaces the event mask of the serial port, which also completes pending `WaitCommEvent()` calls.
+	pub fn wait_for_carrier(&self, timeout: Duration) -> std::io::Result<()> {
+		// `EV_RLSD` reports any change of the line, so check the state after each event.
+		self.wait_comm_event(winbase::EV_RLSD, timeout, "the Carrier Detect line", |_| self.read_cd())
+	}
+
+	/// Wait for a Ring Indicator pulse with `WaitCommEvent()` and `EV_RING`.
+	///
+	/// This replaces the event mask of the serial port, which also completes pending `WaitCommEvent()` calls.
+	pub fn wait_for_ring(&self, timeout: Duration) -> std::io::Result<()> {
+		self.wait_comm_event(winbase::EV_RING, timeout, "the Ring Indicator line", Ok)
+	}
+
+	/// Wait for a modem event with `WaitCommEvent()`, until `done` returns true.
+	///
+	/// The `done` function is called with `false` after the event mask is set,
+	/// and with `true` after each time the event occurred.
+	///
+	/// The event mask of the serial port is restored afterwards, even if waiting fails.
+	fn wait_comm_event<F>(&self, event: u32, timeout: Duration, description: &str, done: F) -> std::io::Result<()>
+	where
+		F: FnMut(bool) -> std::io::Result<bool>,
+	{
+		let mut previous_mask = 0;
+		unsafe {
+			check_bool(commapi::GetCommMask(self.file.as_raw_handle(), &mut previous_mask))?;
+		}
+		let result = self.wait_comm_event_with_mask(event, timeout, description, done);
+		let restored = unsafe { check_bool(commapi::SetCommMask(self.file.as_raw_handle(), previous_mask)) };
+		result.and(restored)
+	}
+
+	fn wait_comm_event_with_mask<F>(
+		&self,
+		event: u32,
+		timeout: Duration,
+		description: &str,
+		mut done: F,
+	) -> std::io::Result<()>
+	where
+		F: FnMut(bool) -> std::io::Result<bool>,
+	{
+		let deadline = std::time::Instant::now().checked_add(timeout);
+		unsafe {
+			check_bool(commapi::SetCommMask(self.file.as_raw_handle(), event))?;
+		}
+		let mut occurred = false;
+		loop {
+			// Check after setting the event mask, so a change right before is not missed.
+			if done(occurred)? {
+				return Ok(());
+			}
+			let timeout_ms = match deadline {
+				None => winbase::INFINITE,
+				Some(deadline) => timeout_to_ms(deadline.saturating_duration_since(std::time::Instant::now())),
+			};
+			// The event mask must outlive the operation, since the kernel writes to it.
+			let mut event_mask = 0;
+			let mut operation = Operation::new(&self.file, &self.read_overlapped)?;
+			let ret = unsafe {
+				check_bool(commapi::WaitCommEvent(
+					self.file.as_raw_handle(),
+					&mut event_mask,
+					operation.overlapped(),
+				))
+			};
+			match ret {
+				Ok(()) => (),
+				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_IO_PENDING as i32) => {
+					operation.wait_event(timeout_ms).map_err(|e| match e.kind() {
+						std::io::ErrorKind::TimedOut => std::io::Error::new(
+							std::io::ErrorKind::TimedOut,
+							format!("timed out waiting for {description}"),
+						),
+						_ => e,
+					})?;
+				},
+				Err(e) => return Err(map_disconnect_error(e)),
+			}
+			drop(operation);
+			occurred = event_mask & event != 0;
+			if !occurred {
+				// The wait was completed because the event mask was changed, so set it again.
+				unsafe {
+					check_bool(commapi::SetCommMask(self.file.as_raw_handle(), event))?;
+				}
+			}
+		}
+	}
+
+	pub fn set_break(&self, state: bool) -> std::io::Result<()> {
+		if state {
+			escape_comm_function(&self.file, winbase::SETBREAK)
+		} else {
+			escape_comm_function(&self.file, winbase::CLRBREAK)
+		}
+	}
+}
+
+/// The timeouts and read mode of a serial port, saved by [`SerialPort::save_timeouts()`].
+#[derive(Clone)]
+pub struct SavedTimeouts(Timeouts);
+
+/// The timeouts of a serial port.
+///
+/// We keep track of the timeouts ourselves to avoid calling `GetCommTimeouts()` and `SetCommTimeouts()` all the time.
+#[derive(Clone)]
+struct Timeouts {
+	/// The read timeout as set by the user.
+	read: Duration,
+
+	/// The write timeout as set by the user.
+	write: Duration,
+
+	/// The read mode as set by the user.
+	read_mode: crate::ReadMode,
+
+	/// The timeouts as configured on the handle.
+	comm: winbase::COMMTIMEOUTS,
+}
+
+impl Timeouts {
+	fn from_comm_timeouts(comm: winbase::COMMTIMEOUTS) -> Self {
+		Self {
+			read: Duration::from_millis(comm.ReadTotalTimeoutConstant.into()),
+			write: Duration::from_millis(comm.WriteTotalTimeoutConstant.into()),
+			read_mode: read_mode_from_comm_timeouts(&comm),
+			comm,
+		}
+	}
+
+	/// Apply new timeouts to the handle, unless they are identical to the currently configured timeouts.
+	fn apply(&mut self, file: &std::fs::File, mut comm: winbase::COMMTIMEOUTS) -> std::io::Result<()> {
+		let same = true;
+		let same = same && comm.ReadIntervalTimeout == self.comm.ReadIntervalTimeout;
+		let same = same && comm.ReadTotalTimeoutMultiplier == self.comm.ReadTotalTimeoutMultiplier;
+		let same = same && comm.ReadTotalTimeoutConstant == self.comm.ReadTotalTimeoutConstant;
+		let same = same && comm.WriteTotalTimeoutMultiplier == self.comm.WriteTotalTimeoutMultiplier;
+		let same = same && comm.WriteTotalTimeoutConstant == self.comm.WriteTotalTimeoutConstant;
+		if !same {
+			unsafe {
+				check_bool(commapi::SetCommTimeouts(file.as_raw_handle(), &mut comm))?;
+			}
+			self.comm = comm;
+		}
+		Ok(())
+	}
+}
+
+/// Determine the read mode from the timeouts configured on a handle.
+///
+/// Timeouts that do not match one of the read modes are reported as [`crate::ReadMode::FirstByte`].
+fn read_mode_from_comm_timeouts(comm: &winbase::COMMTIMEOUTS) -> crate::ReadMode {
+	if comm.ReadTotalTimeoutMultiplier == 0 && comm.ReadIntervalTimeout != 0 && comm.ReadIntervalTimeout != u32::MAX {
+		crate::ReadMode::FillBuffer {
+			inter_byte_timeout: Duration::from_millis(comm.ReadIntervalTimeout.into()),
+		}
+	} else {
+		crate::ReadMode::FirstByte
+	}
 }
 
 struct Event {
@@ -319,6 +862,277 @@ impl Drop for Event {
 	}
 }
 
+/// The state for an overlapped I/O operation.
+struct Overlapped {
+	overlapped: minwinbase::OVERLAPPED,
+	event: Event,
+}
+
+// The OVERLAPPED struct and the event handle can be used from any thread.
+unsafe impl Send for Overlapped {}
+
+/// A cache for the state of a single overlapped operation.
+///
+/// This allows us to re-use the event object and the allocation for the OVERLAPPED struct,
+/// instead of creating a new event for every I/O operation.
+///
+/// Multiple threads may perform the same type of I/O operation concurrently,
+/// so the cached state can not simply be shared by all operations.
+/// Instead, an operation takes the state out of the cache and puts it back when it is done.
+/// If the cache is empty because another operation is still using the state, a new state object is created.
+struct OverlappedCache {
+	cached: std::sync::Mutex<Option<Box<Overlapped>>>,
+}
+
+impl OverlappedCache {
+	fn new() -> Self {
+		Self {
+			cached: std::sync::Mutex::new(None),
+		}
+	}
+
+	/// Take the cached state, or create a new one if the cache is empty.
+	fn take(&self) -> std::io::Result<Box<Overlapped>> {
+		let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner()).take();
+		match cached {
+			Some(state) => Ok(state),
+			None => Ok(Box::new(Overlapped {
+				overlapped: unsafe { std::mem::zeroed() },
+				event: Event::create(false, false)?,
+			})),
+		}
+	}
+
+	/// Put the state back in the cache, or close it if the cache is already filled.
+	fn put(&self, state: Box<Overlapped>) {
+		let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+		if cached.is_none() {
+			*cached = Some(state);
+		}
+	}
+}
+
+/// An overlapped I/O operation.
+///
+/// The kernel may access the OVERLAPPED struct and the data buffer until the operation has completed,
+/// even if we stop waiting for it.
+/// To prevent the kernel from writing into memory that has been freed or re-used,
+/// dropping an `Operation` cancels the operation and waits for it if it is still pending.
+///
+/// The lifetime ensures the `Operation` does not outlive the file handle.
+/// Callers must ensure the data buffer outlives the `Operation` too.
+struct Operation<'a> {
+	file: &'a std::fs::File,
+	cache: &'a OverlappedCache,
+	state: Option<Box<Overlapped>>,
+	pending: bool,
+}
+
+impl<'a> Operation<'a> {
+	/// Prepare a new overlapped operation.
+	fn new(file: &'a std::fs::File, cache: &'a OverlappedCache) -> std::io::Result<Self> {
+		let mut state = cache.take()?;
+		unsafe {
+			state.overlapped = std::mem::zeroed();
+		}
+		state.overlapped.hEvent = state.event.handle;
+		Ok(Self {
+			file,
+			cache,
+			state: Some(state),
+			pending: false,
+		})
+	}
+
+	/// Get a pointer to the OVERLAPPED struct to pass to an I/O function.
+	fn overlapped(&mut self) -> *mut minwinbase::OVERLAPPED {
+		self.state_mut()
+	}
+
+	fn state_mut(&mut self) -> &mut minwinbase::OVERLAPPED {
+		// The state is only taken out when the operation is dropped.
+		&mut self.state.as_mut().unwrap().overlapped
+	}
+
+	/// Wait for a pending operation to complete.
+	///
+	/// Must only be called after the I/O function reported `ERROR_IO_PENDING`.
+	///
+	/// If the operation does not complete within `timeout_ms` milliseconds, it is cancelled.
+	/// Pass [`winbase::INFINITE`] to wait without a timeout.
+	fn wait(&mut self, timeout_ms: u32) -> std::io::Result<usize> {
+		self.pending = true;
+		unsafe {
+			let handle = self.file.as_raw_handle();
+			let mut transferred = 0;
+			let ret = check_bool(ioapiset::GetOverlappedResultEx(
+				handle,
+				self.state_mut(),
+				&mut transferred,
+				timeout_ms,
+				0,
+			));
+			match ret {
+				// Windows reports timeouts as a succesfull transfer of 0 bytes.
+				Ok(_) if transferred == 0 => {
+					self.pending = false;
+					Err(std::io::ErrorKind::TimedOut.into())
+				},
+				Ok(_) => {
+					self.pending = false;
+					Ok(transferred as usize)
+				},
+				Err(ref e) if is_wait_timeout(e) => {
+					// Some drivers ignore the COMMTIMEOUTS, so we cancel the operation ourselves.
+					// It may still have completed (partially) before it was cancelled.
+					match self.cancel() {
+						Ok(0) => Err(std::io::ErrorKind::TimedOut.into()),
+						Ok(transferred) => Ok(transferred),
+						Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_OPERATION_ABORTED as i32) => {
+							Err(std::io::ErrorKind::TimedOut.into())
+						},
+						Err(e) => Err(e),
+					}
+				},
+				// The operation was cancelled by `abort_pending_io()`.
+				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_OPERATION_ABORTED as i32) => {
+					self.pending = false;
+					Err(aborted_error())
+				},
+				Err(e) => {
+					// The operation may still be pending, depending on the error.
+					// If it is, dropping the operation will cancel it and wait for it.
+					self.pending = !has_overlapped_io_completed(self.state_mut());
+					Err(e)
+				},
+			}
+		}
+	}
+
+	/// Wait for a pending `WaitCommEvent()` operation to complete.
+	///
+	/// Unlike [`Self::wait()`], this does not treat a completed operation without transferred bytes as a timeout.
+	///
+	/// If the operation does not complete within `timeout_ms` milliseconds, it is cancelled.
+	/// Pass [`winbase::INFINITE`] to wait without a timeout.
+	fn wait_event(&mut self, timeout_ms: u32) -> std::io::Result<()> {
+		self.pending = true;
+		unsafe {
+			let handle = self.file.as_raw_handle();
+			let mut transferred = 0;
+			let ret = check_bool(ioapiset::GetOverlappedResultEx(
+				handle,
+				self.state_mut(),
+				&mut transferred,
+				timeout_ms,
+				0,
+			));
+			match ret {
+				Ok(()) => {
+					self.pending = false;
+					Ok(())
+				},
+				Err(ref e) if is_wait_timeout(e) => {
+					// The event may still have occurred before the operation was cancelled.
+					match self.cancel() {
+						Ok(_) => Ok(()),
+						Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_OPERATION_ABORTED as i32) => {
+							Err(std::io::ErrorKind::TimedOut.into())
+						},
+						Err(e) => Err(map_disconnect_error(e)),
+					}
+				},
+				// The operation was cancelled by `abort_pending_io()`.
+				Err(ref e) if e.raw_os_error() == Some(winerror::ERROR_OPERATION_ABORTED as i32) => {
+					self.pending = false;
+					Err(aborted_error())
+				},
+				Err(e) => {
+					self.pending = !has_overlapped_io_completed(self.state_mut());
+					Err(map_disconnect_error(e))
+				},
+			}
+		}
+	}
+
+	/// Cancel the operation and wait for it to finish.
+	///
+	/// Returns the number of bytes transferred before the operation finished,
+	/// or an error if the operation failed or if it was cancelled.
+	fn cancel(&mut self) -> std::io::Result<usize> {
+		unsafe {
+			let handle = self.file.as_raw_handle();
+			// If this fails, the operation already completed and there is nothing to cancel.
+			ioapiset::CancelIoEx(handle, self.state_mut());
+
+			// The cancellation itself is asynchronous too, so wait for the operation to finish.
+			let mut transferred = 0;
+			let ret = check_bool(ioapiset::GetOverlappedResult(handle, self.state_mut(), &mut transferred, 1));
+			self.pending = false;
+			ret.map(|()| transferred as usize)
+		}
+	}
+}
+
+impl Drop for Operation<'_> {
+	fn drop(&mut self) {
+		if self.pending {
+			// We can't report an error from here,
+			// but it is more important that we don't let the kernel touch freed memory.
+			let _ = self.cancel();
+		}
+		if let Some(state) = self.state.take() {
+			self.cache.put(state);
+		}
+	}
+}
+
+/// Check if waiting for an overlapped operation failed because the timeout expired.
+///
+/// With a timeout of zero, `GetOverlappedResultEx()` reports `ERROR_IO_INCOMPLETE` instead of `WAIT_TIMEOUT`.
+fn is_wait_timeout(error: &std::io::Error) -> bool {
+	let code = error.raw_os_error();
+	code == Some(winerror::WAIT_TIMEOUT as i32) || code == Some(winerror::ERROR_IO_INCOMPLETE as i32)
+}
+
+/// Check if an overlapped operation has completed.
+///
+/// This is `HasOverlappedIoCompleted()` from the Windows API, but it is a macro so winapi doesn't provide it.
+fn has_overlapped_io_completed(overlapped: &minwinbase::OVERLAPPED) -> bool {
+	const STATUS_PENDING: usize = 0x103;
+	overlapped.Internal != STATUS_PENDING
+}
+
+/// Check if an error code indicates that the device was disconnected.
+///
+/// This does not include `ERROR_ACCESS_DENIED`, because opening a serial port that is in use fails with the same error.
+/// Use `is_io_disconnect_os_error()` for errors from I/O on an open serial port.
+pub fn is_disconnect_os_error(code: i32) -> bool {
+	code != winerror::ERROR_ACCESS_DENIED as i32 && is_io_disconnect_os_error(code)
+}
+
+/// Check if an error code from I/O on an open serial port indicates that the device was disconnected.
+fn is_io_disconnect_os_error(code: i32) -> bool {
+	// USB serial adapters report different errors when unplugged, depending on the driver.
+	const DISCONNECT_ERRORS: [u32; 4] = [
+		winerror::ERROR_ACCESS_DENIED,
+		winerror::ERROR_BAD_COMMAND,
+		winerror::ERROR_DEVICE_NOT_CONNECTED,
+		winerror::ERROR_GEN_FAILURE,
+	];
+	DISCONNECT_ERRORS.iter().any(|&x| x as i32 == code)
+}
+
+/// Convert errors from I/O on an open serial port that indicate a disconnected device to [`std::io::ErrorKind::NotConnected`].
+///
+/// The original error is kept as inner error.
+fn map_disconnect_error(error: std::io::Error) -> std::io::Error {
+	match error.raw_os_error() {
+		Some(code) if is_io_disconnect_os_error(code) => std::io::Error::new(std::io::ErrorKind::NotConnected, error),
+		_ => error,
+	}
+}
+
 fn map_broken_pipe(error: std::io::Error) -> std::io::Result<usize> {
 	if error.kind() == std::io::ErrorKind::BrokenPipe {
 		Ok(0)
@@ -327,28 +1141,43 @@ fn map_broken_pipe(error: std::io::Error) -> std::io::Result<usize> {
 	}
 }
 
-fn wait_async_transfer(file: &std::fs::File, overlapped: &mut minwinbase::OVERLAPPED) -> std::io::Result<usize> {
-	unsafe {
-		let mut transferred = 0;
-		let ret = check_bool(ioapiset::GetOverlappedResult(
-			file.as_raw_handle(),
-			overlapped,
-			&mut transferred,
-			1,
-		));
-		match ret {
-			// Windows reports timeouts as a succesfull transfer of 0 bytes.
-			Ok(_) if transferred == 0 => Err(std::io::ErrorKind::TimedOut.into()),
-			Ok(_) => Ok(transferred as usize),
-			Err(e) => Err(e),
-		}
-	}
+/// Convert a timeout to milliseconds for `GetOverlappedResultEx()`.
+///
+/// We can not use `u32::MAX`, since that means infinite.
+fn timeout_to_ms(timeout: Duration) -> u32 {
+	timeout.as_millis().try_into().unwrap_or(u32::MAX).min(u32::MAX - 1)
+}
+
+/// Convert a timeout to milliseconds, rounding up.
+///
+/// This ensures that a remaining time of less than a millisecond still gets a chance to complete,
+/// instead of being truncated to a zero timeout.
+fn timeout_to_ms_rounded_up(timeout: Duration) -> u32 {
+	timeout.checked_add(Duration::from_nanos(999_999)).map_or(u32::MAX - 1, timeout_to_ms)
 }
 
 fn escape_comm_function(file: &std::fs::File, function: u32) -> std::io::Result<()> {
 	unsafe { check_bool(commapi::EscapeCommFunction(file.as_raw_handle(), function)) }
 }
 
+/// The baud rates that have a `CBR_*` constant.
+///
+/// Drivers may support other baud rates too.
+pub const STANDARD_BAUD_RATES: [u32; 15] = [
+	110, 300, 600, 1200, 2400, 4800, 9600, 14400, 19200, 38400, 56000, 57600, 115200, 128000, 256000,
+];
+
+/// The default XON character (DC1).
+const XON: i8 = 0x11;
+
+/// The default XOFF character (DC3).
+const XOFF: i8 = 0x13;
+
+/// Transmit a character ahead of any pending data in the output buffer.
+fn transmit_comm_char(file: &std::fs::File, c: i8) -> std::io::Result<()> {
+	unsafe { check_bool(commapi::TransmitCommChar(file.as_raw_handle(), c)) }
+}
+
 fn read_pin(file: &std::fs::File, pin: u32) -> std::io::Result<bool> {
 	unsafe {
 		let mut bits: u32 = 0;
@@ -375,15 +1204,45 @@ fn check_handle(ret: RawHandle) -> std::io::Result<RawHandle> {
 	}
 }
 
+/// Create the error returned for aborted I/O operations.
+fn aborted_error() -> std::io::Error {
+	other_error("I/O operation aborted")
+}
+
 /// Create an std::io::Error with custom message.
 fn other_error<E>(msg: E) -> std::io::Error
 where
 	E: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-	std::io::Error::new(std::io::ErrorKind::Other, msg)
+	std::io::Error::other(msg)
 }
 
 impl Settings {
+	/// Check if the fields of the DCB that we know about match the requested settings.
+	///
+	/// The DTR and RTS control modes are not compared,
+	/// because some drivers report them differently after the lines have been changed with `EscapeCommFunction()`.
+	fn matches_requested(&self, requested: &Self) -> bool {
+		let a = &self.dcb;
+		let b = &requested.dcb;
+		let same = true;
+		let same = same && a.ByteSize == b.ByteSize;
+		let same = same && a.Parity == b.Parity;
+		let same = same && a.StopBits == b.StopBits;
+		let same = same && a.fParity() == b.fParity();
+		let same = same && a.fOutxCtsFlow() == b.fOutxCtsFlow();
+		let same = same && a.fOutxDsrFlow() == b.fOutxDsrFlow();
+		let same = same && a.fInX() == b.fInX();
+		let same = same && a.fOutX() == b.fOutX();
+		if !same {
+			return false;
+		}
+
+		// Allow for a 2.5% deviation in the actual baud rate, like on Unix.
+		// The driver needs to select proper clock divisors to get the desired baud rate.
+		a.BaudRate.abs_diff(b.BaudRate) <= b.BaudRate / 40
+	}
+
 	pub fn set_raw(&mut self) {
 		self.set_char_size(crate::CharSize::Bits8);
 		self.set_stop_bits(crate::StopBits::One);
@@ -451,6 +1310,14 @@ impl Settings {
 				self.dcb.set_fParity(1);
 				self.dcb.Parity = winbase::EVENPARITY;
 			},
+			crate::Parity::Mark => {
+				self.dcb.set_fParity(1);
+				self.dcb.Parity = winbase::MARKPARITY;
+			},
+			crate::Parity::Space => {
+				self.dcb.set_fParity(1);
+				self.dcb.Parity = winbase::SPACEPARITY;
+			},
 		}
 	}
 
@@ -460,6 +1327,8 @@ impl Settings {
 			winbase::NOPARITY => Ok(crate::Parity::None),
 			winbase::ODDPARITY if parity_enabled => Ok(crate::Parity::Odd),
 			winbase::EVENPARITY if parity_enabled => Ok(crate::Parity::Even),
+			winbase::MARKPARITY if parity_enabled => Ok(crate::Parity::Mark),
+			winbase::SPACEPARITY if parity_enabled => Ok(crate::Parity::Space),
 			_ => Err(other_error("unsupported parity configuration")),
 		}
 	}
@@ -516,6 +1385,92 @@ impl Settings {
 			_ => Err(other_error("unsupported flow control configuration")),
 		}
 	}
+
+	pub fn set_ignore_cr(&mut self, enable: bool) -> std::io::Result<()> {
+		crate::settings::unsupported_option(enable, "discarding carriage returns")
+	}
+
+	pub fn get_ignore_cr(&self) -> bool {
+		false
+	}
+
+	pub fn set_map_nl_to_cr(&mut self, enable: bool) -> std::io::Result<()> {
+		crate::settings::unsupported_option(enable, "translating newlines to carriage returns")
+	}
+
+	pub fn get_map_nl_to_cr(&self) -> bool {
+		false
+	}
+
+	pub fn set_strip_high_bit(&mut self, enable: bool) -> std::io::Result<()> {
+		crate::settings::unsupported_option(enable, "stripping the high bit")
+	}
+
+	pub fn get_strip_high_bit(&self) -> bool {
+		false
+	}
+
+	pub fn set_newline_mode(&mut self, mode: crate::NewlineMode) -> std::io::Result<()> {
+		crate::settings::unsupported_option(mode != crate::NewlineMode::None, "newline translation")
+	}
+
+	pub fn get_newline_mode(&self) -> crate::NewlineMode {
+		crate::NewlineMode::None
+	}
+
+	pub fn set_discard_null(&mut self, enable: bool) -> std::io::Result<()> {
+		self.dcb.set_fNull(enable as u32);
+		Ok(())
+	}
+
+	pub fn get_discard_null(&self) -> bool {
+		self.dcb.fNull() != 0
+	}
+
+	pub fn validate(&self) -> std::io::Result<()> {
+		let dcb = &self.dcb;
+		if dcb.BaudRate == 0 {
+			return Err(invalid_settings("the baud rate can not be zero"));
+		}
+		if !(5..=8).contains(&dcb.ByteSize) {
+			return Err(invalid_settings(format!("unsupported char size: {} bits", dcb.ByteSize)));
+		}
+		match dcb.StopBits {
+			winbase::TWOSTOPBITS if dcb.ByteSize == 5 => {
+				return Err(invalid_settings(
+					"5 data bits can not be used with 2 stop bits, only with 1 or 1.5 stop bits",
+				));
+			},
+			winbase::ONE5STOPBITS if dcb.ByteSize != 5 => {
+				return Err(invalid_settings("1.5 stop bits can only be used with 5 data bits"));
+			},
+			winbase::ONESTOPBIT | winbase::ONE5STOPBITS | winbase::TWOSTOPBITS => (),
+			other => return Err(invalid_settings(format!("unsupported stop bits value: {other}"))),
+		}
+		if dcb.Parity > winbase::SPACEPARITY {
+			return Err(invalid_settings(format!("unsupported parity value: {}", dcb.Parity)));
+		}
+
+		let software_flow = dcb.fInX() != 0 || dcb.fOutX() != 0;
+		let hardware_flow = dcb.fOutxCtsFlow() != 0
+			|| dcb.fOutxDsrFlow() != 0
+			|| dcb.fRtsControl() == winbase::RTS_CONTROL_HANDSHAKE
+			|| dcb.fDtrControl() == winbase::DTR_CONTROL_HANDSHAKE;
+		if software_flow && hardware_flow {
+			return Err(invalid_settings(
+				"hardware (RTS/CTS or DTR/DSR) and software (XON/XOFF) flow control can not be enabled together",
+			));
+		}
+		Ok(())
+	}
+}
+
+/// Create an error for invalid settings, as reported by [`Settings::validate()`].
+fn invalid_settings<E>(msg: E) -> std::io::Error
+where
+	E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+	std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
 }
 
 #[derive(Debug)]
@@ -600,6 +1555,80 @@ impl RegKey {
 	}
 }
 
+impl RegKey {
+	/// Get the names of all subkeys.
+	fn subkey_names(&self) -> std::io::Result<Vec<CString>> {
+		let mut names = Vec::new();
+		for index in 0.. {
+			// Key names are limited to 255 characters.
+			let mut name = vec![0u8; 256];
+			let mut name_len = name.len() as u32;
+			let status = unsafe {
+				winreg::RegEnumKeyExA(
+					self.key,
+					index,
+					name.as_mut_ptr().cast(),
+					&mut name_len,
+					std::ptr::null_mut(),
+					std::ptr::null_mut(),
+					std::ptr::null_mut(),
+					std::ptr::null_mut(),
+				)
+			};
+			if status == winerror::ERROR_NO_MORE_ITEMS as i32 {
+				break;
+			} else if status != 0 {
+				return Err(std::io::Error::from_raw_os_error(status));
+			}
+			name.truncate(name_len as usize);
+			if let Ok(name) = CString::new(name) {
+				names.push(name);
+			}
+		}
+		Ok(names)
+	}
+
+	/// Get a string value by name.
+	///
+	/// Returns `Ok(None)` if the value does not exist or if it is not a string.
+	fn get_named_string_value(&self, name: &CStr) -> std::io::Result<Option<String>> {
+		unsafe {
+			let mut kind = 0;
+			let mut data_len = 0;
+			let status = winreg::RegQueryValueExA(
+				self.key,
+				name.as_ptr(),
+				std::ptr::null_mut(),
+				&mut kind,
+				std::ptr::null_mut(),
+				&mut data_len,
+			);
+			if status == winerror::ERROR_FILE_NOT_FOUND as i32 {
+				return Ok(None);
+			} else if status != 0 {
+				return Err(std::io::Error::from_raw_os_error(status));
+			} else if kind != winnt::REG_SZ {
+				return Ok(None);
+			}
+
+			let mut data = vec![0u8; data_len as usize];
+			let status = winreg::RegQueryValueExA(
+				self.key,
+				name.as_ptr(),
+				std::ptr::null_mut(),
+				&mut kind,
+				data.as_mut_ptr(),
+				&mut data_len,
+			);
+			if status != 0 {
+				return Err(std::io::Error::from_raw_os_error(status));
+			}
+			data.truncate(data_len as usize);
+			Ok(string_from_registry(data))
+		}
+	}
+}
+
 impl Drop for RegKey {
 	fn drop(&mut self) {
 		unsafe {
@@ -608,33 +1637,249 @@ impl Drop for RegKey {
 	}
 }
 
+/// Get the path to open for a serial port name.
+///
+/// Plain names like `COM1` are opened in the win32 device namespace, otherwise we're limited to COM1-9.
+/// This also works with higher numbers.
+/// See <https://docs.microsoft.com/en-us/windows/win32/fileio/naming-a-file#win32-device-namespaces>.
+///
+/// Names that are already full device paths are used as-is.
+/// This includes `\\.\COM1` and device interface paths like `\\?\USB#VID_2341&PID_0043#...#{GUID}`.
+fn device_path(name: &Path) -> OsString {
+	use std::os::windows::ffi::OsStrExt;
+
+	let prefix: Vec<u16> = name.as_os_str().encode_wide().take(4).collect();
+	let is_device_path = match prefix[..] {
+		[a, b, c, d] => {
+			let is_separator = |x: u16| x == u16::from(b'\\') || x == u16::from(b'/');
+			is_separator(a) && is_separator(b) && (c == u16::from(b'.') || c == u16::from(b'?')) && is_separator(d)
+		},
+		_ => false,
+	};
+
+	if is_device_path {
+		name.as_os_str().to_owned()
+	} else {
+		let mut path = OsString::from("\\\\.\\");
+		path.push(name.as_os_str());
+		path
+	}
+}
+
 pub fn enumerate() -> std::io::Result<Vec<PathBuf>> {
+	// Only report the active ports, not the Bluetooth and modem ports of devices that may be disconnected.
+	let mut ports = Vec::with_capacity(16);
+	enumerate_serialcomm(&mut ports)?;
+	Ok(ports.into_iter().map(crate::PortInfo::into_path).collect())
+}
+
+/// Get diagnostics for all candidates considered during port enumeration.
+///
+/// Registered Bluetooth and modem ports that are not active are reported as filtered.
+pub fn enumerate_verbose() -> std::io::Result<Vec<crate::PortDiagnostic>> {
+	let (ports, active) = enumerate_registered()?;
+	let ports = ports
+		.into_iter()
+		.enumerate()
+		.map(|(index, port)| {
+			let status = if index < active {
+				crate::PortStatus::Available(port.kind)
+			} else {
+				crate::PortStatus::Filtered("not listed in SERIALCOMM, the device may not be connected")
+			};
+			crate::PortDiagnostic::new(port.path, status)
+		})
+		.collect();
+	Ok(ports)
+}
+
+/// Call a function for each available serial port.
+///
+/// The ports are collected from the registry first, so this does allocate.
+pub fn for_each_port(f: &mut dyn FnMut(crate::PortInfoRef<'_>)) -> std::io::Result<()> {
+	for port in enumerate_info()? {
+		f(crate::PortInfoRef::new(&port.path, port.kind));
+	}
+	Ok(())
+}
+
+pub fn enumerate_info() -> std::io::Result<Vec<crate::PortInfo>> {
+	Ok(enumerate_registered()?.0)
+}
+
+/// Get the active serial ports, followed by the registered Bluetooth and modem ports that are not active.
+///
+/// Returns the ports and the number of active ports at the start of the list.
+fn enumerate_registered() -> std::io::Result<(Vec<crate::PortInfo>, usize)> {
+	let mut ports = Vec::with_capacity(16);
+	enumerate_serialcomm(&mut ports)?;
+	let active = ports.len();
+
+	// Bluetooth and modem ports do not always show up in SERIALCOMM,
+	// for example when the device is not currently connected.
+	// Failing to read these keys is not fatal: they only add information.
+	let _ = enumerate_modems(&mut ports);
+	let _ = enumerate_bluetooth(&mut ports);
+	enumerate_instance_ids(&mut ports);
+
+	Ok((ports, active))
+}
+
+/// Add all serial ports from `HKLM\Hardware\DEVICEMAP\SERIALCOMM`.
+fn enumerate_serialcomm(ports: &mut Vec<crate::PortInfo>) -> std::io::Result<()> {
 	let subkey = unsafe { CStr::from_bytes_with_nul_unchecked(b"Hardware\\DEVICEMAP\\SERIALCOMM\x00") };
 	let device_map = match RegKey::open(winreg::HKEY_LOCAL_MACHINE, subkey, winnt::KEY_READ) {
 		Ok(x) => x,
 		Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {
 			// The registry key doesn't exist until a serial port was available at-least once.
-			return Ok(Vec::new());
+			return Ok(());
 		},
 		Err(e) => return Err(e),
 	};
 
 	let (value_count, max_value_name_len, max_value_data_len) = device_map.get_value_info()?;
 
-	let mut entries = Vec::with_capacity(16);
 	for i in 0..value_count {
-		let mut name = match device_map.get_string_value(i, max_value_name_len, max_value_data_len) {
-			Ok(Some((_name, data))) => data,
+		let (device, name) = match device_map.get_string_value(i, max_value_name_len, max_value_data_len) {
+			Ok(Some((device, name))) => (device, name),
 			Ok(None) => continue,
 			Err(_) => continue,
 		};
-		if let Some(i) = name.iter().rposition(|&b| b != 0) {
-			name.truncate(i + 1);
-			if let Ok(name) = String::from_utf8(name) {
-				entries.push(name.into());
+		let name = match string_from_registry(name) {
+			Some(x) => x,
+			None => continue,
+		};
+		// The value name is the kernel device, like `\Device\Serial0` or `\Device\BthModem0`.
+		let kind = match string_from_registry(device) {
+			Some(device) if device.contains("BthModem") => crate::PortKind::Bluetooth,
+			_ => crate::PortKind::Unknown,
+		};
+		add_port(ports, name, kind);
+	}
+
+	Ok(())
+}
+
+/// Add the ports of all modems in the modem device class.
+///
+/// Each modem has a subkey in the class key, with an `AttachedTo` value holding the name of the COM port.
+fn enumerate_modems(ports: &mut Vec<crate::PortInfo>) -> std::io::Result<()> {
+	let subkey = unsafe {
+		CStr::from_bytes_with_nul_unchecked(
+			b"SYSTEM\\CurrentControlSet\\Control\\Class\\{4D36E96D-E325-11CE-BFC1-08002BE10318}\x00",
+		)
+	};
+	let attached_to = unsafe { CStr::from_bytes_with_nul_unchecked(b"AttachedTo\x00") };
+	let class = RegKey::open(winreg::HKEY_LOCAL_MACHINE, subkey, winnt::KEY_READ)?;
+	for modem in class.subkey_names()? {
+		// Some subkeys (like `Properties`) are not accessible, so ignore errors.
+		let modem = match RegKey::open(class.key, &modem, winnt::KEY_READ) {
+			Ok(x) => x,
+			Err(_) => continue,
+		};
+		if let Ok(Some(name)) = modem.get_named_string_value(attached_to) {
+			add_port(ports, name, crate::PortKind::Modem);
+		}
+	}
+	Ok(())
+}
+
+/// Add the virtual COM ports of all Bluetooth devices.
+///
+/// Bluetooth devices are enumerated under `HKLM\SYSTEM\CurrentControlSet\Enum\BTHENUM`.
+/// Devices with a virtual COM port have a `PortName` value in the `Device Parameters` subkey of the device instance.
+fn enumerate_bluetooth(ports: &mut Vec<crate::PortInfo>) -> std::io::Result<()> {
+	enumerate_device_instances(ports, "BTHENUM", |ports, name| {
+		Some(add_port(ports, name, crate::PortKind::Bluetooth))
+	})
+}
+
+/// Set the device instance ID of ports that belong to a USB, FTDI or ACPI device.
+///
+/// The registry also has entries for devices that are not currently connected,
+/// so this only updates ports that are already in the list.
+fn enumerate_instance_ids(ports: &mut Vec<crate::PortInfo>) {
+	for enumerator in ["USB", "FTDIBUS", "ACPI"] {
+		let _ = enumerate_device_instances(ports, enumerator, |ports, name| find_port(ports, &name));
+	}
+}
+
+/// Walk all device instances of an enumerator under `HKLM\SYSTEM\CurrentControlSet\Enum`.
+///
+/// For each instance with a `PortName` value in the `Device Parameters` subkey,
+/// `get_port` is called with the port name to get the matching entry in the list.
+/// The device instance ID is then stored in that entry, unless it already has one.
+fn enumerate_device_instances<F>(
+	ports: &mut Vec<crate::PortInfo>,
+	enumerator: &str,
+	mut get_port: F,
+) -> std::io::Result<()>
+where
+	F: FnMut(&mut Vec<crate::PortInfo>, String) -> Option<&mut crate::PortInfo>,
+{
+	let subkey = CString::new(format!("SYSTEM\\CurrentControlSet\\Enum\\{enumerator}"))?;
+	let port_name = unsafe { CStr::from_bytes_with_nul_unchecked(b"PortName\x00") };
+	let devices = RegKey::open(winreg::HKEY_LOCAL_MACHINE, &subkey, winnt::KEY_READ)?;
+	for device_name in devices.subkey_names()? {
+		let device = match RegKey::open(devices.key, &device_name, winnt::KEY_READ) {
+			Ok(x) => x,
+			Err(_) => continue,
+		};
+		let instances = match device.subkey_names() {
+			Ok(x) => x,
+			Err(_) => continue,
+		};
+		for instance in instances {
+			let id = format!("{}\\{}\\{}", enumerator, device_name.to_string_lossy(), instance.to_string_lossy());
+			let mut path = instance.into_bytes();
+			path.extend_from_slice(b"\\Device Parameters");
+			let path = match CString::new(path) {
+				Ok(x) => x,
+				Err(_) => continue,
+			};
+			let parameters = match RegKey::open(device.key, &path, winnt::KEY_READ) {
+				Ok(x) => x,
+				Err(_) => continue,
+			};
+			if let Ok(Some(name)) = parameters.get_named_string_value(port_name) {
+				if let Some(port) = get_port(ports, name) {
+					if port.id.is_none() {
+						port.id = Some(id);
+					}
+				}
 			}
 		}
 	}
+	Ok(())
+}
+
+/// Find a port in the list by name.
+fn find_port<'a>(ports: &'a mut [crate::PortInfo], name: &str) -> Option<&'a mut crate::PortInfo> {
+	ports
+		.iter_mut()
+		.find(|port| port.path.to_str().is_some_and(|path| path.eq_ignore_ascii_case(name)))
+}
+
+/// Add a port to the list, or update the kind of the port if it is already in the list.
+fn add_port(ports: &mut Vec<crate::PortInfo>, name: String, kind: crate::PortKind) -> &mut crate::PortInfo {
+	match ports.iter().position(|port| port.path.to_str().is_some_and(|path| path.eq_ignore_ascii_case(&name))) {
+		Some(index) => {
+			let port = &mut ports[index];
+			if port.kind == crate::PortKind::Unknown {
+				port.kind = kind;
+			}
+			port
+		},
+		None => {
+			ports.push(crate::PortInfo::new(name, kind));
+			ports.last_mut().unwrap()
+		},
+	}
+}
 
-	Ok(entries)
+/// Convert string data from the registry to a [`String`], removing trailing null bytes.
+fn string_from_registry(mut data: Vec<u8>) -> Option<String> {
+	let end = data.iter().rposition(|&b| b != 0)?;
+	data.truncate(end + 1);
+	String::from_utf8(data).ok()
 }
@@ -0,0 +1,229 @@
+//! Raw TCP serial server transport.
+//!
+//! Many serial device servers and software like `ser2net` can forward a serial port over a plain TCP connection,
+//! without any control channel.
+//! The [`TcpSerialPort`] reads and writes the raw data of such a connection,
+//! and it implements [`SerialTransport`][crate::SerialTransport].
+//!
+//! Because there is no control channel, the serial port settings must be configured on the server,
+//! and the control lines can not be used.
+//! If your server supports it, consider using the [`rfc2217`][crate::rfc2217] module instead.
+//!
+//! # Example
+//! ```no_run
+//! # fn example() -> std::io::Result<()> {
+//! use serial2::{SerialPort, SerialTransport};
+//! use serial2::tcp::TcpSerialPort;
+//!
+//! fn open(name: &str) -> std::io::Result<Box<dyn SerialTransport>> {
+//!     match name.strip_prefix("tcp://") {
+//!         Some(address) => Ok(Box::new(TcpSerialPort::connect(address)?)),
+//!         None => Ok(Box::new(SerialPort::open(name, 115200)?)),
+//!     }
+//! }
+//!
+//! let port = open("tcp://192.168.1.10:4001")?;
+//! port.write_all(b"Hello!")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::util::{lock, map_timeout_error, socket_timeout};
+
+/// A serial port forwarded over a raw TCP connection.
+///
+/// See the [module documentation][self] for more information.
+pub struct TcpSerialPort {
+	stream: TcpStream,
+	read_timeout: Mutex<Duration>,
+	write_timeout: Mutex<Duration>,
+}
+
+impl TcpSerialPort {
+	/// Connect to a raw TCP serial server.
+	pub fn connect(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+		Self::from_stream(TcpStream::connect(address)?)
+	}
+
+	/// Use an existing TCP connection to a raw TCP serial server.
+	pub fn from_stream(stream: TcpStream) -> std::io::Result<Self> {
+		stream.set_nodelay(true)?;
+		let port = Self {
+			stream,
+			read_timeout: Mutex::new(Duration::ZERO),
+			write_timeout: Mutex::new(Duration::ZERO),
+		};
+		let default_timeout = Duration::from_millis(crate::sys::DEFAULT_TIMEOUT_MS.into());
+		port.set_read_timeout(default_timeout)?;
+		port.set_write_timeout(default_timeout)?;
+		Ok(port)
+	}
+
+	/// Get a reference to the underlying TCP stream.
+	pub fn get_ref(&self) -> &TcpStream {
+		&self.stream
+	}
+
+	/// Read bytes from the connection.
+	///
+	/// This blocks until data is available, or until the read timeout expires.
+	/// Returns `Ok(0)` if the connection was closed by the server.
+	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		(&self.stream).read(buf).map_err(map_timeout_error)
+	}
+
+	/// Read the exact number of bytes required to fill the buffer.
+	pub fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+		(&self.stream).read_exact(buf).map_err(map_timeout_error)
+	}
+
+	/// Write bytes to the connection.
+	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		(&self.stream).write(buf).map_err(map_timeout_error)
+	}
+
+	/// Write all bytes to the connection.
+	pub fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+		(&self.stream).write_all(buf).map_err(map_timeout_error)
+	}
+
+	/// Flush all data queued to be written.
+	///
+	/// Note that this does not wait for the server to transmit the data on the serial port.
+	pub fn flush(&self) -> std::io::Result<()> {
+		(&self.stream).flush()
+	}
+
+	/// Set the read timeout.
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.stream.set_read_timeout(Some(socket_timeout(timeout)))?;
+		*lock(&self.read_timeout) = timeout;
+		Ok(())
+	}
+
+	/// Get the read timeout.
+	pub fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		Ok(*lock(&self.read_timeout))
+	}
+
+	/// Set the write timeout.
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.stream.set_write_timeout(Some(socket_timeout(timeout)))?;
+		*lock(&self.write_timeout) = timeout;
+		Ok(())
+	}
+
+	/// Get the write timeout.
+	pub fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		Ok(*lock(&self.write_timeout))
+	}
+
+	/// Discard all data that has been received but not read yet.
+	///
+	/// There is no control channel, so the buffers of the server can not be discarded.
+	pub fn discard_buffers(&self) -> std::io::Result<()> {
+		// Only read the data that is already available, so the socket can stay in blocking mode.
+		// Switching to non-blocking mode would also affect reads and writes in other threads.
+		let mut buffer = [0; 1024];
+		loop {
+			let available = bytes_available(&self.stream)?;
+			if available == 0 {
+				return Ok(());
+			}
+			let len = available.min(buffer.len());
+			match (&self.stream).read(&mut buffer[..len]) {
+				Ok(0) => return Ok(()),
+				Ok(_) => continue,
+				// Another thread may have read the data first.
+				Err(e) if e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut => {
+					return Ok(())
+				},
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+	}
+
+	/// Control lines are not available on a raw TCP connection: this always returns an error.
+	pub fn set_rts(&self, _state: bool) -> std::io::Result<()> {
+		Err(no_control_lines())
+	}
+
+	/// Control lines are not available on a raw TCP connection: this always returns an error.
+	pub fn read_cts(&self) -> std::io::Result<bool> {
+		Err(no_control_lines())
+	}
+
+	/// Control lines are not available on a raw TCP connection: this always returns an error.
+	pub fn set_dtr(&self, _state: bool) -> std::io::Result<()> {
+		Err(no_control_lines())
+	}
+
+	/// Control lines are not available on a raw TCP connection: this always returns an error.
+	pub fn read_dsr(&self) -> std::io::Result<bool> {
+		Err(no_control_lines())
+	}
+
+	/// Control lines are not available on a raw TCP connection: this always returns an error.
+	pub fn read_ri(&self) -> std::io::Result<bool> {
+		Err(no_control_lines())
+	}
+
+	/// Control lines are not available on a raw TCP connection: this always returns an error.
+	pub fn read_cd(&self) -> std::io::Result<bool> {
+		Err(no_control_lines())
+	}
+}
+
+impl Drop for TcpSerialPort {
+	fn drop(&mut self) {
+		let _ = self.stream.shutdown(Shutdown::Both);
+	}
+}
+
+impl std::fmt::Debug for TcpSerialPort {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("TcpSerialPort")
+			.field("peer_addr", &self.stream.peer_addr().ok())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Get the number of bytes that can be read from a TCP stream without blocking.
+#[cfg(unix)]
+fn bytes_available(stream: &TcpStream) -> std::io::Result<usize> {
+	use std::os::unix::io::AsRawFd;
+	let mut available: libc::c_int = 0;
+	unsafe {
+		if libc::ioctl(stream.as_raw_fd(), libc::FIONREAD, &mut available) == -1 {
+			return Err(std::io::Error::last_os_error());
+		}
+	}
+	Ok(available.try_into().unwrap_or(0))
+}
+
+/// Get the number of bytes that can be read from a TCP stream without blocking.
+#[cfg(windows)]
+fn bytes_available(stream: &TcpStream) -> std::io::Result<usize> {
+	use std::os::windows::io::AsRawSocket;
+	use winapi::um::winsock2::{ioctlsocket, WSAGetLastError, FIONREAD, SOCKET, SOCKET_ERROR};
+	let mut available: winapi::ctypes::c_ulong = 0;
+	unsafe {
+		if ioctlsocket(stream.as_raw_socket() as SOCKET, FIONREAD, &mut available) == SOCKET_ERROR {
+			return Err(std::io::Error::from_raw_os_error(WSAGetLastError()));
+		}
+	}
+	Ok(available.try_into().unwrap_or(usize::MAX))
+}
+
+fn no_control_lines() -> std::io::Error {
+	std::io::Error::new(
+		std::io::ErrorKind::Unsupported,
+		"control lines are not available on a raw TCP connection",
+	)
+}
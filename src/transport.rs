@@ -0,0 +1,307 @@
+use std::time::{Duration, Instant};
+
+use crate::SerialPort;
+
+/// Object-safe trait for serial port like transports.
+///
+/// This trait covers reading, writing, timeouts and control lines.
+/// It is implemented by [`SerialPort`], and it can be implemented by other transports,
+/// such as mock serial ports for testing or network bridges.
+///
+/// The trait is object-safe, so you can write code that uses a `Box<dyn SerialTransport>`
+/// and decide at runtime which transport to use.
+///
+/// All functions take `&self`, so a transport can be used from multiple threads at the same time,
+/// just like [`SerialPort`].
+///
+/// # Example
+/// ```no_run
+/// # fn example() -> std::io::Result<()> {
+/// use serial2::{SerialPort, SerialTransport};
+///
+/// fn ping(transport: &dyn SerialTransport) -> std::io::Result<()> {
+///     transport.write_all(b"PING\n")?;
+///     let mut buffer = [0; 5];
+///     transport.read_exact(&mut buffer)
+/// }
+///
+/// let port = SerialPort::open("/dev/ttyUSB0", 115200)?;
+/// ping(&port)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait SerialTransport {
+	/// Read bytes from the transport.
+	///
+	/// See [`SerialPort::read()`] for more information.
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize>;
+
+	/// Write bytes to the transport.
+	///
+	/// See [`SerialPort::write()`] for more information.
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize>;
+
+	/// Flush all data queued to be written.
+	///
+	/// See [`SerialPort::flush()`] for more information.
+	fn flush(&self) -> std::io::Result<()>;
+
+	/// Read the exact number of bytes required to fill the buffer.
+	///
+	/// See [`SerialPort::read_exact()`] for more information.
+	fn read_exact(&self, mut buf: &mut [u8]) -> std::io::Result<()> {
+		while !buf.is_empty() {
+			match self.read(buf) {
+				Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+				Ok(n) => buf = &mut buf[n..],
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Write all bytes to the transport.
+	///
+	/// See [`SerialPort::write_all()`] for more information.
+	fn write_all(&self, mut buf: &[u8]) -> std::io::Result<()> {
+		while !buf.is_empty() {
+			match self.write(buf) {
+				Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+				Ok(n) => buf = &buf[n..],
+				Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(())
+	}
+
+	/// Set the read timeout.
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()>;
+
+	/// Get the read timeout.
+	fn get_read_timeout(&self) -> std::io::Result<Duration>;
+
+	/// Set the write timeout.
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()>;
+
+	/// Get the write timeout.
+	fn get_write_timeout(&self) -> std::io::Result<Duration>;
+
+	/// Discard the kernel input and output buffers.
+	fn discard_buffers(&self) -> std::io::Result<()>;
+
+	/// Set the state of the Ready To Send line.
+	fn set_rts(&self, state: bool) -> std::io::Result<()>;
+
+	/// Read the state of the Clear To Send line.
+	fn read_cts(&self) -> std::io::Result<bool>;
+
+	/// Set the state of the Data Terminal Ready line.
+	fn set_dtr(&self, state: bool) -> std::io::Result<()>;
+
+	/// Read the state of the Data Set Ready line.
+	fn read_dsr(&self) -> std::io::Result<bool>;
+
+	/// Read the state of the Ring Indicator line.
+	fn read_ri(&self) -> std::io::Result<bool>;
+
+	/// Read the state of the Carrier Detect line.
+	fn read_cd(&self) -> std::io::Result<bool>;
+
+	/// Get the current time of the clock used by the transport.
+	///
+	/// The wrappers in this crate use this clock for their delays and timestamps,
+	/// so that they follow the clock of the wrapped transport.
+	/// The default implementation uses [`Instant::now()`].
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+
+	/// Sleep for a duration on the clock used by the transport.
+	///
+	/// The default implementation uses [`std::thread::sleep()`].
+	fn sleep(&self, duration: Duration) {
+		std::thread::sleep(duration)
+	}
+}
+
+/// Implement [`SerialTransport`] by forwarding to inherent functions with the same name.
+///
+/// Additional functions to put in the implementation can be given in braces after the type.
+macro_rules! impl_serial_transport {
+	($type:ty) => {
+		impl_serial_transport!($type, {});
+	};
+	($type:ty, { $($extra:tt)* }) => {
+		impl SerialTransport for $type {
+			fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+				<$type>::read(self, buf)
+			}
+
+			fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+				<$type>::write(self, buf)
+			}
+
+			fn flush(&self) -> std::io::Result<()> {
+				<$type>::flush(self)
+			}
+
+			fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+				<$type>::read_exact(self, buf)
+			}
+
+			fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+				<$type>::write_all(self, buf)
+			}
+
+			fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+				<$type>::set_read_timeout(self, timeout)
+			}
+
+			fn get_read_timeout(&self) -> std::io::Result<Duration> {
+				<$type>::get_read_timeout(self)
+			}
+
+			fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+				<$type>::set_write_timeout(self, timeout)
+			}
+
+			fn get_write_timeout(&self) -> std::io::Result<Duration> {
+				<$type>::get_write_timeout(self)
+			}
+
+			fn discard_buffers(&self) -> std::io::Result<()> {
+				<$type>::discard_buffers(self)
+			}
+
+			fn set_rts(&self, state: bool) -> std::io::Result<()> {
+				<$type>::set_rts(self, state)
+			}
+
+			fn read_cts(&self) -> std::io::Result<bool> {
+				<$type>::read_cts(self)
+			}
+
+			fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+				<$type>::set_dtr(self, state)
+			}
+
+			fn read_dsr(&self) -> std::io::Result<bool> {
+				<$type>::read_dsr(self)
+			}
+
+			fn read_ri(&self) -> std::io::Result<bool> {
+				<$type>::read_ri(self)
+			}
+
+			fn read_cd(&self) -> std::io::Result<bool> {
+				<$type>::read_cd(self)
+			}
+
+			$($extra)*
+		}
+	};
+}
+
+impl_serial_transport!(SerialPort);
+
+#[cfg(any(feature = "doc", feature = "mock"))]
+impl_serial_transport!(crate::mock::MockSerialPort, {
+	fn now(&self) -> Instant {
+		crate::mock::MockSerialPort::now(self)
+	}
+
+	fn sleep(&self, duration: Duration) {
+		crate::mock::MockSerialPort::sleep(self, duration)
+	}
+});
+
+#[cfg(any(feature = "doc", feature = "rfc2217"))]
+impl_serial_transport!(crate::rfc2217::Rfc2217Port);
+
+#[cfg(any(feature = "doc", feature = "tcp"))]
+impl_serial_transport!(crate::tcp::TcpSerialPort);
+
+/// Implement [`SerialTransport`] for smart pointers by forwarding to the pointee.
+macro_rules! impl_serial_transport_deref {
+	($($type:tt)*) => {
+		impl<T: SerialTransport + ?Sized> SerialTransport for $($type)* {
+			fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+				(**self).read(buf)
+			}
+
+			fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+				(**self).write(buf)
+			}
+
+			fn flush(&self) -> std::io::Result<()> {
+				(**self).flush()
+			}
+
+			fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+				(**self).read_exact(buf)
+			}
+
+			fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+				(**self).write_all(buf)
+			}
+
+			fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+				(**self).set_read_timeout(timeout)
+			}
+
+			fn get_read_timeout(&self) -> std::io::Result<Duration> {
+				(**self).get_read_timeout()
+			}
+
+			fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+				(**self).set_write_timeout(timeout)
+			}
+
+			fn get_write_timeout(&self) -> std::io::Result<Duration> {
+				(**self).get_write_timeout()
+			}
+
+			fn discard_buffers(&self) -> std::io::Result<()> {
+				(**self).discard_buffers()
+			}
+
+			fn set_rts(&self, state: bool) -> std::io::Result<()> {
+				(**self).set_rts(state)
+			}
+
+			fn read_cts(&self) -> std::io::Result<bool> {
+				(**self).read_cts()
+			}
+
+			fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+				(**self).set_dtr(state)
+			}
+
+			fn read_dsr(&self) -> std::io::Result<bool> {
+				(**self).read_dsr()
+			}
+
+			fn read_ri(&self) -> std::io::Result<bool> {
+				(**self).read_ri()
+			}
+
+			fn read_cd(&self) -> std::io::Result<bool> {
+				(**self).read_cd()
+			}
+
+			fn now(&self) -> Instant {
+				(**self).now()
+			}
+
+			fn sleep(&self, duration: Duration) {
+				(**self).sleep(duration)
+			}
+		}
+	};
+}
+
+impl_serial_transport_deref!(&T);
+impl_serial_transport_deref!(Box<T>);
+impl_serial_transport_deref!(std::sync::Arc<T>);
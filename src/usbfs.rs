@@ -0,0 +1,750 @@
+//! Serial communication with USB CDC-ACM devices through a usbfs file descriptor.
+//!
+//! On Android, unrooted apps can not open TTY devices like `/dev/ttyACM0`.
+//! The only way to talk to a USB serial device is through the Java USB Host API:
+//! `UsbManager.openDevice()` returns a `UsbDeviceConnection`,
+//! and `UsbDeviceConnection.getFileDescriptor()` gives a file descriptor for the usbfs device node.
+//!
+//! That file descriptor is not a TTY, so it can not be used with [`SerialPort`][crate::SerialPort]:
+//! all termios functions fail on it, and plain reads and writes do not transfer serial data.
+//! Instead, [`UsbCdcPort`] talks to the device with usbfs ioctls,
+//! using the bulk endpoints of the CDC data interface for data and CDC control requests for the line settings.
+//!
+//! This also works on regular Linux systems with a file descriptor for a device node in `/dev/bus/usb`,
+//! as long as the process has permission to detach the kernel driver.
+//!
+//! Only devices that implement the standard USB CDC-ACM class are supported.
+//! Vendor specific USB serial chips (such as FTDI, CP210x, PL2303 and CH340) need their own protocol.
+
+use std::io::{IoSlice, IoSliceMut};
+use std::os::raw::{c_int, c_uint, c_void};
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::{AsRawFd, OwnedFd};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::util::lock;
+use crate::{CharSize, FlowControl, IntoSettings, Parity, SerialTransport, Settings, StopBits};
+
+/// A serial port for a USB CDC-ACM device, accessed through a usbfs file descriptor.
+///
+/// See the [module documentation][self] for more information.
+///
+/// Reading the state of the CTS, DSR, RI and CD lines is not supported,
+/// because CDC-ACM devices only report them through asynchronous notifications.
+pub struct UsbCdcPort {
+	file: std::fs::File,
+	interfaces: CdcInterfaces,
+	read_timeout_ms: AtomicU32,
+	write_timeout_ms: AtomicU32,
+
+	/// The currently applied line coding.
+	line_coding: Mutex<LineCoding>,
+
+	/// The state of the DTR and RTS lines, as sent with `SET_CONTROL_LINE_STATE`.
+	control_lines: Mutex<u16>,
+
+	/// Data received in a bulk transfer that did not fit in the buffer of the reader.
+	pending: Mutex<PendingData>,
+}
+
+impl UsbCdcPort {
+	/// Use a usbfs file descriptor for a USB CDC-ACM device as serial port.
+	///
+	/// This claims the CDC interfaces of the device, detaching the kernel driver if needed,
+	/// and applies the given settings.
+	/// Only the baud rate, character size, parity and stop bits are used.
+	/// Flow control is not supported by CDC-ACM and must be disabled.
+	///
+	/// On Android, the file descriptor can be obtained with `UsbDeviceConnection.getFileDescriptor()`.
+	/// Note that the `UsbDeviceConnection` still owns the file descriptor:
+	/// use `dup()` or `ParcelFileDescriptor.detachFd()` to get a file descriptor you can give away.
+	pub fn from_fd(fd: OwnedFd, settings: impl IntoSettings) -> std::io::Result<Self> {
+		let file = std::fs::File::from(fd);
+		let interfaces = CdcInterfaces::find(&read_descriptors(&file)?)?;
+		claim_interface(&file, interfaces.control_interface)?;
+		if let Err(e) = claim_interface(&file, interfaces.data_interface) {
+			release_interface(&file, interfaces.control_interface);
+			return Err(e);
+		}
+
+		let port = Self {
+			file,
+			interfaces,
+			read_timeout_ms: AtomicU32::new(crate::sys::DEFAULT_TIMEOUT_MS),
+			write_timeout_ms: AtomicU32::new(crate::sys::DEFAULT_TIMEOUT_MS),
+			line_coding: Mutex::new(LineCoding::default()),
+			control_lines: Mutex::new(0),
+			pending: Mutex::new(PendingData::default()),
+		};
+		let mut port_settings = port.get_configuration()?;
+		settings.apply_to_settings(&mut port_settings)?;
+		port.set_configuration(&port_settings)?;
+		Ok(port)
+	}
+
+	/// Get the current configuration of the serial port.
+	///
+	/// CDC-ACM devices can not report their settings, so this returns the settings applied last,
+	/// or 9600 baud 8N1 if no settings have been applied yet.
+	pub fn get_configuration(&self) -> std::io::Result<Settings> {
+		let line_coding = *lock(&self.line_coding);
+		let mut settings = Settings {
+			inner: crate::sys::Settings {
+				termios: unsafe { std::mem::zeroed() },
+				unsupported_parity: None,
+			},
+		};
+		settings.set_raw();
+		settings.set_baud_rate(line_coding.baud_rate)?;
+		settings.set_char_size(line_coding.char_size);
+		settings.set_parity(line_coding.parity);
+		settings.set_stop_bits(line_coding.stop_bits);
+		Ok(settings)
+	}
+
+	/// Apply the baud rate, character size, parity and stop bits from the settings to the device.
+	///
+	/// Returns an error if the settings enable flow control, because CDC-ACM does not support configuring it.
+	pub fn set_configuration(&self, settings: &Settings) -> std::io::Result<()> {
+		if settings.get_flow_control()? != FlowControl::None {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"USB CDC-ACM devices do not support configuring flow control",
+			));
+		}
+		let line_coding = LineCoding {
+			baud_rate: settings.get_baud_rate()?,
+			char_size: settings.get_char_size()?,
+			parity: settings.get_parity()?,
+			stop_bits: settings.get_stop_bits()?,
+		};
+		let mut current = lock(&self.line_coding);
+		let mut data = line_coding.encode();
+		self.control_request(CDC_SET_LINE_CODING, 0, &mut data)?;
+		*current = line_coding;
+		Ok(())
+	}
+
+	/// Get the numbers of the CDC control interface and the CDC data interface of the device.
+	pub fn interfaces(&self) -> (u8, u8) {
+		(self.interfaces.control_interface, self.interfaces.data_interface)
+	}
+
+	/// Read bytes from the device.
+	///
+	/// This waits at most for the read timeout for data to arrive.
+	///
+	/// Note that usbfs discards the data of a bulk transfer that times out.
+	/// This only happens if the device sends exactly a multiple of the maximum packet size and then stops sending,
+	/// which CDC-ACM devices should avoid by sending a zero length packet.
+	pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		if buf.is_empty() {
+			return Ok(0);
+		}
+
+		let mut pending = lock(&self.pending);
+		if !pending.is_empty() {
+			return Ok(pending.take(buf));
+		}
+
+		// A bulk IN transfer must be a multiple of the maximum packet size,
+		// or the device may send more data than fits in the buffer.
+		let packet_size = usize::from(self.interfaces.max_packet_size_in.max(1));
+		let len = buf.len().div_ceil(packet_size) * packet_size;
+		pending.data.resize(len, 0);
+		pending.start = 0;
+
+		let deadline = Instant::now() + Duration::from_millis(self.read_timeout_ms.load(Ordering::Relaxed).into());
+		loop {
+			let timeout = deadline.saturating_duration_since(Instant::now());
+			let read = self.bulk_transfer(self.interfaces.endpoint_in, &mut pending.data, timeout);
+			match read {
+				// Skip zero length packets, they only mark the end of a transfer.
+				Ok(0) if Instant::now() < deadline => continue,
+				Ok(0) => {
+					pending.data.clear();
+					return Err(std::io::ErrorKind::TimedOut.into());
+				},
+				Ok(read) => {
+					pending.data.truncate(read);
+					return Ok(pending.take(buf));
+				},
+				Err(e) => {
+					pending.data.clear();
+					return Err(e);
+				},
+			}
+		}
+	}
+
+	/// Read bytes from the device into multiple buffers.
+	///
+	/// usbfs does not support vectored transfers, so this only reads into the first non-empty buffer.
+	pub fn read_vectored(&self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		match buf.iter_mut().find(|buf| !buf.is_empty()) {
+			Some(buf) => self.read(buf),
+			None => Ok(0),
+		}
+	}
+
+	/// Read the exact number of bytes required to fill the buffer from the device.
+	pub fn read_exact(&self, buf: &mut [u8]) -> std::io::Result<()> {
+		SerialTransport::read_exact(self, buf)
+	}
+
+	/// Write bytes to the device.
+	///
+	/// This waits at most for the write timeout for the device to accept the data.
+	pub fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		if buf.is_empty() {
+			return Ok(0);
+		}
+		let timeout = Duration::from_millis(self.write_timeout_ms.load(Ordering::Relaxed).into());
+		// The kernel only reads from the buffer for OUT transfers, but the ioctl needs a mutable pointer.
+		let mut data = buf.to_vec();
+		self.bulk_transfer(self.interfaces.endpoint_out, &mut data, timeout)
+	}
+
+	/// Write bytes to the device from multiple buffers.
+	///
+	/// usbfs does not support vectored transfers, so this only writes the first non-empty buffer.
+	pub fn write_vectored(&self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		match buf.iter().find(|buf| !buf.is_empty()) {
+			Some(buf) => self.write(buf),
+			None => Ok(0),
+		}
+	}
+
+	/// Write all bytes to the device.
+	pub fn write_all(&self, buf: &[u8]) -> std::io::Result<()> {
+		SerialTransport::write_all(self, buf)
+	}
+
+	/// Flush all data queued to be written.
+	///
+	/// Bulk transfers complete only after the device accepted the data,
+	/// so there is never any data queued on the host side.
+	pub fn flush(&self) -> std::io::Result<()> {
+		Ok(())
+	}
+
+	/// Set the read timeout.
+	pub fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.read_timeout_ms.store(timeout_to_ms(timeout), Ordering::Relaxed);
+		Ok(())
+	}
+
+	/// Get the read timeout.
+	pub fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		Ok(Duration::from_millis(self.read_timeout_ms.load(Ordering::Relaxed).into()))
+	}
+
+	/// Set the write timeout.
+	pub fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		self.write_timeout_ms.store(timeout_to_ms(timeout), Ordering::Relaxed);
+		Ok(())
+	}
+
+	/// Get the write timeout.
+	pub fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		Ok(Duration::from_millis(self.write_timeout_ms.load(Ordering::Relaxed).into()))
+	}
+
+	/// Discard data that was received but not read yet.
+	///
+	/// Data buffered inside the device can not be discarded.
+	pub fn discard_buffers(&self) -> std::io::Result<()> {
+		lock(&self.pending).data.clear();
+		Ok(())
+	}
+
+	/// Set the state of the Request To Send line.
+	pub fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.set_control_line(CDC_CONTROL_LINE_RTS, state)
+	}
+
+	/// Read the state of the Clear To Send line.
+	///
+	/// This is not supported for CDC-ACM devices and always returns an error.
+	pub fn read_cts(&self) -> std::io::Result<bool> {
+		Err(unsupported_control_line())
+	}
+
+	/// Set the state of the Data Terminal Ready line.
+	pub fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.set_control_line(CDC_CONTROL_LINE_DTR, state)
+	}
+
+	/// Read the state of the Data Set Ready line.
+	///
+	/// This is not supported for CDC-ACM devices and always returns an error.
+	pub fn read_dsr(&self) -> std::io::Result<bool> {
+		Err(unsupported_control_line())
+	}
+
+	/// Read the state of the Ring Indicator line.
+	///
+	/// This is not supported for CDC-ACM devices and always returns an error.
+	pub fn read_ri(&self) -> std::io::Result<bool> {
+		Err(unsupported_control_line())
+	}
+
+	/// Read the state of the Carrier Detect line.
+	///
+	/// This is not supported for CDC-ACM devices and always returns an error.
+	pub fn read_cd(&self) -> std::io::Result<bool> {
+		Err(unsupported_control_line())
+	}
+
+	/// Set or clear a bit in the control line state and send it to the device.
+	fn set_control_line(&self, bit: u16, state: bool) -> std::io::Result<()> {
+		let mut control_lines = lock(&self.control_lines);
+		let new_state = if state { *control_lines | bit } else { *control_lines & !bit };
+		self.control_request(CDC_SET_CONTROL_LINE_STATE, new_state, &mut [])?;
+		*control_lines = new_state;
+		Ok(())
+	}
+
+	/// Send a class specific control request to the CDC control interface.
+	fn control_request(&self, request: u8, value: u16, data: &mut [u8]) -> std::io::Result<()> {
+		let mut transfer = CtrlTransfer {
+			request_type: CDC_REQUEST_TYPE,
+			request,
+			value,
+			index: self.interfaces.control_interface.into(),
+			length: data.len() as u16,
+			timeout: self.write_timeout_ms.load(Ordering::Relaxed).max(1),
+			data: data.as_mut_ptr().cast(),
+		};
+		unsafe {
+			check(libc::ioctl(self.file.as_raw_fd(), USBDEVFS_CONTROL as _, &mut transfer))?;
+		}
+		Ok(())
+	}
+
+	/// Perform a bulk transfer on an endpoint.
+	fn bulk_transfer(&self, endpoint: u8, data: &mut [u8], timeout: Duration) -> std::io::Result<usize> {
+		let mut transfer = BulkTransfer {
+			endpoint: endpoint.into(),
+			len: data.len().try_into().unwrap_or(c_uint::MAX),
+			// A timeout of zero means "wait forever" for usbfs.
+			timeout: timeout_to_ms(timeout).max(1),
+			data: data.as_mut_ptr().cast(),
+		};
+		let transferred = unsafe { check(libc::ioctl(self.file.as_raw_fd(), USBDEVFS_BULK as _, &mut transfer))? };
+		Ok(transferred as usize)
+	}
+}
+
+impl Drop for UsbCdcPort {
+	fn drop(&mut self) {
+		release_interface(&self.file, self.interfaces.data_interface);
+		release_interface(&self.file, self.interfaces.control_interface);
+	}
+}
+
+impl std::fmt::Debug for UsbCdcPort {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("UsbCdcPort")
+			.field("fd", &self.file.as_raw_fd())
+			.field("control_interface", &self.interfaces.control_interface)
+			.field("data_interface", &self.interfaces.data_interface)
+			.finish_non_exhaustive()
+	}
+}
+
+impl std::io::Read for UsbCdcPort {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		UsbCdcPort::read(self, buf)
+	}
+
+	fn read_vectored(&mut self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		UsbCdcPort::read_vectored(self, buf)
+	}
+}
+
+impl std::io::Read for &'_ UsbCdcPort {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		UsbCdcPort::read(self, buf)
+	}
+
+	fn read_vectored(&mut self, buf: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+		UsbCdcPort::read_vectored(self, buf)
+	}
+}
+
+impl std::io::Write for UsbCdcPort {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		UsbCdcPort::write(self, buf)
+	}
+
+	fn write_vectored(&mut self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		UsbCdcPort::write_vectored(self, buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		UsbCdcPort::flush(self)
+	}
+}
+
+impl std::io::Write for &'_ UsbCdcPort {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		UsbCdcPort::write(self, buf)
+	}
+
+	fn write_vectored(&mut self, buf: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		UsbCdcPort::write_vectored(self, buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		UsbCdcPort::flush(self)
+	}
+}
+
+impl SerialTransport for UsbCdcPort {
+	fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+		UsbCdcPort::read(self, buf)
+	}
+
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		UsbCdcPort::write(self, buf)
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		UsbCdcPort::flush(self)
+	}
+
+	fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		UsbCdcPort::set_read_timeout(self, timeout)
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		UsbCdcPort::get_read_timeout(self)
+	}
+
+	fn set_write_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+		UsbCdcPort::set_write_timeout(self, timeout)
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		UsbCdcPort::get_write_timeout(self)
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		UsbCdcPort::discard_buffers(self)
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		UsbCdcPort::set_rts(self, state)
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		UsbCdcPort::read_cts(self)
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		UsbCdcPort::set_dtr(self, state)
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		UsbCdcPort::read_dsr(self)
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		UsbCdcPort::read_ri(self)
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		UsbCdcPort::read_cd(self)
+	}
+}
+
+/// The interfaces and endpoints of a CDC-ACM device.
+#[derive(Debug, Copy, Clone)]
+struct CdcInterfaces {
+	control_interface: u8,
+	data_interface: u8,
+	endpoint_in: u8,
+	endpoint_out: u8,
+	max_packet_size_in: u16,
+}
+
+impl CdcInterfaces {
+	/// Find the CDC interfaces in the descriptors of a device.
+	///
+	/// The descriptors start with the device descriptor, followed by the configuration descriptors.
+	/// Only the first configuration is used.
+	fn find(descriptors: &[u8]) -> std::io::Result<Self> {
+		let mut control_interface = None;
+		let mut data_interface = None;
+		let mut current_interface = None;
+		let mut endpoint_in = None;
+		let mut endpoint_out = None;
+
+		let mut configurations = 0;
+		let mut remaining = descriptors;
+		while let [len, kind, ..] = *remaining {
+			let len = usize::from(len);
+			if len < 2 || len > remaining.len() {
+				return Err(invalid_descriptors());
+			}
+			let (descriptor, rest) = remaining.split_at(len);
+			remaining = rest;
+
+			match (kind, descriptor) {
+				(DESCRIPTOR_CONFIGURATION, _) => {
+					configurations += 1;
+					if configurations > 1 {
+						break;
+					}
+				},
+				(DESCRIPTOR_INTERFACE, &[_, _, number, _alternate, _endpoints, class, subclass, ..]) => {
+					current_interface = Some((number, class));
+					if class == CLASS_CDC && subclass == SUBCLASS_ACM && control_interface.is_none() {
+						control_interface = Some(number);
+					}
+				},
+				(DESCRIPTOR_ENDPOINT, &[_, _, address, attributes, size_low, size_high, ..]) => {
+					let is_data_interface = match (current_interface, data_interface) {
+						(Some((number, CLASS_CDC_DATA)), None) => {
+							data_interface = Some(number);
+							true
+						},
+						(Some((number, CLASS_CDC_DATA)), Some(data_interface)) => number == data_interface,
+						_ => false,
+					};
+					if is_data_interface && attributes & ENDPOINT_TYPE_MASK == ENDPOINT_TYPE_BULK {
+						if address & ENDPOINT_DIR_IN != 0 {
+							endpoint_in.get_or_insert((address, u16::from_le_bytes([size_low, size_high])));
+						} else {
+							endpoint_out.get_or_insert(address);
+						}
+					}
+				},
+				_ => (),
+			}
+		}
+
+		match (control_interface, data_interface, endpoint_in, endpoint_out) {
+			(Some(control_interface), Some(data_interface), Some((endpoint_in, max_packet_size_in)), Some(endpoint_out)) => {
+				Ok(Self {
+					control_interface,
+					data_interface,
+					endpoint_in,
+					endpoint_out,
+					max_packet_size_in,
+				})
+			},
+			_ => Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidData,
+				"the USB device does not have a CDC-ACM interface with bulk endpoints",
+			)),
+		}
+	}
+}
+
+/// The line coding of a CDC-ACM device.
+#[derive(Debug, Copy, Clone)]
+struct LineCoding {
+	baud_rate: u32,
+	char_size: CharSize,
+	parity: Parity,
+	stop_bits: StopBits,
+}
+
+impl Default for LineCoding {
+	fn default() -> Self {
+		Self {
+			baud_rate: 9600,
+			char_size: CharSize::Bits8,
+			parity: Parity::None,
+			stop_bits: StopBits::One,
+		}
+	}
+}
+
+impl LineCoding {
+	/// Encode the line coding as data for the `SET_LINE_CODING` request.
+	fn encode(&self) -> [u8; 7] {
+		let [b0, b1, b2, b3] = self.baud_rate.to_le_bytes();
+		let stop_bits = match self.stop_bits {
+			StopBits::One => 0,
+			StopBits::Two => 2,
+		};
+		let parity = match self.parity {
+			Parity::None => 0,
+			Parity::Odd => 1,
+			Parity::Even => 2,
+			Parity::Mark => 3,
+			Parity::Space => 4,
+		};
+		[b0, b1, b2, b3, stop_bits, parity, self.char_size.as_u8()]
+	}
+}
+
+/// Data received in a bulk transfer that has not been read yet.
+#[derive(Default)]
+struct PendingData {
+	data: Vec<u8>,
+	start: usize,
+}
+
+impl PendingData {
+	fn is_empty(&self) -> bool {
+		self.start >= self.data.len()
+	}
+
+	/// Copy as much pending data as possible into `buf`.
+	fn take(&mut self, buf: &mut [u8]) -> usize {
+		let available = &self.data[self.start..];
+		let len = available.len().min(buf.len());
+		buf[..len].copy_from_slice(&available[..len]);
+		self.start += len;
+		if self.is_empty() {
+			self.data.clear();
+			self.start = 0;
+		}
+		len
+	}
+}
+
+/// Read the device and configuration descriptors from a usbfs file descriptor.
+fn read_descriptors(file: &std::fs::File) -> std::io::Result<Vec<u8>> {
+	let mut descriptors = vec![0; 4096];
+	let len = file.read_at(&mut descriptors, 0)?;
+	descriptors.truncate(len);
+	Ok(descriptors)
+}
+
+/// Claim an interface, detaching the kernel driver if it is bound to the interface.
+fn claim_interface(file: &std::fs::File, interface: u8) -> std::io::Result<()> {
+	let interface = c_uint::from(interface);
+	unsafe {
+		match check(libc::ioctl(file.as_raw_fd(), USBDEVFS_CLAIMINTERFACE as _, &interface)) {
+			Ok(_) => Ok(()),
+			Err(e) if e.raw_os_error() == Some(libc::EBUSY) => {
+				let mut request = UsbIoctl {
+					interface: interface as c_int,
+					ioctl_code: USBDEVFS_DISCONNECT as c_int,
+					data: std::ptr::null_mut(),
+				};
+				check(libc::ioctl(file.as_raw_fd(), USBDEVFS_IOCTL as _, &mut request))?;
+				check(libc::ioctl(file.as_raw_fd(), USBDEVFS_CLAIMINTERFACE as _, &interface))?;
+				Ok(())
+			},
+			Err(e) => Err(e),
+		}
+	}
+}
+
+/// Release a claimed interface, ignoring errors.
+fn release_interface(file: &std::fs::File, interface: u8) {
+	let interface = c_uint::from(interface);
+	unsafe {
+		libc::ioctl(file.as_raw_fd(), USBDEVFS_RELEASEINTERFACE as _, &interface);
+	}
+}
+
+fn timeout_to_ms(timeout: Duration) -> u32 {
+	timeout.as_millis().try_into().unwrap_or(u32::MAX)
+}
+
+fn invalid_descriptors() -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid USB descriptors")
+}
+
+fn unsupported_control_line() -> std::io::Error {
+	std::io::Error::new(
+		std::io::ErrorKind::Unsupported,
+		"reading control lines is not supported for USB CDC-ACM devices",
+	)
+}
+
+fn check(ret: c_int) -> std::io::Result<c_int> {
+	if ret == -1 {
+		Err(std::io::Error::last_os_error())
+	} else {
+		Ok(ret)
+	}
+}
+
+const DESCRIPTOR_CONFIGURATION: u8 = 0x02;
+const DESCRIPTOR_INTERFACE: u8 = 0x04;
+const DESCRIPTOR_ENDPOINT: u8 = 0x05;
+const CLASS_CDC: u8 = 0x02;
+const CLASS_CDC_DATA: u8 = 0x0A;
+const SUBCLASS_ACM: u8 = 0x02;
+const ENDPOINT_DIR_IN: u8 = 0x80;
+const ENDPOINT_TYPE_MASK: u8 = 0x03;
+const ENDPOINT_TYPE_BULK: u8 = 0x02;
+
+/// Host to device, class request, interface recipient.
+const CDC_REQUEST_TYPE: u8 = 0x21;
+const CDC_SET_LINE_CODING: u8 = 0x20;
+const CDC_SET_CONTROL_LINE_STATE: u8 = 0x22;
+const CDC_CONTROL_LINE_DTR: u16 = 0x01;
+const CDC_CONTROL_LINE_RTS: u16 = 0x02;
+
+/// `struct usbdevfs_ctrltransfer` from `linux/usbdevice_fs.h`.
+#[repr(C)]
+struct CtrlTransfer {
+	request_type: u8,
+	request: u8,
+	value: u16,
+	index: u16,
+	length: u16,
+	timeout: u32,
+	data: *mut c_void,
+}
+
+/// `struct usbdevfs_bulktransfer` from `linux/usbdevice_fs.h`.
+#[repr(C)]
+struct BulkTransfer {
+	endpoint: c_uint,
+	len: c_uint,
+	timeout: c_uint,
+	data: *mut c_void,
+}
+
+/// `struct usbdevfs_ioctl` from `linux/usbdevice_fs.h`.
+#[repr(C)]
+struct UsbIoctl {
+	interface: c_int,
+	ioctl_code: c_int,
+	data: *mut c_void,
+}
+
+cfg_if::cfg_if! {
+	if #[cfg(any(
+		target_arch = "mips",
+		target_arch = "mips64",
+		target_arch = "powerpc",
+		target_arch = "powerpc64",
+		target_arch = "sparc",
+		target_arch = "sparc64",
+	))] {
+		const IOC_NONE: u32 = 1;
+		const IOC_READ: u32 = 2;
+		const IOC_WRITE: u32 = 4;
+		const IOC_DIRSHIFT: u32 = 29;
+	} else {
+		const IOC_NONE: u32 = 0;
+		const IOC_WRITE: u32 = 1;
+		const IOC_READ: u32 = 2;
+		const IOC_DIRSHIFT: u32 = 30;
+	}
+}
+
+/// Compute an ioctl request number, like the `_IOC()` macro from `linux/ioctl.h`.
+const fn ioc(direction: u32, number: u32, size: usize) -> u32 {
+	(direction << IOC_DIRSHIFT) | ((size as u32) << 16) | ((b'U' as u32) << 8) | number
+}
+
+const USBDEVFS_CONTROL: u32 = ioc(IOC_READ | IOC_WRITE, 0, std::mem::size_of::<CtrlTransfer>());
+const USBDEVFS_BULK: u32 = ioc(IOC_READ | IOC_WRITE, 2, std::mem::size_of::<BulkTransfer>());
+const USBDEVFS_CLAIMINTERFACE: u32 = ioc(IOC_READ, 15, std::mem::size_of::<c_uint>());
+const USBDEVFS_RELEASEINTERFACE: u32 = ioc(IOC_READ, 16, std::mem::size_of::<c_uint>());
+const USBDEVFS_IOCTL: u32 = ioc(IOC_READ | IOC_WRITE, 18, std::mem::size_of::<UsbIoctl>());
+const USBDEVFS_DISCONNECT: u32 = ioc(IOC_NONE, 22, 0);
@@ -0,0 +1,25 @@
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(any(feature = "doc", feature = "rfc2217", feature = "tcp"))]
+use std::time::Duration;
+
+/// Lock a mutex, ignoring poisoning.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A zero timeout is not allowed for a socket, so use the shortest timeout possible instead.
+#[cfg(any(feature = "doc", feature = "rfc2217", feature = "tcp"))]
+pub(crate) fn socket_timeout(timeout: Duration) -> Duration {
+	timeout.max(Duration::from_nanos(1))
+}
+
+/// Timeouts on sockets are reported as `WouldBlock` on Unix, but we report all timeouts as `TimedOut`.
+#[cfg(any(feature = "doc", feature = "rfc2217", feature = "tcp"))]
+pub(crate) fn map_timeout_error(error: std::io::Error) -> std::io::Error {
+	if error.kind() == std::io::ErrorKind::WouldBlock {
+		std::io::ErrorKind::TimedOut.into()
+	} else {
+		error
+	}
+}
@@ -1,4 +1,4 @@
-use serial2::SerialPort;
+use serial2::{SerialPort, SharedSerialPort};
 use std::io::{Read, Write};
 
 fn assert_read<T: Read>() {}
@@ -10,6 +10,8 @@ fn assert_read_is_implemented() {
 	assert_read::<&SerialPort>();
 	assert_read::<&mut SerialPort>();
 	assert_read::<&mut &SerialPort>();
+	assert_read::<SharedSerialPort>();
+	assert_read::<&SharedSerialPort>();
 }
 
 #[test]
@@ -18,4 +20,6 @@ fn assert_write_is_implemented() {
 	assert_write::<&SerialPort>();
 	assert_write::<&mut SerialPort>();
 	assert_write::<&mut &SerialPort>();
+	assert_write::<SharedSerialPort>();
+	assert_write::<&SharedSerialPort>();
 }
@@ -0,0 +1,21 @@
+use assert2::{assert, let_assert};
+use serial2::bootstrap;
+use serial2::mock::MockSerialPort;
+
+#[test]
+fn esp_enter_bootloader() {
+	let port = MockSerialPort::new();
+	let_assert!(Ok(()) = port.set_rts(true));
+	let_assert!(Ok(()) = port.set_dtr(true));
+	let_assert!(Ok(()) = bootstrap::esp_enter_bootloader(&port));
+	assert!(port.rts() == false);
+	assert!(port.dtr() == false);
+}
+
+#[test]
+fn esp_hard_reset() {
+	let port = MockSerialPort::new();
+	let_assert!(Ok(()) = bootstrap::esp_hard_reset(&port));
+	assert!(port.rts() == false);
+	assert!(port.dtr() == false);
+}
@@ -0,0 +1,79 @@
+use assert2::{assert, let_assert};
+use serial2::mock::{Fault, MockSerialPort};
+use serial2::{SerialBufWriter, SerialTransport};
+
+#[test]
+fn batch_small_writes() {
+	let port = MockSerialPort::new();
+	let writer = SerialBufWriter::with_capacity(8, port.clone());
+	assert!(let Ok(()) = writer.write_all(b"abc"));
+	assert!(let Ok(()) = writer.write_all(b"def"));
+	assert!(writer.buffered_len() == 6);
+	assert!(port.take_written() == b"");
+
+	// Data that does not fit anymore causes the buffer to be written first.
+	assert!(let Ok(()) = writer.write_all(b"ghi"));
+	assert!(port.take_written() == b"abcdef");
+	assert!(writer.buffered_len() == 3);
+
+	assert!(let Ok(()) = writer.flush());
+	assert!(port.take_written() == b"ghi");
+	assert!(writer.buffered_len() == 0);
+}
+
+#[test]
+fn large_writes_bypass_buffer() {
+	let port = MockSerialPort::new();
+	let writer = SerialBufWriter::with_capacity(4, port.clone());
+	assert!(let Ok(()) = writer.write_all(b"a"));
+	assert!(let Ok(5) = writer.write(b"bcdef"));
+	assert!(port.take_written() == b"abcdef");
+	assert!(writer.buffered_len() == 0);
+}
+
+#[test]
+fn flush_on_drop() {
+	let port = MockSerialPort::new();
+	let writer = SerialBufWriter::new(port.clone());
+	assert!(let Ok(()) = writer.write_all(b"Hello"));
+	assert!(port.take_written() == b"");
+	drop(writer);
+	assert!(port.take_written() == b"Hello");
+
+	let writer = SerialBufWriter::new(port.clone());
+	assert!(let Ok(()) = writer.write_all(b"world"));
+	let_assert!(Ok(_port) = writer.into_inner());
+	assert!(port.take_written() == b"world");
+}
+
+#[test]
+fn keep_data_after_error() {
+	let port = MockSerialPort::new();
+	let writer = SerialBufWriter::new(port.clone());
+	assert!(let Ok(()) = writer.write_all(b"Hello"));
+	port.push_write_fault(Fault::Partial(2));
+	port.push_write_error(std::io::ErrorKind::PermissionDenied.into());
+	let_assert!(Err(e) = writer.flush_buffer());
+	assert!(e.kind() == std::io::ErrorKind::PermissionDenied);
+	assert!(port.take_written() == b"He");
+	assert!(writer.buffered_len() == 3);
+	assert!(let Ok(()) = writer.flush());
+	assert!(port.take_written() == b"llo");
+}
+
+#[test]
+fn into_inner_returns_writer_on_error() {
+	let port = MockSerialPort::new();
+	let writer = SerialBufWriter::new(port.clone());
+	assert!(let Ok(()) = writer.write_all(b"Hello"));
+	port.push_write_fault(Fault::Partial(2));
+	port.push_write_error(std::io::ErrorKind::PermissionDenied.into());
+
+	let_assert!(Err(e) = writer.into_inner());
+	assert!(e.error().kind() == std::io::ErrorKind::PermissionDenied);
+	assert!(port.take_written() == b"He");
+
+	let (_port, buffered) = e.into_inner().into_parts();
+	assert!(buffered == b"llo");
+	assert!(port.take_written() == b"");
+}
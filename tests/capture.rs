@@ -0,0 +1,207 @@
+use assert2::{assert, let_assert};
+use serial2::capture::Capture;
+use serial2::mock::MockSerialPort;
+use std::time::{Duration, Instant};
+
+fn mock() -> MockSerialPort {
+	let port = MockSerialPort::new();
+	let_assert!(Ok(()) = port.set_read_timeout(Duration::from_millis(5)));
+	port
+}
+
+#[test]
+fn capture_chunks() {
+	let port = mock();
+	let before = Instant::now();
+	let_assert!(Ok(capture) = Capture::start(port.clone(), 1024));
+	port.push_read_data(b"hello");
+
+	let_assert!(Ok(chunk) = capture.recv_timeout(Duration::from_secs(3)));
+	assert!(chunk.data == b"hello");
+	assert!(chunk.timestamp >= before);
+
+	let_assert!(Err(e) = capture.recv_timeout(Duration::from_millis(10)));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(capture.is_running());
+
+	// The transport is returned when the capture stops.
+	let port = capture.stop();
+	port.push_read_data(b"world");
+	let mut buffer = [0; 5];
+	let_assert!(Ok(()) = port.read_exact(&mut buffer));
+	assert!(&buffer == b"world");
+}
+
+#[test]
+fn drop_oldest_data() {
+	let port = mock();
+	port.push_read_data(b"0123");
+	port.push_read_data(b"4567");
+	port.push_read_data(b"89");
+	let_assert!(Ok(capture) = Capture::start(port.clone(), 6));
+
+	let deadline = Instant::now() + Duration::from_secs(3);
+	while capture.dropped_bytes() + (capture.buffered_bytes() as u64) < 10 && Instant::now() < deadline {
+		std::thread::sleep(Duration::from_millis(1));
+	}
+
+	assert!(capture.dropped_bytes() == 4);
+	assert!(capture.buffered_bytes() == 6);
+	let data: Vec<u8> = capture.drain().into_iter().flat_map(|chunk| chunk.data).collect();
+	assert!(data == b"456789");
+	assert!(capture.buffered_bytes() == 0);
+	assert!(capture.try_recv().is_none());
+}
+
+#[test]
+fn read_error_stops_capture() {
+	let port = mock();
+	port.push_read_data(b"data");
+	port.push_read_error(std::io::Error::other("boom"));
+	let_assert!(Ok(capture) = Capture::start(port, 1024));
+
+	let_assert!(Ok(chunk) = capture.recv_timeout(Duration::from_secs(3)));
+	assert!(chunk.data == b"data");
+	let_assert!(Err(e) = capture.recv_timeout(Duration::from_secs(3)));
+	assert!(e.to_string() == "boom");
+	assert!(!capture.is_running());
+	let_assert!(Err(e) = capture.recv_timeout(Duration::from_secs(3)));
+	assert!(e.kind() == std::io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn tee_channel() {
+	use serial2::capture::{Direction, TeePort};
+	use serial2::SerialTransport;
+
+	let port = mock();
+	let (sender, receiver) = std::sync::mpsc::channel();
+	let tee = TeePort::new(port.clone(), sender);
+
+	let_assert!(Ok(()) = tee.write_all(b"PING"));
+	port.push_read_data(b"PONG");
+	let mut buffer = [0; 4];
+	let_assert!(Ok(()) = tee.read_exact(&mut buffer));
+	assert!(&buffer == b"PONG");
+
+	// Failed reads are not mirrored.
+	let_assert!(Err(_) = tee.read(&mut buffer));
+
+	let_assert!(Ok(write) = receiver.try_recv());
+	assert!(write.direction == Direction::Write);
+	assert!(write.data == b"PING");
+	let_assert!(Ok(read) = receiver.try_recv());
+	assert!(read.direction == Direction::Read);
+	assert!(read.data == b"PONG");
+	assert!(read.timestamp >= write.timestamp);
+	assert!(let Err(_) = receiver.try_recv());
+	assert!(port.take_written() == b"PING");
+}
+
+#[test]
+fn tee_recorder() {
+	use serial2::capture::{Recorder, TeePort};
+	use serial2::SerialTransport;
+
+	let port = mock();
+	let_assert!(Ok(recorder) = Recorder::new(Vec::new()));
+	let tee = TeePort::new(port.clone(), recorder);
+	let_assert!(Ok(()) = tee.write_all(&[0x01, 0xAB]));
+	port.push_read_data(&[0xFF]);
+	let mut buffer = [0; 1];
+	let_assert!(Ok(()) = tee.read_exact(&mut buffer));
+
+	let (_port, recorder) = tee.into_inner();
+	let_assert!(Ok(output) = recorder.finish());
+	let_assert!(Ok(output) = String::from_utf8(output));
+	let lines: Vec<&str> = output.lines().collect();
+	assert!(lines.len() == 3);
+	assert!(lines[0] == "# serial2 capture");
+	let_assert!(Some((_, write)) = lines[1].split_once(' '));
+	assert!(write == "W 01AB");
+	let_assert!(Some((_, read)) = lines[2].split_once(' '));
+	assert!(read == "R FF");
+}
+
+#[test]
+fn record_and_replay() {
+	use serial2::capture::{Direction, Recorder, Recording, TeePort};
+	use serial2::SerialTransport;
+
+	// Record a session with a device that responds after 30 milliseconds.
+	let device = mock();
+	device.add_response(b"PING", b"PONG");
+	let_assert!(Ok(recorder) = Recorder::new(Vec::new()));
+	let tee = TeePort::new(device, recorder);
+	let_assert!(Ok(()) = tee.write_all(b"PING"));
+	std::thread::sleep(Duration::from_millis(30));
+	let mut buffer = [0; 4];
+	let_assert!(Ok(()) = tee.read_exact(&mut buffer));
+	let (_device, recorder) = tee.into_inner();
+	let_assert!(Ok(output) = recorder.finish());
+
+	let_assert!(Ok(recording) = Recording::parse(output.as_slice()));
+	assert!(recording.events().len() == 2);
+	assert!(recording.events()[0].direction == Direction::Write);
+	assert!(recording.events()[1].offset >= Duration::from_millis(30));
+	assert!(recording.written_data() == b"PING");
+	assert!(recording.read_data() == b"PONG");
+
+	// Replay it, and check that the response is delayed again.
+	let port = mock();
+	let_assert!(Ok(()) = port.set_read_timeout(Duration::from_secs(3)));
+	recording.replay(&port);
+	let_assert!(Ok(()) = port.write_all(b"PING"));
+	let start = Instant::now();
+	let_assert!(Ok(()) = port.read_exact(&mut buffer));
+	assert!(&buffer == b"PONG");
+	assert!(start.elapsed() >= Duration::from_millis(25));
+	assert!(port.take_written() == recording.written_data());
+}
+
+#[test]
+fn parse_recording() {
+	use serial2::capture::{Direction, RecordedEvent, Recording};
+
+	let input = "# serial2 capture\n\n10 W 0102\n1500 R\n";
+	let_assert!(Ok(recording) = Recording::parse(input.as_bytes()));
+	assert!(recording.events() == [
+		RecordedEvent { offset: Duration::from_micros(10), direction: Direction::Write, data: vec![1, 2] },
+		RecordedEvent { offset: Duration::from_micros(1500), direction: Direction::Read, data: vec![] },
+	]);
+
+	let_assert!(Err(e) = Recording::parse("# serial2 capture\n10 X 01\n".as_bytes()));
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+	assert!(e.to_string().contains("line 2"));
+	assert!(let Err(_) = Recording::parse("10 R 0\n".as_bytes()));
+	assert!(let Err(_) = Recording::parse("10 R ZZ\n".as_bytes()));
+}
+
+#[test]
+fn recv_without_timeout() {
+	let port = mock();
+	let_assert!(Ok(capture) = Capture::start(port.clone(), 1024));
+	port.push_read_data(b"hello");
+	let_assert!(Ok(chunk) = capture.recv_timeout(Duration::MAX));
+	assert!(chunk.data == b"hello");
+}
+
+#[test]
+fn tee_follows_manual_clock() {
+	use serial2::capture::TeePort;
+	use serial2::SerialTransport;
+
+	let port = MockSerialPort::with_manual_clock();
+	let (sender, receiver) = std::sync::mpsc::channel();
+	let tee = TeePort::new(port.clone(), sender);
+
+	let start = port.now();
+	let_assert!(Ok(()) = tee.write_all(b"a"));
+	port.advance_time(Duration::from_secs(3));
+	let_assert!(Ok(()) = tee.write_all(b"b"));
+
+	let_assert!(Ok(first) = receiver.try_recv());
+	let_assert!(Ok(second) = receiver.try_recv());
+	assert!(first.timestamp == start);
+	assert!(second.timestamp - first.timestamp == Duration::from_secs(3));
+}
@@ -51,13 +51,17 @@ fn test_serde_parity() {
 	assert!(serial2::Parity::None.as_str() == "none");
 	assert!(serial2::Parity::Odd.as_str() == "odd");
 	assert!(serial2::Parity::Even.as_str() == "even");
+	assert!(serial2::Parity::Mark.as_str() == "mark");
+	assert!(serial2::Parity::Space.as_str() == "space");
 
 	assert!(let Ok(serial2::Parity::None) = serial2::Parity::from_str("none"));
 	assert!(let Ok(serial2::Parity::Odd) = serial2::Parity::from_str("odd"));
 	assert!(let Ok(serial2::Parity::Even) = serial2::Parity::from_str("even"));
+	assert!(let Ok(serial2::Parity::Mark) = serial2::Parity::from_str("mark"));
+	assert!(let Ok(serial2::Parity::Space) = serial2::Parity::from_str("space"));
 
 	let_assert!(Err(e) = serial2::Parity::from_str("even-then-odd"));
-	assert!(e.to_string() == "invalid value: \"even-then-odd\", expected the string \"none\", \"odd\" or \"even\"");
+	assert!(e.to_string() == "invalid value: \"even-then-odd\", expected the string \"none\", \"odd\", \"even\", \"mark\" or \"space\"");
 }
 
 #[test]
@@ -73,3 +77,17 @@ fn test_serde_flow_control() {
 	let_assert!(Err(e) = serial2::FlowControl::from_str("plug-in/plug-out"));
 	assert!(e.to_string() == "invalid value: \"plug-in/plug-out\", expected the string \"none\", \"xon/xoff\" or \"rts/cts\"");
 }
+
+#[test]
+#[cfg(all(unix, feature = "unix"))]
+fn test_convert_termios_speed() {
+	use serial2::os::unix::{baud_rate_to_speed, speed_to_baud_rate};
+
+	#[cfg(any(target_os = "linux", target_os = "android"))]
+	assert!(baud_rate_to_speed(12345) == None);
+
+	for &baud_rate in serial2::standard_baud_rates() {
+		let_assert!(Some(speed) = baud_rate_to_speed(baud_rate));
+		assert!(speed_to_baud_rate(speed) == Some(baud_rate));
+	}
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use assert2::{assert, let_assert};
+use serial2::dmx::{self, DmxSender, Timing};
+use serial2::{SerialPort, StopBits};
+use std::time::Duration;
+
+#[test]
+fn configure() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	assert!(let Ok(()) = dmx::configure(&a));
+	let_assert!(Ok(settings) = a.get_configuration());
+	assert!(let Ok(dmx::BAUD_RATE) = settings.get_baud_rate());
+	assert!(let Ok(StopBits::Two) = settings.get_stop_bits());
+}
+
+#[test]
+fn send_packet() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = b.set_read_timeout(Duration::from_secs(1)));
+	assert!(let Ok(()) = dmx::send_packet(&a, &Timing::default(), dmx::NULL_START_CODE, &[1, 2, 3]));
+	let mut buffer = [0; 4];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(buffer == [0, 1, 2, 3]);
+
+	let_assert!(Err(e) = dmx::send_packet(&a, &Timing::default(), 0, &[0; 513]));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn sender() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = b.set_read_timeout(Duration::from_secs(1)));
+
+	let timing = Timing {
+		refresh_interval: Duration::from_millis(5),
+		..Timing::default()
+	};
+	let_assert!(Ok(sender) = DmxSender::start(a, timing));
+	sender.set_channel(1, 1);
+	sender.set_channel(512, 9);
+	assert!(sender.get_channel(512) == 9);
+	assert!(sender.is_running());
+
+	// Skip packets that were sent before the channels were changed.
+	let mut packet = [0; 513];
+	let mut found = false;
+	for _ in 0..100 {
+		assert!(let Ok(()) = b.read_exact(&mut packet));
+		assert!(packet[0] == dmx::NULL_START_CODE);
+		if packet[1] == 1 && packet[512] == 9 {
+			found = true;
+			break;
+		}
+	}
+	assert!(found);
+
+	let_assert!(Err(e) = sender.set_universe(&[0; 513]));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	assert!(let Ok(_port) = sender.stop());
+}
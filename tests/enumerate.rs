@@ -0,0 +1,42 @@
+use assert2::{assert, let_assert};
+use serial2::SerialPort;
+
+#[test]
+fn verbose_enumeration_matches_available_ports() {
+	let_assert!(Ok(mut ports) = SerialPort::available_ports());
+	let_assert!(Ok(diagnostics) = SerialPort::available_ports_verbose());
+	let mut available: Vec<_> = diagnostics
+		.into_iter()
+		.filter_map(|entry| entry.into_port_info())
+		.map(|info| info.into_path())
+		.collect();
+	ports.sort();
+	available.sort();
+	assert!(available == ports);
+}
+
+#[test]
+fn open_by_unknown_id() {
+	let_assert!(Err(e) = SerialPort::open_by_id("no-such-device-id", 115200));
+	assert!(e.kind() == std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn port_ids_are_unique() {
+	let_assert!(Ok(ports) = SerialPort::available_ports_info());
+	let mut ids: Vec<_> = ports.iter().filter_map(|port| port.id()).collect();
+	let count = ids.len();
+	ids.sort();
+	ids.dedup();
+	assert!(ids.len() == count);
+}
+
+#[test]
+fn for_each_port_matches_available_ports() {
+	let_assert!(Ok(mut ports) = SerialPort::available_ports());
+	let mut visited = Vec::new();
+	assert!(let Ok(()) = SerialPort::for_each_port(|port| visited.push(port.path().to_path_buf())));
+	ports.sort();
+	visited.sort();
+	assert!(visited == ports);
+}
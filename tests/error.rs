@@ -0,0 +1,39 @@
+use assert2::{assert, let_assert};
+use serial2::{Error, SerialPort, Setting};
+use std::path::Path;
+
+#[test]
+fn open_nonexistent() {
+	let_assert!(Err(e) = SerialPort::open_detailed("/nonexistent/serial2-test", 9600));
+	assert!(let Error::Open { .. } = &e);
+	assert!(e.kind() == std::io::ErrorKind::NotFound);
+	assert!(e.port() == Some(Path::new("/nonexistent/serial2-test")));
+	assert!(e.setting() == None);
+	assert!(e.to_string().starts_with("failed to open serial port /nonexistent/serial2-test: "));
+
+	let e: std::io::Error = e.into();
+	assert!(e.kind() == std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn configure_error_display() {
+	let e = Error::Configure {
+		port: Some("/dev/ttyS0".into()),
+		setting: Some(Setting::BaudRate),
+		source: std::io::Error::other("failed to apply some or all settings"),
+	};
+	assert!(e.setting() == Some(Setting::BaudRate));
+	assert!(e.to_string() == "failed to configure serial port /dev/ttyS0: failed to apply baud rate: failed to apply some or all settings");
+	assert!(e.into_io_error().to_string() == "failed to apply some or all settings");
+}
+
+#[test]
+#[cfg(unix)]
+fn set_configuration_detailed() {
+	let_assert!(Ok((_a, b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = b.get_configuration());
+	let_assert!(Ok(()) = settings.set_baud_rate(57600));
+	let_assert!(Ok(()) = b.set_configuration_detailed(&settings));
+	let_assert!(Ok(settings) = b.get_configuration());
+	assert!(let Ok(57600) = settings.get_baud_rate());
+}
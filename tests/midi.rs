@@ -0,0 +1,63 @@
+use assert2::{assert, let_assert};
+use serial2::midi::{Framer, Message};
+
+fn frame(data: &[u8]) -> Vec<Vec<u8>> {
+	let mut framer = Framer::new();
+	framer.push_slice(data).map(|message| message.as_bytes().to_vec()).collect()
+}
+
+#[test]
+fn channel_messages() {
+	assert!(frame(&[0x90, 60, 100, 0x80, 60, 0]) == [vec![0x90, 60, 100], vec![0x80, 60, 0]]);
+	assert!(frame(&[0xC5, 7, 0xD0, 64]) == [vec![0xC5, 7], vec![0xD0, 64]]);
+
+	let mut framer = Framer::new();
+	let_assert!(Some(Message::Short(message)) = framer.push_slice(&[0xB3, 7, 127]).next());
+	assert!(message.status() == 0xB3);
+	assert!(message.channel() == Some(3));
+	assert!(message.data() == [7, 127]);
+}
+
+#[test]
+fn running_status() {
+	assert!(frame(&[0x90, 60, 100, 62, 100, 64, 0]) == [vec![0x90, 60, 100], vec![0x90, 62, 100], vec![0x90, 64, 0]]);
+	assert!(frame(&[0xC0, 1, 2, 3]) == [vec![0xC0, 1], vec![0xC0, 2], vec![0xC0, 3]]);
+
+	// System common messages cancel the running status.
+	assert!(frame(&[0x90, 60, 100, 0xF3, 5, 62, 100]) == [vec![0x90, 60, 100], vec![0xF3, 5]]);
+}
+
+#[test]
+fn realtime_interleaving() {
+	assert!(frame(&[0x90, 0xF8, 60, 0xFA, 100]) == [vec![0xF8], vec![0xFA], vec![0x90, 60, 100]]);
+	assert!(frame(&[0xF0, 1, 0xF8, 2, 0xF7]) == [vec![0xF8], vec![0xF0, 1, 2, 0xF7]]);
+}
+
+#[test]
+fn system_messages() {
+	assert!(frame(&[0xF6, 0xF2, 1, 2]) == [vec![0xF6], vec![0xF2, 1, 2]]);
+	assert!(frame(&[0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7]) == [vec![0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7]]);
+
+	// A status byte interrupts the system exclusive message, which is discarded.
+	assert!(frame(&[0xF0, 1, 2, 0x90, 60, 100, 0xF7]) == [vec![0x90, 60, 100]]);
+
+	// Undefined status bytes and stray data bytes are ignored.
+	assert!(frame(&[1, 2, 0xF4, 3, 0xF7, 0xF5]).is_empty());
+}
+
+#[test]
+fn sysex_limit() {
+	let mut framer = Framer::with_max_sysex_len(4);
+	assert!(framer.push_slice(&[0xF0, 1, 2, 3, 4, 5, 0xF7]).count() == 0);
+	let_assert!(Some(Message::SysEx(data)) = framer.push_slice(&[0xF0, 1, 2, 0xF7]).next());
+	assert!(data == [0xF0, 1, 2, 0xF7]);
+}
+
+#[test]
+#[cfg(unix)]
+fn configure() {
+	let_assert!(Ok((a, _b)) = serial2::SerialPort::pair());
+	assert!(let Ok(()) = serial2::midi::configure(&a));
+	let_assert!(Ok(settings) = a.get_configuration());
+	assert!(let Ok(serial2::midi::BAUD_RATE) = settings.get_baud_rate());
+}
@@ -0,0 +1,271 @@
+use assert2::{assert, let_assert};
+use serial2::mock::{Fault, MockSerialPort};
+use std::time::{Duration, Instant};
+
+#[test]
+fn read_queued_data() {
+	let port = MockSerialPort::new();
+	port.push_read_data(b"Hello");
+	port.push_read_data(b" world!");
+	assert!(port.read_queue_len() == 12);
+
+	let mut buffer = [0; 12];
+	assert!(let Ok(()) = port.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello world!");
+	assert!(port.read_queue_len() == 0);
+}
+
+#[test]
+fn read_timeout() {
+	let port = MockSerialPort::new();
+	assert!(let Ok(()) = port.set_read_timeout(Duration::from_millis(10)));
+	let mut buffer = [0; 1];
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn read_delay() {
+	let port = MockSerialPort::new();
+	port.push_read_delay(Duration::from_millis(50));
+	port.push_read_data(b"late");
+
+	let start = Instant::now();
+	let mut buffer = [0; 4];
+	assert!(let Ok(()) = port.read_exact(&mut buffer));
+	assert!(&buffer == b"late");
+	assert!(start.elapsed() >= Duration::from_millis(50));
+
+	// A delay longer than the read timeout causes a timeout, but the delay keeps counting.
+	port.push_read_delay(Duration::from_millis(50));
+	port.push_read_data(b"!");
+	assert!(let Ok(()) = port.set_read_timeout(Duration::from_millis(10)));
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	std::thread::sleep(Duration::from_millis(50));
+	assert!(let Ok(1) = port.read(&mut buffer));
+	assert!(buffer[0] == b'!');
+}
+
+#[test]
+fn injected_errors() {
+	let port = MockSerialPort::new();
+	port.push_read_data(b"a");
+	port.push_read_error(std::io::ErrorKind::BrokenPipe.into());
+	port.push_write_error(std::io::ErrorKind::PermissionDenied.into());
+
+	let mut buffer = [0; 2];
+	assert!(let Ok(1) = port.read(&mut buffer));
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::BrokenPipe);
+
+	let_assert!(Err(e) = port.write(b"b"));
+	assert!(e.kind() == std::io::ErrorKind::PermissionDenied);
+	assert!(let Ok(1) = port.write(b"b"));
+	assert!(port.take_written() == b"b");
+}
+
+#[test]
+fn scripted_responses() {
+	let port = MockSerialPort::new();
+	port.add_response(b"AT\r", b"OK\r");
+	port.add_response(b"ATI\r", b"mock\r");
+
+	assert!(let Ok(()) = port.write_all(b"AT"));
+	assert!(port.read_queue_len() == 0);
+	assert!(let Ok(()) = port.write_all(b"\rATI\r"));
+	let mut buffer = [0; 8];
+	assert!(let Ok(()) = port.read_exact(&mut buffer));
+	assert!(&buffer == b"OK\rmock\r");
+	assert!(port.take_written() == b"AT\rATI\r");
+	assert!(port.take_written() == b"");
+}
+
+#[test]
+fn shared_between_clones() {
+	let port = MockSerialPort::new();
+	let reader = std::thread::spawn({
+		let port = port.clone();
+		move || {
+			let mut buffer = [0; 5];
+			port.read_exact(&mut buffer).map(|()| buffer)
+		}
+	});
+	std::thread::sleep(Duration::from_millis(10));
+	port.push_read_data(b"Hello");
+	let_assert!(Ok(Ok(buffer)) = reader.join());
+	assert!(&buffer == b"Hello");
+}
+
+#[test]
+fn control_lines() {
+	let port = MockSerialPort::new();
+	assert!(let Ok(()) = port.set_rts(true));
+	assert!(port.rts());
+	assert!(!port.dtr());
+	port.set_cts(true);
+	assert!(let Ok(true) = port.read_cts());
+	assert!(let Ok(false) = port.read_cd());
+}
+
+#[test]
+fn manual_clock() {
+	let port = MockSerialPort::with_manual_clock();
+	let start = port.now();
+	port.advance_time(Duration::from_secs(5));
+	assert!(port.now() - start == Duration::from_secs(5));
+
+	// A read only times out when the clock is advanced past the read timeout.
+	assert!(let Ok(()) = port.set_read_timeout(Duration::from_secs(1)));
+	let reader = std::thread::spawn({
+		let port = port.clone();
+		move || {
+			let mut buffer = [0; 1];
+			port.read(&mut buffer)
+		}
+	});
+	std::thread::sleep(Duration::from_millis(20));
+	port.advance_time(Duration::from_millis(999));
+	std::thread::sleep(Duration::from_millis(20));
+	assert!(!reader.is_finished());
+	port.advance_time(Duration::from_millis(1));
+	let_assert!(Ok(Err(e)) = reader.join());
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn manual_clock_delays() {
+	let port = MockSerialPort::with_manual_clock();
+	assert!(let Ok(()) = port.set_read_timeout(Duration::MAX));
+	port.push_read_delay(Duration::from_secs(60));
+	port.push_read_data(b"late");
+
+	let reader = std::thread::spawn({
+		let port = port.clone();
+		move || {
+			let mut buffer = [0; 4];
+			port.read_exact(&mut buffer).map(|()| buffer)
+		}
+	});
+	std::thread::sleep(Duration::from_millis(20));
+	assert!(!reader.is_finished());
+	port.advance_time(Duration::from_secs(60));
+	let_assert!(Ok(Ok(buffer)) = reader.join());
+	assert!(&buffer == b"late");
+
+	// Writes wait for the write delay on the manual clock too.
+	port.set_write_delay(Duration::from_secs(2));
+	assert!(let Ok(()) = port.set_write_timeout(Duration::from_secs(1)));
+	let writer = std::thread::spawn({
+		let port = port.clone();
+		move || port.write(b"a")
+	});
+	std::thread::sleep(Duration::from_millis(20));
+	assert!(!writer.is_finished());
+	port.advance_time(Duration::from_secs(1));
+	let_assert!(Ok(Err(e)) = writer.join());
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[should_panic]
+fn advance_system_clock() {
+	MockSerialPort::new().advance_time(Duration::from_secs(1));
+}
+
+#[test]
+fn read_faults() {
+	let port = MockSerialPort::new();
+	assert!(let Ok(()) = port.set_read_timeout(Duration::from_millis(10)));
+	port.push_read_data(b"ab");
+	port.push_read_fault(Fault::Timeout);
+	port.push_read_fault(Fault::Partial(2));
+	port.push_read_data(b"cdef");
+	port.push_read_fault(Fault::Interrupted);
+	port.push_read_fault(Fault::Garbage(vec![0xFF, 0x00]));
+	port.push_read_fault(Fault::Disconnect);
+	port.push_read_data(b"never");
+
+	let mut buffer = [0; 8];
+	assert!(let Ok(2) = port.read(&mut buffer));
+	let start = Instant::now();
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(start.elapsed() >= Duration::from_millis(10));
+	assert!(let Ok(2) = port.read(&mut buffer));
+	assert!(&buffer[..2] == b"cd");
+	assert!(let Ok(2) = port.read(&mut buffer));
+	assert!(&buffer[..2] == b"ef");
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::Interrupted);
+	assert!(let Ok(2) = port.read(&mut buffer));
+	assert!(&buffer[..2] == [0xFF, 0x00]);
+
+	assert!(!port.is_disconnected());
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(serial2::SerialPort::is_disconnected_error(&e));
+	assert!(port.is_disconnected());
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(serial2::SerialPort::is_disconnected_error(&e));
+	let_assert!(Err(e) = port.write(b"a"));
+	assert!(serial2::SerialPort::is_disconnected_error(&e));
+}
+
+#[test]
+fn write_faults() {
+	let port = MockSerialPort::new();
+	assert!(let Ok(()) = port.set_write_timeout(Duration::from_millis(10)));
+	port.push_write_fault(Fault::Partial(3));
+	port.push_write_fault(Fault::Interrupted);
+	port.push_write_fault(Fault::Garbage(b"~~".to_vec()));
+	port.push_write_fault(Fault::Timeout);
+	port.push_write_fault(Fault::Disconnect);
+
+	assert!(let Ok(3) = port.write(b"Hello"));
+	let_assert!(Err(e) = port.write(b"lo"));
+	assert!(e.kind() == std::io::ErrorKind::Interrupted);
+	assert!(let Ok(2) = port.write(b"lo"));
+	assert!(port.take_written() == b"Hel~~lo");
+
+	let start = Instant::now();
+	let_assert!(Err(e) = port.write(b"!"));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(start.elapsed() >= Duration::from_millis(10));
+
+	let_assert!(Err(e) = port.write(b"!"));
+	assert!(serial2::SerialPort::is_disconnected_error(&e));
+	assert!(port.is_disconnected());
+	assert!(port.take_written() == b"");
+}
+
+#[test]
+fn write_all_retries_faults() {
+	let port = MockSerialPort::new();
+	port.push_write_fault(Fault::Partial(1));
+	port.push_write_fault(Fault::Interrupted);
+	port.push_write_fault(Fault::Partial(0));
+	assert!(let Ok(()) = port.write_all(b"Hello"));
+	assert!(port.take_written() == b"Hello");
+}
+
+#[test]
+fn manual_clock_sleep() {
+	use serial2::SerialTransport;
+
+	let port = MockSerialPort::with_manual_clock();
+	let sleeper = std::thread::spawn({
+		let port = port.clone();
+		move || SerialTransport::sleep(&port, Duration::from_secs(2))
+	});
+	std::thread::sleep(Duration::from_millis(20));
+	assert!(!sleeper.is_finished());
+	port.advance_time(Duration::from_secs(2));
+	assert!(let Ok(()) = sleeper.join());
+
+	// Without any delay on the manual clock, the measured round-trip time is exactly zero.
+	for _ in 0..3 {
+		port.add_response(b"PING", b"PONG");
+	}
+	let_assert!(Ok(stats) = serial2::measure_rtt(&port, b"PING", 4, 3));
+	assert!(stats.max() == Duration::ZERO);
+}
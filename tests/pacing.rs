@@ -0,0 +1,55 @@
+use assert2::{assert, let_assert};
+use serial2::mock::MockSerialPort;
+use serial2::{PacedPort, SerialTransport};
+use std::time::{Duration, Instant};
+
+#[test]
+fn write_in_chunks() {
+	let port = MockSerialPort::new();
+	let paced = PacedPort::new(port.clone(), 2, Duration::from_millis(10));
+	assert!(let Ok(2) = paced.write(b"abcdef"));
+	assert!(port.take_written() == b"ab");
+
+	let start = Instant::now();
+	let_assert!(Ok(()) = paced.write_all(b"cdefg"));
+	// The first chunk waits for the delay after the previous write, and there are two more gaps after it.
+	assert!(start.elapsed() >= Duration::from_millis(30));
+	assert!(port.take_written() == b"cdefg");
+}
+
+#[test]
+fn zero_chunk_size() {
+	let port = MockSerialPort::new();
+	let paced = PacedPort::new(port.clone(), 0, Duration::ZERO);
+	assert!(paced.chunk_size() == 1);
+	assert!(let Ok(1) = paced.write(b"abc"));
+	assert!(let Ok(0) = paced.write(b""));
+}
+
+#[test]
+fn reads_pass_through() {
+	let port = MockSerialPort::new();
+	let paced = PacedPort::new(port.clone(), 1, Duration::from_millis(10));
+	port.push_read_data(b"hello");
+	let mut buffer = [0; 5];
+	let_assert!(Ok(()) = paced.read_exact(&mut buffer));
+	assert!(&buffer == b"hello");
+}
+
+#[test]
+fn delay_follows_manual_clock() {
+	let port = MockSerialPort::with_manual_clock();
+	let paced = std::sync::Arc::new(PacedPort::new(port.clone(), 1, Duration::from_secs(10)));
+	let writer = std::thread::spawn({
+		let paced = paced.clone();
+		move || paced.write_all(b"ab")
+	});
+
+	// The second byte waits for the delay on the clock of the mock port.
+	std::thread::sleep(Duration::from_millis(20));
+	assert!(!writer.is_finished());
+	assert!(port.take_written() == b"a");
+	port.advance_time(Duration::from_secs(10));
+	let_assert!(Ok(Ok(())) = writer.join());
+	assert!(port.take_written() == b"b");
+}
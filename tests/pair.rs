@@ -1,5 +1,3 @@
-#![cfg(unix)]
-
 use assert2::{assert, let_assert};
 use serial2::SerialPort;
 
@@ -16,3 +14,1072 @@ fn open_pair() {
 	assert!(let Ok(()) = a.read_exact(&mut buffer));
 	assert!(&buffer == b"Goodbye!");
 }
+
+#[test]
+fn write_all_vectored() {
+	use std::io::IoSlice;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let mut buffers = [IoSlice::new(b""), IoSlice::new(b"Hello"), IoSlice::new(b""), IoSlice::new(b" world!")];
+	assert!(let Ok(()) = a.write_all_vectored(&mut buffers));
+	let mut buffer = [0; 12];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello world!");
+}
+
+#[test]
+fn set_timeout_through_shared_reference() {
+	use std::sync::Arc;
+	use std::time::Duration;
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let a = Arc::new(a);
+	assert!(let Ok(()) = a.set_read_timeout(Duration::from_millis(10)));
+	assert!(let Ok(()) = a.set_write_timeout(Duration::from_millis(20)));
+	let_assert!(Ok(read_timeout) = a.get_read_timeout());
+	let_assert!(Ok(write_timeout) = a.get_write_timeout());
+	assert!(read_timeout == Duration::from_millis(10));
+	assert!(write_timeout == Duration::from_millis(20));
+
+	let mut buffer = [0; 1];
+	let_assert!(Err(e) = a.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn set_configuration_through_shared_reference() {
+	use std::sync::Arc;
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let a = Arc::new(a);
+	let_assert!(Ok(mut settings) = a.get_configuration());
+	assert!(let Ok(()) = settings.set_baud_rate(9600));
+	assert!(let Ok(()) = a.set_configuration(&settings));
+	let_assert!(Ok(settings) = a.get_configuration());
+	assert!(let Ok(9600) = settings.get_baud_rate());
+}
+
+#[test]
+#[cfg(unix)]
+fn modify_configuration() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	assert!(let Ok(()) = a.modify_configuration(|settings| settings.set_baud_rate(19200)));
+	let_assert!(Ok(settings) = a.get_configuration());
+	assert!(let Ok(19200) = settings.get_baud_rate());
+
+	// An error from the closure should leave the configuration untouched.
+	let_assert!(Err(e) = a.modify_configuration(|settings| {
+		settings.set_baud_rate(9600)?;
+		Err(std::io::Error::other("nope"))
+	}));
+	assert!(e.to_string() == "nope");
+	let_assert!(Ok(settings) = a.get_configuration());
+	assert!(let Ok(19200) = settings.get_baud_rate());
+}
+
+#[test]
+#[cfg(unix)]
+fn set_baud_rate() {
+	use serial2::StopBits;
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = a.get_configuration());
+	settings.set_stop_bits(StopBits::Two);
+	assert!(let Ok(()) = a.set_configuration(&settings));
+
+	assert!(let Ok(()) = a.set_baud_rate(38400));
+	let_assert!(Ok(settings) = a.get_configuration());
+	assert!(let Ok(38400) = settings.get_baud_rate());
+	assert!(let Ok(StopBits::Two) = settings.get_stop_bits());
+}
+
+#[test]
+#[cfg(unix)]
+fn flush_timeout() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = a.write_all(b"Hello!"));
+	assert!(let Ok(()) = a.flush_timeout(Duration::from_secs(1)));
+	assert!(let Ok(true) = a.output_drained());
+	let mut buffer = [0; 6];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello!");
+}
+
+#[test]
+#[cfg(unix)]
+fn suspend_and_resume_output() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = a.suspend_output());
+	assert!(let Ok(()) = a.resume_output());
+	assert!(let Ok(()) = a.write_all(b"Hello!"));
+	let mut buffer = [0; 6];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello!");
+}
+
+#[test]
+#[cfg(unix)]
+fn send_xon_xoff() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = a.send_xoff());
+	assert!(let Ok(()) = a.send_xon());
+	let mut buffer = [0; 2];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(buffer == [0x13, 0x11]);
+}
+
+#[test]
+fn abort_pending_io() {
+	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let a = Arc::new(a);
+	assert!(let Ok(()) = a.set_read_timeout(Duration::from_secs(10)));
+
+	let start = Instant::now();
+	let reader = std::thread::spawn({
+		let a = a.clone();
+		move || {
+			let mut buffer = [0; 1];
+			a.read(&mut buffer)
+		}
+	});
+	std::thread::sleep(Duration::from_millis(50));
+	assert!(let Ok(()) = a.abort_pending_io());
+	let_assert!(Ok(Err(e)) = reader.join());
+	assert!(e.kind() == std::io::ErrorKind::Other);
+	assert!(start.elapsed() < Duration::from_secs(5));
+
+	// New operations should not be affected by the earlier abort.
+	assert!(let Ok(()) = a.set_read_timeout(Duration::from_millis(10)));
+	let mut buffer = [0; 1];
+	let_assert!(Err(e) = a.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn disconnected_error() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	drop(b);
+	let mut buffer = [0; 1];
+	let_assert!(Err(e) = a.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::NotConnected);
+	assert!(SerialPort::is_disconnected_error(&e));
+
+	let other = std::io::Error::from(std::io::ErrorKind::TimedOut);
+	assert!(!SerialPort::is_disconnected_error(&other));
+}
+
+#[test]
+#[cfg(unix)]
+fn is_connected() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(true) = a.is_connected());
+	assert!(let Ok(true) = b.is_connected());
+	drop(b);
+	assert!(let Ok(false) = a.is_connected());
+}
+
+#[test]
+#[cfg(unix)]
+fn pts_name() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(path) = a.pts_name());
+	let_assert!(Ok(c) = SerialPort::open(&path, serial2::KeepSettings));
+	assert!(let Ok(()) = c.write_all(b"Hello!"));
+	let mut buffer = [0; 6];
+	assert!(let Ok(()) = a.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello!");
+}
+
+#[test]
+#[cfg(unix)]
+fn name() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(path) = a.pts_name());
+	assert!(a.name() == None);
+	assert!(b.name() == Some(path.as_path()));
+
+	let_assert!(Ok(c) = b.try_clone());
+	assert!(c.name() == Some(path.as_path()));
+	assert!(format!("{:?}", c).contains(&format!("{:?}", path)));
+}
+
+#[test]
+#[cfg(unix)]
+fn set_os_buffer_sizes() {
+	// The size of the kernel buffers can not be changed on Unix.
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Err(e) = a.set_os_buffer_sizes(64 * 1024, 4096));
+	assert!(e.kind() == std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+#[cfg(unix)]
+fn supported_baud_rates() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(supported) = a.supported_baud_rates());
+	assert!(supported.common_rates().contains(&9600));
+	#[cfg(target_os = "linux")]
+	assert!(supported.custom_rates());
+}
+
+#[test]
+#[cfg(unix)]
+fn cdc_acm_quirks() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(!b.cdc_acm_quirks());
+	b.set_cdc_acm_quirks(true);
+	assert!(b.cdc_acm_quirks());
+	let_assert!(Ok(c) = b.try_clone());
+	assert!(c.cdc_acm_quirks());
+
+	// Settings are still applied with the compatibility mode enabled.
+	let_assert!(Ok(()) = b.set_baud_rate(19200));
+	let_assert!(Ok(settings) = b.get_configuration());
+	assert!(let Ok(19200) = settings.get_baud_rate());
+
+	let_assert!(Ok(()) = a.write_all(b"ping"));
+	let mut buffer = [0; 4];
+	let_assert!(Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"ping");
+}
+
+#[test]
+#[cfg(unix)]
+fn statistics() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(a.statistics() == None);
+	a.enable_statistics();
+	b.enable_statistics();
+	let_assert!(Ok(c) = b.try_clone());
+
+	let_assert!(Ok(()) = a.write_all(b"hello"));
+	let mut buffer = [0; 5];
+	let_assert!(Ok(()) = c.read_exact(&mut buffer));
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_millis(10)));
+	let_assert!(Err(_) = b.read(&mut buffer));
+
+	let_assert!(Some(stats) = a.statistics());
+	assert!(stats.bytes_written() == 5);
+	assert!(stats.writes() >= 1);
+	assert!(stats.bytes_read() == 0);
+
+	// The clone shares the counters with the original port.
+	let_assert!(Some(stats) = b.statistics());
+	assert!(stats.bytes_read() == 5);
+	assert!(stats.reads() >= 1);
+	assert!(stats.timeouts() == 1);
+	assert!(stats.errors() == 0);
+	assert!(c.statistics() == Some(stats));
+
+	b.reset_statistics();
+	let_assert!(Some(stats) = c.statistics());
+	assert!(stats == serial2::PortStats::default());
+}
+
+#[test]
+#[cfg(unix)]
+fn write_all_with_progress() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let mut reports = Vec::new();
+	let_assert!(Ok(()) = a.write_all_with_progress(b"0123456789", 4, |sent, total| {
+		reports.push((sent, total));
+		Ok(())
+	}));
+	assert!(reports == [(4, 10), (8, 10), (10, 10)]);
+	let mut buffer = [0; 10];
+	let_assert!(Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"0123456789");
+
+	// Returning an error from the progress function cancels the transfer.
+	let_assert!(Err(e) = a.write_all_with_progress(b"abcdefgh", 3, |_, _| Err(std::io::Error::other("cancelled"))));
+	assert!(e.to_string() == "cancelled");
+	let mut buffer = [0; 3];
+	let_assert!(Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"abc");
+	let_assert!(Ok(()) = b.set_read_timeout(std::time::Duration::from_millis(10)));
+	let_assert!(Err(_) = b.read(&mut buffer));
+}
+
+#[test]
+#[cfg(unix)]
+fn read_exact_deadline() {
+	use std::time::{Duration, Instant};
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_secs(10)));
+	let_assert!(Ok(()) = a.write_all(b"abc"));
+	let mut buffer = [0; 3];
+	let_assert!(Ok(()) = b.read_exact_deadline(&mut buffer, Instant::now() + Duration::from_secs(3)));
+	assert!(&buffer == b"abc");
+
+	// The deadline limits the total time, even though the read timeout is much longer.
+	let_assert!(Ok(()) = a.write_all(b"de"));
+	let start = Instant::now();
+	let_assert!(Err(e) = b.read_exact_deadline(&mut buffer, Instant::now() + Duration::from_millis(50)));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(start.elapsed() < Duration::from_secs(5));
+	assert!(&buffer[..2] == b"de");
+
+	let_assert!(Ok(()) = a.write_all_deadline(b"fg", Instant::now() + Duration::from_secs(3)));
+	let mut buffer = [0; 2];
+	let_assert!(Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"fg");
+}
+
+#[test]
+#[cfg(unix)]
+fn timeout_behavior() {
+	use serial2::TimeoutBehavior;
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_millis(10)));
+	assert!(b.get_timeout_behavior() == TimeoutBehavior::Error);
+	let mut buffer = [0; 4];
+	let_assert!(Err(e) = b.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+
+	b.set_timeout_behavior(TimeoutBehavior::ReturnZero);
+	assert!(let Ok(0) = b.read(&mut buffer));
+
+	b.set_timeout_behavior(TimeoutBehavior::WouldBlock);
+	let_assert!(Err(e) = b.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	// Reading multiple times still reports a timeout as error.
+	let_assert!(Ok(c) = b.try_clone());
+	assert!(c.get_timeout_behavior() == TimeoutBehavior::WouldBlock);
+	let_assert!(Ok(()) = a.write_all(b"ab"));
+	let_assert!(Err(e) = b.read_exact(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn read_mode() {
+	use serial2::ReadMode;
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(ReadMode::FirstByte) = b.get_read_mode());
+	let mode = ReadMode::FillBuffer {
+		inter_byte_timeout: Duration::from_millis(200),
+	};
+	let_assert!(Ok(()) = b.set_read_mode(mode));
+	assert!(let Ok(ReadMode::FillBuffer { .. }) = b.get_read_mode());
+	let_assert!(Ok(c) = b.try_clone());
+	assert!(c.get_read_mode().ok() == Some(mode));
+
+	// The read waits for the second write to fill the buffer.
+	let mut buffer = [0; 10];
+	std::thread::scope(|s| {
+		s.spawn(|| {
+			assert!(let Ok(()) = a.write_all(b"Hello"));
+			std::thread::sleep(Duration::from_millis(20));
+			assert!(let Ok(()) = a.write_all(b"world"));
+		});
+		assert!(let Ok(10) = b.read(&mut buffer));
+	});
+	assert!(&buffer == b"Helloworld");
+
+	// An idle line ends the read early.
+	let_assert!(Ok(()) = b.set_read_mode(ReadMode::FillBuffer {
+		inter_byte_timeout: Duration::from_millis(10),
+	}));
+	let_assert!(Ok(()) = a.write_all(b"abc"));
+	assert!(let Ok(3) = b.read(&mut buffer));
+	assert!(&buffer[..3] == b"abc");
+
+	// Without data, the read timeout still applies.
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_millis(10)));
+	let_assert!(Err(e) = b.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn into_settings_tuples() {
+	use serial2::{CharSize, IntoSettings, Parity, StopBits};
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = a.get_configuration());
+	let_assert!(Ok(()) = (9600, "7E2").apply_to_settings(&mut settings));
+	assert!(let Ok(9600) = settings.get_baud_rate());
+	assert!(let Ok(CharSize::Bits7) = settings.get_char_size());
+	assert!(let Ok(Parity::Even) = settings.get_parity());
+	assert!(let Ok(StopBits::Two) = settings.get_stop_bits());
+
+	let_assert!(Ok(()) = (19200, CharSize::Bits8, Parity::Odd, StopBits::One).apply_to_settings(&mut settings));
+	assert!(let Ok(19200) = settings.get_baud_rate());
+	assert!(let Ok(CharSize::Bits8) = settings.get_char_size());
+	assert!(let Ok(Parity::Odd) = settings.get_parity());
+	assert!(let Ok(StopBits::One) = settings.get_stop_bits());
+
+	let_assert!(Ok(mut other) = a.get_configuration());
+	let_assert!(Ok(()) = (&settings).apply_to_settings(&mut other));
+	assert!(let Ok(Parity::Odd) = other.get_parity());
+
+	let_assert!(Err(e) = (9600, "8X1").apply_to_settings(&mut settings));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	assert!(let Err(_) = (9600, "8N").apply_to_settings(&mut settings));
+	assert!(let Err(_) = (9600, "9N1").apply_to_settings(&mut settings));
+}
+
+#[test]
+#[cfg(unix)]
+fn reopen() {
+	use serial2::ReadMode;
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(()) = b.modify_configuration(|settings| settings.set_baud_rate(19200)));
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_millis(123)));
+	let mode = ReadMode::FillBuffer {
+		inter_byte_timeout: Duration::from_millis(5),
+	};
+	let_assert!(Ok(()) = b.set_read_mode(mode));
+	b.set_timeout_behavior(serial2::TimeoutBehavior::ReturnZero);
+	b.set_apply_mode(serial2::ApplyMode::Immediate);
+	let name = b.name().map(|name| name.to_owned());
+
+	let_assert!(Ok(reopened) = b.reopen());
+	drop(b);
+	let b = reopened;
+	assert!(b.name().map(|name| name.to_owned()) == name);
+	let_assert!(Ok(settings) = b.get_configuration());
+	assert!(let Ok(19200) = settings.get_baud_rate());
+	assert!(b.get_read_timeout().ok() == Some(Duration::from_millis(123)));
+	assert!(b.get_read_mode().ok() == Some(mode));
+	assert!(b.get_timeout_behavior() == serial2::TimeoutBehavior::ReturnZero);
+	assert!(b.get_apply_mode() == serial2::ApplyMode::Immediate);
+
+	assert!(let Ok(()) = a.write_all(b"Hello"));
+	let mut buffer = [0; 5];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello");
+}
+
+#[test]
+#[cfg(unix)]
+fn open_options() {
+	use serial2::{KeepSettings, OpenOptions};
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Some(name) = b.name());
+
+	let_assert!(Ok(reader) = OpenOptions::new().write(false).open(name, KeepSettings));
+	assert!(let Err(_) = reader.write(b"Hello"));
+	assert!(let Ok(()) = a.write_all(b"Hello"));
+	let mut buffer = [0; 5];
+	assert!(let Ok(()) = reader.read_exact(&mut buffer));
+	assert!(&buffer == b"Hello");
+
+	// Ports opened in blocking mode still respect the read timeout.
+	let_assert!(Ok(port) = OpenOptions::new()
+		.open_nonblocking(false)
+		.custom_flags(libc::O_SYNC)
+		.open(name, KeepSettings));
+	let_assert!(Ok(()) = port.set_read_timeout(std::time::Duration::from_millis(10)));
+	let_assert!(Err(e) = port.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn validate_settings() {
+	use serial2::FlowControl;
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = a.get_configuration());
+	settings.set_flow_control(FlowControl::RtsCts);
+	assert!(let Ok(()) = settings.validate());
+
+	settings.as_termios_mut().c_iflag |= libc::IXON | libc::IXOFF;
+	let_assert!(Err(e) = settings.validate());
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+
+	settings.set_flow_control(FlowControl::XonXoff);
+	assert!(let Ok(()) = settings.validate());
+}
+
+#[test]
+#[cfg(unix)]
+fn windows_dcb_options_elsewhere() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = a.get_configuration());
+
+	assert!(let Ok(()) = settings.set_discard_null(false));
+	assert!(let Ok(()) = settings.set_abort_on_error(false));
+	assert!(let Ok(()) = settings.set_tx_continue_on_xoff(false));
+
+	let_assert!(Err(e) = settings.set_abort_on_error(true));
+	assert!(e.kind() == std::io::ErrorKind::Unsupported);
+	assert!(settings.get_discard_null() == false);
+	assert!(settings.get_abort_on_error() == false);
+	assert!(settings.get_tx_continue_on_xoff() == false);
+}
+
+#[test]
+#[cfg(unix)]
+fn input_processing_options() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = b.get_configuration());
+	settings.set_raw();
+	assert!(settings.get_ignore_cr() == false);
+	assert!(settings.get_map_nl_to_cr() == false);
+	assert!(settings.get_strip_high_bit() == false);
+
+	assert!(let Ok(()) = settings.set_ignore_cr(true));
+	assert!(let Ok(()) = settings.set_strip_high_bit(true));
+	assert!(settings.get_ignore_cr());
+	assert!(settings.get_strip_high_bit());
+	assert!(let Ok(()) = b.set_configuration(&settings));
+
+	assert!(let Ok(()) = a.write_all(b"a\rb\xE3"));
+	let mut buffer = [0; 3];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"abc");
+
+	let_assert!(Err(e) = settings.set_discard_null(true));
+	assert!(e.kind() == std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+#[cfg(unix)]
+fn newline_mode() {
+	use serial2::NewlineMode;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = b.get_configuration());
+	settings.set_raw();
+	assert!(settings.get_newline_mode() == NewlineMode::None);
+	assert!(let Ok(()) = settings.set_newline_mode(NewlineMode::Text));
+	assert!(settings.get_newline_mode() == NewlineMode::Text);
+	assert!(let Ok(()) = b.set_configuration(&settings));
+
+	assert!(let Ok(()) = a.write_all(b"a\rb"));
+	let mut buffer = [0; 3];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"a\nb");
+
+	assert!(let Ok(()) = b.write_all(b"c\n"));
+	let mut buffer = [0; 3];
+	assert!(let Ok(()) = a.read_exact(&mut buffer));
+	assert!(&buffer == b"c\r\n");
+
+	assert!(let Ok(()) = settings.set_newline_mode(NewlineMode::InputCrToLf));
+	assert!(settings.get_newline_mode() == NewlineMode::InputCrToLf);
+}
+
+#[test]
+#[cfg(unix)]
+fn read_without_poll_keeps_timeout_semantics() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = b.set_read_timeout(Duration::from_millis(20)));
+
+	// Fill the buffer completely, so the next read is attempted before polling.
+	assert!(let Ok(()) = a.write_all(b"abcd"));
+	let mut buffer = [0; 2];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"cd");
+
+	// With no data left, the read must still wait for the timeout instead of failing immediately.
+	let start = std::time::Instant::now();
+	let_assert!(Err(e) = b.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(start.elapsed() >= Duration::from_millis(20));
+
+	// An infinite timeout reads available data without polling.
+	assert!(let Ok(()) = b.set_read_timeout(Duration::MAX));
+	assert!(let Ok(()) = a.write_all(b"e"));
+	std::thread::sleep(Duration::from_millis(10));
+	assert!(let Ok(1) = b.read(&mut buffer));
+	assert!(buffer[0] == b'e');
+}
+
+#[test]
+#[cfg(unix)]
+fn read_without_poll_on_blocking_fd() {
+	use std::time::Duration;
+
+	// Open the slave side of a pair again as a file descriptor in blocking mode.
+	let_assert!(Ok((b, _slave)) = SerialPort::pair());
+	let_assert!(Ok(path) = b.pts_name());
+	let_assert!(Ok(file) = std::fs::OpenOptions::new().read(true).write(true).open(path));
+	let a = SerialPort::from(std::os::fd::OwnedFd::from(file));
+	assert!(let Ok(()) = a.set_read_timeout(Duration::from_millis(20)));
+
+	assert!(let Ok(()) = b.write_all(b"ab"));
+	let mut buffer = [0; 2];
+	assert!(let Ok(()) = a.read_exact(&mut buffer));
+
+	// The read must not block, even though the previous read filled the buffer.
+	let_assert!(Err(e) = a.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn loopback_test() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let pattern = b"Hello loopback!";
+
+	// Echo the pattern back from the other side, like a loopback plug.
+	std::thread::scope(|s| {
+		s.spawn(|| {
+			let mut buffer = [0; 15];
+			assert!(let Ok(()) = b.read_exact(&mut buffer));
+			assert!(let Ok(()) = b.write_all(&buffer));
+		});
+		// A timeout that does not fit in an `Instant` must not panic.
+		assert!(let Ok(()) = a.loopback_test(pattern, std::time::Duration::MAX));
+	});
+
+	// Send back corrupted data.
+	std::thread::scope(|s| {
+		s.spawn(|| {
+			let mut buffer = [0; 15];
+			assert!(let Ok(()) = b.read_exact(&mut buffer));
+			buffer[3] = b'L';
+			assert!(let Ok(()) = b.write_all(&buffer));
+		});
+		let_assert!(Err(e) = a.loopback_test(pattern, std::time::Duration::from_secs(1)));
+		assert!(e.kind() == std::io::ErrorKind::InvalidData);
+		assert!(e.to_string().contains("byte 3"));
+	});
+
+	// Nothing is sent back.
+	let_assert!(Err(e) = a.loopback_test(pattern, std::time::Duration::from_millis(50)));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn measure_rtt() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+
+	// Reply to each request with a short response.
+	std::thread::scope(|s| {
+		s.spawn(|| {
+			let mut buffer = [0; 5];
+			for _ in 0..10 {
+				assert!(let Ok(()) = b.read_exact(&mut buffer));
+				assert!(&buffer == b"PING\n");
+				assert!(let Ok(()) = b.write_all(b"OK\n"));
+			}
+		});
+		let_assert!(Ok(stats) = serial2::measure_rtt(&a, b"PING\n", 3, 10));
+		assert!(stats.iterations() == 10);
+		assert!(stats.min() <= stats.avg());
+		assert!(stats.avg() <= stats.max());
+	});
+
+	let_assert!(Err(e) = serial2::measure_rtt(&a, b"PING\n", 3, 0));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+
+	// Nothing replies anymore.
+	let_assert!(Err(e) = serial2::measure_rtt(&a, b"PING\n", 3, 1));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn lines() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = b.set_read_timeout(Duration::from_millis(50)));
+	let mut lines = b.lines();
+
+	assert!(let Ok(()) = a.write_all(b"first\r\nsecond\nthi"));
+	let_assert!(Some(Ok(line)) = lines.next());
+	assert!(line == "first");
+	let_assert!(Some(Ok(line)) = lines.next());
+	assert!(line == "second");
+
+	// Time out in the middle of a line, and continue it afterwards.
+	let_assert!(Some(Err(e)) = lines.next());
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(lines.pending() == b"thi");
+	assert!(let Ok(()) = a.write_all(b"rd\n"));
+	let_assert!(Some(Ok(line)) = lines.next());
+	assert!(line == "third");
+
+	// Time out between lines.
+	let_assert!(Some(Err(e)) = lines.next());
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(lines.pending() == b"");
+
+	assert!(let Ok(()) = a.write_all(b"\xFF\nok\n"));
+	let_assert!(Some(Err(e)) = lines.next());
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+	let_assert!(Some(Ok(line)) = lines.next());
+	assert!(line == "ok");
+}
+
+#[test]
+fn lines_ignore_timeout_behavior() {
+	use serial2::TimeoutBehavior;
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = b.set_read_timeout(Duration::from_millis(50)));
+
+	// A timeout must not end the iterator as if it was end-of-file.
+	b.set_timeout_behavior(TimeoutBehavior::ReturnZero);
+	let mut lines = b.lines();
+	let_assert!(Some(Err(e)) = lines.next());
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(let Ok(()) = a.write_all(b"first\n"));
+	let_assert!(Some(Ok(line)) = lines.next());
+	assert!(line == "first");
+
+	// A timeout is reported as `TimedOut`, not `WouldBlock`.
+	b.set_timeout_behavior(TimeoutBehavior::WouldBlock);
+	let mut lines = b.lines();
+	let_assert!(Some(Err(e)) = lines.next());
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn read_write_bytes() {
+	use bytes::Buf;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+
+	let mut data = bytes::Bytes::from_static(b"Hello ").chain(bytes::Bytes::from_static(b"world!"));
+	while data.has_remaining() {
+		assert!(let Ok(_) = a.write_bytes(&mut data));
+	}
+	assert!(let Ok(0) = a.write_bytes(&mut data));
+
+	let mut buffer = bytes::BytesMut::with_capacity(4);
+	buffer.extend_from_slice(b">");
+	while buffer.len() < 13 {
+		let_assert!(Ok(read) = b.read_bytes(&mut buffer));
+		assert!(read > 0);
+	}
+	assert!(&buffer[..] == b">Hello world!");
+}
+
+#[test]
+#[cfg(unix)]
+fn read_bytes_limits_read_size() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(()) = a.write_all(&[0x55; 5000]));
+
+	let mut buffer = bytes::BytesMut::with_capacity(64 * 1024);
+	let capacity = buffer.capacity();
+	while buffer.len() < 5000 {
+		let_assert!(Ok(read) = b.read_bytes(&mut buffer));
+		assert!(read > 0);
+		assert!(read <= 4096);
+	}
+	assert!(buffer.len() == 5000);
+	assert!(buffer.capacity() == capacity);
+}
+
+#[test]
+#[cfg(unix)]
+fn register_with_polling() {
+	use polling::{Event, Events, Poller};
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(let Ok(()) = a.set_read_timeout(Duration::ZERO));
+	a.set_timeout_behavior(serial2::TimeoutBehavior::WouldBlock);
+
+	let_assert!(Ok(poller) = Poller::new());
+	// SAFETY: The port is deleted from the poller before it is dropped.
+	assert!(let Ok(()) = unsafe { poller.add(&a, Event::readable(7)) });
+
+	let mut events = Events::new();
+	assert!(let Ok(0) = poller.wait(&mut events, Some(Duration::from_millis(10))));
+	let mut buffer = [0; 5];
+	let_assert!(Err(e) = a.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	assert!(let Ok(()) = b.write_all(b"Hello"));
+	assert!(let Ok(1) = poller.wait(&mut events, Some(Duration::from_secs(1))));
+	let_assert!(Some(event) = events.iter().next());
+	assert!(event.key == 7);
+	assert!(event.readable);
+	assert!(let Ok(5) = a.read(&mut buffer));
+	assert!(&buffer == b"Hello");
+
+	assert!(let Ok(()) = poller.delete(&a));
+}
+
+#[test]
+#[cfg(unix)]
+fn verify_control_lines() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	assert!(!a.get_verify_control_lines());
+	assert!(let Ok(()) = a.set_verify_control_lines(true));
+	assert!(a.get_verify_control_lines());
+	let_assert!(Ok(clone) = a.try_clone());
+	assert!(clone.get_verify_control_lines());
+	assert!(let Ok(()) = a.set_verify_control_lines(false));
+	assert!(!a.get_verify_control_lines());
+}
+
+#[test]
+#[cfg(unix)]
+fn line_script() {
+	use serial2::LineScript;
+	use std::time::{Duration, Instant};
+
+	let script = LineScript::new()
+		.sleep(Duration::from_millis(10))
+		.sleep(Duration::from_millis(5))
+		.sleep(Duration::from_millis(15));
+	assert!(script.duration() == Duration::from_millis(30));
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let start = Instant::now();
+	assert!(let Ok(()) = script.run(&a));
+	let elapsed = start.elapsed();
+	assert!(elapsed >= Duration::from_millis(30));
+	assert!(elapsed < Duration::from_millis(100));
+
+	// Consecutive line changes are merged into one step.
+	let merged = LineScript::new().rts(true).dtr(false).rts(false);
+	assert!(merged == LineScript::new().dtr(false).rts(false));
+	assert!(merged != LineScript::new().rts(false).sleep(Duration::ZERO).dtr(false));
+}
+
+#[test]
+#[cfg(unix)]
+fn wait_for_carrier_reports_errors() {
+	use std::time::{Duration, Instant};
+
+	// Pseudo terminals have no modem control lines, so reading the Carrier Detect line fails immediately.
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let start = Instant::now();
+	let_assert!(Err(e) = a.wait_for_carrier(Duration::from_secs(5)));
+	assert!(e.kind() != std::io::ErrorKind::TimedOut);
+	assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+#[cfg(unix)]
+fn wait_for_ring_reports_errors() {
+	use std::time::{Duration, Instant};
+
+	// Pseudo terminals have no ring counter and no modem control lines, so waiting for a ring fails immediately.
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	assert!(let Err(_) = a.ring_count());
+	let start = Instant::now();
+	let_assert!(Err(e) = a.wait_for_ring(Duration::from_secs(5)));
+	assert!(e.kind() != std::io::ErrorKind::TimedOut);
+	assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+#[cfg(unix)]
+fn save_and_restore_settings() {
+	use serial2::{ReadMode, TimeoutBehavior};
+	use std::time::Duration;
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(()) = a.set_baud_rate(9600));
+	let_assert!(Ok(()) = a.set_read_timeout(Duration::from_millis(150)));
+	let_assert!(Ok(saved) = a.save_settings());
+
+	let_assert!(Ok(()) = a.set_baud_rate(115200));
+	let_assert!(Ok(()) = a.set_read_timeout(Duration::from_millis(20)));
+	let_assert!(Ok(()) = a.set_write_timeout(Duration::from_millis(30)));
+	let_assert!(Ok(()) = a.set_read_mode(ReadMode::FillBuffer { inter_byte_timeout: Duration::from_millis(5) }));
+	a.set_timeout_behavior(TimeoutBehavior::WouldBlock);
+
+	let_assert!(Ok(()) = a.restore_settings(&saved));
+	assert!(let Ok(9600) = a.get_configuration().and_then(|settings| settings.get_baud_rate()));
+	assert!(a.get_read_timeout().ok() == Some(Duration::from_millis(150)));
+	assert!(a.get_write_timeout().ok() == Some(Duration::from_secs(3)));
+	assert!(let Ok(ReadMode::FirstByte) = a.get_read_mode());
+	assert!(a.get_timeout_behavior() == TimeoutBehavior::Error);
+}
+
+#[test]
+fn try_clone_copies_timeout_state() {
+	use serial2::{ApplyMode, ReadMode, TimeoutBehavior};
+	use std::time::Duration;
+
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let_assert!(Ok(()) = a.set_read_timeout(Duration::from_millis(150)));
+	let_assert!(Ok(()) = a.set_write_timeout(Duration::from_millis(250)));
+	let_assert!(Ok(()) = a.set_read_mode(ReadMode::FillBuffer { inter_byte_timeout: Duration::from_millis(5) }));
+	a.set_timeout_behavior(TimeoutBehavior::WouldBlock);
+	a.set_apply_mode(ApplyMode::Immediate);
+
+	let_assert!(Ok(clone) = a.try_clone());
+	assert!(clone.get_read_timeout().ok() == Some(Duration::from_millis(150)));
+	assert!(clone.get_write_timeout().ok() == Some(Duration::from_millis(250)));
+	assert!(let Ok(ReadMode::FillBuffer { .. }) = clone.get_read_mode());
+	assert!(clone.get_timeout_behavior() == TimeoutBehavior::WouldBlock);
+	assert!(clone.get_apply_mode() == ApplyMode::Immediate);
+
+	// On Unix, the timeouts are enforced per object, so later changes are not shared.
+	#[cfg(unix)]
+	{
+		let_assert!(Ok(()) = clone.set_read_timeout(Duration::from_millis(20)));
+		assert!(a.get_read_timeout().ok() == Some(Duration::from_millis(150)));
+	}
+	clone.set_timeout_behavior(TimeoutBehavior::Error);
+	assert!(a.get_timeout_behavior() == TimeoutBehavior::WouldBlock);
+}
+
+#[test]
+fn raw_parts_preserve_state() {
+	use serial2::{ApplyMode, ReadMode, TimeoutBehavior};
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(()) = a.set_read_timeout(Duration::from_millis(150)));
+	let_assert!(Ok(()) = a.set_write_timeout(Duration::from_millis(250)));
+	let_assert!(Ok(()) = a.set_read_mode(ReadMode::FillBuffer { inter_byte_timeout: Duration::from_millis(5) }));
+	a.set_timeout_behavior(TimeoutBehavior::WouldBlock);
+	a.set_apply_mode(ApplyMode::Immediate);
+
+	let (fd, state) = a.into_raw_parts();
+	assert!(state.read_timeout == Duration::from_millis(150));
+	assert!(state.write_timeout == Duration::from_millis(250));
+	assert!(state.timeout_behavior == TimeoutBehavior::WouldBlock);
+	assert!(state.apply_mode == ApplyMode::Immediate);
+
+	let_assert!(Ok(a) = SerialPort::from_raw_parts(fd, &state));
+	assert!(a.get_read_timeout().ok() == Some(Duration::from_millis(150)));
+	assert!(a.get_write_timeout().ok() == Some(Duration::from_millis(250)));
+	assert!(let Ok(ReadMode::FillBuffer { .. }) = a.get_read_mode());
+	assert!(a.get_timeout_behavior() == TimeoutBehavior::WouldBlock);
+	assert!(a.get_apply_mode() == ApplyMode::Immediate);
+
+	let_assert!(Ok(()) = b.write_all(b"hello"));
+	let mut buffer = [0; 5];
+	let_assert!(Ok(()) = a.read_exact(&mut buffer));
+	assert!(&buffer == b"hello");
+}
+
+#[test]
+#[cfg(unix)]
+fn apply_mode_discard() {
+	use serial2::ApplyMode;
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	assert!(b.get_apply_mode() == ApplyMode::Drain);
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_millis(50)));
+
+	// Unread input is kept when new settings are applied with the default mode.
+	let_assert!(Ok(()) = a.write_all(b"junk"));
+	std::thread::sleep(Duration::from_millis(20));
+	let_assert!(Ok(()) = b.set_baud_rate(57600));
+	let mut buffer = [0; 4];
+	let_assert!(Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"junk");
+
+	// And discarded with `ApplyMode::Discard`.
+	b.set_apply_mode(ApplyMode::Discard);
+	let_assert!(Ok(()) = a.write_all(b"junk"));
+	std::thread::sleep(Duration::from_millis(20));
+	let_assert!(Ok(()) = b.set_baud_rate(115200));
+	let_assert!(Err(e) = b.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+#[cfg(unix)]
+fn typed_termios_flags() {
+	use serial2::os::unix::{InputFlags, OutputFlags};
+
+	let_assert!(Ok((_a, b)) = SerialPort::pair());
+	let_assert!(Ok(mut settings) = b.get_configuration());
+	settings.set_raw();
+	assert!(!settings.get_input_flags().contains(InputFlags::ICRNL));
+	assert!(!settings.get_output_flags().contains(OutputFlags::OPOST));
+
+	let mut input_flags = settings.get_input_flags();
+	input_flags.insert(InputFlags::ICRNL | InputFlags::IGNBRK);
+	settings.set_input_flags(input_flags);
+	settings.set_output_flags(settings.get_output_flags() | OutputFlags::OPOST | OutputFlags::ONLCR);
+	settings.control_chars_mut()[libc::VMIN] = 0;
+	let_assert!(Ok(()) = b.set_configuration(&settings));
+
+	let_assert!(Ok(applied) = b.get_configuration());
+	assert!(applied.get_input_flags().contains(InputFlags::ICRNL | InputFlags::IGNBRK));
+	assert!(applied.get_output_flags().contains(OutputFlags::OPOST | OutputFlags::ONLCR));
+	assert!(applied.control_chars() == settings.control_chars());
+}
+
+#[test]
+fn capability_probes() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	// A pseudo terminal or named pipe has no RS-485 transceiver.
+	assert!(!a.is_rs485_supported());
+	// Whether a break is supported depends on the platform, but probing must not break the port.
+	let _ = a.is_break_supported();
+	let_assert!(Ok(()) = a.write_all(b"probe"));
+}
+
+#[test]
+#[cfg(unix)]
+fn error_reader_decodes_marked_data() {
+	use serial2::ErrorReader;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(mut reader) = ErrorReader::new(&b));
+	let_assert!(Ok(()) = a.write_all(&[1, 0xFF, 2, 0]));
+
+	let mut buffer = [0; 8];
+	let mut received = Vec::new();
+	while received.len() < 4 {
+		let_assert!(Ok(chunk) = reader.read(&mut buffer));
+		assert!(chunk.errors.is_empty());
+		received.extend_from_slice(&buffer[..chunk.len]);
+	}
+	assert!(received == [1, 0xFF, 2, 0]);
+}
+
+#[test]
+fn nine_bit_parity_for() {
+	use serial2::{NineBit, Parity};
+
+	assert!(NineBit::parity_for(0x000) == Parity::Space);
+	assert!(NineBit::parity_for(0x0FF) == Parity::Space);
+	assert!(NineBit::parity_for(NineBit::ADDRESS_BIT) == Parity::Mark);
+	assert!(NineBit::parity_for(NineBit::ADDRESS_BIT | 0x08) == Parity::Mark);
+}
+
+#[test]
+fn nine_bit_decodes_words() {
+	use serial2::{ErrorReader, NineBit};
+
+	// The master side does no input processing, so the slave can send data marked with `PARMRK` directly.
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let_assert!(Ok(mut reader) = ErrorReader::new(&a));
+	let_assert!(Ok(()) = b.write_all(&[0xFF, 0x00, 0x08, 0x01, 0xFF, 0xFF, 0xFF, 0x00, 0x41, 0x02]));
+
+	let mut buffer = [0; 8];
+	let mut received = Vec::new();
+	while received.len() < 5 {
+		let_assert!(Ok(chunk) = reader.read(&mut buffer));
+		let mut words = [0; 8];
+		let len = NineBit::decode_words(&buffer[..chunk.len], &chunk.errors, &mut words);
+		assert!(len == chunk.len);
+		received.extend_from_slice(&words[..len]);
+	}
+	assert!(received == [0x108, 0x001, 0x0FF, 0x141, 0x002]);
+}
@@ -0,0 +1,89 @@
+use assert2::{assert, let_assert};
+use serial2::mock::{Fault, MockSerialPort};
+use serial2::{RetryPolicy, RetryPort, SerialTransport};
+use std::io::ErrorKind;
+use std::time::Duration;
+
+#[test]
+fn backoff() {
+	let policy = RetryPolicy::new().backoff(Duration::from_millis(1), Duration::from_millis(5));
+	assert!(policy.get_backoff(0) == Duration::from_millis(1));
+	assert!(policy.get_backoff(1) == Duration::from_millis(2));
+	assert!(policy.get_backoff(2) == Duration::from_millis(4));
+	assert!(policy.get_backoff(3) == Duration::from_millis(5));
+	assert!(policy.get_backoff(100) == Duration::from_millis(5));
+	assert!(RetryPolicy::new().max_attempts(0).get_max_attempts() == 1);
+}
+
+#[test]
+fn retry_transient_errors() {
+	let port = MockSerialPort::new();
+	let retry = RetryPort::new(port.clone(), RetryPolicy::new().backoff(Duration::ZERO, Duration::ZERO));
+
+	port.push_read_error(ErrorKind::NotConnected.into());
+	port.push_read_fault(Fault::Interrupted);
+	port.push_read_data(b"ok");
+	let mut buffer = [0; 2];
+	assert!(let Ok(2) = retry.read(&mut buffer));
+	assert!(&buffer == b"ok");
+
+	port.push_write_error(ErrorKind::NotConnected.into());
+	assert!(let Ok(2) = retry.write(b"ok"));
+	assert!(port.take_written() == b"ok");
+}
+
+#[test]
+fn give_up_after_max_attempts() {
+	let port = MockSerialPort::new();
+	let retry = RetryPort::new(port.clone(), RetryPolicy::new().max_attempts(2));
+
+	port.push_write_error(ErrorKind::NotConnected.into());
+	port.push_write_error(ErrorKind::NotConnected.into());
+	let_assert!(Err(e) = retry.write(b"a"));
+	assert!(e.kind() == ErrorKind::NotConnected);
+	assert!(let Ok(1) = retry.write(b"a"));
+
+	// A permanent disconnect is reported after the last attempt.
+	port.push_read_fault(Fault::Disconnect);
+	let mut buffer = [0; 1];
+	let_assert!(Err(e) = retry.read(&mut buffer));
+	assert!(serial2::SerialPort::is_disconnected_error(&e));
+}
+
+#[test]
+fn other_errors_are_not_retried() {
+	let port = MockSerialPort::new();
+	assert!(let Ok(()) = port.set_read_timeout(Duration::from_millis(10)));
+	let retry = RetryPort::new(port.clone(), RetryPolicy::new());
+
+	port.push_read_error(ErrorKind::PermissionDenied.into());
+	let mut buffer = [0; 1];
+	let_assert!(Err(e) = retry.read(&mut buffer));
+	assert!(e.kind() == ErrorKind::PermissionDenied);
+
+	// Timeouts are only retried if the policy says so.
+	let retry = RetryPort::new(port.clone(), RetryPolicy::new().retryable_kinds(&[ErrorKind::TimedOut]));
+	port.push_read_fault(Fault::Timeout);
+	port.push_read_data(b"a");
+	assert!(let Ok(1) = retry.read(&mut buffer));
+	assert!(buffer[0] == b'a');
+}
+
+#[test]
+fn backoff_follows_manual_clock() {
+	let port = MockSerialPort::with_manual_clock();
+	let policy = RetryPolicy::new().backoff(Duration::from_secs(5), Duration::from_secs(5));
+	let retry = std::sync::Arc::new(RetryPort::new(port.clone(), policy));
+	port.push_write_error(ErrorKind::NotConnected.into());
+	let writer = std::thread::spawn({
+		let retry = retry.clone();
+		move || retry.write(b"ok")
+	});
+
+	// The retry waits for the backoff on the clock of the mock port.
+	std::thread::sleep(Duration::from_millis(20));
+	assert!(!writer.is_finished());
+	port.advance_time(Duration::from_secs(5));
+	let_assert!(Ok(Ok(2)) = writer.join());
+	assert!(port.take_written() == b"ok");
+}
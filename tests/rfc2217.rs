@@ -0,0 +1,97 @@
+use assert2::{assert, let_assert};
+use serial2::rfc2217::Rfc2217Port;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+const IAC: u8 = 255;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const WILL: u8 = 251;
+const DO: u8 = 253;
+const COM_PORT_OPTION: u8 = 44;
+
+/// Connect a client to a fake server, and consume the initial negotiation from the client.
+fn connect() -> (Rfc2217Port, TcpStream) {
+	let_assert!(Ok(listener) = TcpListener::bind("127.0.0.1:0"));
+	let_assert!(Ok(address) = listener.local_addr());
+	let_assert!(Ok(client) = Rfc2217Port::connect(address));
+	let_assert!(Ok((mut server, _)) = listener.accept());
+	let_assert!(Ok(()) = server.set_read_timeout(Some(Duration::from_secs(3))));
+
+	// Negotiation of BINARY, SGA and COM-PORT-OPTION, followed by SET-MODEMSTATE-MASK.
+	let expected = [
+		IAC, WILL, 0, IAC, DO, 0, IAC, WILL, 3, IAC, DO, 3, IAC, WILL, COM_PORT_OPTION,
+		IAC, SB, COM_PORT_OPTION, 11, 0xFF, 0xFF, IAC, SE,
+	];
+	let mut buffer = [0; 23];
+	let_assert!(Ok(()) = server.read_exact(&mut buffer));
+	assert!(buffer == expected);
+	(client, server)
+}
+
+#[test]
+fn set_baud_rate() {
+	let (client, mut server) = connect();
+	let_assert!(Ok(()) = client.set_baud_rate(115200));
+	let mut buffer = [0; 10];
+	let_assert!(Ok(()) = server.read_exact(&mut buffer));
+	assert!(buffer == [IAC, SB, COM_PORT_OPTION, 1, 0x00, 0x01, 0xC2, 0x00, IAC, SE]);
+}
+
+#[test]
+fn set_rts() {
+	let (client, mut server) = connect();
+	let_assert!(Ok(()) = client.set_rts(false));
+	let mut buffer = [0; 7];
+	let_assert!(Ok(()) = server.read_exact(&mut buffer));
+	assert!(buffer == [IAC, SB, COM_PORT_OPTION, 5, 12, IAC, SE]);
+}
+
+#[test]
+fn escape_data() {
+	let (client, mut server) = connect();
+	let_assert!(Ok(()) = client.write_all(&[1, IAC, 2]));
+	let mut buffer = [0; 4];
+	let_assert!(Ok(()) = server.read_exact(&mut buffer));
+	assert!(buffer == [1, IAC, IAC, 2]);
+
+	// Mix escaped data with a negotiation and a subnegotiation that should be filtered out.
+	let_assert!(Ok(()) = server.write_all(&[3, IAC, IAC, IAC, WILL, 0, IAC, SB, COM_PORT_OPTION, 101, 0, IAC, SE, 4]));
+	let mut buffer = [0; 3];
+	let_assert!(Ok(()) = client.read_exact(&mut buffer));
+	assert!(buffer == [3, IAC, 4]);
+}
+
+#[test]
+fn read_timeout() {
+	let (client, _server) = connect();
+	let_assert!(Ok(()) = client.set_read_timeout(Duration::from_millis(10)));
+	let mut buffer = [0; 4];
+	let_assert!(Err(e) = client.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn modem_state() {
+	let (client, mut server) = connect();
+	assert!(let Ok(false) = client.read_cts());
+
+	// Send a modem state notification, followed by a data byte so we know when it has been processed.
+	let_assert!(Ok(()) = server.write_all(&[IAC, SB, COM_PORT_OPTION, 107, 0x10 | 0x80, IAC, SE, 7]));
+	let mut buffer = [0; 1];
+	let_assert!(Ok(()) = client.read_exact(&mut buffer));
+	assert!(buffer == [7]);
+	assert!(let Ok(true) = client.read_cts());
+	assert!(let Ok(false) = client.read_dsr());
+	assert!(let Ok(false) = client.read_ri());
+	assert!(let Ok(true) = client.read_cd());
+}
+
+#[test]
+fn closed_connection() {
+	let (client, server) = connect();
+	drop(server);
+	let mut buffer = [0; 4];
+	assert!(let Ok(0) = client.read(&mut buffer));
+}
@@ -0,0 +1,199 @@
+use assert2::{assert, let_assert};
+use serial2::rs4xx::{DirectionLine, SoftwareRs485Config, SoftwareRs485Port};
+use serial2::SerialTransport;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Event {
+	Rts(bool),
+	Dtr(bool),
+	Write(Vec<u8>),
+	Flush,
+}
+
+/// A transport that records all calls that affect the direction control.
+#[derive(Default)]
+struct Recorder {
+	events: Mutex<Vec<Event>>,
+}
+
+impl Recorder {
+	fn take(&self) -> Vec<Event> {
+		std::mem::take(&mut *self.events.lock().unwrap())
+	}
+
+	fn push(&self, event: Event) -> std::io::Result<()> {
+		self.events.lock().unwrap().push(event);
+		Ok(())
+	}
+}
+
+impl SerialTransport for Recorder {
+	fn read(&self, _buf: &mut [u8]) -> std::io::Result<usize> {
+		Err(std::io::ErrorKind::TimedOut.into())
+	}
+
+	fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+		self.push(Event::Write(buf.to_vec()))?;
+		Ok(buf.len())
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		self.push(Event::Flush)
+	}
+
+	fn set_read_timeout(&self, _timeout: Duration) -> std::io::Result<()> {
+		Ok(())
+	}
+
+	fn get_read_timeout(&self) -> std::io::Result<Duration> {
+		Ok(Duration::ZERO)
+	}
+
+	fn set_write_timeout(&self, _timeout: Duration) -> std::io::Result<()> {
+		Ok(())
+	}
+
+	fn get_write_timeout(&self) -> std::io::Result<Duration> {
+		Ok(Duration::ZERO)
+	}
+
+	fn discard_buffers(&self) -> std::io::Result<()> {
+		Ok(())
+	}
+
+	fn set_rts(&self, state: bool) -> std::io::Result<()> {
+		self.push(Event::Rts(state))
+	}
+
+	fn read_cts(&self) -> std::io::Result<bool> {
+		Ok(false)
+	}
+
+	fn set_dtr(&self, state: bool) -> std::io::Result<()> {
+		self.push(Event::Dtr(state))
+	}
+
+	fn read_dsr(&self) -> std::io::Result<bool> {
+		Ok(false)
+	}
+
+	fn read_ri(&self) -> std::io::Result<bool> {
+		Ok(false)
+	}
+
+	fn read_cd(&self) -> std::io::Result<bool> {
+		Ok(false)
+	}
+}
+
+#[test]
+fn software_rs485_rts() {
+	let_assert!(Ok(port) = SoftwareRs485Port::new(Recorder::default(), SoftwareRs485Config::new()));
+	assert!(port.get_ref().take() == [Event::Rts(false)]);
+
+	let_assert!(Ok(()) = port.write_all(b"hello"));
+	assert!(port.get_ref().take() == [
+		Event::Rts(true),
+		Event::Write(b"hello".to_vec()),
+		Event::Flush,
+		Event::Rts(false),
+	]);
+}
+
+#[test]
+fn software_rs485_inverted_dtr() {
+	let mut config = SoftwareRs485Config::new();
+	config.set_direction_line(DirectionLine::Dtr);
+	config.set_invert_line(true);
+	config.set_delay_after_send(Duration::from_millis(1));
+	let_assert!(Ok(port) = SoftwareRs485Port::new(Recorder::default(), config));
+	assert!(port.get_ref().take() == [Event::Dtr(true)]);
+
+	assert!(let Ok(3) = port.write(b"abc"));
+	assert!(port.get_ref().take() == [
+		Event::Dtr(false),
+		Event::Write(b"abc".to_vec()),
+		Event::Flush,
+		Event::Dtr(true),
+	]);
+}
+
+#[test]
+fn software_rs485_custom_direction_control() {
+	let gpio = std::sync::Arc::new(Mutex::new(Vec::new()));
+	let direction_control = {
+		let gpio = gpio.clone();
+		move |transmit| {
+			gpio.lock().unwrap().push(transmit);
+			Ok(())
+		}
+	};
+	let config = SoftwareRs485Config::new();
+	let_assert!(Ok(port) = SoftwareRs485Port::with_direction_control(Recorder::default(), config, direction_control));
+	let_assert!(Ok(()) = port.write_all(b"hello"));
+
+	// The RTS and DTR lines should not be touched.
+	assert!(port.get_ref().take() == [Event::Write(b"hello".to_vec()), Event::Flush]);
+	assert!(*gpio.lock().unwrap() == [false, true, false]);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn supported_features_pty() {
+	// Pseudo terminals do not support RS-485 at all.
+	let_assert!(Ok((a, _b)) = serial2::SerialPort::pair());
+	match serial2::rs4xx::supported_features(&a) {
+		Ok(features) => assert!(features == serial2::rs4xx::Rs4xxFeatures::default()),
+		Err(e) => assert!(e.raw_os_error() == Some(libc::ENOTTY)),
+	}
+}
+
+#[test]
+fn software_rs485_echo_suppression() {
+	let mock = serial2::mock::MockSerialPort::new();
+	let mut config = SoftwareRs485Config::new();
+	config.set_direction_line(DirectionLine::None);
+	config.set_echo_suppression(true);
+	let_assert!(Ok(port) = SoftwareRs485Port::new(mock.clone(), config));
+
+	// The mock echoes the request, followed by the response of the other device.
+	mock.add_response(b"ping", b"pingpong");
+	let_assert!(Ok(()) = port.write_all(b"ping"));
+	let mut buffer = [0; 4];
+	let_assert!(Ok(()) = port.read_exact(&mut buffer));
+	assert!(&buffer == b"pong");
+	assert!(mock.read_queue_len() == 0);
+
+	// A corrupted echo indicates a collision.
+	mock.add_response(b"ping", b"pinx");
+	let_assert!(Err(e) = port.write_all(b"ping"));
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn software_rs485_turnaround_delay() {
+	let mock = serial2::mock::MockSerialPort::new();
+	let mut config = SoftwareRs485Config::new();
+	config.set_turnaround_delay(Duration::from_millis(50));
+	let_assert!(Ok(port) = SoftwareRs485Port::new(mock.clone(), config));
+
+	mock.push_read_data(b"request");
+	let mut buffer = [0; 7];
+	let_assert!(Ok(()) = port.read_exact(&mut buffer));
+	let received = std::time::Instant::now();
+	let_assert!(Ok(()) = port.write_all(b"response"));
+	assert!(received.elapsed() >= Duration::from_millis(50));
+	assert!(mock.take_written() == b"response");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn addressed_reader_requires_addressing_mode() {
+	use serial2::rs4xx::AddressedReader;
+
+	// Pseudo terminals do not support RS-485 at all.
+	let_assert!(Ok((a, _b)) = serial2::SerialPort::pair());
+	assert!(let Err(_) = AddressedReader::new(&a));
+}
@@ -45,13 +45,17 @@ fn test_serde_parity() {
 	assert!(let Ok("\"none\"") = serde_json::to_string(&serial2::Parity::None).as_deref());
 	assert!(let Ok("\"even\"") = serde_json::to_string(&serial2::Parity::Even).as_deref());
 	assert!(let Ok("\"odd\"") = serde_json::to_string(&serial2::Parity::Odd).as_deref());
+	assert!(let Ok("\"mark\"") = serde_json::to_string(&serial2::Parity::Mark).as_deref());
+	assert!(let Ok("\"space\"") = serde_json::to_string(&serial2::Parity::Space).as_deref());
 
 	assert!(let Ok(serial2::Parity::None) = serde_json::from_str::<serial2::Parity>("\"none\""));
 	assert!(let Ok(serial2::Parity::Even) = serde_json::from_str::<serial2::Parity>("\"even\""));
 	assert!(let Ok(serial2::Parity::Odd) = serde_json::from_str::<serial2::Parity>("\"odd\""));
+	assert!(let Ok(serial2::Parity::Mark) = serde_json::from_str::<serial2::Parity>("\"mark\""));
+	assert!(let Ok(serial2::Parity::Space) = serde_json::from_str::<serial2::Parity>("\"space\""));
 
 	let_assert!(Err(e) = serde_json::from_str::<serial2::Parity>("\"even-then-odd\""));
-	assert!(e.to_string() == "invalid value: string \"even-then-odd\", expected the string \"none\", \"odd\" or \"even\" at line 1 column 15");
+	assert!(e.to_string() == "invalid value: string \"even-then-odd\", expected the string \"none\", \"odd\", \"even\", \"mark\" or \"space\" at line 1 column 15");
 }
 
 #[test]
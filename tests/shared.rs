@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+use assert2::{assert, let_assert};
+use serial2::{SerialPort, SharedSerialPort};
+use std::time::Duration;
+
+#[test]
+fn clones_share_the_port() {
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let a = SharedSerialPort::new(a);
+	let b = SharedSerialPort::from(b);
+
+	let reader = b.clone();
+	assert!(b.handle_count() == 2);
+	let_assert!(Ok(()) = b.set_read_timeout(Duration::from_millis(123)));
+	assert!(reader.get_read_timeout().ok() == Some(Duration::from_millis(123)));
+
+	let thread = std::thread::spawn(move || {
+		let mut buffer = [0; 6];
+		reader.read_exact(&mut buffer).map(|()| buffer)
+	});
+	assert!(let Ok(()) = a.write_all(b"Hello!"));
+	let_assert!(Ok(Ok(buffer)) = thread.join());
+	assert!(&buffer == b"Hello!");
+
+	let_assert!(Ok(()) = b.modify_configuration(|settings| settings.set_baud_rate(19200)));
+	let_assert!(Ok(settings) = b.get_configuration());
+	assert!(let Ok(19200) = settings.get_baud_rate());
+}
+
+#[test]
+fn into_inner() {
+	let_assert!(Ok((a, _b)) = SerialPort::pair());
+	let a = SharedSerialPort::new(a);
+	let clone = a.clone();
+	let_assert!(Err(a) = a.into_inner());
+	drop(clone);
+	assert!(a.handle_count() == 1);
+	assert!(let Ok(_) = a.into_inner());
+}
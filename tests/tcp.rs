@@ -0,0 +1,65 @@
+use assert2::{assert, let_assert};
+use serial2::tcp::TcpSerialPort;
+use serial2::SerialTransport;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+fn connect() -> (TcpSerialPort, TcpStream) {
+	let_assert!(Ok(listener) = TcpListener::bind("127.0.0.1:0"));
+	let_assert!(Ok(address) = listener.local_addr());
+	let_assert!(Ok(client) = TcpSerialPort::connect(address));
+	let_assert!(Ok((server, _)) = listener.accept());
+	let_assert!(Ok(()) = server.set_read_timeout(Some(Duration::from_secs(3))));
+	(client, server)
+}
+
+#[test]
+fn read_write() {
+	let (client, mut server) = connect();
+	let transport: Box<dyn SerialTransport> = Box::new(client);
+
+	let_assert!(Ok(()) = transport.write_all(&[1, 255, 2]));
+	let mut buffer = [0; 3];
+	let_assert!(Ok(()) = server.read_exact(&mut buffer));
+	assert!(buffer == [1, 255, 2]);
+
+	let_assert!(Ok(()) = server.write_all(b"world"));
+	let mut buffer = [0; 5];
+	let_assert!(Ok(()) = transport.read_exact(&mut buffer));
+	assert!(&buffer == b"world");
+}
+
+#[test]
+fn read_timeout() {
+	let (client, _server) = connect();
+	let_assert!(Ok(()) = client.set_read_timeout(Duration::from_millis(10)));
+	let_assert!(Ok(timeout) = client.get_read_timeout());
+	assert!(timeout == Duration::from_millis(10));
+	let mut buffer = [0; 4];
+	let_assert!(Err(e) = client.read(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn discard_buffers() {
+	let (client, mut server) = connect();
+	let_assert!(Ok(()) = server.write_all(b"stale"));
+	let_assert!(Ok(()) = server.flush());
+	std::thread::sleep(Duration::from_millis(50));
+	let_assert!(Ok(()) = client.discard_buffers());
+
+	let_assert!(Ok(()) = server.write_all(b"fresh"));
+	let mut buffer = [0; 5];
+	let_assert!(Ok(()) = client.read_exact(&mut buffer));
+	assert!(&buffer == b"fresh");
+}
+
+#[test]
+fn no_control_lines() {
+	let (client, _server) = connect();
+	let_assert!(Err(e) = client.set_rts(true));
+	assert!(e.kind() == std::io::ErrorKind::Unsupported);
+	let_assert!(Err(e) = client.read_cts());
+	assert!(e.kind() == std::io::ErrorKind::Unsupported);
+}
@@ -0,0 +1,43 @@
+use assert2::{assert, let_assert};
+use serial2::mock::MockSerialPort;
+use serial2::SerialTransport;
+use std::time::Duration;
+
+fn ping(transport: &dyn SerialTransport) -> std::io::Result<[u8; 5]> {
+	transport.write_all(b"PING\n")?;
+	let mut buffer = [0; 5];
+	transport.read_exact(&mut buffer)?;
+	Ok(buffer)
+}
+
+#[test]
+fn mock_as_transport() {
+	let mock = MockSerialPort::new();
+	mock.add_response(b"PING\n", b"PONG\n");
+	let transport: Box<dyn SerialTransport> = Box::new(mock.clone());
+	let_assert!(Ok(response) = ping(&transport));
+	assert!(&response == b"PONG\n");
+
+	assert!(let Ok(()) = transport.set_read_timeout(Duration::from_millis(20)));
+	let_assert!(Ok(timeout) = transport.get_read_timeout());
+	assert!(timeout == Duration::from_millis(20));
+	assert!(let Ok(()) = transport.set_rts(true));
+	assert!(mock.rts());
+}
+
+#[test]
+#[cfg(unix)]
+fn serial_port_as_transport() {
+	use serial2::SerialPort;
+	use std::sync::Arc;
+
+	let_assert!(Ok((a, b)) = SerialPort::pair());
+	let a: Arc<dyn SerialTransport + Send + Sync> = Arc::new(a);
+	assert!(let Ok(()) = b.write_all(b"PONG\n"));
+	let_assert!(Ok(response) = ping(&a));
+	assert!(&response == b"PONG\n");
+
+	let mut buffer = [0; 5];
+	assert!(let Ok(()) = b.read_exact(&mut buffer));
+	assert!(&buffer == b"PING\n");
+}
@@ -0,0 +1,49 @@
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use assert2::{assert, let_assert};
+use serial2::usbfs::UsbCdcPort;
+use std::io::Write;
+use std::os::unix::io::OwnedFd;
+
+const DEVICE: [u8; 18] = [18, 0x01, 0x00, 0x02, 0x02, 0x00, 0x00, 64, 0x34, 0x12, 0x78, 0x56, 0x00, 0x01, 1, 2, 3, 1];
+const CONFIGURATION: [u8; 9] = [9, 0x02, 67, 0, 2, 1, 0, 0x80, 50];
+const CONTROL_INTERFACE: [u8; 9] = [9, 0x04, 0, 0, 1, 0x02, 0x02, 0x01, 0];
+const NOTIFY_ENDPOINT: [u8; 7] = [7, 0x05, 0x83, 0x03, 8, 0, 16];
+const DATA_INTERFACE: [u8; 9] = [9, 0x04, 1, 0, 2, 0x0A, 0x00, 0x00, 0];
+const BULK_IN: [u8; 7] = [7, 0x05, 0x81, 0x02, 64, 0, 0];
+const BULK_OUT: [u8; 7] = [7, 0x05, 0x02, 0x02, 64, 0, 0];
+
+/// Create a file descriptor for a regular file with the given contents.
+fn file_with_descriptors(name: &str, descriptors: &[&[u8]]) -> OwnedFd {
+	let path = std::env::temp_dir().join(format!("serial2-usbfs-{}-{}", std::process::id(), name));
+	let_assert!(Ok(mut file) = std::fs::File::create(&path));
+	let_assert!(Ok(()) = file.write_all(&descriptors.concat()));
+	let_assert!(Ok(file) = std::fs::File::open(&path));
+	let_assert!(Ok(()) = std::fs::remove_file(&path));
+	file.into()
+}
+
+#[test]
+fn cdc_acm_descriptors_are_accepted() {
+	let fd = file_with_descriptors(
+		"valid",
+		&[&DEVICE, &CONFIGURATION, &CONTROL_INTERFACE, &NOTIFY_ENDPOINT, &DATA_INTERFACE, &BULK_IN, &BULK_OUT],
+	);
+	// The descriptors are fine, but a regular file does not support the usbfs ioctls.
+	let_assert!(Err(e) = UsbCdcPort::from_fd(fd, 115200));
+	assert!(e.raw_os_error() == Some(libc::ENOTTY));
+}
+
+#[test]
+fn missing_cdc_interface_is_rejected() {
+	let fd = file_with_descriptors("no-cdc", &[&DEVICE, &CONFIGURATION, &DATA_INTERFACE, &BULK_IN, &BULK_OUT]);
+	let_assert!(Err(e) = UsbCdcPort::from_fd(fd, 115200));
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn truncated_descriptors_are_rejected() {
+	let fd = file_with_descriptors("truncated", &[&DEVICE, &CONFIGURATION, &CONTROL_INTERFACE[..5]]);
+	let_assert!(Err(e) = UsbCdcPort::from_fd(fd, 115200));
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+}